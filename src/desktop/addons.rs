@@ -0,0 +1,151 @@
+//! Metrics logged by Heroku Postgres and Heroku Redis
+//!
+//! Add-ons report their health every few minutes as logfmt lines under their
+//! own dyno name, such as
+//! `app[heroku-postgres]: source=DATABASE addon=postgresql-curly-12345
+//! sample#load-avg-1m=0.02 sample#memory-free=3263740kB ...`. These lines
+//! are filed in the Metrics category (`category:metrics`) and their samples
+//! kept for the charts panel.
+
+use crate::metrics::{sparkline_of, MetricChart};
+use crate::parser::LogEntry;
+use std::collections::{BTreeMap, VecDeque};
+
+/// Dyno names add-ons log metrics under
+const ADDON_DYNOS: [&str; 2] = ["heroku-postgres", "heroku-redis"];
+
+/// Prefix of a metric field
+const SAMPLE_PREFIX: &str = "sample#";
+
+/// Samples kept per metric, one sparkline bar each
+const MAX_SAMPLES: usize = 60;
+
+/// Whether lines from this dyno are add-on metric reports
+pub fn is_addon_dyno(dyno: &str) -> bool {
+    ADDON_DYNOS.contains(&dyno)
+}
+
+/// Whether an entry belongs to the Metrics category
+pub fn is_addon_metrics(entry: &LogEntry) -> bool {
+    is_addon_dyno(&entry.dyno) && entry.fields.keys().any(|k| k.starts_with(SAMPLE_PREFIX))
+}
+
+/// Numeric value of a sample such as `0.02`, `24866320bytes` or `4020564kB`,
+/// with sizes in bytes
+fn sample_value(value: &str) -> Option<f64> {
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))
+        .unwrap_or(value.len());
+    let number: f64 = value[..split].parse().ok()?;
+    let scale = match &value[split..] {
+        "kB" => 1024.0,
+        "MB" => 1024.0 * 1024.0,
+        _ => 1.0,
+    };
+    Some(number * scale)
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Series {
+    values: VecDeque<f64>,
+    latest: String,
+}
+
+/// Samples of every add-on metric seen, by add-on and metric name
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AddonMetrics {
+    series: BTreeMap<(String, String), Series>,
+}
+
+impl AddonMetrics {
+    /// Record the samples of an add-on metrics line; other lines are ignored
+    pub fn ingest(&mut self, entry: &LogEntry) {
+        if !is_addon_metrics(entry) {
+            return;
+        }
+        let addon = entry.fields.get("addon").unwrap_or(&entry.dyno);
+        for (key, raw) in &entry.fields {
+            let Some(metric) = key.strip_prefix(SAMPLE_PREFIX) else {
+                continue;
+            };
+            let Some(value) = sample_value(raw) else {
+                continue;
+            };
+            let series = self
+                .series
+                .entry((addon.clone(), metric.to_string()))
+                .or_default();
+            series.values.push_back(value);
+            if series.values.len() > MAX_SAMPLES {
+                series.values.pop_front();
+            }
+            series.latest = raw.clone();
+        }
+    }
+
+    /// A chart per metric, ordered by add-on and metric name
+    pub fn charts(&self) -> Vec<MetricChart> {
+        self.series
+            .iter()
+            .map(|((addon, metric), series)| {
+                let values: Vec<f64> = series.values.iter().copied().collect();
+                MetricChart {
+                    group: addon.clone(),
+                    metric: metric.clone(),
+                    sparkline: sparkline_of(&values),
+                    latest: series.latest.clone(),
+                }
+            })
+            .collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.series.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_log_line;
+
+    const POSTGRES: &str = "2024-02-17T10:30:45.123456+00:00 app[heroku-postgres]: source=DATABASE addon=postgresql-curly-12345 sample#current_transaction=1873 sample#db_size=24866320bytes sample#load-avg-1m=0.02 sample#memory-free=3263740kB";
+
+    #[test]
+    fn test_addon_lines_are_metrics() {
+        let entry = parse_log_line(POSTGRES).unwrap();
+        assert!(is_addon_metrics(&entry));
+        assert_eq!(entry.fields["sample#load-avg-1m"], "0.02");
+        assert_eq!(entry.fields["addon"], "postgresql-curly-12345");
+
+        let app =
+            parse_log_line("2024-02-17T10:30:45.123456+00:00 app[web.1]: sample#load-avg-1m=0.02")
+                .unwrap();
+        assert!(!is_addon_metrics(&app));
+    }
+
+    #[test]
+    fn test_sample_values() {
+        assert_eq!(sample_value("0.02"), Some(0.02));
+        assert_eq!(sample_value("24866320bytes"), Some(24866320.0));
+        assert_eq!(sample_value("2kB"), Some(2048.0));
+        assert_eq!(sample_value("n/a"), None);
+    }
+
+    #[test]
+    fn test_charts_follow_samples() {
+        let mut addons = AddonMetrics::default();
+        let entry = parse_log_line(POSTGRES).unwrap();
+        addons.ingest(&entry);
+        addons.ingest(
+            &parse_log_line(&POSTGRES.replace("load-avg-1m=0.02", "load-avg-1m=0.08")).unwrap(),
+        );
+
+        let charts = addons.charts();
+        assert_eq!(charts.len(), 4);
+        let load = charts.iter().find(|c| c.metric == "load-avg-1m").unwrap();
+        assert_eq!(load.group, "postgresql-curly-12345");
+        assert_eq!(load.latest, "0.08");
+        assert_eq!(load.sparkline, "▂█");
+    }
+}
@@ -0,0 +1,285 @@
+//! Alert rules with a fixed threshold, loaded from `~/.config/logs-parser/alerts`
+//!
+//! One rule per line: a filter query, then how many matching lines within a
+//! window are too many:
+//!
+//! ```text
+//! level:error > 20 in 1m
+//! "connection refused" > 0 in 5m
+//! dyno:worker level:warn+ > 100 in 10m
+//! ```
+//!
+//! Rules are checked as lines arrive, counting each line by its timestamp,
+//! so history fetched on connect or replayed from a session that is older
+//! than the window doesn't count. A rule fires once when its count goes
+//! over the threshold, then again only after the count has fallen back.
+//! `:alert <query> > <count> in <window>` adds a rule to the file.
+
+use crate::config::config_dir;
+use crate::filters::{parse_query, Filter};
+use crate::parser::LogEntry;
+use crate::settings::parse_duration;
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Parse the `<count> in <window>` after a rule's `>`
+pub fn parse_condition(condition: &str) -> Option<(usize, Duration)> {
+    let mut words = condition.split_whitespace();
+    let threshold = words.next()?.parse().ok()?;
+    if words.next() != Some("in") {
+        return None;
+    }
+    let window = parse_duration(words.next()?).filter(|window| window.as_secs() > 0)?;
+    words.next().is_none().then_some((threshold, window))
+}
+
+/// A window as written in the alerts file, e.g. `90s`, `5m` or `1h`
+fn format_window(secs: u64) -> String {
+    match secs {
+        s if s % 3600 == 0 => format!("{}h", s / 3600),
+        s if s % 60 == 0 => format!("{}m", s / 60),
+        s => format!("{}s", s),
+    }
+}
+
+/// One rule and the per-second counts of its matches within the window, by
+/// log time
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertRule {
+    pub query: String,
+    filters: Vec<Filter>,
+    pub threshold: usize,
+    pub window: Duration,
+    counts: BTreeMap<i64, usize>,
+    firing: bool,
+}
+
+impl AlertRule {
+    /// A rule for a query; `None` if the query has no filter terms
+    pub fn new(query: &str, threshold: usize, window: Duration) -> Option<Self> {
+        let filters = parse_query(query);
+        if filters.is_empty() {
+            return None;
+        }
+        Some(Self {
+            query: query.trim().to_string(),
+            filters,
+            threshold,
+            window,
+            counts: BTreeMap::new(),
+            firing: false,
+        })
+    }
+
+    /// The rule as a line of the alerts file
+    pub fn line(&self) -> String {
+        format!(
+            "{} > {} in {}",
+            self.query,
+            self.threshold,
+            format_window(self.window.as_secs())
+        )
+    }
+
+    /// Count an entry that arrived during second `now`, if it matches and
+    /// was logged within the window; returns a message when the rule fires
+    pub fn record(&mut self, entry: &LogEntry, now: i64) -> Option<String> {
+        let start = now - self.window.as_secs() as i64;
+        self.counts = self.counts.split_off(&(start + 1));
+        let second = entry.timestamp.timestamp();
+        if second > start && self.filters.iter().all(|f| f.matches(entry)) {
+            *self.counts.entry(second).or_default() += 1;
+        }
+        let count: usize = self.counts.values().sum();
+        let over = count > self.threshold;
+        let fires = over && !self.firing;
+        self.firing = over;
+        fires.then(|| {
+            format!(
+                "\"{}\": {} lines in the last {}, more than {}",
+                self.query,
+                count,
+                format_window(self.window.as_secs()),
+                self.threshold
+            )
+        })
+    }
+}
+
+/// Every alert rule in the alerts file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AlertRules {
+    rules: Vec<AlertRule>,
+}
+
+impl AlertRules {
+    /// Parse an alerts file, skipping blank lines, comments and lines that
+    /// aren't rules
+    pub fn parse(contents: &str) -> Self {
+        let rules = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let (query, condition) = line.rsplit_once('>')?;
+                let (threshold, window) = parse_condition(condition)?;
+                AlertRule::new(query, threshold, window)
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// Load the user's alerts file; no file means no rules
+    pub fn load() -> Self {
+        std::fs::read_to_string(config_dir().join("alerts"))
+            .map(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    /// Write the rules back to the alerts file
+    pub fn save(&self) -> Result<()> {
+        let dir = config_dir();
+        std::fs::create_dir_all(&dir).context("Failed to create config directory")?;
+        let contents: String = self
+            .rules
+            .iter()
+            .map(|rule| format!("{}\n", rule.line()))
+            .collect();
+        std::fs::write(dir.join("alerts"), contents).context("Failed to save alert rules")
+    }
+
+    /// Add a rule, replacing any other rule for the same query
+    pub fn add(&mut self, rule: AlertRule) {
+        self.rules.retain(|r| r.query != rule.query);
+        self.rules.push(rule);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.rules.clear();
+    }
+
+    /// Queries and messages of the rules an entry arriving during second
+    /// `now` makes fire
+    pub fn record(&mut self, entry: &LogEntry, now: i64) -> Vec<(String, String)> {
+        self.rules
+            .iter_mut()
+            .filter_map(|rule| {
+                rule.record(entry, now)
+                    .map(|message| (rule.query.clone(), message))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_log_line;
+
+    /// Logged at 10:30:45, the `now` the tests start from
+    const START: i64 = 1708165845;
+
+    fn line(message: &str) -> LogEntry {
+        parse_log_line(&format!(
+            "2024-02-17T10:30:45.123456+00:00 app[web.1]: {}",
+            message
+        ))
+        .unwrap()
+    }
+
+    /// A line logged `offset` seconds after [`START`]
+    fn line_at(message: &str, offset: i64) -> LogEntry {
+        let mut entry = line(message);
+        entry.timestamp += chrono::Duration::seconds(offset);
+        entry
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let rules = AlertRules::parse(
+            "# noisy\nlevel:error > 20 in 1m\n\"connection refused\" > 0 in 90s\nbogus\ntimeout > 5 in soon\n",
+        );
+        let lines: Vec<String> = rules.rules.iter().map(AlertRule::line).collect();
+        assert_eq!(
+            lines,
+            [
+                "level:error > 20 in 1m",
+                "\"connection refused\" > 0 in 90s"
+            ]
+        );
+        assert_eq!(
+            parse_condition(" 3 in 2h"),
+            Some((3, Duration::from_secs(7200)))
+        );
+        assert_eq!(parse_condition("3x 10m"), None);
+        assert_eq!(parse_condition("3 in 0s"), None);
+    }
+
+    #[test]
+    fn test_fires_once_over_threshold() {
+        let mut rules = AlertRules::default();
+        rules.add(AlertRule::new("timeout", 2, Duration::from_secs(60)).unwrap());
+
+        let mut record =
+            |message: &str, offset: i64| rules.record(&line_at(message, offset), START + offset);
+        assert!(record("timeout", 0).is_empty());
+        assert!(record("all good", 1).is_empty());
+        assert!(record("timeout", 2).is_empty());
+        let fired = record("timeout", 3);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].0, "timeout");
+        assert!(fired[0].1.contains("3 lines in the last 1m"));
+        // Still over: no repeat
+        assert!(record("timeout", 4).is_empty());
+        // The window has moved past the burst, then it starts again
+        assert!(record("all good", 100).is_empty());
+        for second in 101..103 {
+            assert!(record("timeout", second).is_empty());
+        }
+        assert_eq!(record("timeout", 103).len(), 1);
+    }
+
+    #[test]
+    fn test_counts_by_log_time() {
+        let mut rules = AlertRules::default();
+        rules.add(AlertRule::new("timeout", 2, Duration::from_secs(60)).unwrap());
+
+        // History from an hour ago arriving all at once doesn't count
+        for _ in 0..10 {
+            assert!(rules.record(&line_at("timeout", -3600), START).is_empty());
+        }
+        // Recent history does, by when it was logged
+        assert!(rules.record(&line_at("timeout", -50), START).is_empty());
+        assert!(rules.record(&line_at("timeout", -10), START).is_empty());
+        assert_eq!(rules.record(&line_at("timeout", 0), START).len(), 1);
+    }
+
+    #[test]
+    fn test_quoted_phrase_rule_matches() {
+        let mut rules = AlertRules::parse("\"connection refused\" > 0 in 5m\n");
+        assert!(rules
+            .record(&line("db connect: connection timed out"), START)
+            .is_empty());
+        let fired = rules.record(
+            &line("db connect: Connection refused (os error 111)"),
+            START,
+        );
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].0, "\"connection refused\"");
+    }
+
+    #[test]
+    fn test_add_replaces_same_query() {
+        let mut rules = AlertRules::default();
+        rules.add(AlertRule::new("level:error", 20, Duration::from_secs(60)).unwrap());
+        rules.add(AlertRule::new("level:error", 5, Duration::from_secs(60)).unwrap());
+        assert_eq!(rules.rules.len(), 1);
+        assert_eq!(rules.rules[0].threshold, 5);
+        assert_eq!(AlertRule::new("  ", 1, Duration::from_secs(60)), None);
+    }
+}
@@ -0,0 +1,283 @@
+//! Continuous export of filtered lines to rotating files
+//!
+//! With `auto_export = hourly` (or `daily`, or a size like `50MB`) every line
+//! that passes the filters is appended to a file named from the
+//! `auto_export_to` template. A new file is started each period, or once the
+//! current one reaches the size, and only the newest `auto_export_keep`
+//! files matching the template are kept.
+//!
+//! Templates understand `{app}`, `{date}` (`2024-02-17`), `{hour}` (`10`)
+//! and `{time}` (`103045`, when the file was started).
+
+use crate::export::expand_home;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use regex::Regex;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+/// Template used when `auto_export_to` isn't set
+pub const DEFAULT_TEMPLATE: &str = "~/Downloads/logs-parser-{app}-{date}-{time}.log";
+
+const PLACEHOLDERS: [&str; 4] = ["{app}", "{date}", "{hour}", "{time}"];
+
+/// When a new export file is started
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    Hourly,
+    Daily,
+    /// Once the current file holds this many bytes
+    Size(u64),
+}
+
+impl Rotation {
+    /// Parse `hourly`, `daily` or a size like `500KB`, `50MB`, `1GB`
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "hourly" => Some(Self::Hourly),
+            "daily" => Some(Self::Daily),
+            _ => crate::settings::parse_size(value)
+                .filter(|&size| size > 0)
+                .map(Self::Size),
+        }
+    }
+
+    /// Period a line written at `now` belongs to; a change starts a new file
+    fn period(&self, now: &DateTime<Local>) -> String {
+        match self {
+            Self::Hourly => now.format("%Y-%m-%d %H").to_string(),
+            Self::Daily => now.format("%Y-%m-%d").to_string(),
+            Self::Size(_) => String::new(),
+        }
+    }
+}
+
+/// Fill in a file name template
+fn render(template: &str, app: &str, now: &DateTime<Local>) -> String {
+    template
+        .replace("{app}", app)
+        .replace("{date}", &now.format("%Y-%m-%d").to_string())
+        .replace("{hour}", &now.format("%H").to_string())
+        .replace("{time}", &now.format("%H%M%S").to_string())
+}
+
+/// Pattern matching every file name the template can produce, for retention
+fn name_pattern(template_name: &str) -> Regex {
+    let mut pattern = regex::escape(template_name);
+    for placeholder in PLACEHOLDERS {
+        pattern = pattern.replace(&regex::escape(placeholder), ".+");
+    }
+    Regex::new(&format!("^{}$", pattern)).expect("escaped template is a valid regex")
+}
+
+#[derive(Debug)]
+struct OpenFile {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    period: String,
+    bytes: u64,
+}
+
+/// Writes lines to the current export file, rotating and pruning as it goes
+#[derive(Debug)]
+pub struct AutoExport {
+    template: String,
+    rotation: Rotation,
+    keep: Option<usize>,
+    app: String,
+    current: Option<OpenFile>,
+}
+
+impl AutoExport {
+    pub fn new(template: &str, rotation: Rotation, keep: Option<usize>) -> Self {
+        Self {
+            template: template.to_string(),
+            rotation,
+            keep,
+            app: "local".to_string(),
+            current: None,
+        }
+    }
+
+    /// App name used for `{app}` in files started from now on
+    pub fn set_app(&mut self, app: &str) {
+        self.app = app.to_string();
+    }
+
+    fn needs_rotation(&self, now: &DateTime<Local>) -> bool {
+        match &self.current {
+            None => true,
+            Some(file) => match self.rotation {
+                Rotation::Size(limit) => file.bytes >= limit,
+                _ => file.period != self.rotation.period(now),
+            },
+        }
+    }
+
+    /// Append a raw line received at `now`
+    pub fn write(&mut self, raw: &str, now: DateTime<Local>) -> Result<()> {
+        if self.needs_rotation(&now) {
+            self.rotate(&now)?;
+        }
+        let file = self.current.as_mut().expect("rotate opened a file");
+        file.writer
+            .write_all(format!("{}\n", raw).as_bytes())
+            .with_context(|| format!("Failed to write {}", file.path.display()))?;
+        file.bytes += raw.len() as u64 + 1;
+        Ok(())
+    }
+
+    /// Write buffered lines out to the current file
+    pub fn flush(&mut self) -> Result<()> {
+        match &mut self.current {
+            Some(file) => file
+                .writer
+                .flush()
+                .with_context(|| format!("Failed to write {}", file.path.display())),
+            None => Ok(()),
+        }
+    }
+
+    /// Close the current file, start the next one and drop old ones
+    fn rotate(&mut self, now: &DateTime<Local>) -> Result<()> {
+        self.flush()?;
+        let path = expand_home(&render(&self.template, &self.app, now));
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        // A restart within the same period carries on with its file
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        let bytes = file.metadata().map(|m| m.len()).unwrap_or(0);
+        self.current = Some(OpenFile {
+            path,
+            writer: BufWriter::new(file),
+            period: self.rotation.period(now),
+            bytes,
+        });
+        self.prune()
+    }
+
+    /// Delete all but the newest `keep` files the template produced
+    fn prune(&self) -> Result<()> {
+        let (Some(keep), Some(current)) = (self.keep, &self.current) else {
+            return Ok(());
+        };
+        let template = expand_home(&self.template);
+        let (Some(dir), Some(name)) = (current.path.parent(), template.file_name()) else {
+            return Ok(());
+        };
+        let pattern = name_pattern(&name.to_string_lossy());
+        let mut files: Vec<(std::time::SystemTime, PathBuf)> = std::fs::read_dir(dir)
+            .with_context(|| format!("Failed to list {}", dir.display()))?
+            .flatten()
+            .filter(|entry| pattern.is_match(&entry.file_name().to_string_lossy()))
+            .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+            .collect();
+        // Newest first; the file just opened always stays
+        files.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+        files.retain(|(_, path)| *path != current.path);
+        for (_, path) in files.into_iter().skip(keep.saturating_sub(1)) {
+            std::fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::path::Path;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2024, 2, 17, hour, minute, 0)
+            .unwrap()
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "logs-parser-auto-export-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn file_names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_parse_rotation() {
+        assert_eq!(Rotation::parse("hourly"), Some(Rotation::Hourly));
+        assert_eq!(Rotation::parse("Daily"), Some(Rotation::Daily));
+        assert_eq!(Rotation::parse("50MB"), Some(Rotation::Size(50 << 20)));
+        assert_eq!(Rotation::parse("weekly"), None);
+        assert_eq!(Rotation::parse("0MB"), None);
+    }
+
+    #[test]
+    fn test_render_template() {
+        assert_eq!(
+            render("{app}/{date}-{hour}h-{time}.log", "myapp", &at(9, 5)),
+            "myapp/2024-02-17-09h-090500.log"
+        );
+        let pattern = name_pattern("logs-{app}-{date}.log");
+        assert!(pattern.is_match("logs-myapp-2024-02-17.log"));
+        assert!(!pattern.is_match("logs-myapp-2024-02-17.log.manifest.json"));
+        assert!(!pattern.is_match("notes.txt"));
+    }
+
+    #[test]
+    fn test_hourly_rotation_and_retention() {
+        let dir = temp_dir("hourly");
+        let template = format!("{}/logs-{{app}}-{{date}}-{{hour}}.log", dir.display());
+        let mut export = AutoExport::new(&template, Rotation::Hourly, Some(2));
+        export.set_app("myapp");
+        export.write("first", at(10, 0)).unwrap();
+        export.write("second", at(10, 59)).unwrap();
+        export.write("third", at(11, 0)).unwrap();
+        export.write("fourth", at(12, 30)).unwrap();
+        export.flush().unwrap();
+
+        assert_eq!(
+            file_names(&dir),
+            [
+                "logs-myapp-2024-02-17-11.log",
+                "logs-myapp-2024-02-17-12.log"
+            ]
+        );
+        let latest = std::fs::read_to_string(dir.join("logs-myapp-2024-02-17-12.log")).unwrap();
+        assert_eq!(latest, "fourth\n");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_size_rotation() {
+        let dir = temp_dir("size");
+        let template = format!("{}/{{time}}.log", dir.display());
+        let mut export = AutoExport::new(&template, Rotation::Size(10), None);
+        export.write("0123456789", at(10, 0)).unwrap();
+        export.write("next", at(10, 1)).unwrap();
+        export.write("same", at(10, 2)).unwrap();
+        export.flush().unwrap();
+
+        assert_eq!(file_names(&dir), ["100000.log", "100100.log"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,93 @@
+//! Recent history fetched when a Heroku stream (re)connects
+//!
+//! The stream asks for the last `stream_history` lines (at most
+//! [`HISTORY_LINES`]) before tailing, so the view fills straight away
+//! instead of waiting for new traffic. That history overlaps whatever was
+//! already seen — lines restored from the last run, or streamed before a
+//! reconnect — so raw lines are checked against the most recent ones and
+//! repeats are dropped.
+
+use std::collections::{HashSet, VecDeque};
+
+/// Most lines of history `heroku logs --num` returns, and the default
+pub const HISTORY_LINES: usize = 1500;
+
+/// Parse a `--num` line count, which Heroku allows from 1 to 1500
+pub fn parse_history_lines(value: &str) -> Option<usize> {
+    value
+        .parse()
+        .ok()
+        .filter(|lines| (1..=HISTORY_LINES).contains(lines))
+}
+
+/// The most recent raw lines, to recognise history already shown
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeenLines {
+    capacity: usize,
+    order: VecDeque<String>,
+    lines: HashSet<String>,
+}
+
+impl SeenLines {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            order: VecDeque::new(),
+            lines: HashSet::new(),
+        }
+    }
+
+    /// Remember `raw`, returning false if it was among the recent lines
+    pub fn first_sight(&mut self, raw: &str) -> bool {
+        if self.lines.contains(raw) {
+            return false;
+        }
+        self.lines.insert(raw.to_string());
+        self.order.push_back(raw.to_string());
+        if self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.lines.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+impl Default for SeenLines {
+    fn default() -> Self {
+        Self::new(HISTORY_LINES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_history_lines() {
+        assert_eq!(parse_history_lines("200"), Some(200));
+        assert_eq!(parse_history_lines("1500"), Some(1500));
+        assert_eq!(parse_history_lines("0"), None);
+        assert_eq!(parse_history_lines("1501"), None);
+        assert_eq!(parse_history_lines("lots"), None);
+    }
+
+    #[test]
+    fn test_repeats_are_dropped() {
+        let mut seen = SeenLines::default();
+        assert!(seen.first_sight("a"));
+        assert!(seen.first_sight("b"));
+        assert!(!seen.first_sight("a"));
+        assert!(seen.first_sight("c"));
+    }
+
+    #[test]
+    fn test_oldest_lines_are_forgotten() {
+        let mut seen = SeenLines::new(2);
+        seen.first_sight("a");
+        seen.first_sight("b");
+        seen.first_sight("c");
+        assert!(seen.first_sight("a"));
+        assert!(!seen.first_sight("c"));
+    }
+}
@@ -0,0 +1,196 @@
+//! Collapse high-frequency repeats, such as health checks, into summaries
+//!
+//! Once lines sharing a fingerprint arrive faster than [`DEFAULT_THRESHOLD`]
+//! per window, further repeats are held back and counted; a summary entry
+//! stands in for them each window:
+//!
+//! ```text
+//! 58 more like this in the last 60s: at=info method=GET path="/healthz" ...
+//! ```
+//!
+//! Warnings and errors are never coalesced.
+
+use crate::ignore::fingerprint;
+use crate::parser::{LogEntry, LogLevel};
+use chrono::{DateTime, Duration, FixedOffset};
+use std::collections::{HashMap, VecDeque};
+
+/// Repeats per window before a line counts as noise
+pub const DEFAULT_THRESHOLD: usize = 10;
+/// Seconds covered by each summary
+pub const DEFAULT_WINDOW_SECS: i64 = 60;
+/// Fingerprints tracked before quiet ones are forgotten
+const MAX_GROUPS: usize = 1000;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Group {
+    /// Arrival times within the current window
+    seen: VecDeque<DateTime<FixedOffset>>,
+    /// Start of the summary being accumulated, while coalescing
+    since: Option<DateTime<FixedOffset>>,
+    suppressed: usize,
+}
+
+/// Tracks repeat rates and decides which entries reach the buffer
+#[derive(Debug, Clone, PartialEq)]
+pub struct Coalescer {
+    threshold: usize,
+    window: Duration,
+    groups: HashMap<String, Group>,
+}
+
+impl Default for Coalescer {
+    fn default() -> Self {
+        Self::new(DEFAULT_THRESHOLD, Duration::seconds(DEFAULT_WINDOW_SECS))
+    }
+}
+
+impl Coalescer {
+    pub fn new(threshold: usize, window: Duration) -> Self {
+        Self {
+            threshold,
+            window,
+            groups: HashMap::new(),
+        }
+    }
+
+    /// Entries to buffer in place of `entry`: the entry itself, a summary of
+    /// the repeats it closes out, or nothing while repeats are being counted
+    pub fn push(&mut self, entry: LogEntry) -> Vec<LogEntry> {
+        if matches!(entry.level, LogLevel::Error | LogLevel::Warn) {
+            return vec![entry];
+        }
+        if self.groups.len() > MAX_GROUPS {
+            self.forget_quiet(entry.timestamp);
+        }
+
+        let now = entry.timestamp;
+        let cutoff = now - self.window;
+        let group = self.groups.entry(fingerprint(&entry)).or_default();
+        while group.seen.front().is_some_and(|&t| t < cutoff) {
+            group.seen.pop_front();
+        }
+        group.seen.push_back(now);
+
+        let Some(since) = group.since else {
+            if group.seen.len() > self.threshold {
+                group.since = Some(now);
+                group.suppressed = 1;
+                return Vec::new();
+            }
+            return vec![entry];
+        };
+
+        // The noise died down: report what was held back and pass this one
+        if group.seen.len() <= self.threshold {
+            let summary = summarize(&entry, group.suppressed, now - since);
+            group.since = None;
+            group.suppressed = 0;
+            return vec![summary, entry];
+        }
+
+        group.suppressed += 1;
+        if now - since >= self.window {
+            let summary = summarize(&entry, group.suppressed, now - since);
+            group.since = Some(now);
+            group.suppressed = 0;
+            return vec![summary];
+        }
+        Vec::new()
+    }
+
+    fn forget_quiet(&mut self, now: DateTime<FixedOffset>) {
+        let cutoff = now - self.window;
+        self.groups.retain(|_, group| {
+            group.since.is_some() || group.seen.back().is_some_and(|&t| t >= cutoff)
+        });
+    }
+}
+
+/// Summary entry standing in for `count` repeats of `sample`
+fn summarize(sample: &LogEntry, count: usize, span: Duration) -> LogEntry {
+    let mut summary = LogEntry::builder()
+        .timestamp(sample.timestamp)
+        .source(sample.source.clone())
+        .dyno(sample.dyno.clone())
+        .origin(sample.origin.clone())
+        .message(format!(
+            "{} more like this in the last {}s: {}",
+            count,
+            span.num_seconds().max(1),
+            sample.message
+        ))
+        .build()
+        .expect("sample source and dyno are valid");
+    summary.level = sample.level;
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_log_line;
+
+    fn health_check(second: u32) -> LogEntry {
+        parse_log_line(&format!(
+            "2024-02-17T10:{:02}:{:02}.000000+00:00 heroku[router]: at=info method=GET path=\"/healthz\" service={}ms status=200",
+            second / 60,
+            second % 60,
+            second % 7
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rare_lines_pass_through() {
+        let mut coalescer = Coalescer::new(3, Duration::seconds(60));
+
+        for second in [0, 30, 60, 90, 120] {
+            assert_eq!(coalescer.push(health_check(second)).len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_repeats_collapse_into_summaries() {
+        let mut coalescer = Coalescer::new(3, Duration::seconds(10));
+        let kept: Vec<LogEntry> = (0..30)
+            .flat_map(|second| coalescer.push(health_check(second)))
+            .collect();
+
+        // Three get through, then one summary per window
+        assert_eq!(kept.len(), 5);
+        assert_eq!(
+            kept[3].message,
+            "11 more like this in the last 10s: at=info method=GET path=\"/healthz\" service=6ms status=200"
+        );
+        assert!(kept[4]
+            .message
+            .starts_with("10 more like this in the last 10s"));
+        assert_eq!(kept[3].level, LogLevel::Info);
+    }
+
+    #[test]
+    fn test_quieting_down_flushes_summary() {
+        let mut coalescer = Coalescer::new(3, Duration::seconds(10));
+        for second in 0..6 {
+            coalescer.push(health_check(second));
+        }
+
+        let kept = coalescer.push(health_check(40));
+        assert_eq!(kept.len(), 2);
+        assert!(kept[0].message.starts_with("3 more like this"));
+        assert_eq!(kept[1], health_check(40));
+    }
+
+    #[test]
+    fn test_errors_are_never_coalesced() {
+        let mut coalescer = Coalescer::new(1, Duration::seconds(60));
+        let error =
+            parse_log_line("2024-02-17T10:30:45.123456+00:00 app[web.1]: ERROR connection refused")
+                .unwrap();
+
+        for _ in 0..5 {
+            assert_eq!(coalescer.push(error.clone()).len(), 1);
+        }
+    }
+}
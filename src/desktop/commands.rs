@@ -0,0 +1,573 @@
+//! Commands typed into the filter bar
+//!
+//! Input starting with `:` is a command rather than a filter query, e.g.
+//! `:await "migration complete"`.
+
+use crate::alert_rules::parse_condition;
+use crate::formats::LogFormat;
+use crate::presets;
+use crate::rate_alerts::DEFAULT_BASELINE;
+use crate::settings::parse_duration;
+use crate::share;
+use crate::sorting::{SortKey, SortOrder};
+use std::time::Duration;
+
+/// A parsed filter-bar command
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Alert once when an entry matching the query first appears
+    Await(String),
+    /// Alert when a minute holds more than `factor` times as many lines
+    /// matching the query as an average minute of the `baseline` before it
+    Alert {
+        query: String,
+        factor: f64,
+        baseline: Duration,
+    },
+    /// Alert when more than `threshold` lines matching the query arrive
+    /// within `window`, and keep the rule in the alerts file
+    AlertRule {
+        query: String,
+        threshold: usize,
+        window: Duration,
+    },
+    /// Remove every `:alert`
+    ClearAlerts,
+    /// Silence every alert for a while, or end the snooze (`None`)
+    Snooze(Option<Duration>),
+    /// Silence the `:alert` and `:await` rules with this query
+    Mute(String),
+    /// Let a muted rule alert again
+    Unmute(String),
+    /// Highlight a term in its own color; without a term, clear highlights
+    Highlight(Option<String>),
+    /// Count buffered entries matching every query term, optionally only
+    /// those from the last `since`
+    Count {
+        query: String,
+        since: Option<Duration>,
+    },
+    /// Replay a recorded session file
+    Replay(String),
+    /// Parse new lines as a format, or detect it again (`None`); with
+    /// `reparse`, the buffered lines too
+    Format {
+        format: Option<LogFormat>,
+        reparse: bool,
+    },
+    /// Reload the level rules and parse the buffered lines again
+    Reparse,
+    /// Order a paused list by a key, or back in time order (`None`)
+    Sort(Option<SortOrder>),
+    /// Save the lines currently shown to a file, or into a directory, under
+    /// `~/Downloads` unless given a path; with `encrypt`, the passphrase is
+    /// asked for separately so it never shows in the command
+    Export { path: Option<String>, encrypt: bool },
+    /// Serve the current view read-only on a port, or stop (`None`)
+    Share(Option<u16>),
+    /// Add an annotation to the session being recorded
+    Note(String),
+    /// Replace the filters with a saved preset's
+    Preset(String),
+    /// Save the current filters as a preset
+    SavePreset(String),
+    /// Forget a saved preset
+    DeletePreset(String),
+    /// List the saved presets
+    ListPresets,
+    /// Look for a newer release now
+    CheckUpdates,
+    /// Show or hide the settings and keys editor
+    EditSettings,
+    /// Show the lines around the most recent dyno crash
+    JumpToCrash,
+}
+
+/// Check whether input should be treated as a command
+pub fn is_command(input: &str) -> bool {
+    input.trim_start().starts_with(':')
+}
+
+/// Strip one pair of surrounding double quotes, if present
+fn unquote(arg: &str) -> &str {
+    arg.strip_prefix('"')
+        .and_then(|a| a.strip_suffix('"'))
+        .unwrap_or(arg)
+}
+
+/// Parse `:count` arguments, pulling out a `since:<age>` term
+fn parse_count(arg: &str) -> Result<Command, String> {
+    let mut since = None;
+    let mut terms = Vec::new();
+    for term in arg.split_whitespace() {
+        match term.strip_prefix("since:") {
+            Some(age) => {
+                since = Some(parse_duration(age).ok_or_else(|| format!("Invalid age: {}", age))?)
+            }
+            None => terms.push(term),
+        }
+    }
+    Ok(Command::Count {
+        query: terms.join(" "),
+        since,
+    })
+}
+
+/// Parse `:alert` arguments: a query, `>`, a factor such as `3x`, then an
+/// optional baseline length
+fn parse_alert(arg: &str) -> Result<Command, String> {
+    const USAGE: &str =
+        "Usage: :alert <query> > <factor>x [<baseline>], :alert <query> > <count> in <window>, or :alert off";
+    if arg == "off" {
+        return Ok(Command::ClearAlerts);
+    }
+    let (query, condition) = arg.rsplit_once('>').ok_or(USAGE)?;
+    let query = query.trim();
+    if let Some((threshold, window)) = parse_condition(condition) {
+        if query.is_empty() {
+            return Err(USAGE.to_string());
+        }
+        return Ok(Command::AlertRule {
+            query: query.to_string(),
+            threshold,
+            window,
+        });
+    }
+    let mut words = condition.split_whitespace();
+    let factor = words
+        .next()
+        .and_then(|word| word.strip_suffix(['x', '×']))
+        .and_then(|factor| factor.parse::<f64>().ok())
+        .filter(|factor| *factor > 0.0)
+        .ok_or(USAGE)?;
+    let baseline = match words.next() {
+        Some(age) => parse_duration(age)
+            .filter(|baseline| baseline.as_secs() >= 60)
+            .ok_or_else(|| format!("Invalid baseline: {} (one minute or more)", age))?,
+        None => DEFAULT_BASELINE,
+    };
+    if query.is_empty() || words.next().is_some() {
+        return Err(USAGE.to_string());
+    }
+    Ok(Command::Alert {
+        query: query.to_string(),
+        factor,
+        baseline,
+    })
+}
+
+/// Parse `:format` arguments: a format name or `auto`, then an optional
+/// `reparse`
+fn parse_format(arg: &str) -> Result<Command, String> {
+    const USAGE: &str = "Usage: :format heroku|json|syslog|plain|auto [reparse]";
+    let mut words = arg.split_whitespace();
+    let format = match words.next() {
+        Some("auto") => None,
+        Some(name) => Some(LogFormat::from_name(name).ok_or(USAGE)?),
+        None => return Err(USAGE.to_string()),
+    };
+    let reparse = match words.next() {
+        Some("reparse") => true,
+        Some(_) => return Err(USAGE.to_string()),
+        None => false,
+    };
+    Ok(Command::Format { format, reparse })
+}
+
+/// Parse `:sort` arguments: `time`, or a key and optional `asc`/`desc`
+fn parse_sort(arg: &str) -> Result<Command, String> {
+    const USAGE: &str = "Usage: :sort time|level|dyno|source|<field> [asc|desc]";
+    let mut words = arg.split_whitespace();
+    let key = match words.next() {
+        Some("time") => return Ok(Command::Sort(None)),
+        Some(key) => SortKey::parse(key),
+        None => return Err(USAGE.to_string()),
+    };
+    let descending = match words.next() {
+        Some("desc") => true,
+        Some("asc") | None => false,
+        Some(_) => return Err(USAGE.to_string()),
+    };
+    Ok(Command::Sort(Some(SortOrder { key, descending })))
+}
+
+/// Parse `:export` arguments: an optional path, then an optional `encrypt`
+fn parse_export(arg: &str) -> Result<Command, String> {
+    const USAGE: &str = "Usage: :export [<path>] [encrypt] (the passphrase is asked for next)";
+    const ENCRYPT: &str = "encrypt";
+    // The keyword as a word of its own, so paths may contain it
+    let keyword = arg.match_indices(ENCRYPT).map(|(at, _)| at).find(|&at| {
+        arg[..at].chars().last().is_none_or(char::is_whitespace)
+            && arg[at + ENCRYPT.len()..]
+                .chars()
+                .next()
+                .is_none_or(char::is_whitespace)
+    });
+    let (path, encrypt) = match keyword {
+        // Nothing may follow, so a passphrase typed here isn't used
+        Some(at) if !arg[at + ENCRYPT.len()..].trim().is_empty() => {
+            return Err(USAGE.to_string());
+        }
+        Some(at) => (arg[..at].trim(), true),
+        None => (arg, false),
+    };
+    let path = unquote(path);
+    Ok(Command::Export {
+        path: (!path.is_empty()).then(|| path.to_string()),
+        encrypt,
+    })
+}
+
+/// Parse `:preset` arguments: a name, `save <name>` or `delete <name>`
+fn parse_preset(arg: &str) -> Result<Command, String> {
+    const USAGE: &str = "Usage: :preset [<name>|save <name>|delete <name>]";
+    let words: Vec<&str> = arg.split_whitespace().collect();
+    let command = match words.as_slice() {
+        [] => return Ok(Command::ListPresets),
+        ["save", name] => Command::SavePreset(name.to_string()),
+        ["delete", name] => Command::DeletePreset(name.to_string()),
+        [name] => Command::Preset(name.to_string()),
+        _ => return Err(USAGE.to_string()),
+    };
+    match &command {
+        Command::SavePreset(name) if !presets::is_valid_name(name) => {
+            Err(format!("Invalid preset name: {}", name))
+        }
+        _ => Ok(command),
+    }
+}
+
+/// Parse a command line
+///
+/// Returns `None` if the input is not a command, or an error message
+/// describing why the command could not be parsed.
+pub fn parse_command(input: &str) -> Option<Result<Command, String>> {
+    let body = input.trim().strip_prefix(':')?;
+    let (name, arg) = body.split_once(char::is_whitespace).unwrap_or((body, ""));
+    let arg = unquote(arg.trim());
+
+    Some(match name {
+        "await" if arg.is_empty() => Err("Usage: :await <query>".to_string()),
+        "await" => Ok(Command::Await(arg.to_string())),
+        "alert" => parse_alert(arg),
+        "snooze" if arg == "off" => Ok(Command::Snooze(None)),
+        "snooze" => parse_duration(arg)
+            .filter(|length| !length.is_zero())
+            .map(|length| Command::Snooze(Some(length)))
+            .ok_or_else(|| "Usage: :snooze <duration>|off".to_string()),
+        "mute" if arg.is_empty() => Err("Usage: :mute <query>".to_string()),
+        "mute" => Ok(Command::Mute(arg.to_string())),
+        "unmute" if arg.is_empty() => Err("Usage: :unmute <query>".to_string()),
+        "unmute" => Ok(Command::Unmute(arg.to_string())),
+        "highlight" | "hl" if arg.is_empty() => Ok(Command::Highlight(None)),
+        "highlight" | "hl" => Ok(Command::Highlight(Some(arg.to_string()))),
+        "count" => parse_count(arg),
+        "replay" if arg.is_empty() => Err("Usage: :replay <session file>".to_string()),
+        "replay" => Ok(Command::Replay(arg.to_string())),
+        "format" => parse_format(arg),
+        "reparse" => Ok(Command::Reparse),
+        "sort" => parse_sort(arg),
+        "export" => parse_export(arg),
+        "share" if arg.is_empty() => Ok(Command::Share(Some(share::DEFAULT_PORT))),
+        "share" if arg == "off" => Ok(Command::Share(None)),
+        "share" => arg
+            .parse()
+            .map(|port| Command::Share(Some(port)))
+            .map_err(|_| "Usage: :share [<port>|off]".to_string()),
+        "note" if arg.is_empty() => Err("Usage: :note <text>".to_string()),
+        "note" => Ok(Command::Note(arg.to_string())),
+        "preset" => parse_preset(arg),
+        "update" => Ok(Command::CheckUpdates),
+        "settings" => Ok(Command::EditSettings),
+        "crash" => Ok(Command::JumpToCrash),
+        _ => Err(format!("Unknown command: :{}", name)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_not_a_command() {
+        assert!(!is_command("level:error"));
+        assert_eq!(parse_command("level:error"), None);
+    }
+
+    #[test]
+    fn test_parse_await_quoted() {
+        assert_eq!(
+            parse_command(":await \"migration complete\""),
+            Some(Ok(Command::Await("migration complete".to_string())))
+        );
+    }
+
+    #[test]
+    fn test_parse_await_query() {
+        assert_eq!(
+            parse_command("  :await level:error"),
+            Some(Ok(Command::Await("level:error".to_string())))
+        );
+    }
+
+    #[test]
+    fn test_parse_alert() {
+        assert_eq!(
+            parse_command(":alert level:error > 3x 30m"),
+            Some(Ok(Command::Alert {
+                query: "level:error".to_string(),
+                factor: 3.0,
+                baseline: Duration::from_secs(1800),
+            }))
+        );
+        assert_eq!(
+            parse_command(":alert status:5 > 2.5x"),
+            Some(Ok(Command::Alert {
+                query: "status:5".to_string(),
+                factor: 2.5,
+                baseline: DEFAULT_BASELINE,
+            }))
+        );
+        assert_eq!(parse_command(":alert off"), Some(Ok(Command::ClearAlerts)));
+        assert!(matches!(parse_command(":alert level:error"), Some(Err(_))));
+        assert!(matches!(parse_command(":alert > 3x"), Some(Err(_))));
+        assert!(matches!(
+            parse_command(":alert timeout > three"),
+            Some(Err(_))
+        ));
+        assert!(matches!(
+            parse_command(":alert timeout > 3x 10s"),
+            Some(Err(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_alert_rule() {
+        assert_eq!(
+            parse_command(":alert level:error > 20 in 1m"),
+            Some(Ok(Command::AlertRule {
+                query: "level:error".to_string(),
+                threshold: 20,
+                window: Duration::from_secs(60),
+            }))
+        );
+        assert!(matches!(parse_command(":alert > 0 in 1m"), Some(Err(_))));
+        assert!(matches!(
+            parse_command(":alert timeout > 5 in"),
+            Some(Err(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_snooze_and_mute() {
+        assert_eq!(
+            parse_command(":snooze 30m"),
+            Some(Ok(Command::Snooze(Some(Duration::from_secs(1800)))))
+        );
+        assert_eq!(
+            parse_command(":snooze off"),
+            Some(Ok(Command::Snooze(None)))
+        );
+        assert!(matches!(parse_command(":snooze"), Some(Err(_))));
+        assert!(matches!(parse_command(":snooze later"), Some(Err(_))));
+        assert_eq!(
+            parse_command(":mute level:error"),
+            Some(Ok(Command::Mute("level:error".to_string())))
+        );
+        assert_eq!(
+            parse_command(":unmute \"cache miss\""),
+            Some(Ok(Command::Unmute("cache miss".to_string())))
+        );
+        assert!(matches!(parse_command(":mute"), Some(Err(_))));
+    }
+
+    #[test]
+    fn test_parse_update() {
+        assert_eq!(parse_command(":update"), Some(Ok(Command::CheckUpdates)));
+    }
+
+    #[test]
+    fn test_parse_settings() {
+        assert_eq!(parse_command(":settings"), Some(Ok(Command::EditSettings)));
+    }
+
+    #[test]
+    fn test_parse_crash() {
+        assert_eq!(parse_command(":crash"), Some(Ok(Command::JumpToCrash)));
+    }
+
+    #[test]
+    fn test_parse_preset() {
+        assert_eq!(parse_command(":preset"), Some(Ok(Command::ListPresets)));
+        assert_eq!(
+            parse_command(":preset errors"),
+            Some(Ok(Command::Preset("errors".to_string())))
+        );
+        assert_eq!(
+            parse_command(":preset save slow-web"),
+            Some(Ok(Command::SavePreset("slow-web".to_string())))
+        );
+        assert_eq!(
+            parse_command(":preset delete errors"),
+            Some(Ok(Command::DeletePreset("errors".to_string())))
+        );
+        assert!(matches!(parse_command(":preset save a=b"), Some(Err(_))));
+        assert!(matches!(parse_command(":preset one two"), Some(Err(_))));
+    }
+
+    #[test]
+    fn test_parse_highlight() {
+        assert_eq!(
+            parse_command(":hl \"cache miss\""),
+            Some(Ok(Command::Highlight(Some("cache miss".to_string()))))
+        );
+        assert_eq!(
+            parse_command(":highlight"),
+            Some(Ok(Command::Highlight(None)))
+        );
+    }
+
+    #[test]
+    fn test_parse_count() {
+        assert_eq!(
+            parse_command(":count level:error since:15m"),
+            Some(Ok(Command::Count {
+                query: "level:error".to_string(),
+                since: Some(Duration::from_secs(900)),
+            }))
+        );
+        assert_eq!(
+            parse_command(":count"),
+            Some(Ok(Command::Count {
+                query: String::new(),
+                since: None,
+            }))
+        );
+        assert!(matches!(parse_command(":count since:soon"), Some(Err(_))));
+    }
+
+    #[test]
+    fn test_parse_replay() {
+        assert_eq!(
+            parse_command(":replay \"/tmp/my session.log\""),
+            Some(Ok(Command::Replay("/tmp/my session.log".to_string())))
+        );
+        assert!(matches!(parse_command(":replay"), Some(Err(_))));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(parse_command(":await"), Some(Err(_))));
+        assert!(matches!(parse_command(":bogus x"), Some(Err(_))));
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(
+            parse_command(":format json"),
+            Some(Ok(Command::Format {
+                format: Some(LogFormat::Json),
+                reparse: false,
+            }))
+        );
+        assert_eq!(
+            parse_command(":format auto reparse"),
+            Some(Ok(Command::Format {
+                format: None,
+                reparse: true,
+            }))
+        );
+        assert!(matches!(parse_command(":format"), Some(Err(_))));
+        assert!(matches!(parse_command(":format xml"), Some(Err(_))));
+        assert!(matches!(parse_command(":format json now"), Some(Err(_))));
+        assert_eq!(parse_command(":reparse"), Some(Ok(Command::Reparse)));
+    }
+
+    #[test]
+    fn test_parse_sort() {
+        assert_eq!(
+            parse_command(":sort service desc"),
+            Some(Ok(Command::Sort(Some(SortOrder {
+                key: SortKey::Field("service".to_string()),
+                descending: true,
+            }))))
+        );
+        assert_eq!(
+            parse_command(":sort level"),
+            Some(Ok(Command::Sort(Some(SortOrder {
+                key: SortKey::Level,
+                descending: false,
+            }))))
+        );
+        assert_eq!(parse_command(":sort time"), Some(Ok(Command::Sort(None))));
+        assert!(matches!(parse_command(":sort"), Some(Err(_))));
+        assert!(matches!(
+            parse_command(":sort level sideways"),
+            Some(Err(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_export() {
+        assert_eq!(
+            parse_command(":export"),
+            Some(Ok(Command::Export {
+                path: None,
+                encrypt: false
+            }))
+        );
+        assert_eq!(
+            parse_command(":export encrypt"),
+            Some(Ok(Command::Export {
+                path: None,
+                encrypt: true
+            }))
+        );
+        assert_eq!(
+            parse_command(":export ~/incidents/db outage.log encrypt"),
+            Some(Ok(Command::Export {
+                path: Some("~/incidents/db outage.log".to_string()),
+                encrypt: true
+            }))
+        );
+        assert_eq!(
+            parse_command(":export ~/encrypted/"),
+            Some(Ok(Command::Export {
+                path: Some("~/encrypted/".to_string()),
+                encrypt: false
+            }))
+        );
+        // A passphrase never goes in the command, where it would show
+        assert!(matches!(
+            parse_command(":export encrypt \"correct horse\""),
+            Some(Err(_))
+        ));
+        assert!(matches!(
+            parse_command(":export x.log encrypt pw"),
+            Some(Err(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_share() {
+        assert_eq!(
+            parse_command(":share"),
+            Some(Ok(Command::Share(Some(share::DEFAULT_PORT))))
+        );
+        assert_eq!(
+            parse_command(":share 9000"),
+            Some(Ok(Command::Share(Some(9000))))
+        );
+        assert_eq!(parse_command(":share off"), Some(Ok(Command::Share(None))));
+        assert!(matches!(parse_command(":share everyone"), Some(Err(_))));
+    }
+
+    #[test]
+    fn test_parse_note() {
+        assert_eq!(
+            parse_command(":note rollback started"),
+            Some(Ok(Command::Note("rollback started".to_string())))
+        );
+        assert!(matches!(parse_command(":note"), Some(Err(_))));
+    }
+}
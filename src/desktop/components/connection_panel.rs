@@ -1,6 +1,7 @@
 //! Connection panel component for app selection and connect/disconnect
 
 use crate::heroku_cli::AppInfo;
+use crate::i18n::t;
 use dioxus::prelude::*;
 
 use super::custom_select::{CustomSelect, SelectOption};
@@ -40,18 +41,18 @@ pub fn ConnectionPanel(
             if is_logging_in {
                 span {
                     style: "font-size: 14px; flex: 1; color: var(--warning);",
-                    "Waiting for browser login..."
+                    {t("login.waiting")}
                 }
                 button {
                     class: "btn btn-neutral",
                     onclick: move |_| on_cancel_login.call(()),
-                    "Cancel"
+                    {t("common.cancel")}
                 }
             } else if is_auth_error {
                 button {
                     class: "btn btn-connect",
                     onclick: move |_| on_login.call(()),
-                    "Login to Heroku"
+                    {t("login.button")}
                 }
             } else {
                 div {
@@ -59,13 +60,13 @@ pub fn ConnectionPanel(
 
                     label {
                         style: "color: var(--text-dim); font-size: 12px; font-weight: 500;",
-                        "Heroku App"
+                        {t("app.label")}
                     }
 
                     CustomSelect {
                         options: app_options,
                         value: selected_app.clone(),
-                        placeholder: "Select an app...".to_string(),
+                        placeholder: t("app.placeholder").to_string(),
                         disabled: is_connected || is_connecting,
                         on_change: move |val: String| {
                             on_app_select.call(val);
@@ -77,20 +78,20 @@ pub fn ConnectionPanel(
                     button {
                         class: "btn btn-disconnect",
                         onclick: move |_| on_disconnect.call(()),
-                        "Disconnect"
+                        {t("disconnect")}
                     }
                 } else if is_connecting {
                     button {
                         class: "btn btn-neutral",
                         disabled: true,
-                        "Connecting..."
+                        {t("connecting")}
                     }
                 } else {
                     button {
                         class: "btn btn-connect",
                         disabled: selected_app.is_none(),
                         onclick: move |_| on_connect.call(()),
-                        "Connect"
+                        {t("connect")}
                     }
                 }
             }
@@ -101,13 +102,13 @@ pub fn ConnectionPanel(
 
                 span {
                     class: "theme-picker-label",
-                    "Theme:"
+                    {t("theme.label")}
                 }
 
                 CustomSelect {
                     options: theme_options,
                     value: Some(theme.clone()),
-                    placeholder: "Select theme...".to_string(),
+                    placeholder: t("theme.placeholder").to_string(),
                     on_change: move |val: String| {
                         on_theme_change.call(val);
                     },
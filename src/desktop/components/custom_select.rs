@@ -30,6 +30,8 @@ pub struct CustomSelectProps {
 #[component]
 pub fn CustomSelect(props: CustomSelectProps) -> Element {
     let mut is_open = use_signal(|| false);
+    // Option under the keyboard cursor while the list is open
+    let mut highlighted = use_signal(|| None::<usize>);
 
     let selected_label = props
         .options
@@ -43,6 +45,51 @@ pub fn CustomSelect(props: CustomSelectProps) -> Element {
     let options = props.options.clone();
     let current_value = props.value.clone();
 
+    let selected_idx = options
+        .iter()
+        .position(|o| Some(&o.value) == current_value.as_ref());
+    let option_count = options.len();
+    let key_options = options.clone();
+
+    // Arrows open the list and move the cursor, Enter/Space pick, Escape closes
+    let on_key = move |evt: Event<KeyboardData>| {
+        if disabled || option_count == 0 {
+            return;
+        }
+        let step = |delta: isize| {
+            let from = highlighted().or(selected_idx).map_or(-1, |i| i as isize);
+            (from + delta).clamp(0, option_count as isize - 1) as usize
+        };
+        let key = evt.key();
+        let picks = key == Key::Enter || key == Key::Character(" ".to_string());
+        match key {
+            Key::ArrowDown | Key::ArrowUp => {
+                let delta = if key == Key::ArrowDown { 1 } else { -1 };
+                if is_open() {
+                    highlighted.set(Some(step(delta)));
+                } else {
+                    is_open.set(true);
+                    highlighted.set(Some(selected_idx.unwrap_or(0)));
+                }
+            }
+            _ if picks && is_open() => {
+                if let Some(opt) = highlighted().and_then(|i| key_options.get(i)) {
+                    props.on_change.call(opt.value.clone());
+                }
+                is_open.set(false);
+            }
+            Key::Escape if is_open() => is_open.set(false),
+            Key::Tab => {
+                is_open.set(false);
+                return;
+            }
+            _ => return,
+        }
+        // Handled keys must not reach app-level shortcuts
+        evt.prevent_default();
+        evt.stop_propagation();
+    };
+
     let trigger_class = if disabled {
         "select-trigger select-trigger--disabled"
     } else {
@@ -52,6 +99,7 @@ pub fn CustomSelect(props: CustomSelectProps) -> Element {
     rsx! {
         div {
             class: "custom-select",
+            onkeydown: on_key,
 
             // Backdrop: catches clicks outside the dropdown to close it
             if is_open() {
@@ -66,8 +114,11 @@ pub fn CustomSelect(props: CustomSelectProps) -> Element {
                 class: "{trigger_class}",
                 r#type: "button",
                 disabled,
+                aria_haspopup: "listbox",
+                aria_expanded: "{is_open()}",
                 onclick: move |_| {
                     if !disabled {
+                        highlighted.set(selected_idx);
                         is_open.set(!is_open());
                     }
                 },
@@ -98,18 +149,23 @@ pub fn CustomSelect(props: CustomSelectProps) -> Element {
             if is_open() {
                 div {
                     class: "select-dropdown",
-                    for opt in options {
+                    role: "listbox",
+                    for (idx, opt) in options.into_iter().enumerate() {
                         {
                             let is_selected = current_value.as_ref() == Some(&opt.value);
                             let opt_value = opt.value.clone();
-                            let opt_class = if is_selected {
-                                "select-option select-option--selected"
-                            } else {
-                                "select-option"
+                            let opt_class = match (is_selected, highlighted() == Some(idx)) {
+                                (true, true) => "select-option select-option--selected select-option--active",
+                                (true, false) => "select-option select-option--selected",
+                                (false, true) => "select-option select-option--active",
+                                (false, false) => "select-option",
                             };
                             rsx! {
                                 div {
                                     class: "{opt_class}",
+                                    role: "option",
+                                    aria_selected: "{is_selected}",
+                                    onmouseenter: move |_| highlighted.set(Some(idx)),
                                     onclick: move |_| {
                                         props.on_change.call(opt_value.clone());
                                         is_open.set(false);
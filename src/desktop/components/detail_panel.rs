@@ -0,0 +1,126 @@
+//! Side pane showing every part of the selected log entry
+
+use crate::i18n::t;
+use crate::parser::LogEntry;
+use crate::requests::{StepKind, Waterfall, WaterfallStep};
+use dioxus::prelude::*;
+
+/// Position and length of a step's bar, as percentages of the request
+fn bar_style(step: &WaterfallStep, total_ms: i64) -> String {
+    let total = total_ms.max(1) as f64;
+    let left = step.from_ms as f64 / total * 100.0;
+    let width = (step.at_ms - step.from_ms) as f64 / total * 100.0;
+    format!("left: {:.1}%; width: max({:.1}%, 2px)", left, width)
+}
+
+#[component]
+pub fn DetailPanel(
+    entry: LogEntry,
+    /// Address a router line's request can be repeated at
+    request_url: Option<String>,
+    /// Timeline of the buffered lines sharing the entry's request id
+    waterfall: Option<Waterfall>,
+    /// Command line that opens the entry's session on it, for a replayed line
+    session_link: Option<String>,
+    on_open_url: EventHandler<String>,
+    on_copy_link: EventHandler<String>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let mut fields: Vec<_> = entry.fields.iter().collect();
+    fields.sort();
+
+    rsx! {
+        div {
+            class: "detail-panel",
+            role: "region",
+            aria_label: t("detail.title"),
+
+            div {
+                class: "detail-header",
+                span { class: "detail-title", {t("detail.title")} }
+                button {
+                    class: "detail-close",
+                    title: t("detail.close_title"),
+                    onclick: move |_| on_close.call(()),
+                    "×"
+                }
+            }
+
+            dl {
+                dt { {t("detail.time")} }
+                dd { "{entry.timestamp.to_rfc3339()}" }
+                dt { {t("detail.level")} }
+                dd { "{entry.level:?}" }
+                dt { {t("detail.source")} }
+                dd { "{entry.source}[{entry.dyno}]" }
+                if !entry.origin.is_empty() {
+                    dt { {t("detail.origin")} }
+                    dd { "{entry.origin}" }
+                }
+                if let (Some(id), Some(link)) = (entry.session_id, session_link) {
+                    dt { {t("detail.session_line")} }
+                    dd {
+                        span { class: "detail-text", "#{id} " }
+                        button {
+                            class: "detail-open",
+                            title: "{link}",
+                            onclick: move |_| on_copy_link.call(link.clone()),
+                            {t("detail.copy_link")}
+                        }
+                    }
+                }
+                dt { {t("detail.message")} }
+                dd { class: "detail-text", "{entry.message}" }
+                if !fields.is_empty() {
+                    dt { {t("detail.fields")} }
+                    dd {
+                        for (key, value) in fields {
+                            div {
+                                key: "{key}",
+                                span { class: "detail-field-key", "{key}" }
+                                " = {value}"
+                            }
+                        }
+                    }
+                }
+                if let Some(waterfall) = waterfall {
+                    dt { {t("detail.timeline")} }
+                    dd {
+                        for (idx, step) in waterfall.steps.iter().enumerate() {
+                            div {
+                                key: "{idx}",
+                                class: "waterfall-step",
+                                div {
+                                    class: "waterfall-row",
+                                    span { class: "waterfall-label", "{step.label}" }
+                                    span {
+                                        class: "waterfall-track",
+                                        span {
+                                            class: if step.kind == StepKind::App { "waterfall-bar" } else { "waterfall-bar waterfall-router" },
+                                            style: bar_style(step, waterfall.total_ms),
+                                        }
+                                    }
+                                    span { class: "waterfall-offset", "+{step.at_ms}ms" }
+                                }
+                                div { class: "waterfall-message", title: "{step.message}", "{step.message}" }
+                            }
+                        }
+                    }
+                }
+                if let Some(url) = request_url {
+                    dt { {t("detail.request")} }
+                    dd {
+                        span { class: "detail-text", "{url} " }
+                        button {
+                            class: "detail-open",
+                            onclick: move |_| on_open_url.call(url.clone()),
+                            {t("detail.open_request")}
+                        }
+                    }
+                }
+                dt { {t("detail.raw")} }
+                dd { class: "detail-text", "{entry.raw}" }
+            }
+        }
+    }
+}
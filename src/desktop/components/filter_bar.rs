@@ -1,44 +1,111 @@
 //! Filter bar component for adding and managing log filters
 
 use dioxus::prelude::*;
+use super::TimeRangePicker;
 use crate::filters::Filter;
+use crate::highlights::Highlights;
+use crate::i18n::{t, tf};
+use std::time::Duration;
+
+/// How long typing must pause before the in-progress query is previewed
+const PREVIEW_DEBOUNCE: Duration = Duration::from_millis(200);
 
 #[derive(Props, Clone, PartialEq)]
 pub struct FilterBarProps {
     pub filters: Vec<Filter>,
+    /// Whether each filter is turned on
+    pub enabled: Vec<bool>,
+    /// Turn a filter off or back on, by index
+    pub on_toggle_filter: EventHandler<usize>,
+    /// Remove a filter, by index
+    pub on_remove_filter: EventHandler<usize>,
+    /// Queries registered with `:await` that haven't matched yet
+    pub awaiting: Vec<String>,
+    /// Highlighted terms, shown as a color legend
+    pub highlights: Highlights,
+    /// Would-be match count while a provisional filter is being previewed
+    pub preview_count: Option<usize>,
     pub on_add_filter: EventHandler<String>,
+    pub on_preview_filter: EventHandler<String>,
+    pub on_cancel_preview: EventHandler<()>,
     pub on_clear_filters: EventHandler<()>,
+    /// Drop the time range filter
+    pub on_clear_time_range: EventHandler<()>,
+    /// True while a clear is waiting for confirmation
+    pub confirm_clear: bool,
+    /// Shortcut bound to clearing filters, shown as a hint
+    pub clear_key: String,
     pub on_toggle_mode: EventHandler<()>,
+    pub on_export_timeline: EventHandler<()>,
+    /// True while the stream is being recorded to a session file
+    pub recording: bool,
+    pub on_toggle_recording: EventHandler<()>,
     pub filter_mode_and: bool,
+    /// Text a shortcut starts the input with, taken once it is shown
+    pub prompt: Signal<Option<String>>,
 }
 
 #[component]
 pub fn FilterBar(props: FilterBarProps) -> Element {
     let mut input_value = use_signal(String::new);
+    let mut show_time_range = use_signal(|| false);
+    let time_range_active = props
+        .filters
+        .iter()
+        .any(|f| matches!(f, Filter::TimeRange(..)));
     let filter_mode = if props.filter_mode_and { "AND" } else { "OR" };
 
+    // Fill in and focus the input when a shortcut opens a prompt
+    let mut prompt = props.prompt;
+    use_effect(move || {
+        if let Some(text) = prompt() {
+            input_value.set(text);
+            prompt.set(None);
+            let _ = document::eval("document.getElementById('filter-input')?.focus()");
+        }
+    });
+
     let on_input = move |evt: Event<FormData>| {
-        input_value.set(evt.value());
-    };
+        let value = evt.value();
+        input_value.set(value.clone());
 
-    let on_key_press = move |evt: Event<KeyboardData>| {
-        if evt.key() == Key::Enter {
-            let value = input_value();
-            if !value.is_empty() {
-                props.on_add_filter.call(value.clone());
-                input_value.set(String::new());
+        // Debounce: only preview once the input has settled
+        spawn(async move {
+            tokio::time::sleep(PREVIEW_DEBOUNCE).await;
+            if input_value() == value {
+                props.on_preview_filter.call(value);
             }
-        }
+        });
     };
 
-    let on_add_click = move |_| {
+    // Commit the typed query as a filter
+    let mut apply_search = move || {
         let value = input_value();
         if !value.is_empty() {
-            props.on_add_filter.call(value.clone());
+            props.on_add_filter.call(value);
             input_value.set(String::new());
         }
     };
 
+    // Discard the typed query and revert to the committed filters. Clearing
+    // the input also invalidates any debounced preview still in flight.
+    let mut cancel_search = move || {
+        input_value.set(String::new());
+        props.on_cancel_preview.call(());
+    };
+
+    let on_key_press = move |evt: Event<KeyboardData>| {
+        // Typed characters must not trigger app-level shortcuts
+        evt.stop_propagation();
+        match evt.key() {
+            Key::Enter => apply_search(),
+            Key::Escape => cancel_search(),
+            _ => {}
+        }
+    };
+
+    let on_add_click = move |_| apply_search();
+
     let on_clear_click = move |_| {
         props.on_clear_filters.call(());
     };
@@ -56,44 +123,130 @@ pub fn FilterBar(props: FilterBarProps) -> Element {
                 style: "display: flex; gap: 10px; align-items: center; margin-bottom: 10px;",
 
                 input {
+                    id: "filter-input",
                     r#type: "text",
                     class: "themed-input",
                     value: "{input_value}",
-                    placeholder: "Enter filter (text, dyno:web.1, source:app, level:error, /regex/)",
+                    placeholder: t("filter.placeholder"),
+                    aria_label: t("filter.placeholder"),
                     oninput: on_input,
                     onkeydown: on_key_press,
                     style: "flex: 1;",
                 }
 
+                if let Some(count) = props.preview_count {
+                    span {
+                        class: "filter-preview-count",
+                        {tf("filter.matches", &[("count", &count)])}
+                    }
+                }
+
                 button {
                     class: "btn btn-connect",
                     style: "padding: 8px 16px;",
                     onclick: on_add_click,
-                    "Add Filter"
+                    {t("filter.add")}
                 }
 
                 button {
                     class: "btn btn-disconnect",
                     style: "padding: 8px 16px;",
+                    title: tf("filter.clear_title", &[("key", &props.clear_key)]),
                     onclick: on_clear_click,
-                    "Clear"
+                    if props.confirm_clear { {t("filter.confirm_clear")} } else { {t("filter.clear")} }
                 }
 
                 button {
                     class: "btn btn-neutral",
                     style: "padding: 8px 16px;",
                     onclick: on_toggle_click,
-                    "Toggle {filter_mode}"
+                    {tf("filter.toggle_mode", &[("mode", &filter_mode)])}
+                }
+
+                button {
+                    class: if time_range_active { "btn btn-connect" } else { "btn btn-neutral" },
+                    style: "padding: 8px 16px;",
+                    title: t("time.title"),
+                    aria_expanded: "{show_time_range}",
+                    onclick: move |_| show_time_range.toggle(),
+                    {t("time.button")}
+                }
+
+                button {
+                    class: "btn btn-neutral",
+                    style: "padding: 8px 16px;",
+                    title: t("export.timeline_title"),
+                    onclick: move |_| props.on_export_timeline.call(()),
+                    {t("export.timeline")}
+                }
+
+                button {
+                    class: if props.recording { "btn btn-disconnect" } else { "btn btn-neutral" },
+                    style: "padding: 8px 16px;",
+                    title: t("record.title"),
+                    onclick: move |_| props.on_toggle_recording.call(()),
+                    if props.recording { {t("record.stop")} } else { {t("record.start")} }
+                }
+            }
+
+            if show_time_range() {
+                TimeRangePicker {
+                    active: time_range_active,
+                    on_apply: move |query| props.on_add_filter.call(query),
+                    on_clear: move |_| props.on_clear_time_range.call(()),
                 }
             }
 
-            if !props.filters.is_empty() {
+            if !props.filters.is_empty() || !props.awaiting.is_empty() || !props.highlights.is_empty() {
                 div {
                     style: "display: flex; flex-wrap: wrap; gap: 8px;",
-                    for filter in props.filters.iter() {
+                    role: "list",
+                    for (idx, filter) in props.filters.iter().enumerate() {
                         div {
-                            class: "filter-tag",
+                            key: "{idx}",
+                            class: if props.enabled.get(idx).copied().unwrap_or(true) { "filter-tag filter-tag-toggle" } else { "filter-tag filter-tag-toggle filter-tag-off" },
+                            role: "listitem",
+                            tabindex: "0",
+                            title: t("filter.toggle_title"),
+                            onclick: move |_| props.on_toggle_filter.call(idx),
+                            onkeydown: move |evt: Event<KeyboardData>| {
+                                // Keys on a focused chip act on it, not the app
+                                evt.stop_propagation();
+                                match evt.key() {
+                                    Key::Enter => props.on_toggle_filter.call(idx),
+                                    Key::Character(c) if c == " " => props.on_toggle_filter.call(idx),
+                                    Key::Delete | Key::Backspace => props.on_remove_filter.call(idx),
+                                    Key::Character(c) if c.eq_ignore_ascii_case("d") => props.on_remove_filter.call(idx),
+                                    _ => {}
+                                }
+                            },
                             "{filter.display()}"
+                            button {
+                                class: "filter-tag-remove",
+                                title: t("filter.remove_title"),
+                                aria_label: t("filter.remove_title"),
+                                onclick: move |evt| {
+                                    evt.stop_propagation();
+                                    props.on_remove_filter.call(idx);
+                                },
+                                "×"
+                            }
+                        }
+                    }
+                    for query in props.awaiting.iter() {
+                        div {
+                            class: "filter-tag filter-tag-await",
+                            role: "listitem",
+                            tabindex: "0",
+                            {tf("filter.awaiting", &[("query", query)])}
+                        }
+                    }
+                    for highlight in props.highlights.iter() {
+                        div {
+                            class: "filter-tag",
+                            role: "listitem",
+                            tabindex: "0",
+                            mark { class: "hl hl-{highlight.color}", "{highlight.label}" }
                         }
                     }
                 }
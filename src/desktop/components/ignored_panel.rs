@@ -0,0 +1,35 @@
+//! Panel listing ignored fingerprints, each with a button to stop ignoring it
+
+use crate::i18n::t;
+use dioxus::prelude::*;
+
+#[component]
+pub fn IgnoredPanel(fingerprints: Vec<String>, on_remove: EventHandler<usize>) -> Element {
+    rsx! {
+        div {
+            class: "toolbar-bar requests-panel",
+
+            if fingerprints.is_empty() {
+                div {
+                    class: "requests-empty",
+                    {t("ignored.empty")}
+                }
+            }
+
+            for (idx, fingerprint) in fingerprints.iter().enumerate() {
+                div {
+                    key: "{fingerprint}",
+                    class: "pinned-entry",
+
+                    span { class: "log-msg", "{fingerprint}" }
+                    button {
+                        class: "pinned-remove",
+                        title: t("ignored.remove_title"),
+                        onclick: move |_| on_remove.call(idx),
+                        "×"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,19 +1,77 @@
 //! Log view component for displaying filtered log entries
 
+use chrono::{DateTime, FixedOffset};
 use dioxus::prelude::*;
-use crate::parser::{LogEntry, LogLevel};
+use crate::i18n::t;
+use crate::parser::{day_boundaries, LogEntry, LogLevel};
+use crate::truncate::{format_size, truncate};
+use std::collections::{HashMap, HashSet};
+use crate::dyno_colors::DynoPalette;
+use crate::filters::{self, Filter};
+use crate::highlights::Highlights;
+use crate::rewrite::RewriteRules;
+use crate::source_refs::{self, SourceRef};
+use crate::sql;
+use crate::thresholds::ThresholdRules;
 
 #[derive(Props, Clone, PartialEq)]
 pub struct LogViewProps {
     pub logs: Vec<LogEntry>,
     pub scroll_position: f64,
+    /// strftime-style format for the time column
+    pub time_format: String,
+    /// When set, show each entry's elapsed time since this instant
+    pub elapsed_since: Option<DateTime<FixedOffset>>,
+    /// Entries older than this are dimmed
+    pub dim_before: Option<DateTime<FixedOffset>>,
+    /// Readability rewrites applied to displayed messages
+    pub rewrites: RewriteRules,
+    /// Row styles for entries whose numeric fields cross a threshold
+    pub thresholds: ThresholdRules,
+    /// Highlight keywords in messages containing SQL statements
+    pub sql_highlight: bool,
+    /// Terms marked in color wherever they occur
+    pub highlights: Highlights,
+    /// Text and regex filters whose matches are marked in each message
+    pub search: Vec<Filter>,
+    /// Show which input each entry came from, once there is more than one
+    pub show_origin: bool,
+    /// When set, color each dyno name from this palette
+    pub dyno_palette: Option<DynoPalette>,
+    /// Shorten messages longer than this many characters until clicked
+    pub truncate_at: Option<usize>,
+    /// Called with a clicked `file:line` reference
+    pub on_open_ref: EventHandler<SourceRef>,
+    /// Called to hide every line like this one
+    pub on_ignore: EventHandler<LogEntry>,
+    /// Called to keep this entry in the pinned strip
+    pub on_pin: EventHandler<LogEntry>,
+    /// Raw line of the entry shown in the detail pane
+    pub selected: Option<String>,
+    /// Called to show an entry in the detail pane (Enter or double-click)
+    pub on_select: EventHandler<LogEntry>,
+    /// Called when a row gets keyboard focus
+    pub on_focus: EventHandler<LogEntry>,
+    /// Called whenever the list is scrolled
+    pub on_scroll: EventHandler<()>,
 }
 
-fn log_row_class(level: LogLevel) -> &'static str {
-    match level {
-        LogLevel::Error => "log-entry log-entry-error",
-        _ => "log-entry",
+fn log_row_class(level: LogLevel, stale: bool, selected: bool, thresholds: &str) -> String {
+    let mut class = String::from("log-entry");
+    if level == LogLevel::Error {
+        class.push_str(" log-entry-error");
+    }
+    if !thresholds.is_empty() {
+        class.push(' ');
+        class.push_str(thresholds);
+    }
+    if stale {
+        class.push_str(" log-entry-stale");
     }
+    if selected {
+        class.push_str(" log-entry-selected");
+    }
+    class
 }
 
 fn level_class(level: LogLevel) -> &'static str {
@@ -26,31 +84,295 @@ fn level_class(level: LogLevel) -> &'static str {
     }
 }
 
+fn render_message(
+    message: &str,
+    sql_highlight: bool,
+    highlights: &Highlights,
+    search: &[Filter],
+    on_open_ref: EventHandler<SourceRef>,
+) -> Element {
+    if !highlights.is_empty() {
+        let spans = highlights.spans(message);
+        if spans.iter().any(|span| span.color.is_some()) {
+            return rsx! {
+                span {
+                    class: "log-msg",
+                    for span in spans {
+                        if let Some(color) = span.color {
+                            mark { class: "hl hl-{color}", "{span.text}" }
+                        } else {
+                            "{span.text}"
+                        }
+                    }
+                }
+            };
+        }
+    }
+
+    let hits = filters::match_ranges(search, message);
+    if !hits.is_empty() {
+        let mut pieces = Vec::new();
+        let mut last = 0;
+        for (start, end) in hits {
+            pieces.push((&message[last..start], false));
+            pieces.push((&message[start..end], true));
+            last = end;
+        }
+        pieces.push((&message[last..], false));
+        return rsx! {
+            span {
+                class: "log-msg",
+                for (text, hit) in pieces {
+                    if hit {
+                        mark { class: "search-hit", "{text}" }
+                    } else {
+                        "{text}"
+                    }
+                }
+            }
+        };
+    }
+
+    if !sql_highlight || !sql::is_sql(message) {
+        return render_refs(message, on_open_ref);
+    }
+
+    // Hovering shows the statement with one clause per line
+    rsx! {
+        span {
+            class: "log-msg",
+            title: sql::format_sql(message),
+            for token in sql::highlight(message) {
+                if token.keyword {
+                    span { class: "sql-keyword", "{token.text}" }
+                } else {
+                    "{token.text}"
+                }
+            }
+        }
+    }
+}
+
+/// Render a message with its `file:line` references as clickable links
+fn render_refs(message: &str, on_open_ref: EventHandler<SourceRef>) -> Element {
+    let refs = source_refs::find_refs(message);
+    if refs.is_empty() {
+        return rsx! {
+            span { class: "log-msg", "{message}" }
+        };
+    }
+
+    let mut last = 0;
+    let mut pieces = Vec::new();
+    for source_ref in refs {
+        pieces.push((message[last..source_ref.start].to_string(), None));
+        last = source_ref.end;
+        pieces.push((
+            message[source_ref.start..source_ref.end].to_string(),
+            Some(source_ref),
+        ));
+    }
+    pieces.push((message[last..].to_string(), None));
+
+    rsx! {
+        span {
+            class: "log-msg",
+            for (text, source_ref) in pieces {
+                if let Some(source_ref) = source_ref {
+                    span {
+                        class: "source-ref",
+                        role: "link",
+                        tabindex: "0",
+                        title: t("log.open_ref_title"),
+                        onclick: {
+                            let source_ref = source_ref.clone();
+                            move |_| on_open_ref.call(source_ref.clone())
+                        },
+                        onkeydown: move |evt: Event<KeyboardData>| {
+                            if evt.key() == Key::Enter {
+                                evt.stop_propagation();
+                                on_open_ref.call(source_ref.clone());
+                            }
+                        },
+                        "{text}"
+                    }
+                } else {
+                    "{text}"
+                }
+            }
+        }
+    }
+}
+
+/// Fields of a structured line as `key=value` pairs, sorted by key, leaving
+/// out those the message already shows (e.g. a router line's logfmt)
+fn format_fields(fields: &HashMap<String, String>, message: &str) -> String {
+    let mut pairs: Vec<_> = fields
+        .iter()
+        .filter(|(key, _)| !message.contains(&format!("{}=", key)))
+        .collect();
+    pairs.sort();
+    pairs
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 #[component]
 pub fn LogView(props: LogViewProps) -> Element {
     if props.logs.is_empty() {
         return rsx! {
             div {
                 class: "log-view-empty",
-                "No logs to display. Waiting for log input..."
+                {t("log.empty")}
             }
         };
     }
 
+    // Raw lines of long entries the user expanded
+    let mut expanded = use_signal(HashSet::<String>::new);
+
+    // Separator rows keep time-only timestamps unambiguous across midnight
+    let days: HashMap<usize, String> = day_boundaries(&props.logs)
+        .into_iter()
+        .map(|(idx, day)| (idx, day.format("%Y-%m-%d").to_string()))
+        .collect();
+
     rsx! {
         div {
             class: "log-view",
+            role: "log",
+            onscroll: move |_| props.on_scroll.call(()),
 
             for (idx, log) in props.logs.iter().enumerate() {
+                if let Some(day) = days.get(&idx) {
+                    div {
+                        key: "day-{idx}",
+                        class: "log-day-separator",
+                        "──── {day} ────"
+                    }
+                }
                 div {
                     key: "{idx}",
-                    class: "{log_row_class(log.level)}",
+                    id: "log-{idx}",
+                    tabindex: "0",
+                    class: log_row_class(
+                        log.level,
+                        props.dim_before.is_some_and(|cutoff| log.timestamp < cutoff),
+                        props.selected.as_ref() == Some(&log.raw),
+                        &props.thresholds.classes(log),
+                    ),
+                    // Up/Down move between rows, Enter opens the detail pane
+                    onkeydown: {
+                        let entry = log.clone();
+                        move |evt: Event<KeyboardData>| {
+                            let target = match evt.key() {
+                                Key::ArrowDown => idx + 1,
+                                Key::ArrowUp if idx > 0 => idx - 1,
+                                Key::Enter => {
+                                    props.on_select.call(entry.clone());
+                                    return;
+                                }
+                                _ => return,
+                            };
+                            evt.prevent_default();
+                            let _ = document::eval(&format!(
+                                "document.getElementById('log-{}')?.focus()",
+                                target
+                            ));
+                        }
+                    },
+                    ondoubleclick: {
+                        let entry = log.clone();
+                        move |_| props.on_select.call(entry.clone())
+                    },
+                    onfocus: {
+                        let entry = log.clone();
+                        move |_| props.on_focus.call(entry.clone())
+                    },
 
-                    span { class: "log-time",   "{log.format_time()}" }
+                    span { class: "log-time",   "{log.format_time(&props.time_format)}" }
+                    if let Some(start) = props.elapsed_since {
+                        span { class: "log-elapsed", "{log.format_elapsed(start)}" }
+                    }
+                    if props.show_origin {
+                        span { class: "log-origin", "{log.origin}" }
+                    }
                     span { class: "log-source", "{log.source}" }
-                    span { class: "log-dyno",   "[{log.dyno}]" }
+                    span {
+                        class: "log-dyno",
+                        style: props.dyno_palette.map(|p| format!("color: {}", p.color(&log.dyno))),
+                        "[{log.dyno}]"
+                    }
                     span { class: "{level_class(log.level)}", "{log.level:?}:" }
-                    span { class: "log-msg",    "{log.message}" }
+                    {
+                        let message = props.rewrites.apply(&log.message);
+                        let cut = props
+                            .truncate_at
+                            .filter(|_| !expanded.read().contains(&log.raw))
+                            .and_then(|limit| truncate(&message, limit));
+                        match cut {
+                            Some((shown, hidden)) => {
+                                let raw = log.raw.clone();
+                                let key_raw = log.raw.clone();
+                                rsx! {
+                                    {render_message(shown, props.sql_highlight, &props.highlights, &props.search, props.on_open_ref)}
+                                    span {
+                                        class: "log-truncated",
+                                        role: "button",
+                                        tabindex: "0",
+                                        title: t("log.expand_title"),
+                                        onclick: move |_| {
+                                            expanded.write().insert(raw.clone());
+                                        },
+                                        onkeydown: move |evt: Event<KeyboardData>| {
+                                            if evt.key() == Key::Enter {
+                                                evt.stop_propagation();
+                                                expanded.write().insert(key_raw.clone());
+                                            }
+                                        },
+                                        "…(+{format_size(hidden)})"
+                                    }
+                                }
+                            }
+                            None => render_message(&message, props.sql_highlight, &props.highlights, &props.search, props.on_open_ref),
+                        }
+                    }
+                    {
+                        let fields = format_fields(&log.fields, &log.message);
+                        rsx! {
+                            if !fields.is_empty() {
+                                span { class: "log-fields", "{fields}" }
+                            }
+                        }
+                    }
+                    {
+                        let entry = log.clone();
+                        rsx! {
+                            button {
+                                class: "log-pin",
+                                onkeydown: |evt: Event<KeyboardData>| evt.stop_propagation(),
+                                title: t("log.pin_title"),
+                                aria_label: t("log.pin_title"),
+                                onclick: move |_| props.on_pin.call(entry.clone()),
+                                "📌"
+                            }
+                        }
+                    }
+                    {
+                        let entry = log.clone();
+                        rsx! {
+                            button {
+                                class: "log-ignore",
+                                onkeydown: |evt: Event<KeyboardData>| evt.stop_propagation(),
+                                title: t("log.ignore_title"),
+                                aria_label: t("log.ignore_title"),
+                                onclick: move |_| props.on_ignore.call(entry.clone()),
+                                "⊘"
+                            }
+                        }
+                    }
                 }
             }
         }
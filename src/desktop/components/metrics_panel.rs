@@ -0,0 +1,34 @@
+//! Metrics panel charting Heroku Postgres and Redis samples and the
+//! `count#`/`measure#`/`sample#` metrics apps log
+
+use crate::i18n::t;
+use crate::metrics::MetricChart;
+use dioxus::prelude::*;
+
+#[component]
+pub fn MetricsPanel(charts: Vec<MetricChart>) -> Element {
+    rsx! {
+        div {
+            class: "toolbar-bar metrics-panel",
+
+            if charts.is_empty() {
+                div {
+                    class: "requests-empty",
+                    {t("addons.empty")}
+                }
+            }
+
+            for chart in charts {
+                div {
+                    key: "{chart.group}/{chart.metric}",
+                    class: "metric-row",
+
+                    span { class: "metric-group", "{chart.group}" }
+                    span { class: "metric-name", "{chart.metric}" }
+                    span { class: "sparkline metric-chart", "{chart.sparkline}" }
+                    span { class: "metric-latest", "{chart.latest}" }
+                }
+            }
+        }
+    }
+}
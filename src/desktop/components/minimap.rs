@@ -0,0 +1,49 @@
+//! Thin overview of the whole buffer with error and warning ticks
+
+use crate::minimap::Bucket;
+use crate::parser::LogLevel;
+use dioxus::html::input_data::MouseButton;
+use dioxus::prelude::*;
+
+fn bucket_class(marker: Option<LogLevel>) -> &'static str {
+    match marker {
+        Some(LogLevel::Error) => "minimap-bucket minimap-error",
+        Some(LogLevel::Warn) => "minimap-bucket minimap-warn",
+        _ => "minimap-bucket",
+    }
+}
+
+/// `viewport` is the visible part of the log view, as the fractions of its
+/// height scrolled past and shown
+#[component]
+pub fn Minimap(
+    buckets: Vec<Bucket>,
+    viewport: (f64, f64),
+    on_seek: EventHandler<usize>,
+) -> Element {
+    let (top, height) = viewport;
+
+    rsx! {
+        div {
+            class: "minimap",
+
+            for bucket in buckets {
+                div {
+                    class: bucket_class(bucket.marker),
+                    onmousedown: move |_| on_seek.call(bucket.start),
+                    // Dragging across the minimap scrolls along with the pointer
+                    onmousemove: move |evt| {
+                        if evt.held_buttons().contains(MouseButton::Primary) {
+                            on_seek.call(bucket.start);
+                        }
+                    },
+                }
+            }
+
+            div {
+                class: "minimap-viewport",
+                style: format!("top: {:.2}%; height: {:.2}%", top * 100.0, height * 100.0),
+            }
+        }
+    }
+}
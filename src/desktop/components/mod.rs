@@ -2,13 +2,37 @@
 
 pub mod connection_panel;
 pub mod custom_select;
+pub mod detail_panel;
 pub mod filter_bar;
+pub mod ignored_panel;
 pub mod log_view;
+pub mod metrics_panel;
+pub mod minimap;
+pub mod onboarding_wizard;
+pub mod passphrase_prompt;
+pub mod pinned_strip;
+pub mod platform_panel;
+pub mod replay_bar;
+pub mod requests_panel;
+pub mod settings_panel;
 pub mod stats_header;
 mod status_indicator;
+pub mod time_range_picker;
 
 pub use connection_panel::ConnectionPanel;
+pub use detail_panel::DetailPanel;
 pub use filter_bar::FilterBar;
+pub use ignored_panel::IgnoredPanel;
 pub use log_view::LogView;
+pub use metrics_panel::MetricsPanel;
+pub use minimap::Minimap;
+pub use onboarding_wizard::OnboardingWizard;
+pub use passphrase_prompt::PassphrasePrompt;
+pub use pinned_strip::PinnedStrip;
+pub use platform_panel::PlatformPanel;
+pub use replay_bar::ReplayBar;
+pub use requests_panel::RequestsPanel;
+pub use settings_panel::SettingsPanel;
 pub use stats_header::StatsHeader;
 pub use status_indicator::{ConnectionStatus, LoadingStep, StatusIndicator};
+pub use time_range_picker::TimeRangePicker;
@@ -0,0 +1,108 @@
+//! First-run guide from installing the Heroku CLI to the first connection
+
+use crate::heroku_cli::AppInfo;
+use crate::i18n::t;
+use crate::onboarding::{install_instructions, OnboardingStep};
+use dioxus::prelude::*;
+
+/// Apps offered as shortcuts on the "Pick an app" step
+const APP_SHORTCUTS: usize = 8;
+
+fn step_class(step: OnboardingStep, current: OnboardingStep) -> &'static str {
+    let index = |s| OnboardingStep::ALL.iter().position(|&x| x == s);
+    match index(step).cmp(&index(current)) {
+        std::cmp::Ordering::Less => "onboarding-step onboarding-step-done",
+        std::cmp::Ordering::Equal => "onboarding-step onboarding-step-current",
+        std::cmp::Ordering::Greater => "onboarding-step",
+    }
+}
+
+#[component]
+pub fn OnboardingWizard(
+    step: OnboardingStep,
+    is_logging_in: bool,
+    available_apps: Vec<AppInfo>,
+    on_retry: EventHandler<()>,
+    on_login: EventHandler<()>,
+    on_app_select: EventHandler<String>,
+    on_connect: EventHandler<()>,
+    on_skip: EventHandler<()>,
+) -> Element {
+    rsx! {
+        div {
+            class: "onboarding",
+
+            div {
+                class: "onboarding-steps",
+                for (idx, s) in OnboardingStep::ALL.into_iter().enumerate() {
+                    span { class: step_class(s, step), "{idx + 1}. {s.title()}" }
+                }
+            }
+
+            div {
+                class: "onboarding-body",
+
+                match step {
+                    OnboardingStep::InstallCli => rsx! {
+                        p { {t("onboarding.cli_missing")} }
+                        for (how, command) in install_instructions(std::env::consts::OS) {
+                            div {
+                                class: "onboarding-command",
+                                span { class: "onboarding-command-label", "{how}" }
+                                code { "{command}" }
+                            }
+                        }
+                        p {
+                            {t("onboarding.download")}
+                            code { "devcenter.heroku.com/articles/heroku-cli" }
+                            "."
+                        }
+                        button {
+                            class: "btn btn-connect",
+                            onclick: move |_| on_retry.call(()),
+                            {t("onboarding.installed")}
+                        }
+                    },
+                    OnboardingStep::Login if is_logging_in => rsx! {
+                        p { {t("onboarding.logging_in")} }
+                    },
+                    OnboardingStep::Login => rsx! {
+                        p { {t("onboarding.login")} }
+                        button {
+                            class: "btn btn-connect",
+                            onclick: move |_| on_login.call(()),
+                            {t("onboarding.login_button")}
+                        }
+                    },
+                    OnboardingStep::PickApp => rsx! {
+                        p { {t("onboarding.pick_app")} }
+                        div {
+                            class: "onboarding-apps",
+                            for app in available_apps.into_iter().take(APP_SHORTCUTS) {
+                                button {
+                                    class: "btn btn-neutral",
+                                    onclick: move |_| on_app_select.call(app.name.clone()),
+                                    "{app.name}"
+                                }
+                            }
+                        }
+                    },
+                    OnboardingStep::Connect => rsx! {
+                        p { {t("onboarding.connect")} }
+                        button {
+                            class: "btn btn-connect",
+                            onclick: move |_| on_connect.call(()),
+                            {t("connect")}
+                        }
+                    },
+                }
+            }
+
+            button {
+                class: "onboarding-skip",
+                onclick: move |_| on_skip.call(()),
+                {t("onboarding.skip")}
+            }
+        }
+    }
+}
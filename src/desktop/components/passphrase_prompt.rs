@@ -0,0 +1,66 @@
+//! Asking for an encrypted export's passphrase in a masked field, so it
+//! doesn't show on screen or in a screen share
+
+use crate::i18n::t;
+use dioxus::prelude::*;
+
+#[component]
+pub fn PassphrasePrompt(on_submit: EventHandler<String>, on_cancel: EventHandler<()>) -> Element {
+    let mut passphrase = use_signal(String::new);
+
+    use_effect(|| {
+        let _ = document::eval("document.getElementById('passphrase-input')?.focus()");
+    });
+
+    let mut submit = move || {
+        let value = passphrase();
+        if !value.is_empty() {
+            passphrase.set(String::new());
+            on_submit.call(value);
+        }
+    };
+    let on_key = move |evt: Event<KeyboardData>| {
+        // Typed characters must not trigger app-level shortcuts
+        evt.stop_propagation();
+        match evt.key() {
+            Key::Enter => submit(),
+            Key::Escape => on_cancel.call(()),
+            _ => {}
+        }
+    };
+
+    rsx! {
+        div {
+            class: "toolbar-bar passphrase-prompt",
+            role: "group",
+            aria_label: t("passphrase.title"),
+
+            label {
+                class: "passphrase-field",
+                {t("passphrase.title")}
+                input {
+                    id: "passphrase-input",
+                    r#type: "password",
+                    class: "themed-input",
+                    autocomplete: "new-password",
+                    value: "{passphrase}",
+                    oninput: move |evt| passphrase.set(evt.value()),
+                    onkeydown: on_key,
+                }
+            }
+
+            button {
+                class: "btn btn-connect",
+                disabled: passphrase().is_empty(),
+                onclick: move |_| submit(),
+                {t("passphrase.export")}
+            }
+
+            button {
+                class: "btn btn-neutral",
+                onclick: move |_| on_cancel.call(()),
+                {t("common.cancel")}
+            }
+        }
+    }
+}
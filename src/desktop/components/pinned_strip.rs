@@ -0,0 +1,39 @@
+//! Strip of pinned entries kept visible above the log list
+
+use crate::i18n::t;
+use crate::parser::LogEntry;
+use dioxus::prelude::*;
+
+#[component]
+pub fn PinnedStrip(
+    entries: Vec<LogEntry>,
+    time_format: String,
+    on_unpin: EventHandler<usize>,
+) -> Element {
+    if entries.is_empty() {
+        return rsx! {};
+    }
+
+    rsx! {
+        div {
+            class: "toolbar-bar pinned-strip",
+
+            for (idx, log) in entries.iter().enumerate() {
+                div {
+                    key: "{idx}",
+                    class: "pinned-entry",
+
+                    span { class: "log-time", "{log.format_time(&time_format)}" }
+                    span { class: "log-dyno", "[{log.dyno}]" }
+                    span { class: "log-msg",  "{log.message}" }
+                    button {
+                        class: "pinned-remove",
+                        title: t("pinned.unpin_title"),
+                        onclick: move |_| on_unpin.call(idx),
+                        "×"
+                    }
+                }
+            }
+        }
+    }
+}
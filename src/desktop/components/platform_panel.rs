@@ -0,0 +1,66 @@
+//! Panel listing dyno crashes, restarts and Heroku error codes, newest first
+
+use crate::i18n::t;
+use crate::platform_events::{PlatformEvent, PlatformEventKind};
+use dioxus::prelude::*;
+
+#[component]
+pub fn PlatformPanel(
+    events: Vec<PlatformEvent>,
+    time_format: String,
+    /// Show the lines around an event
+    on_select: EventHandler<PlatformEvent>,
+    on_jump_crash: EventHandler<()>,
+) -> Element {
+    let has_crash = events.iter().any(PlatformEvent::is_crash);
+
+    rsx! {
+        div {
+            class: "toolbar-bar requests-panel",
+
+            div {
+                class: "requests-tabs",
+                span { class: "platform-title", {t("platform.title")} }
+                button {
+                    class: "requests-tab",
+                    disabled: !has_crash,
+                    onclick: move |_| on_jump_crash.call(()),
+                    {t("platform.latest_crash")}
+                }
+            }
+
+            if events.is_empty() {
+                div {
+                    class: "requests-empty",
+                    {t("platform.empty")}
+                }
+            }
+
+            for (idx, event) in events.into_iter().enumerate() {
+                {
+                    let time = event.entry.timestamp.format(&time_format).to_string();
+                    let (kind, class) = match &event.kind {
+                        PlatformEventKind::Crash => (t("platform.crash").to_string(), "status-error"),
+                        PlatformEventKind::Restart => (t("platform.restart").to_string(), "status-warning"),
+                        PlatformEventKind::Error(code) => (code.clone(), "status-error"),
+                    };
+                    let description = event.description.clone();
+                    let dyno = event.dyno.clone();
+                    rsx! {
+                        div {
+                            key: "{idx}",
+                            class: "request-row",
+                            title: t("platform.select_title"),
+                            onclick: move |_| on_select.call(event.clone()),
+
+                            span { class: "request-id", "{time}" }
+                            span { class: "{class} platform-kind", "{kind}" }
+                            span { class: "request-method", "{dyno}" }
+                            span { class: "request-path", "{description}" }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! Playback controls for a replayed session
+
+use crate::i18n::t;
+use crate::session::SPEEDS;
+use dioxus::prelude::*;
+
+#[component]
+pub fn ReplayBar(
+    position: usize,
+    len: usize,
+    playing: bool,
+    speed: f64,
+    on_toggle_play: EventHandler<()>,
+    on_seek: EventHandler<usize>,
+    on_speed: EventHandler<f64>,
+    on_close: EventHandler<()>,
+) -> Element {
+    rsx! {
+        div {
+            class: "toolbar-bar replay-bar",
+
+            button {
+                class: "btn btn-neutral",
+                onclick: move |_| on_toggle_play.call(()),
+                if playing { {t("replay.pause")} } else { {t("replay.play")} }
+            }
+
+            input {
+                class: "replay-scrubber",
+                r#type: "range",
+                min: "0",
+                max: "{len}",
+                value: "{position}",
+                onchange: move |evt| {
+                    if let Ok(position) = evt.value().parse() {
+                        on_seek.call(position);
+                    }
+                },
+            }
+
+            span { class: "replay-position", "{position} / {len}" }
+
+            select {
+                class: "replay-speed",
+                onchange: move |evt| {
+                    if let Ok(speed) = evt.value().parse() {
+                        on_speed.call(speed);
+                    }
+                },
+                for option in SPEEDS {
+                    option {
+                        value: "{option}",
+                        selected: option == speed,
+                        "{option}×"
+                    }
+                }
+            }
+
+            button {
+                class: "btn btn-neutral",
+                title: t("replay.close_title"),
+                onclick: move |_| on_close.call(()),
+                {t("replay.close")}
+            }
+        }
+    }
+}
@@ -0,0 +1,167 @@
+//! Requests panel listing recent request ids with their router outcome,
+//! request stats per route, or router latency and status codes over the
+//! last few minutes
+
+use crate::i18n::{t, tf};
+use crate::requests::RequestSummary;
+use crate::router_metrics::{RouterSummary, WINDOW_SECS};
+use crate::routes::RouteSummary;
+use dioxus::prelude::*;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Tab {
+    Recent,
+    Routes,
+    Latency,
+}
+
+fn status_class(status: Option<u16>) -> &'static str {
+    match status {
+        Some(500..) => "status-error",
+        Some(400..=499) => "status-warning",
+        Some(_) => "status-ok",
+        None => "status-dim",
+    }
+}
+
+#[component]
+pub fn RequestsPanel(
+    requests: Vec<RequestSummary>,
+    routes: Vec<RouteSummary>,
+    router: RouterSummary,
+    on_select: EventHandler<String>,
+) -> Element {
+    let mut tab = use_signal(|| Tab::Recent);
+    let tab_class = move |this: Tab| {
+        if tab() == this {
+            "requests-tab requests-tab-active"
+        } else {
+            "requests-tab"
+        }
+    };
+    let percent = |n: usize| n * 100 / router.requests.max(1);
+
+    rsx! {
+        div {
+            class: "toolbar-bar requests-panel",
+
+            div {
+                class: "requests-tabs",
+                button {
+                    class: tab_class(Tab::Recent),
+                    onclick: move |_| tab.set(Tab::Recent),
+                    {t("requests.recent")}
+                }
+                button {
+                    class: tab_class(Tab::Routes),
+                    onclick: move |_| tab.set(Tab::Routes),
+                    {t("requests.routes")}
+                }
+                button {
+                    class: tab_class(Tab::Latency),
+                    onclick: move |_| tab.set(Tab::Latency),
+                    {t("requests.latency")}
+                }
+            }
+
+            if requests.is_empty() {
+                div {
+                    class: "requests-empty",
+                    {t("requests.empty")}
+                }
+            }
+
+            if tab() == Tab::Latency && router.requests > 0 {
+                div {
+                    class: "router-summary",
+                    {tf("requests.latency_window", &[
+                        ("minutes", &(WINDOW_SECS / 60)),
+                        ("count", &router.requests),
+                        ("rate", &format!("{:.1}", router.per_second)),
+                        ("kb", &(router.bytes_per_second / 1024)),
+                    ])}
+                }
+                div {
+                    class: "router-stats",
+                    for (label, ms) in [("p50", router.p50_ms), ("p95", router.p95_ms), ("p99", router.p99_ms)] {
+                        span {
+                            key: "{label}",
+                            class: "router-stat",
+                            span { class: "router-stat-label", "{label}" }
+                            span { class: "request-service", "{ms}ms" }
+                        }
+                    }
+                    span {
+                        class: "router-stat",
+                        span { class: "router-stat-label", {t("requests.connect")} }
+                        span { class: "request-service", "{router.avg_connect_ms}ms" }
+                    }
+                }
+                div {
+                    class: "router-stats",
+                    for (class, count) in router.classes.iter().copied() {
+                        span {
+                            key: "{class}",
+                            class: "router-stat",
+                            span { class: "{status_class(Some(class))} request-status", "{class / 100}xx" }
+                            "{count} ({percent(count)}%)"
+                        }
+                    }
+                }
+                div {
+                    class: "router-stats",
+                    for (code, count) in router.codes.iter().take(8).copied() {
+                        span {
+                            key: "{code}",
+                            class: "router-stat",
+                            span { class: "{status_class(Some(code))} request-status", "{code}" }
+                            "{count}"
+                        }
+                    }
+                }
+            } else if tab() == Tab::Routes {
+                for route in routes {
+                    div {
+                        key: "{route.method} {route.template}",
+                        class: "request-row route-row",
+
+                        span { class: "request-status", "{route.count}" }
+                        span { class: "request-method", "{route.method}" }
+                        span { class: "request-path", "{route.template}" }
+                        span {
+                            class: if route.errors > 0 { "status-error route-errors" } else { "status-dim route-errors" },
+                            {tf("requests.route_errors", &[("errors", &route.errors)])}
+                        }
+                        span {
+                            class: "request-service",
+                            {tf("requests.route_service", &[("avg", &route.avg_service_ms()), ("max", &route.max_service_ms)])}
+                        }
+                    }
+                }
+            } else {
+                for req in requests {
+                    {
+                        let id = req.request_id.clone();
+                        let status = req.status.map(|s| s.to_string()).unwrap_or_else(|| "—".to_string());
+                        let service = req.service_ms.map(|ms| format!("{}ms", ms)).unwrap_or_default();
+                        rsx! {
+                            div {
+                                key: "{req.request_id}",
+                                class: "request-row",
+                                title: t("requests.select_title"),
+                                onclick: move |_| on_select.call(id.clone()),
+
+                                span { class: "{status_class(req.status)} request-status", "{status}" }
+                                span { class: "request-method", "{req.method.clone().unwrap_or_default()}" }
+                                span { class: "request-path", "{req.path.clone().unwrap_or_default()}" }
+                                span { class: "request-service", "{service}" }
+                                span { class: "request-lines", "{req.app_lines} app lines" }
+                                span { class: "request-id", "{req.request_id}" }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! Editor for the settings and keys files, opened with `:settings`
+
+use crate::i18n::t;
+use dioxus::prelude::*;
+
+/// One `name = value` line, saved when the input loses focus or on Enter
+#[component]
+fn ConfigRow(
+    name: String,
+    value: String,
+    /// Shown when no value is set
+    default: String,
+    on_save: EventHandler<(String, String)>,
+) -> Element {
+    let field = name.clone();
+    rsx! {
+        label {
+            class: "settings-row",
+            span { class: "settings-name", "{name}" }
+            input {
+                class: "themed-input settings-value",
+                r#type: "text",
+                value: "{value}",
+                placeholder: "{default}",
+                onchange: move |evt| on_save.call((field.clone(), evt.value())),
+                // Typed characters must not trigger app-level shortcuts
+                onkeydown: move |evt| evt.stop_propagation(),
+            }
+        }
+    }
+}
+
+#[component]
+pub fn SettingsPanel(
+    /// Name, value in the settings file (empty if unset) and default
+    settings: Vec<(String, String, String)>,
+    /// Action name, bound key and default key
+    keys: Vec<(String, String, String)>,
+    on_save_setting: EventHandler<(String, String)>,
+    on_save_key: EventHandler<(String, String)>,
+    on_close: EventHandler<()>,
+) -> Element {
+    rsx! {
+        div {
+            class: "toolbar-bar settings-panel",
+
+            div {
+                class: "settings-header",
+                span { class: "settings-note", {t("settings.note")} }
+                button {
+                    class: "btn btn-neutral",
+                    onclick: move |_| on_close.call(()),
+                    {t("settings.close")}
+                }
+            }
+
+            h3 { class: "settings-heading", {t("settings.title")} }
+            div {
+                class: "settings-grid",
+                for (name, value, default) in settings {
+                    ConfigRow {
+                        key: "{name}",
+                        name: name.clone(),
+                        value: value,
+                        default: default,
+                        on_save: on_save_setting,
+                    }
+                }
+            }
+
+            h3 { class: "settings-heading", {t("settings.keys")} }
+            div {
+                class: "settings-grid",
+                for (name, key, default) in keys {
+                    ConfigRow {
+                        key: "{name}",
+                        name: name.clone(),
+                        value: key,
+                        default: default,
+                        on_save: on_save_key,
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,54 +1,223 @@
+use crate::i18n::{t, tf};
+use crate::metrics::{sparkline, RateHistory, WINDOW_SECS};
+use crate::parse_stats::ParseStats;
+use crate::status_layout::{StatusLayout, StatusSegment};
+use chrono::{DateTime, Local};
 use dioxus::prelude::*;
+use std::time::Duration;
 
 #[component]
-pub fn StatsHeader(total_logs: usize, filtered_logs: usize, filter_mode_and: bool) -> Element {
+pub fn StatsHeader(
+    layout: StatusLayout,
+    app_name: Option<String>,
+    total_logs: usize,
+    filtered_logs: usize,
+    filter_count: usize,
+    filter_mode_and: bool,
+    rate: f64,
+    activity: RateHistory,
+    parse_stats: ParseStats,
+    /// Platform events not yet looked at, and how many are crashes
+    platform_unseen: usize,
+    platform_crashes: usize,
+    /// When the most recent alert fired, and its message
+    last_alert: Option<(DateTime<Local>, String)>,
+) -> Element {
     let filter_text = if filter_mode_and { "AND" } else { "OR" };
+    let ticks = layout
+        .left
+        .iter()
+        .chain(layout.right.iter())
+        .any(|s| matches!(s, StatusSegment::Clock | StatusSegment::Activity));
 
-    rsx! {
-        div {
-            class: "toolbar-bar",
-            style: "padding: 12px 16px; border-bottom: 2px solid var(--accent); display: flex; justify-content: space-between; align-items: center;",
-
-            div {
-                style: "display: flex; gap: 24px;",
+    // Tick once a second so the clock and the sparkline stay current
+    let mut now = use_signal(Local::now);
+    use_future(move || async move {
+        if !ticks {
+            return;
+        }
+        loop {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            now.set(Local::now());
+        }
+    });
 
-                div {
-                    span {
-                        style: "color: var(--text-dim); font-size: 12px;",
-                        "Total Logs: "
+    let segment = |seg: StatusSegment| -> Element {
+        let (label, value, color) = match seg {
+            StatusSegment::Title => {
+                return rsx! {
+                    h1 {
+                        style: "margin: 0; font-size: 18px; font-weight: 600;",
+                        {t("stats.title")}
                     }
-                    span {
-                        style: "color: var(--accent); font-weight: bold; font-size: 14px;",
-                        "{total_logs}"
+                };
+            }
+            StatusSegment::Unparsed => {
+                let failing = parse_stats.failing();
+                let Some((name, worst)) = failing.first() else {
+                    return rsx! {};
+                };
+                // Hover lists every failing input with its recent failed lines
+                let details: Vec<String> = failing
+                    .iter()
+                    .map(|(name, stats)| {
+                        let samples: Vec<&str> = stats.samples.iter().map(String::as_str).collect();
+                        format!(
+                            "{}\n  {}",
+                            tf(
+                                "stats.unparsed_detail",
+                                &[
+                                    ("name", name),
+                                    ("failed", &stats.failed),
+                                    ("total", &(stats.parsed + stats.failed)),
+                                ],
+                            ),
+                            samples.join("\n  ")
+                        )
+                    })
+                    .collect();
+                let value = format!("{:.0}% {}", worst.failure_ratio() * 100.0, name);
+                return rsx! {
+                    div {
+                        title: details.join("\n"),
+                        span {
+                            style: "color: var(--text-dim); font-size: 12px;",
+                            {t("stats.unparsed")}
+                        }
+                        span {
+                            style: "color: var(--danger); font-weight: bold; font-size: 14px;",
+                            "{value}"
+                        }
                     }
+                };
+            }
+            StatusSegment::Platform => {
+                if platform_unseen == 0 {
+                    return rsx! {};
                 }
-
-                div {
-                    span {
-                        style: "color: var(--text-dim); font-size: 12px;",
-                        "Filtered: "
+                let color = if platform_crashes > 0 {
+                    "var(--danger)"
+                } else {
+                    "var(--warning)"
+                };
+                return rsx! {
+                    div {
+                        title: tf(
+                            "stats.platform_title",
+                            &[("events", &platform_unseen), ("crashes", &platform_crashes)],
+                        ),
+                        span {
+                            style: "color: var(--text-dim); font-size: 12px;",
+                            {t("stats.platform")}
+                        }
+                        span {
+                            style: "color: {color}; font-weight: bold; font-size: 14px;",
+                            "{platform_unseen}"
+                        }
                     }
-                    span {
-                        style: "color: var(--success); font-weight: bold; font-size: 14px;",
-                        "{filtered_logs}"
+                };
+            }
+            StatusSegment::LastAlert => {
+                let (value, message) = match &last_alert {
+                    Some((at, message)) => (at.format("%H:%M:%S").to_string(), message.clone()),
+                    None => ("—".to_string(), String::new()),
+                };
+                return rsx! {
+                    div {
+                        title: message,
+                        span {
+                            style: "color: var(--text-dim); font-size: 12px;",
+                            "{t(\"stats.last_alert\")}: "
+                        }
+                        span {
+                            style: "color: var(--danger); font-weight: bold; font-size: 14px;",
+                            "{value}"
+                        }
                     }
+                };
+            }
+            StatusSegment::Activity => {
+                let second = now().timestamp();
+                let totals: Vec<usize> = activity.window(second).iter().map(|c| c.total).collect();
+                let peak = totals.iter().copied().max().unwrap_or(0);
+                let per_second = totals.iter().sum::<usize>() as f64 / WINDOW_SECS as f64;
+                let errors = activity.error_rate(second);
+                return rsx! {
+                    div {
+                        title: tf(
+                            "stats.activity_title",
+                            &[("rate", &format!("{:.1}", per_second)), ("peak", &peak)],
+                        ),
+                        span {
+                            style: "color: var(--text-dim); font-size: 12px;",
+                            "{t(\"stats.activity\")}: "
+                        }
+                        span { class: "sparkline", "{sparkline(&totals)}" }
+                        if errors > 0.0 {
+                            span {
+                                style: "color: var(--danger); font-weight: bold; font-size: 12px; margin-left: 6px;",
+                                {tf("stats.errors", &[("percent", &format!("{:.1}", errors * 100.0))])}
+                            }
+                        }
+                    }
+                };
+            }
+            StatusSegment::App => (
+                t("stats.app"),
+                app_name.clone().unwrap_or_else(|| "—".to_string()),
+                "var(--accent)",
+            ),
+            StatusSegment::Total => (t("stats.total"), total_logs.to_string(), "var(--accent)"),
+            StatusSegment::Filtered => (
+                t("stats.filtered"),
+                filtered_logs.to_string(),
+                "var(--success)",
+            ),
+            StatusSegment::Mode => (t("stats.mode"), filter_text.to_string(), "var(--warning)"),
+            StatusSegment::Filters => (
+                t("stats.filters"),
+                filter_count.to_string(),
+                "var(--warning)",
+            ),
+            StatusSegment::Rate => (t("stats.rate"), format!("{:.1}/s", rate), "var(--accent)"),
+            StatusSegment::Clock => (
+                t("stats.time"),
+                now().format("%H:%M:%S").to_string(),
+                "var(--text-primary)",
+            ),
+        };
+
+        rsx! {
+            div {
+                span {
+                    style: "color: var(--text-dim); font-size: 12px;",
+                    "{label}: "
                 }
+                span {
+                    style: "color: {color}; font-weight: bold; font-size: 14px;",
+                    "{value}"
+                }
+            }
+        }
+    };
 
-                div {
-                    span {
-                        style: "color: var(--text-dim); font-size: 12px;",
-                        "Filter Mode: "
-                    }
-                    span {
-                        style: "color: var(--warning); font-weight: bold; font-size: 14px;",
-                        "{filter_text}"
-                    }
+    rsx! {
+        div {
+            class: "toolbar-bar",
+            style: "padding: 12px 16px; border-bottom: 2px solid var(--accent); display: flex; justify-content: space-between; align-items: center;",
+
+            div {
+                style: "display: flex; gap: 24px; align-items: center;",
+                for seg in layout.left.iter() {
+                    {segment(*seg)}
                 }
             }
 
-            h1 {
-                style: "margin: 0; font-size: 18px; font-weight: 600;",
-                "Heroku Logs Parser"
+            div {
+                style: "display: flex; gap: 24px; align-items: center;",
+                for seg in layout.right.iter() {
+                    {segment(*seg)}
+                }
             }
         }
     }
@@ -1,5 +1,6 @@
 //! Status indicator component for connection state visualization
 
+use crate::i18n::{t, tf};
 use dioxus::prelude::*;
 
 #[derive(Clone, PartialEq)]
@@ -26,6 +27,7 @@ pub enum ConnectionStatus {
     Connecting,
     Streaming,
     Reconnecting(u32),
+    CliMissing,
     NotAuthenticated,
     LoggingIn,
     Error(String),
@@ -47,18 +49,46 @@ fn LoadingProgress(step: LoadingStep) -> Element {
     rsx! {
         div {
             class: "loading-progress",
-            span { class: "{step_class(0, i)}", "Checking CLI" }
+            span { class: "{step_class(0, i)}", {t("loading.cli")} }
             span { class: "loading-arrow", "→" }
-            span { class: "{step_class(1, i)}", "Verifying auth" }
+            span { class: "{step_class(1, i)}", {t("loading.auth")} }
             span { class: "loading-arrow", "→" }
-            span { class: "{step_class(2, i)}", "Fetching apps" }
+            span { class: "{step_class(2, i)}", {t("loading.apps")} }
         }
     }
 }
 
+/// Short-lived message shown at the right of the status line
 #[component]
-pub fn StatusIndicator(status: ConnectionStatus) -> Element {
-    let (text_class, dot_class, text) = match status {
+fn Notice(text: Option<String>) -> Element {
+    rsx! {
+        if let Some(text) = text {
+            span { class: "status-notice", "{text}" }
+        }
+    }
+}
+
+impl ConnectionStatus {
+    /// Short description of the state, as shown next to the status dot
+    pub fn label(&self) -> String {
+        match self {
+            ConnectionStatus::Loading(_) => t("status.starting").to_string(),
+            ConnectionStatus::Ready => t("status.ready").to_string(),
+            ConnectionStatus::Connecting => t("status.connecting").to_string(),
+            ConnectionStatus::Streaming => t("status.streaming").to_string(),
+            ConnectionStatus::Reconnecting(n) => tf("status.reconnecting", &[("attempt", n)]),
+            ConnectionStatus::CliMissing => t("status.cli_missing").to_string(),
+            ConnectionStatus::NotAuthenticated => t("status.not_authenticated").to_string(),
+            ConnectionStatus::LoggingIn => t("status.logging_in").to_string(),
+            ConnectionStatus::Error(msg) => msg.clone(),
+        }
+    }
+}
+
+#[component]
+pub fn StatusIndicator(status: ConnectionStatus, notice: Option<String>) -> Element {
+    let text = status.label();
+    let (text_class, dot_class) = match status {
         ConnectionStatus::Loading(step) => {
             return rsx! {
                 div {
@@ -66,44 +96,18 @@ pub fn StatusIndicator(status: ConnectionStatus) -> Element {
                     style: "display: flex; align-items: center; gap: 8px; padding: 8px 16px;",
                     div { class: "status-dot status-dot-warning" }
                     LoadingProgress { step }
+                    Notice { text: notice }
                 }
             };
         }
-        ConnectionStatus::Ready => (
-            "status-dim",
-            "status-dot status-dot-dim",
-            "Ready to connect".to_string(),
-        ),
-        ConnectionStatus::Connecting => (
-            "status-warning",
-            "status-dot status-dot-warning",
-            "Connecting...".to_string(),
-        ),
-        ConnectionStatus::Streaming => (
-            "status-ok",
-            "status-dot status-dot-ok",
-            "Streaming".to_string(),
-        ),
-        ConnectionStatus::Reconnecting(n) => (
-            "status-warning",
-            "status-dot status-dot-warning",
-            format!("Reconnecting (attempt {}/5)...", n),
-        ),
-        ConnectionStatus::NotAuthenticated => (
-            "status-error",
-            "status-dot status-dot-error",
-            "Not logged in to Heroku".to_string(),
-        ),
-        ConnectionStatus::LoggingIn => (
-            "status-warning",
-            "status-dot status-dot-warning",
-            "Logging in to Heroku...".to_string(),
-        ),
-        ConnectionStatus::Error(msg) => (
-            "status-error",
-            "status-dot status-dot-error",
-            msg,
-        ),
+        ConnectionStatus::Ready => ("status-dim", "status-dot status-dot-dim"),
+        ConnectionStatus::Connecting
+        | ConnectionStatus::Reconnecting(_)
+        | ConnectionStatus::LoggingIn => ("status-warning", "status-dot status-dot-warning"),
+        ConnectionStatus::Streaming => ("status-ok", "status-dot status-dot-ok"),
+        ConnectionStatus::CliMissing
+        | ConnectionStatus::NotAuthenticated
+        | ConnectionStatus::Error(_) => ("status-error", "status-dot status-dot-error"),
     };
 
     rsx! {
@@ -116,6 +120,7 @@ pub fn StatusIndicator(status: ConnectionStatus) -> Element {
                 style: "font-size: 14px; font-weight: 500;",
                 "{text}"
             }
+            Notice { text: notice }
         }
     }
 }
@@ -0,0 +1,99 @@
+//! Picking the time range shown, as a `since:`/`until:` filter
+
+use crate::i18n::{t, tf};
+use dioxus::prelude::*;
+
+/// Ages offered as one-click ranges, in query syntax
+const RECENT: [&str; 4] = ["5m", "15m", "1h", "24h"];
+
+/// Query for a range picked in the date and time fields, which give
+/// `2024-02-17T10:00`; `None` when both are empty
+fn range_query(from: &str, to: &str) -> Option<String> {
+    let since = (!from.is_empty()).then(|| format!("since:{}", from));
+    let until = (!to.is_empty()).then(|| format!("until:{}", to));
+    let query: Vec<String> = since.into_iter().chain(until).collect();
+    (!query.is_empty()).then(|| query.join(" "))
+}
+
+#[component]
+pub fn TimeRangePicker(
+    /// Whether a time range filter is set
+    active: bool,
+    on_apply: EventHandler<String>,
+    on_clear: EventHandler<()>,
+) -> Element {
+    let mut from = use_signal(String::new);
+    let mut to = use_signal(String::new);
+
+    let apply_fields = move || {
+        if let Some(query) = range_query(&from(), &to()) {
+            on_apply.call(query);
+        }
+    };
+    let on_key = move |evt: Event<KeyboardData>| {
+        // Typed characters must not trigger app-level shortcuts
+        evt.stop_propagation();
+        if evt.key() == Key::Enter {
+            apply_fields();
+        }
+    };
+
+    rsx! {
+        div {
+            class: "time-range-picker",
+            role: "group",
+            aria_label: t("time.title"),
+
+            for age in RECENT {
+                button {
+                    class: "btn btn-neutral time-range-recent",
+                    onclick: move |_| on_apply.call(format!("since:{}", age)),
+                    {tf("time.last", &[("age", &age)])}
+                }
+            }
+
+            label {
+                class: "time-range-field",
+                {t("time.from")}
+                input {
+                    r#type: "datetime-local",
+                    class: "themed-input",
+                    value: "{from}",
+                    oninput: move |evt| from.set(evt.value()),
+                    onkeydown: on_key,
+                }
+            }
+
+            label {
+                class: "time-range-field",
+                {t("time.to")}
+                input {
+                    r#type: "datetime-local",
+                    class: "themed-input",
+                    value: "{to}",
+                    oninput: move |evt| to.set(evt.value()),
+                    onkeydown: on_key,
+                }
+            }
+
+            button {
+                class: "btn btn-connect",
+                disabled: from().is_empty() && to().is_empty(),
+                onclick: move |_| apply_fields(),
+                {t("time.apply")}
+            }
+
+            if active {
+                button {
+                    class: "btn btn-neutral",
+                    onclick: move |_| {
+                        from.set(String::new());
+                        to.set(String::new());
+                        on_clear.call(());
+                    },
+                    {t("time.clear")}
+                }
+            }
+        }
+    }
+}
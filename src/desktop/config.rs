@@ -0,0 +1,113 @@
+//! Location of user configuration files
+
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+/// Directory holding all user configuration (`~/.config/logs-parser`)
+pub fn config_dir() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    std::path::Path::new(&home)
+        .join(".config")
+        .join("logs-parser")
+}
+
+/// `name = value` pairs of a settings or keys file, skipping blank lines
+/// and comments
+pub fn values(contents: &str) -> Vec<(String, String)> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+/// `name = value` file contents with `name` set to `value`, replacing the
+/// line that set it before or adding one at the end; `None` removes it.
+/// The last line setting a name is the one read, so that's the one
+/// replaced, and earlier ones are dropped
+pub fn with_value(contents: &str, name: &str, value: Option<&str>) -> String {
+    let sets_name = |line: &String| line.split_once('=').is_some_and(|(n, _)| n.trim() == name);
+    let mut lines: Vec<String> = contents.lines().map(str::to_string).collect();
+    match (lines.iter().rposition(sets_name), value) {
+        (Some(at), Some(value)) => {
+            lines[at] = format!("{} = {}", name, value);
+            lines = lines
+                .into_iter()
+                .enumerate()
+                .filter(|(idx, line)| *idx >= at || !sets_name(line))
+                .map(|(_, line)| line)
+                .collect();
+        }
+        (None, Some(value)) => lines.push(format!("{} = {}", name, value)),
+        (_, None) => lines.retain(|line| !sets_name(line)),
+    }
+    let mut contents = lines.join("\n");
+    if !contents.is_empty() {
+        contents.push('\n');
+    }
+    contents
+}
+
+/// Set or remove one value in a `name = value` file in the config
+/// directory, keeping everything else
+pub fn save_value(file: &str, name: &str, value: Option<&str>) -> Result<()> {
+    let dir = config_dir();
+    std::fs::create_dir_all(&dir).context("Failed to create config directory")?;
+    let path = dir.join(file);
+    let contents = std::fs::read_to_string(&path).unwrap_or_default();
+    std::fs::write(&path, with_value(&contents, name, value))
+        .with_context(|| format!("Failed to save {}", file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_value() {
+        let contents = "# look\ntheme = wmp\ndim_after = 5m\n";
+        assert_eq!(
+            with_value(contents, "theme", Some("aero")),
+            "# look\ntheme = aero\ndim_after = 5m\n"
+        );
+        assert_eq!(with_value("", "theme", Some("aero")), "theme = aero\n");
+        assert_eq!(
+            with_value(contents, "theme", None),
+            "# look\ndim_after = 5m\n"
+        );
+        assert_eq!(with_value("theme = wmp\n", "theme", None), "");
+    }
+
+    #[test]
+    fn test_with_value_replaces_last_setting() {
+        let contents = "theme = wmp\ndim_after = 5m\ntheme = win2k\n";
+        let saved = with_value(contents, "theme", Some("win7"));
+        assert_eq!(saved, "dim_after = 5m\ntheme = win7\n");
+        assert_eq!(
+            values(&saved)
+                .iter()
+                .rev()
+                .find(|(n, _)| n == "theme")
+                .unwrap()
+                .1,
+            "win7"
+        );
+        assert_eq!(with_value(contents, "theme", None), "dim_after = 5m\n");
+    }
+
+    #[test]
+    fn test_values() {
+        assert_eq!(
+            values("# look\ntheme = wmp\n\nnonsense\nopen_command = code --goto {file}:{line}\n"),
+            [
+                ("theme".to_string(), "wmp".to_string()),
+                (
+                    "open_command".to_string(),
+                    "code --goto {file}:{line}".to_string()
+                )
+            ]
+        );
+    }
+}
@@ -0,0 +1,303 @@
+//! Control socket for driving a running instance from scripts
+//!
+//! When `control_socket = on` is set, the app listens on
+//! `~/.config/logs-parser/control.sock` and the same binary acts as a client:
+//!
+//! ```text
+//! logs-parser-desktop ctl filter add 'level:error'
+//! logs-parser-desktop ctl jump 14:05
+//! ```
+//!
+//! `ctl status` prints a one-line summary, such as `my-app · 3 new errors`,
+//! for a tmux status bar or shell prompt.
+
+use crate::config::config_dir;
+use crate::parser::LogEntry;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, FixedOffset, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::mpsc;
+
+/// How long a client has to send its command after connecting
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+const USAGE: &str = "Usage: ctl filter add <query> | ctl filter clear | ctl jump <time> | ctl export timeline | ctl status";
+
+/// An action requested through the control socket
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ControlCommand {
+    AddFilter(String),
+    ClearFilters,
+    Jump(String),
+    ExportTimeline,
+    /// Answered from the shared status line rather than sent to the app
+    Status,
+}
+
+impl ControlCommand {
+    /// Parse the arguments following `ctl`
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        match args.as_slice() {
+            ["filter", "add", query @ ..] if !query.is_empty() => {
+                Ok(Self::AddFilter(query.join(" ")))
+            }
+            ["filter", "clear"] => Ok(Self::ClearFilters),
+            ["jump", time] => JumpTarget::parse(time)
+                .map(|_| Self::Jump(time.to_string()))
+                .ok_or_else(|| format!("Invalid time: {}", time)),
+            ["export", "timeline"] => Ok(Self::ExportTimeline),
+            ["status"] => Ok(Self::Status),
+            _ => Err(USAGE.to_string()),
+        }
+    }
+}
+
+/// A point in the buffer to scroll to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JumpTarget {
+    At(DateTime<FixedOffset>),
+    /// `HH:MM[:SS]`, compared with each entry's own time of day
+    TimeOfDay(NaiveTime),
+}
+
+impl JumpTarget {
+    pub fn parse(input: &str) -> Option<Self> {
+        if let Ok(at) = DateTime::parse_from_rfc3339(input) {
+            return Some(Self::At(at));
+        }
+        NaiveTime::parse_from_str(input, "%H:%M:%S")
+            .or_else(|_| NaiveTime::parse_from_str(input, "%H:%M"))
+            .ok()
+            .map(Self::TimeOfDay)
+    }
+
+    /// Index of the earliest entry at or after the target, in the order
+    /// the entries are shown, which may not be time order
+    pub fn find(&self, logs: &[LogEntry]) -> Option<usize> {
+        logs.iter()
+            .enumerate()
+            .filter(|(_, log)| match self {
+                Self::At(at) => log.timestamp >= *at,
+                Self::TimeOfDay(time) => log.timestamp.time() >= *time,
+            })
+            .min_by_key(|(_, log)| log.timestamp)
+            .map(|(idx, _)| idx)
+    }
+}
+
+/// Latest status line, updated by the app and read by `ctl status`
+pub type SharedStatus = Arc<RwLock<String>>;
+
+/// One-line summary of the monitored state, e.g. `my-app · 3 new errors`
+pub fn status_line(app: Option<&str>, unseen_errors: usize, paused: bool) -> String {
+    let mut parts = vec![app.unwrap_or("logs-parser").to_string()];
+    match unseen_errors {
+        0 => {}
+        1 => parts.push("1 new error".to_string()),
+        n => parts.push(format!("{} new errors", n)),
+    }
+    if paused {
+        parts.push("paused".to_string());
+    }
+    parts.join(" · ")
+}
+
+/// Where the app listens for control commands
+pub fn socket_path() -> PathBuf {
+    config_dir().join("control.sock")
+}
+
+/// Accept commands until the receiving side goes away
+pub async fn serve(
+    path: &Path,
+    sender: mpsc::UnboundedSender<ControlCommand>,
+    status: SharedStatus,
+) -> Result<()> {
+    // A socket file left behind by a previous run blocks bind(), but one
+    // that still answers belongs to another running instance
+    if UnixStream::connect(path).await.is_ok() {
+        bail!(
+            "Another instance is already listening on {}",
+            path.display()
+        );
+    }
+    let _ = std::fs::remove_file(path);
+    let listener = UnixListener::bind(path)
+        .with_context(|| format!("Failed to listen on {}", path.display()))?;
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        if sender.is_closed() {
+            return Ok(());
+        }
+        // Each client on its own task, so a slow one doesn't hold up the rest
+        tokio::spawn(answer(stream, sender.clone(), status.clone()));
+    }
+}
+
+/// Read one command from a client and reply; a client that doesn't send a
+/// line within [`READ_TIMEOUT`] is dropped
+async fn answer(
+    stream: UnixStream,
+    sender: mpsc::UnboundedSender<ControlCommand>,
+    status: SharedStatus,
+) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    let Ok(Ok(Some(line))) = tokio::time::timeout(READ_TIMEOUT, lines.next_line()).await else {
+        return;
+    };
+    let reply = match serde_json::from_str::<ControlCommand>(&line) {
+        Ok(ControlCommand::Status) => status.read().map(|s| s.clone()).unwrap_or_default(),
+        Ok(command) => {
+            if sender.send(command).is_err() {
+                return;
+            }
+            "ok".to_string()
+        }
+        Err(e) => format!("error: {}", e),
+    };
+    let _ = writer.write_all(format!("{}\n", reply).as_bytes()).await;
+}
+
+/// Send one command to a running instance and return its reply
+pub async fn send(path: &Path, command: &ControlCommand) -> Result<String> {
+    let mut stream = UnixStream::connect(path).await.with_context(|| {
+        format!(
+            "No running instance at {} (is control_socket enabled?)",
+            path.display()
+        )
+    })?;
+    stream
+        .write_all(format!("{}\n", serde_json::to_string(command)?).as_bytes())
+        .await?;
+
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply).await?;
+    let reply = reply.trim().to_string();
+    if let Some(error) = reply.strip_prefix("error: ") {
+        bail!("{}", error);
+    }
+    Ok(reply)
+}
+
+/// Entry point for `logs-parser-desktop ctl ...`
+pub fn run_ctl(args: &[String]) -> Result<()> {
+    let command = ControlCommand::from_args(args).map_err(anyhow::Error::msg)?;
+    let runtime = tokio::runtime::Runtime::new()?;
+    let reply = runtime.block_on(send(&socket_path(), &command))?;
+    println!("{}", reply);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_log_line;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_from_args() {
+        assert_eq!(
+            ControlCommand::from_args(&args(&["filter", "add", "level:error"])),
+            Ok(ControlCommand::AddFilter("level:error".to_string()))
+        );
+        assert_eq!(
+            ControlCommand::from_args(&args(&["filter", "add", "connection", "reset"])),
+            Ok(ControlCommand::AddFilter("connection reset".to_string()))
+        );
+        assert_eq!(
+            ControlCommand::from_args(&args(&["export", "timeline"])),
+            Ok(ControlCommand::ExportTimeline)
+        );
+        assert_eq!(
+            ControlCommand::from_args(&args(&["status"])),
+            Ok(ControlCommand::Status)
+        );
+        assert!(ControlCommand::from_args(&args(&["jump", "noon"])).is_err());
+        assert!(ControlCommand::from_args(&args(&["filter", "add"])).is_err());
+    }
+
+    #[test]
+    fn test_status_line() {
+        assert_eq!(status_line(None, 0, false), "logs-parser");
+        assert_eq!(
+            status_line(Some("my-app"), 1, false),
+            "my-app · 1 new error"
+        );
+        assert_eq!(
+            status_line(Some("my-app"), 3, true),
+            "my-app · 3 new errors · paused"
+        );
+    }
+
+    #[test]
+    fn test_jump_target_find() {
+        let logs: Vec<LogEntry> = ["10:00:00", "10:05:00", "10:10:00"]
+            .iter()
+            .map(|t| {
+                parse_log_line(&format!("2024-02-17T{}.000000+00:00 app[web.1]: x", t)).unwrap()
+            })
+            .collect();
+
+        let time_of_day = JumpTarget::parse("10:04").unwrap();
+        assert_eq!(time_of_day.find(&logs), Some(1));
+
+        let exact = JumpTarget::parse("2024-02-17T10:10:00+00:00").unwrap();
+        assert_eq!(exact.find(&logs), Some(2));
+
+        let after_all = JumpTarget::parse("11:00:00").unwrap();
+        assert_eq!(after_all.find(&logs), None);
+
+        // Sorted newest first
+        let reversed: Vec<LogEntry> = logs.into_iter().rev().collect();
+        assert_eq!(time_of_day.find(&reversed), Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_send_round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("logs-parser-ctl-{}.sock", std::process::id()));
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let server_path = path.clone();
+        let status: SharedStatus = Arc::new(RwLock::new(status_line(Some("my-app"), 2, false)));
+        tokio::spawn(async move { serve(&server_path, tx, status).await });
+
+        let command = ControlCommand::AddFilter("dyno:web.1".to_string());
+        let reply = loop {
+            if let Ok(reply) = send(&path, &command).await {
+                break reply;
+            }
+            tokio::task::yield_now().await;
+        };
+
+        assert_eq!(reply, "ok");
+        assert_eq!(rx.recv().await, Some(command));
+        // A client that connects and sends nothing doesn't block the others
+        let _idle = UnixStream::connect(&path).await.unwrap();
+        assert_eq!(
+            send(&path, &ControlCommand::Status).await.unwrap(),
+            "my-app · 2 new errors"
+        );
+        assert!(rx.try_recv().is_err());
+
+        // A second instance doesn't take over the socket
+        let (other_tx, _other_rx) = mpsc::unbounded_channel();
+        let other_status: SharedStatus = Arc::new(RwLock::new(String::new()));
+        assert!(serve(&path, other_tx, other_status).await.is_err());
+        assert_eq!(
+            send(&path, &ControlCommand::Status).await.unwrap(),
+            "my-app · 2 new errors"
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,177 @@
+//! Crash reports, kept only when `crash_reports = on`
+//!
+//! A panic writes the app version, OS, panic message and backtrace to a
+//! file in `~/.config/logs-parser/crashes/`. The next start offers to open
+//! a GitHub issue filled in with the report; nothing is sent anywhere unless
+//! the user submits that issue.
+
+use crate::config::config_dir;
+use crate::updates::{CURRENT_VERSION, REPOSITORY};
+use anyhow::{Context, Result};
+use chrono::Local;
+use std::backtrace::Backtrace;
+use std::path::{Path, PathBuf};
+
+/// Longest report put in an issue link, leaving room for the rest of the
+/// URL within what browsers and GitHub accept
+const MAX_ISSUE_BODY: usize = 6000;
+
+/// Suffix a report is renamed to once it has been offered
+const SEEN_SUFFIX: &str = ".seen.txt";
+
+pub fn crashes_dir() -> PathBuf {
+    config_dir().join("crashes")
+}
+
+/// Text of a report for a panic with `message` at `location`
+pub fn format_report(time: &str, message: &str, location: &str, backtrace: &str) -> String {
+    format!(
+        "logs-parser-desktop {}\nOS: {} {}\nTime: {}\n\nPanic: {}\nAt: {}\n\nBacktrace:\n{}\n",
+        CURRENT_VERSION,
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        time,
+        message,
+        location,
+        backtrace.trim_end()
+    )
+}
+
+/// Write a report for every panic, then let the default hook print it
+pub fn install() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "(no message)".to_string());
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+            .unwrap_or_default();
+        let report = format_report(
+            &Local::now().to_rfc3339(),
+            &message,
+            &location,
+            &Backtrace::force_capture().to_string(),
+        );
+        match write_report(&report) {
+            Ok(path) => eprintln!("Crash report saved to {}", path.display()),
+            Err(e) => eprintln!("{:#}", e),
+        }
+        previous(info);
+    }));
+}
+
+fn write_report(report: &str) -> Result<PathBuf> {
+    let dir = crashes_dir();
+    std::fs::create_dir_all(&dir).context("Failed to create crash report directory")?;
+    let path = dir.join(format!(
+        "crash-{}.txt",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    std::fs::write(&path, report).context("Failed to save crash report")?;
+    Ok(path)
+}
+
+/// Whether a file in the crashes directory is a report not yet offered
+fn is_unseen_report(name: &str) -> bool {
+    name.starts_with("crash-") && name.ends_with(".txt") && !name.ends_with(SEEN_SUFFIX)
+}
+
+/// The newest report not yet offered, if any
+pub fn unseen_report() -> Option<PathBuf> {
+    let mut reports: Vec<PathBuf> = std::fs::read_dir(crashes_dir())
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .is_some_and(|name| is_unseen_report(&name.to_string_lossy()))
+        })
+        .collect();
+    // Time-stamped names sort in time order
+    reports.sort();
+    reports.pop()
+}
+
+/// Keep a report but don't offer it again
+pub fn mark_seen(path: &Path) -> Result<()> {
+    let name = path
+        .file_name()
+        .context("Crash report path has no file name")?
+        .to_string_lossy();
+    let seen = path.with_file_name(name.replace(".txt", SEEN_SUFFIX));
+    std::fs::rename(path, seen).context("Failed to mark crash report as seen")
+}
+
+/// Link to a new GitHub issue filled in with a report
+pub fn issue_url(report: &str) -> String {
+    let panic = report
+        .lines()
+        .find_map(|line| line.strip_prefix("Panic: "))
+        .unwrap_or("unknown panic");
+    let title = format!("Crash: {}", panic.chars().take(80).collect::<String>());
+    let mut body: String = report.chars().take(MAX_ISSUE_BODY).collect();
+    if body.len() < report.len() {
+        body.push_str("\n[truncated]");
+    }
+    let body = format!("```\n{}\n```\n", body.trim_end());
+    format!(
+        "https://github.com/{}/issues/new?title={}&body={}",
+        REPOSITORY,
+        percent_encode(&title),
+        percent_encode(&body)
+    )
+}
+
+/// Encode everything but unreserved characters as `%XX`
+fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            byte => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_report() {
+        let report = format_report(
+            "2024-02-17T10:30:45+00:00",
+            "index out of bounds",
+            "src/desktop/main.rs:10:5",
+            "   0: main\n",
+        );
+        assert!(report.starts_with(&format!("logs-parser-desktop {}\nOS: ", CURRENT_VERSION)));
+        assert!(report.contains("\nPanic: index out of bounds\nAt: src/desktop/main.rs:10:5\n"));
+        assert!(report.ends_with("Backtrace:\n   0: main\n"));
+    }
+
+    #[test]
+    fn test_issue_url() {
+        let url = issue_url("logs-parser-desktop 0.2.0\nPanic: oops & more\n");
+        assert!(url.starts_with(
+            "https://github.com/harveyalex/logs-parser/issues/new?title=Crash%3A%20oops%20%26%20more&body=%60%60%60%0A"
+        ));
+
+        let long = issue_url(&"x".repeat(MAX_ISSUE_BODY * 2));
+        assert!(long.contains("title=Crash%3A%20unknown%20panic"));
+        assert!(long.contains("%5Btruncated%5D"));
+    }
+
+    #[test]
+    fn test_unseen_reports() {
+        assert!(is_unseen_report("crash-20240217-103045.txt"));
+        assert!(!is_unseen_report("crash-20240217-103045.seen.txt"));
+        assert!(!is_unseen_report("notes.txt"));
+    }
+}
@@ -0,0 +1,176 @@
+//! Passphrase encryption for exports that leave the machine
+//!
+//! An encrypted file is AES-256-GCM under a key derived from the passphrase
+//! with PBKDF2-HMAC-SHA256:
+//!
+//! ```text
+//! MAGIC | iterations (u32 BE) | salt (16) | nonce (12) | ciphertext + tag
+//! ```
+//!
+//! `logs-parser-desktop --decrypt <file>` prints the plaintext again.
+
+use anyhow::{anyhow, bail, Context, Result};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use std::io::{IsTerminal, Write};
+use std::num::NonZeroU32;
+use std::path::Path;
+
+/// Start of every encrypted file
+const MAGIC: &[u8] = b"logs-parser-enc1";
+
+const SALT_LEN: usize = 16;
+
+/// PBKDF2 rounds for new files; stored in the header so it can be raised
+const ITERATIONS: u32 = 600_000;
+
+/// Most PBKDF2 rounds a file may ask for, so a crafted header can't keep
+/// `--decrypt` busy for hours
+const MAX_ITERATIONS: u32 = 10 * ITERATIONS;
+
+/// Environment variable read for the passphrase instead of prompting
+pub const PASSPHRASE_VAR: &str = "LOGS_PARSER_PASSPHRASE";
+
+fn derive_key(passphrase: &str, salt: &[u8], iterations: NonZeroU32) -> Result<LessSafeKey> {
+    let mut key = [0u8; 32];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+    let key = UnboundKey::new(&AES_256_GCM, &key).map_err(|_| anyhow!("Invalid key length"))?;
+    Ok(LessSafeKey::new(key))
+}
+
+/// Whether `data` starts like an encrypted file
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Encrypt `plaintext` with a passphrase
+pub fn encrypt(plaintext: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+    encrypt_with(plaintext, passphrase, ITERATIONS)
+}
+
+fn encrypt_with(plaintext: &[u8], passphrase: &str, iterations: u32) -> Result<Vec<u8>> {
+    if passphrase.is_empty() {
+        bail!("An empty passphrase can't protect an export");
+    }
+    let rng = SystemRandom::new();
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rng.fill(&mut salt)
+        .and_then(|_| rng.fill(&mut nonce))
+        .map_err(|_| anyhow!("No secure random numbers available"))?;
+
+    let rounds = NonZeroU32::new(iterations).context("Iterations must be positive")?;
+    let key = derive_key(passphrase, &salt, rounds)?;
+    let mut sealed = plaintext.to_vec();
+    key.seal_in_place_append_tag(
+        Nonce::assume_unique_for_key(nonce),
+        Aad::from(MAGIC),
+        &mut sealed,
+    )
+    .map_err(|_| anyhow!("Encryption failed"))?;
+
+    let mut out = Vec::with_capacity(MAGIC.len() + 4 + SALT_LEN + NONCE_LEN + sealed.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&iterations.to_be_bytes());
+    out.extend_from_slice(&salt);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&sealed);
+    Ok(out)
+}
+
+/// Decrypt a file written by [`encrypt`]
+pub fn decrypt(data: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+    let Some(rest) = data.strip_prefix(MAGIC) else {
+        bail!("Not an encrypted logs-parser file");
+    };
+    if rest.len() < 4 + SALT_LEN + NONCE_LEN {
+        bail!("Encrypted file is truncated");
+    }
+    let (iterations, rest) = rest.split_at(4);
+    let (salt, rest) = rest.split_at(SALT_LEN);
+    let (nonce, sealed) = rest.split_at(NONCE_LEN);
+
+    let iterations = u32::from_be_bytes(iterations.try_into().expect("4 bytes"));
+    if iterations > MAX_ITERATIONS {
+        bail!(
+            "Encrypted file asks for {} key derivation rounds, more than the {} allowed",
+            iterations,
+            MAX_ITERATIONS
+        );
+    }
+    let rounds = NonZeroU32::new(iterations).context("Encrypted file is corrupt")?;
+    let key = derive_key(passphrase, salt, rounds)?;
+    let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| anyhow!("Bad nonce"))?;
+    let mut opened = sealed.to_vec();
+    let plaintext = key
+        .open_in_place(nonce, Aad::from(MAGIC), &mut opened)
+        .map_err(|_| anyhow!("Wrong passphrase, or the file was modified"))?;
+    Ok(plaintext.to_vec())
+}
+
+/// Passphrase from [`PASSPHRASE_VAR`], or asked for on the terminal without
+/// echoing it
+fn read_passphrase() -> Result<String> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_VAR) {
+        return Ok(passphrase);
+    }
+    if !std::io::stdin().is_terminal() {
+        bail!("Set {} to decrypt without a terminal", PASSPHRASE_VAR);
+    }
+    rpassword::prompt_password("Passphrase: ").context("Failed to read the passphrase")
+}
+
+/// Entry point for `logs-parser-desktop --decrypt <file>`: print the
+/// plaintext, e.g. to pipe it back in with `--stdin`
+pub fn run_decrypt(args: &[String]) -> Result<()> {
+    let [path] = args else {
+        bail!("Usage: logs-parser-desktop --decrypt <file>");
+    };
+    let data =
+        std::fs::read(Path::new(path)).with_context(|| format!("Failed to read {}", path))?;
+    let plaintext = decrypt(&data, &read_passphrase()?)?;
+    std::io::stdout().write_all(&plaintext)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let lines = b"2024-02-17T10:30:45.123456+00:00 app[web.1]: user=alice@example.com\n";
+        let sealed = encrypt_with(lines, "correct horse", 1000).unwrap();
+        assert!(is_encrypted(&sealed));
+        assert!(!sealed.windows(5).any(|w| w == b"alice"));
+        assert_eq!(decrypt(&sealed, "correct horse").unwrap(), lines);
+    }
+
+    #[test]
+    fn test_wrong_passphrase_or_tampering_fails() {
+        let mut sealed = encrypt_with(b"secret", "correct horse", 1000).unwrap();
+        assert!(decrypt(&sealed, "battery staple").is_err());
+
+        let last = sealed.len() - 1;
+        sealed[last] ^= 1;
+        assert!(decrypt(&sealed, "correct horse").is_err());
+
+        assert!(decrypt(b"plain text", "correct horse").is_err());
+        assert!(encrypt_with(b"secret", "", 1000).is_err());
+    }
+
+    #[test]
+    fn test_rejects_excessive_iterations() {
+        let mut sealed = encrypt_with(b"secret", "correct horse", 1000).unwrap();
+        sealed[MAGIC.len()..MAGIC.len() + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+        let err = decrypt(&sealed, "correct horse").unwrap_err();
+        assert!(err.to_string().contains("key derivation rounds"));
+    }
+}
@@ -0,0 +1,331 @@
+//! Background daemon that keeps buffering while no window is open
+//!
+//! ```text
+//! logs-parser-desktop daemon --app my-app --file log/development.log
+//! logs-parser-desktop daemon --app my-app --dyno web --num 200
+//! logs-parser-desktop --attach
+//! ```
+//!
+//! The daemon reads the same inputs as a window (a Heroku app and any
+//! `--file`/`--fifo`/`--unix-socket`/`--stdin` sources) without showing
+//! anything, taking the same `--num`, `--dyno` and `--source` options for
+//! the app, and keeps the newest lines in memory, plus in its own on-disk
+//! history when `history` is set. It serves them on
+//! `~/.config/logs-parser/daemon.sock`: a window started with `--attach`
+//! receives the buffered lines and then new ones as they arrive. Closing the
+//! window leaves the daemon running, like detaching from tmux.
+
+use crate::config::config_dir;
+use crate::deep_link::DeepLink;
+use crate::history::History;
+use crate::local_source::LocalSource;
+use crate::parser::ParsedLine;
+use crate::settings::Settings;
+use crate::stream_manager::{StreamManager, StreamOptions};
+use anyhow::{bail, Context, Result};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{broadcast, mpsc};
+use tokio::time::Duration;
+
+const USAGE: &str = "Usage: daemon [--app <name> [--num <lines>] [--dyno <name>] [--source <name>]] [--file <path>] [--fifo <path>] [--unix-socket <path>] [--stdin]";
+
+/// Lines kept for windows that attach later
+const BACKLOG_LINES: usize = 10000;
+
+/// New lines held for an attached window that is slow to read them
+const LIVE_LINES: usize = 4096;
+
+/// How often the daemon's history is written out
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often the Heroku stream is checked and reconnected if it ended
+const MONITOR_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Where the daemon serves its lines
+pub fn socket_path() -> PathBuf {
+    config_dir().join("daemon.sock")
+}
+
+/// The newest raw lines, oldest first
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backlog {
+    lines: VecDeque<String>,
+    capacity: usize,
+}
+
+impl Backlog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lines: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    /// Add a line, dropping the oldest once full
+    pub fn push(&mut self, line: String) {
+        if self.lines.len() == self.capacity {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = &String> {
+        self.lines.iter()
+    }
+}
+
+/// The Heroku app to stream, from `--app <name>`
+fn app_from_args(args: &[String]) -> Result<Option<String>> {
+    let Some(at) = args.iter().position(|arg| arg == "--app") else {
+        return Ok(None);
+    };
+    match args.get(at + 1) {
+        Some(app) if !app.starts_with('-') => Ok(Some(app.clone())),
+        _ => bail!("--app needs an app name"),
+    }
+}
+
+/// The raw text of a received line, whether or not it parsed
+fn raw_line(result: ParsedLine) -> String {
+    match result {
+        Ok(entry) => entry.raw,
+        Err(unparsed) => unparsed.line,
+    }
+}
+
+/// Run the daemon until its inputs end or it is killed
+pub fn run_daemon(args: &[String]) -> Result<()> {
+    let app = app_from_args(args)?;
+    let sources = LocalSource::from_args(args.to_vec());
+    if app.is_none() && sources.is_empty() {
+        bail!(USAGE);
+    }
+    let settings = Settings::load();
+    let options = DeepLink::from_args(args)?
+        .unwrap_or_default()
+        .stream_options(settings.stream_history);
+    let history = match settings.history {
+        Some(max_bytes) => Some(History::open(&config_dir().join("daemon"), max_bytes)?),
+        None => None,
+    };
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(async move {
+        let (tx, rx) = mpsc::unbounded_channel::<(String, String)>();
+        for source in sources {
+            eprintln!("{}", source.describe());
+            let (parsed_tx, mut parsed_rx) = mpsc::unbounded_channel::<ParsedLine>();
+            let label = source.label();
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                while let Some(result) = parsed_rx.recv().await {
+                    if tx.send((label.clone(), raw_line(result))).is_err() {
+                        break;
+                    }
+                }
+            });
+            tokio::spawn(async move {
+                if let Err(e) = source.listen(parsed_tx).await {
+                    eprintln!("{:#}", e);
+                }
+            });
+        }
+        // Once every input has ended the sender is gone and serving stops
+        match app {
+            Some(app) => {
+                tokio::spawn(stream_app(app, options, tx));
+            }
+            None => drop(tx),
+        }
+        eprintln!("Serving on {}", socket_path().display());
+        serve(&socket_path(), rx, history).await
+    })
+}
+
+/// Stream a Heroku app's logs, reconnecting when the stream ends
+async fn stream_app(
+    app: String,
+    options: StreamOptions,
+    sender: mpsc::UnboundedSender<(String, String)>,
+) {
+    let (parsed_tx, mut parsed_rx) = mpsc::unbounded_channel::<ParsedLine>();
+    let label = format!("heroku:{}", app);
+    let mut manager = StreamManager::new(app, options, parsed_tx);
+    if let Err(e) = manager.connect().await {
+        eprintln!("Connection failed: {:#}", e);
+        return;
+    }
+    let mut monitor = tokio::time::interval(MONITOR_INTERVAL);
+    loop {
+        tokio::select! {
+            result = parsed_rx.recv() => {
+                let Some(result) = result else { return };
+                if sender.send((label.clone(), raw_line(result))).is_err() {
+                    return;
+                }
+            }
+            _ = monitor.tick() => {
+                if !manager.is_running().await {
+                    if let Err(e) = manager.reconnect().await {
+                        eprintln!("Reconnection failed: {:#}", e);
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Buffer `(origin, raw)` lines from `receiver` and send them to every
+/// window that attaches on `path`, until the inputs are gone
+pub async fn serve(
+    path: &Path,
+    mut receiver: mpsc::UnboundedReceiver<(String, String)>,
+    mut history: Option<History>,
+) -> Result<()> {
+    // A socket file left behind by a previous run blocks bind(), but one
+    // that still answers belongs to a daemon that is running
+    if UnixStream::connect(path).await.is_ok() {
+        bail!("A daemon is already running on {}", path.display());
+    }
+    let _ = std::fs::remove_file(path);
+    let listener = UnixListener::bind(path)
+        .with_context(|| format!("Failed to listen on {}", path.display()))?;
+
+    let mut backlog = Backlog::new(BACKLOG_LINES);
+    if let Some(history) = history.as_mut() {
+        for entry in history.recent(BACKLOG_LINES)? {
+            backlog.push(entry.raw);
+        }
+    }
+    let (live, _) = broadcast::channel::<String>(LIVE_LINES);
+    let mut flush = tokio::time::interval(FLUSH_INTERVAL);
+    loop {
+        tokio::select! {
+            received = receiver.recv() => {
+                let Some((origin, raw)) = received else { break };
+                if let Some(history) = history.as_mut() {
+                    history.append(&origin, &raw)?;
+                }
+                backlog.push(raw.clone());
+                // No attached window is fine
+                let _ = live.send(raw);
+            }
+            accepted = listener.accept() => {
+                let (stream, _) = accepted?;
+                let lines: Vec<String> = backlog.lines().cloned().collect();
+                tokio::spawn(send_lines(stream, lines, live.subscribe()));
+            }
+            _ = flush.tick() => {
+                if let Some(history) = history.as_mut() {
+                    history.flush()?;
+                }
+            }
+        }
+    }
+    if let Some(history) = history.as_mut() {
+        history.flush()?;
+    }
+    let _ = std::fs::remove_file(path);
+    Ok(())
+}
+
+/// Send an attached window the backlog, then new lines until it detaches
+async fn send_lines(
+    mut stream: UnixStream,
+    backlog: Vec<String>,
+    mut live: broadcast::Receiver<String>,
+) {
+    let mut text = String::new();
+    for line in backlog {
+        text.push_str(&line);
+        text.push('\n');
+    }
+    if stream.write_all(text.as_bytes()).await.is_err() {
+        return;
+    }
+    loop {
+        let line = match live.recv().await {
+            Ok(line) => line,
+            // A window that fell behind misses some lines rather than
+            // holding the daemon up
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => return,
+        };
+        if stream
+            .write_all(format!("{}\n", line).as_bytes())
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_app_from_args() {
+        assert_eq!(
+            app_from_args(&args(&["--stdin", "--app", "my-app"])).unwrap(),
+            Some("my-app".to_string())
+        );
+        assert_eq!(app_from_args(&args(&["--stdin"])).unwrap(), None);
+        assert!(app_from_args(&args(&["--app", "--stdin"])).is_err());
+        assert!(run_daemon(&args(&[])).is_err());
+    }
+
+    #[test]
+    fn test_backlog_keeps_newest() {
+        let mut backlog = Backlog::new(2);
+        for line in ["one", "two", "three"] {
+            backlog.push(line.to_string());
+        }
+        let lines: Vec<&String> = backlog.lines().collect();
+        assert_eq!(lines, ["two", "three"]);
+    }
+
+    #[tokio::test]
+    async fn test_attached_window_gets_backlog_then_live_lines() {
+        let path =
+            std::env::temp_dir().join(format!("logs-parser-daemon-{}.sock", std::process::id()));
+        let (tx, rx) = mpsc::unbounded_channel();
+        tx.send(("stdin".to_string(), "earlier".to_string()))
+            .unwrap();
+        let server = tokio::spawn({
+            let path = path.clone();
+            async move { serve(&path, rx, None).await }
+        });
+
+        let stream = loop {
+            match UnixStream::connect(&path).await {
+                Ok(stream) => break stream,
+                Err(_) => tokio::task::yield_now().await,
+            }
+        };
+        let mut lines = BufReader::new(stream).lines();
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "earlier");
+
+        tx.send(("stdin".to_string(), "later".to_string())).unwrap();
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "later");
+
+        // A second daemon refuses to take over the socket
+        let (_other_tx, other_rx) = mpsc::unbounded_channel();
+        assert!(serve(&path, other_rx, None).await.is_err());
+        assert!(path.exists());
+
+        drop(tx);
+        server.await.unwrap().unwrap();
+        assert!(!path.exists());
+    }
+}
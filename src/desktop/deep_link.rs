@@ -0,0 +1,295 @@
+//! Opening the app on an app and filters, from the command line or a link
+//!
+//! Runbooks, shell aliases and alert messages can launch straight into the
+//! right view:
+//!
+//! ```text
+//! logs-parser-desktop --app my-app --filter level:error --filter dyno:web.1
+//! logsparser://app/my-app?filter=level:error&filter=dyno:web.1
+//! logs-parser-desktop --app my-app --num 200 --dyno web --source app
+//! logs-parser-desktop --session incident.log --goto-id 12345
+//! ```
+//!
+//! The app is connected to as soon as the Heroku CLI has been checked, and
+//! the filters replace any already set. `--num`, `--dyno` and `--source`
+//! (or `num=`, `dyno=` and `source=` in a link) are passed on to
+//! `heroku logs` for that connection. `--session` replays a recorded
+//! session instead, and `--goto-id` opens it paused on one of its lines so
+//! a teammate can be pointed at the line under discussion. On macOS links
+//! arrive as an event rather than an argument, so both ways go through one
+//! queue that the first window takes.
+
+use crate::backfill::parse_history_lines;
+use crate::stream_manager::StreamOptions;
+use anyhow::{anyhow, bail, Result};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use tokio::sync::mpsc;
+
+/// Scheme registered for links, without `://`
+pub const SCHEME: &str = "logsparser";
+
+/// An app to connect to and filters to show
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeepLink {
+    pub app: Option<String>,
+    pub filters: Vec<String>,
+    /// Lines of history to start with, instead of the `stream_history`
+    /// setting
+    pub num: Option<usize>,
+    /// Only stream this dyno or process type
+    pub dyno: Option<String>,
+    /// Only stream this source, such as `app` or `heroku`
+    pub source: Option<String>,
+    /// Session file to replay
+    pub session: Option<PathBuf>,
+    /// Id of the session line to open on
+    pub goto_id: Option<usize>,
+}
+
+impl DeepLink {
+    /// Find `--app <name>`, every `--filter <query>`, the `heroku logs`
+    /// options, `--session`/`--goto-id` and a `logsparser://` link in the
+    /// command-line arguments (without the program name)
+    pub fn from_args(args: &[String]) -> Result<Option<Self>> {
+        let mut link = Self::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--app" => match args.next() {
+                    Some(app) if !app.starts_with('-') => link.app = Some(app.clone()),
+                    _ => bail!("--app needs an app name"),
+                },
+                "--filter" => {
+                    let query = args
+                        .next()
+                        .ok_or_else(|| anyhow!("--filter needs a query"))?;
+                    link.filters.push(query.clone());
+                }
+                "--num" => match args.next().and_then(|value| parse_history_lines(value)) {
+                    Some(num) => link.num = Some(num),
+                    None => bail!("--num needs a line count from 1 to 1500"),
+                },
+                "--dyno" => match args.next() {
+                    Some(dyno) if !dyno.starts_with('-') => link.dyno = Some(dyno.clone()),
+                    _ => bail!("--dyno needs a dyno or process type"),
+                },
+                "--source" => match args.next() {
+                    Some(source) if !source.starts_with('-') => link.source = Some(source.clone()),
+                    _ => bail!("--source needs a source such as app or heroku"),
+                },
+                "--session" => match args.next() {
+                    Some(path) if !path.starts_with('-') => link.session = Some(path.into()),
+                    _ => bail!("--session needs a session file"),
+                },
+                "--goto-id" => match args.next().and_then(|id| id.parse().ok()) {
+                    Some(id) if id > 0 => link.goto_id = Some(id),
+                    _ => bail!("--goto-id needs a line id from the session file"),
+                },
+                url if is_link(url) => {
+                    let parsed = Self::parse_url(url)?;
+                    link.app = parsed.app.or(link.app);
+                    link.filters.extend(parsed.filters);
+                    link.num = parsed.num.or(link.num);
+                    link.dyno = parsed.dyno.or(link.dyno);
+                    link.source = parsed.source.or(link.source);
+                }
+                _ => {}
+            }
+        }
+        if link.goto_id.is_some() && link.session.is_none() {
+            bail!("--goto-id needs --session <file>");
+        }
+        Ok((link != Self::default()).then_some(link))
+    }
+
+    /// What to ask `heroku logs` for, starting with `num` lines of history
+    /// unless the link says otherwise
+    pub fn stream_options(&self, num: usize) -> StreamOptions {
+        StreamOptions {
+            num: self.num.unwrap_or(num),
+            dyno: self.dyno.clone(),
+            source: self.source.clone(),
+        }
+    }
+
+    /// Parse `logsparser://app/<name>?filter=<query>&filter=...`, with
+    /// optional `num=`, `dyno=` and `source=`; the app part is optional
+    pub fn parse_url(url: &str) -> Result<Self> {
+        let rest = url
+            .get(SCHEME.len() + 3..)
+            .filter(|_| is_link(url))
+            .ok_or_else(|| anyhow!("Not a {}:// link: {}", SCHEME, url))?;
+        let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let app = match segments.as_slice() {
+            [] => None,
+            ["app", app] => Some(percent_decode(app)),
+            _ => bail!("Unknown link: {} (expected {}://app/<name>)", url, SCHEME),
+        };
+        let mut link = Self {
+            app,
+            ..Self::default()
+        };
+        for (key, value) in query.split('&').filter_map(|pair| pair.split_once('=')) {
+            let value = percent_decode(value);
+            if value.trim().is_empty() {
+                continue;
+            }
+            match key {
+                "filter" => link.filters.push(value),
+                "num" => {
+                    link.num = Some(
+                        parse_history_lines(&value)
+                            .ok_or_else(|| anyhow!("num must be from 1 to 1500: {}", value))?,
+                    )
+                }
+                "dyno" => link.dyno = Some(value),
+                "source" => link.source = Some(value),
+                _ => {}
+            }
+        }
+        Ok(link)
+    }
+}
+
+fn is_link(arg: &str) -> bool {
+    arg.get(..SCHEME.len() + 3)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case(&format!("{}://", SCHEME)))
+}
+
+/// Decode `%XX` escapes and `+` for a space; invalid escapes are kept as is
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' => {
+                let hex = value
+                    .get(i + 1..i + 3)
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                if let Some(byte) = hex {
+                    decoded.push(byte);
+                    i += 2;
+                } else {
+                    decoded.push(b'%');
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+type Queue = (
+    mpsc::UnboundedSender<DeepLink>,
+    Mutex<Option<mpsc::UnboundedReceiver<DeepLink>>>,
+);
+
+fn queue() -> &'static Queue {
+    static QUEUE: OnceLock<Queue> = OnceLock::new();
+    QUEUE.get_or_init(|| {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (tx, Mutex::new(Some(rx)))
+    })
+}
+
+/// Queue a link for the first window, which may not be open yet
+pub fn open(link: DeepLink) {
+    let _ = queue().0.send(link);
+}
+
+/// Links opened so far and from now on; only the first caller gets them
+pub fn take_links() -> Option<mpsc::UnboundedReceiver<DeepLink>> {
+    queue().1.lock().ok()?.take()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_from_args() {
+        let link = DeepLink::from_args(&args(&[
+            "--app",
+            "my-app",
+            "--filter",
+            "level:error",
+            "--file",
+            "log/development.log",
+            "--filter",
+            "dyno:web.1 timeout",
+        ]))
+        .unwrap()
+        .unwrap();
+        assert_eq!(link.app.as_deref(), Some("my-app"));
+        assert_eq!(link.filters, ["level:error", "dyno:web.1 timeout"]);
+
+        assert_eq!(DeepLink::from_args(&args(&["--stdin"])).unwrap(), None);
+        assert!(DeepLink::from_args(&args(&["--app", "--stdin"])).is_err());
+        assert!(DeepLink::from_args(&args(&["--filter"])).is_err());
+    }
+
+    #[test]
+    fn test_stream_options() {
+        let link = DeepLink::from_args(&args(&[
+            "--app", "my-app", "--num", "200", "--dyno", "web", "--source", "app",
+        ]))
+        .unwrap()
+        .unwrap();
+        assert_eq!(link.num, Some(200));
+        assert_eq!(link.dyno.as_deref(), Some("web"));
+        assert_eq!(link.source.as_deref(), Some("app"));
+        assert!(DeepLink::from_args(&args(&["--num", "5000"])).is_err());
+        assert!(DeepLink::from_args(&args(&["--dyno", "--num"])).is_err());
+
+        let link = DeepLink::parse_url("logsparser://app/my-app?num=50&dyno=worker.2").unwrap();
+        assert_eq!(link.num, Some(50));
+        assert_eq!(link.dyno.as_deref(), Some("worker.2"));
+        assert!(DeepLink::parse_url("logsparser://app/my-app?num=0").is_err());
+    }
+
+    #[test]
+    fn test_session_goto() {
+        let link = DeepLink::from_args(&args(&["--session", "incident.log", "--goto-id", "12345"]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(link.session, Some(PathBuf::from("incident.log")));
+        assert_eq!(link.goto_id, Some(12345));
+        assert!(DeepLink::from_args(&args(&["--goto-id", "12"])).is_err());
+        assert!(DeepLink::from_args(&args(&["--session", "a.log", "--goto-id", "0"])).is_err());
+        assert!(DeepLink::from_args(&args(&["--session", "--goto-id"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_url() {
+        let link =
+            DeepLink::parse_url("logsparser://app/my-app?filter=level:error&filter=cache+miss%21")
+                .unwrap();
+        assert_eq!(link.app.as_deref(), Some("my-app"));
+        assert_eq!(link.filters, ["level:error", "cache miss!"]);
+
+        let link = DeepLink::parse_url("LogsParser://?filter=status%3A5&other=x").unwrap();
+        assert_eq!(link.app, None);
+        assert_eq!(link.filters, ["status:5"]);
+
+        assert!(DeepLink::parse_url("logsparser://apps").is_err());
+        assert!(DeepLink::parse_url("https://app/my-app").is_err());
+    }
+
+    #[test]
+    fn test_link_argument() {
+        let link = DeepLink::from_args(&args(&["logsparser://app/staging/?filter=level:warn%2B"]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(link.app.as_deref(), Some("staging"));
+        assert_eq!(link.filters, ["level:warn+"]);
+    }
+}
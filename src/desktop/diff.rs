@@ -0,0 +1,325 @@
+//! Side-by-side comparison of two captured log files
+//!
+//! `logs-parser-desktop diff good.log bad.log` prints both captures in two
+//! columns, lined up by the time elapsed since each one started. Lines of
+//! the same kind (see [`fingerprint`]) share a row; a line whose kind never
+//! appears in the other capture is marked `-` (left only) or `+` (right
+//! only), which is where a bad deploy usually shows itself:
+//!
+//! ```text
+//!   T+00:00:00.000 app[web.1]: Booting       │   T+00:00:00.000 app[web.1]: Booting
+//!                                            │ + T+00:00:01.250 app[web.1]: PG::ConnectionBad
+//! ```
+
+use crate::crypt;
+use crate::ignore::fingerprint;
+use crate::parser::{parse_log_line, LogEntry};
+use crate::session::Session;
+use crate::truncate::truncate;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, FixedOffset};
+use std::collections::HashSet;
+use std::io::IsTerminal;
+use std::path::Path;
+
+/// Total output width used when `COLUMNS` isn't set
+const DEFAULT_WIDTH: usize = 160;
+
+/// One row of the comparison: a line from either side, or a pair of lines
+/// of the same kind
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffRow {
+    pub left: Option<DiffCell>,
+    pub right: Option<DiffCell>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffCell {
+    /// Index of the entry in its capture
+    pub index: usize,
+    /// No line of this kind appears anywhere in the other capture
+    pub unique: bool,
+}
+
+/// Read a capture: a plain log file, or a session recorded with **● Record**
+///
+/// Lines that don't parse are kept as plain entries with the time of the
+/// line before them, so they stay in place when the sides are lined up.
+pub fn load(path: &Path) -> Result<Vec<LogEntry>> {
+    let contents =
+        std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    if crypt::is_encrypted(&contents) {
+        bail!(
+            "{} is encrypted; decrypt it with --decrypt first",
+            path.display()
+        );
+    }
+    let contents = String::from_utf8_lossy(&contents);
+    let lines: Vec<String> = match Session::parse(&contents) {
+        Ok(session) if !session.is_empty() => session
+            .lines()
+            .iter()
+            .map(|line| line.raw.clone())
+            .collect(),
+        _ => contents.lines().map(str::to_string).collect(),
+    };
+    let mut entries: Vec<LogEntry> = Vec::new();
+    for line in lines.iter().filter(|line| !line.trim().is_empty()) {
+        let entry = parse_log_line(line).unwrap_or_else(|| {
+            let mut entry = LogEntry::local(line, "plain");
+            if let Some(previous) = entries.last() {
+                entry.timestamp = previous.timestamp;
+            }
+            entry
+        });
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// Line up two captures by elapsed time, pairing neighbouring lines of the
+/// same kind
+pub fn diff(left: &[LogEntry], right: &[LogEntry]) -> Vec<DiffRow> {
+    let left_kinds: Vec<String> = left.iter().map(fingerprint).collect();
+    let right_kinds: Vec<String> = right.iter().map(fingerprint).collect();
+    let left_set: HashSet<&String> = left_kinds.iter().collect();
+    let right_set: HashSet<&String> = right_kinds.iter().collect();
+
+    let left_start = left.first().map(|entry| entry.timestamp);
+    let right_start = right.first().map(|entry| entry.timestamp);
+    let elapsed = |entry: &LogEntry, start: Option<DateTime<FixedOffset>>| {
+        start.map_or(chrono::Duration::zero(), |start| entry.timestamp - start)
+    };
+
+    let mut rows = Vec::new();
+    let (mut l, mut r) = (0, 0);
+    while l < left.len() || r < right.len() {
+        let left_cell = |index: usize| DiffCell {
+            index,
+            unique: !right_set.contains(&left_kinds[index]),
+        };
+        let right_cell = |index: usize| DiffCell {
+            index,
+            unique: !left_set.contains(&right_kinds[index]),
+        };
+
+        if l < left.len() && r < right.len() && left_kinds[l] == right_kinds[r] {
+            rows.push(DiffRow {
+                left: Some(left_cell(l)),
+                right: Some(right_cell(r)),
+            });
+            l += 1;
+            r += 1;
+        } else if r == right.len()
+            || (l < left.len() && elapsed(&left[l], left_start) <= elapsed(&right[r], right_start))
+        {
+            rows.push(DiffRow {
+                left: Some(left_cell(l)),
+                right: None,
+            });
+            l += 1;
+        } else {
+            rows.push(DiffRow {
+                left: None,
+                right: Some(right_cell(r)),
+            });
+            r += 1;
+        }
+    }
+    rows
+}
+
+/// One side of a row: a `-`/`+` marker for unique lines, the elapsed time,
+/// origin and message, cut or padded to `width` characters
+fn format_cell(
+    cell: Option<&DiffCell>,
+    entries: &[LogEntry],
+    marker: char,
+    width: usize,
+) -> String {
+    let text = match cell {
+        Some(cell) => {
+            let entry = &entries[cell.index];
+            let start = entries[0].timestamp;
+            format!(
+                "{} {} {}[{}]: {}",
+                if cell.unique { marker } else { ' ' },
+                entry.format_elapsed(start),
+                entry.source,
+                entry.dyno,
+                entry.message
+            )
+        }
+        None => String::new(),
+    };
+    let text = match truncate(&text, width) {
+        Some((shown, _)) => shown.to_string(),
+        None => text,
+    };
+    format!("{:<width$}", text, width = width)
+}
+
+/// Render the rows as two columns of `width` characters in total, coloring
+/// unique lines when `color` is set
+pub fn render(
+    rows: &[DiffRow],
+    left: &[LogEntry],
+    right: &[LogEntry],
+    width: usize,
+    color: bool,
+) -> Vec<String> {
+    let column = width.saturating_sub(3) / 2;
+    let paint = |text: String, unique: bool, code: &str| {
+        if color && unique {
+            format!("\x1b[{}m{}\x1b[0m", code, text)
+        } else {
+            text
+        }
+    };
+    rows.iter()
+        .map(|row| {
+            let left_text = format_cell(row.left.as_ref(), left, '-', column);
+            let right_text = format_cell(row.right.as_ref(), right, '+', column);
+            format!(
+                "{} │ {}",
+                paint(left_text, row.left.as_ref().is_some_and(|c| c.unique), "31"),
+                paint(
+                    right_text,
+                    row.right.as_ref().is_some_and(|c| c.unique),
+                    "32"
+                ),
+            )
+            .trim_end()
+            .to_string()
+        })
+        .collect()
+}
+
+/// Entry point for `logs-parser-desktop diff <left> <right>`
+pub fn run_diff(args: &[String]) -> Result<()> {
+    let [left_path, right_path] = args else {
+        bail!("Usage: logs-parser-desktop diff <left.log> <right.log>");
+    };
+    let left = load(Path::new(left_path))?;
+    let right = load(Path::new(right_path))?;
+    let rows = diff(&left, &right);
+
+    let width = std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .unwrap_or(DEFAULT_WIDTH);
+    for line in render(&rows, &left, &right, width, std::io::stdout().is_terminal()) {
+        println!("{}", line);
+    }
+
+    let left_only = rows
+        .iter()
+        .filter(|r| r.left.as_ref().is_some_and(|c| c.unique))
+        .count();
+    let right_only = rows
+        .iter()
+        .filter(|r| r.right.as_ref().is_some_and(|c| c.unique))
+        .count();
+    println!(
+        "\n{} line(s) only in {}, {} line(s) only in {}",
+        left_only, left_path, right_only, right_path
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capture(lines: &[(&str, &str)]) -> Vec<LogEntry> {
+        lines
+            .iter()
+            .map(|(time, message)| {
+                parse_log_line(&format!(
+                    "2024-02-17T{}.000000+00:00 app[web.1]: {}",
+                    time, message
+                ))
+                .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_pairs_lines_of_the_same_kind() {
+        // The right capture started an hour later and lost its database
+        let left = capture(&[
+            ("10:00:00", "Booting"),
+            ("10:00:01", "Listening on port 5000"),
+            ("10:00:02", "GET /users 200 in 12ms"),
+        ]);
+        let right = capture(&[
+            ("11:00:00", "Booting"),
+            ("11:00:01", "PG::ConnectionBad: could not connect"),
+            ("11:00:01", "Listening on port 5000"),
+            ("11:00:03", "GET /users 500 in 3ms"),
+        ]);
+
+        let rows = diff(&left, &right);
+        let cell = |index, unique| Some(DiffCell { index, unique });
+        assert_eq!(
+            rows,
+            [
+                DiffRow {
+                    left: cell(0, false),
+                    right: cell(0, false)
+                },
+                DiffRow {
+                    left: cell(1, false),
+                    right: None
+                },
+                DiffRow {
+                    left: None,
+                    right: cell(1, true)
+                },
+                DiffRow {
+                    left: None,
+                    right: cell(2, false)
+                },
+                DiffRow {
+                    left: cell(2, false),
+                    right: cell(3, false)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_marks_unique_lines() {
+        let left = capture(&[("10:00:00", "Booting")]);
+        let right = capture(&[("11:00:00", "Booting"), ("11:00:01", "Crashed")]);
+        let rows = diff(&left, &right);
+
+        let lines = render(&rows, &left, &right, 83, false);
+        assert_eq!(
+            lines,
+            [
+                format!(
+                    "{:<40} │   T+00:00:00.000 app[web.1]: Booting",
+                    "  T+00:00:00.000 app[web.1]: Booting"
+                ),
+                format!("{:<40} │ + T+00:00:01.000 app[web.1]: Crashed", ""),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_keeps_plain_lines_in_place() {
+        let path =
+            std::env::temp_dir().join(format!("logs-parser-diff-{}.log", std::process::id()));
+        std::fs::write(
+            &path,
+            "2024-02-17T10:00:05.000000+00:00 app[web.1]: Booting\n  from app.rb:12\n\n",
+        )
+        .unwrap();
+        let entries = load(&path).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].message, "  from app.rb:12");
+        assert_eq!(entries[1].timestamp, entries[0].timestamp);
+        let _ = std::fs::remove_file(&path);
+    }
+}
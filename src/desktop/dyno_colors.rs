@@ -0,0 +1,69 @@
+//! Stable per-dyno colors, so interleaved output from several dynos can be
+//! told apart at a glance
+
+/// Which set of colors dynos are drawn from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DynoPalette {
+    #[default]
+    Vivid,
+    /// Okabe-Ito colors, distinguishable with common color blindness
+    ColorBlind,
+}
+
+const VIVID: &[&str] = &[
+    "#00d4aa", "#00b4d8", "#ff6b9d", "#ffd166", "#a78bfa", "#f97316", "#4ade80", "#f472b6",
+];
+
+const COLOR_BLIND: &[&str] = &[
+    "#e69f00", "#56b4e9", "#009e73", "#f0e442", "#0072b2", "#d55e00", "#cc79a7",
+];
+
+impl DynoPalette {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "vivid" => Some(DynoPalette::Vivid),
+            "colorblind" | "color_blind" => Some(DynoPalette::ColorBlind),
+            _ => None,
+        }
+    }
+
+    fn colors(self) -> &'static [&'static str] {
+        match self {
+            DynoPalette::Vivid => VIVID,
+            DynoPalette::ColorBlind => COLOR_BLIND,
+        }
+    }
+
+    /// Color for a dyno, the same for a given name on every run
+    pub fn color(self, dyno: &str) -> &'static str {
+        // FNV-1a, since std's hasher isn't guaranteed stable across releases
+        let hash = dyno.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+        let colors = self.colors();
+        colors[(hash % colors.len() as u64) as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_is_stable() {
+        let palette = DynoPalette::Vivid;
+        assert_eq!(palette.color("web.1"), palette.color("web.1"));
+        assert!(VIVID.contains(&palette.color("worker.3")));
+    }
+
+    #[test]
+    fn test_dynos_spread_across_palette() {
+        let palette = DynoPalette::ColorBlind;
+        let colors: std::collections::HashSet<&str> =
+            ["web.1", "web.2", "web.3", "worker.1", "router"]
+                .iter()
+                .map(|dyno| palette.color(dyno))
+                .collect();
+        assert!(colors.len() > 1);
+    }
+}
@@ -0,0 +1,388 @@
+//! Exports of the log buffer for postmortems
+
+use crate::l2met::{self, Aggregate, Kind};
+use crate::parser::{LogEntry, LogLevel};
+use crate::requests::router_service_ms;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, FixedOffset, Timelike};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Per-minute counts used for the timeline export
+#[derive(Debug, Default)]
+struct MinuteBucket {
+    total: usize,
+    error: usize,
+    warn: usize,
+    info: usize,
+    debug: usize,
+    unknown: usize,
+    router_ms_sum: u64,
+    router_requests: u64,
+    /// `count#`/`measure#`/`sample#` metrics logged in the minute
+    metrics: BTreeMap<String, (Kind, Aggregate)>,
+}
+
+/// Build a CSV with one row per minute: counts by level, the average
+/// router latency (`service=`) of requests in that minute, and a column per
+/// l2met metric logged (counts summed, measures averaged, last sample)
+pub fn timeline_csv(entries: &[LogEntry]) -> String {
+    let mut buckets: BTreeMap<DateTime<FixedOffset>, MinuteBucket> = BTreeMap::new();
+
+    for entry in entries {
+        let minute = entry
+            .timestamp
+            .with_second(0)
+            .and_then(|t| t.with_nanosecond(0))
+            .unwrap_or(entry.timestamp);
+        let bucket = buckets.entry(minute).or_default();
+
+        bucket.total += 1;
+        match entry.level {
+            LogLevel::Error => bucket.error += 1,
+            LogLevel::Warn => bucket.warn += 1,
+            LogLevel::Info => bucket.info += 1,
+            LogLevel::Debug => bucket.debug += 1,
+            LogLevel::Unknown => bucket.unknown += 1,
+        }
+        if entry.dyno == "router" {
+            if let Some(ms) = router_service_ms(entry) {
+                bucket.router_ms_sum += ms;
+                bucket.router_requests += 1;
+            }
+        }
+        for measurement in l2met::measurements(&entry.message) {
+            let (_, aggregate) = bucket
+                .metrics
+                .entry(measurement.key())
+                .or_insert((measurement.kind, Aggregate::default()));
+            aggregate.add(measurement.value);
+        }
+    }
+
+    let mut metric_names: Vec<&String> = buckets.values().flat_map(|b| b.metrics.keys()).collect();
+    metric_names.sort();
+    metric_names.dedup();
+
+    let mut csv = String::from("minute,total,error,warn,info,debug,unknown,avg_router_ms");
+    for name in &metric_names {
+        csv.push(',');
+        csv.push_str(name);
+    }
+    csv.push('\n');
+    for (minute, b) in &buckets {
+        let avg = if b.router_requests > 0 {
+            format!("{:.1}", b.router_ms_sum as f64 / b.router_requests as f64)
+        } else {
+            String::new()
+        };
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{}",
+            minute.to_rfc3339(),
+            b.total,
+            b.error,
+            b.warn,
+            b.info,
+            b.debug,
+            b.unknown,
+            avg
+        ));
+        for name in &metric_names {
+            csv.push(',');
+            if let Some((kind, aggregate)) = b.metrics.get(*name) {
+                csv.push_str(&l2met::format_value(aggregate.value(*kind)));
+            }
+        }
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Default location for an export: `~/Downloads/logs-parser-<name>-<timestamp>.<ext>`
+pub fn default_export_path(name: &str, extension: &str) -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    let downloads = std::path::Path::new(&home).join("Downloads");
+    let dir = if downloads.is_dir() {
+        downloads
+    } else {
+        PathBuf::from(home)
+    };
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    dir.join(format!("logs-parser-{}-{}.{}", name, stamp, extension))
+}
+
+/// Expand a leading `~` to the home directory
+pub fn expand_home(path: &str) -> PathBuf {
+    let home = || std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    match path.strip_prefix("~/") {
+        Some(rest) => Path::new(&home()).join(rest),
+        None if path == "~" => PathBuf::from(home()),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Where to write an export asked for with a path: the file itself, or a
+/// default-named file inside a directory. Fails if the directory is missing
+/// or read-only, so nothing is half-written.
+pub fn export_destination(path: &str, name: &str, extension: &str) -> Result<PathBuf> {
+    let path = expand_home(path);
+    let path = if path.is_dir() {
+        let default = default_export_path(name, extension);
+        path.join(default.file_name().expect("default path has a file name"))
+    } else {
+        path
+    };
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let metadata = std::fs::metadata(&dir)
+        .ok()
+        .filter(|metadata| metadata.is_dir());
+    match metadata {
+        None => bail!("{} is not a directory", dir.display()),
+        Some(metadata) if metadata.permissions().readonly() => {
+            bail!("{} is not writable", dir.display())
+        }
+        Some(_) => Ok(path),
+    }
+}
+
+/// Raw lines of the given entries, one per line, as received
+pub fn raw_lines(entries: &[LogEntry]) -> String {
+    entries
+        .iter()
+        .map(|entry| format!("{}\n", entry.raw))
+        .collect()
+}
+
+#[derive(Debug, Serialize)]
+struct EntryJson<'a> {
+    timestamp: String,
+    level: String,
+    source: &'a str,
+    dyno: &'a str,
+    origin: &'a str,
+    message: &'a str,
+    fields: BTreeMap<&'a str, &'a str>,
+}
+
+/// One entry as pretty-printed JSON, fields sorted by name
+pub fn entry_json(entry: &LogEntry) -> String {
+    let json = EntryJson {
+        timestamp: entry.timestamp.to_rfc3339(),
+        level: format!("{:?}", entry.level),
+        source: &entry.source,
+        dyno: &entry.dyno,
+        origin: &entry.origin,
+        message: &entry.message,
+        fields: entry
+            .fields
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect(),
+    };
+    serde_json::to_string_pretty(&json).expect("entry serializes")
+}
+
+/// What an export contains, for an incident record: written beside the
+/// export as `<file>.manifest.json` so the file can be checked later
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Manifest {
+    /// File name of the export
+    pub file: String,
+    /// Hex SHA-256 of the exported file's contents
+    pub sha256: String,
+    /// Number of log entries the export was made from
+    pub entries: usize,
+    /// Time of the earliest and latest of those entries
+    pub first: Option<String>,
+    pub last: Option<String>,
+    /// Heroku app being streamed, if any
+    pub app: Option<String>,
+    /// Filters the entries were selected with
+    pub filters: Vec<String>,
+    pub exported_at: String,
+}
+
+impl Manifest {
+    /// Describe the export written to `path` with `contents`, made from
+    /// `entries`
+    pub fn new(path: &Path, contents: &[u8], entries: &[LogEntry]) -> Self {
+        let sha256 = Sha256::digest(contents)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        let time = |t: Option<DateTime<FixedOffset>>| t.map(|t| t.to_rfc3339());
+        Self {
+            file: path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            sha256,
+            entries: entries.len(),
+            first: time(entries.iter().map(|entry| entry.timestamp).min()),
+            last: time(entries.iter().map(|entry| entry.timestamp).max()),
+            app: None,
+            filters: Vec::new(),
+            exported_at: chrono::Local::now().to_rfc3339(),
+        }
+    }
+
+    pub fn app(mut self, app: Option<String>) -> Self {
+        self.app = app;
+        self
+    }
+
+    pub fn filters(mut self, filters: Vec<String>) -> Self {
+        self.filters = filters;
+        self
+    }
+
+    /// Where the manifest of the export at `path` goes
+    pub fn sidecar_path(path: &Path) -> PathBuf {
+        let mut name = path.as_os_str().to_owned();
+        name.push(".manifest.json");
+        PathBuf::from(name)
+    }
+
+    /// Write the manifest beside the export at `path`, returning its path
+    pub fn write(&self, path: &Path) -> Result<PathBuf> {
+        let sidecar = Self::sidecar_path(path);
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(&sidecar, json + "\n")
+            .with_context(|| format!("Failed to write {}", sidecar.display()))?;
+        Ok(sidecar)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_log_line;
+
+    fn entry(line: &str) -> LogEntry {
+        parse_log_line(line).expect("Failed to parse")
+    }
+
+    #[test]
+    fn test_export_destination() {
+        let dir = std::env::temp_dir();
+        let file = dir.join("incident.log");
+        assert_eq!(
+            export_destination(&file.to_string_lossy(), "logs", "log").unwrap(),
+            file
+        );
+
+        let inside = export_destination(&dir.to_string_lossy(), "logs", "log").unwrap();
+        assert_eq!(inside.parent(), Some(dir.as_path()));
+        assert!(inside
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("logs-parser-logs-"));
+
+        let missing = dir.join("logs-parser-no-such-dir").join("x.log");
+        let error = export_destination(&missing.to_string_lossy(), "logs", "log").unwrap_err();
+        assert!(error.to_string().ends_with("is not a directory"));
+
+        let home = std::env::var("HOME").unwrap();
+        assert_eq!(expand_home("~/x.log"), Path::new(&home).join("x.log"));
+        assert_eq!(expand_home("~"), PathBuf::from(&home));
+        assert_eq!(expand_home("x~/y"), PathBuf::from("x~/y"));
+    }
+
+    #[test]
+    fn test_timeline_buckets_per_minute() {
+        let entries = vec![
+            entry("2024-02-17T10:30:05.000000+00:00 app[web.1]: Error: boom"),
+            entry("2024-02-17T10:30:40.000000+00:00 heroku[router]: at=info method=GET path=/ service=100ms status=200"),
+            entry("2024-02-17T10:30:59.999000+00:00 heroku[router]: at=info method=GET path=/ service=50ms status=200"),
+            entry("2024-02-17T10:31:00.000000+00:00 app[web.1]: Warning: slow"),
+        ];
+
+        let csv = timeline_csv(&entries);
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(
+            lines,
+            vec![
+                "minute,total,error,warn,info,debug,unknown,avg_router_ms",
+                "2024-02-17T10:30:00+00:00,3,1,0,2,0,0,75.0",
+                "2024-02-17T10:31:00+00:00,1,0,1,0,0,0,",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_timeline_metric_columns() {
+        let entries = vec![
+            entry("2024-02-17T10:30:05.000000+00:00 app[web.1]: count#hits=1 measure#latency=10ms"),
+            entry("2024-02-17T10:30:40.000000+00:00 app[web.1]: count#hits=2 measure#latency=15ms"),
+            entry("2024-02-17T10:31:00.000000+00:00 app[worker.1]: sample#queue=4"),
+        ];
+
+        let csv = timeline_csv(&entries);
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(
+            lines,
+            vec![
+                "minute,total,error,warn,info,debug,unknown,avg_router_ms,count#hits,measure#latency,sample#queue",
+                "2024-02-17T10:30:00+00:00,2,0,0,0,0,2,,3,12.5,",
+                "2024-02-17T10:31:00+00:00,1,0,0,0,0,1,,,,4",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_timeline_empty_buffer() {
+        assert_eq!(
+            timeline_csv(&[]),
+            "minute,total,error,warn,info,debug,unknown,avg_router_ms\n"
+        );
+    }
+
+    #[test]
+    fn test_entry_json() {
+        let entry = entry(
+            r#"2024-02-17T10:30:45.000000+00:00 app[web.1]: {"level":"error","message":"boom","user":"42"}"#,
+        );
+        let json: serde_json::Value = serde_json::from_str(&entry_json(&entry)).unwrap();
+        assert_eq!(json["timestamp"], "2024-02-17T10:30:45+00:00");
+        assert_eq!(json["level"], "Error");
+        assert_eq!(json["dyno"], "web.1");
+        assert_eq!(json["message"], "boom");
+        assert_eq!(json["fields"]["user"], "42");
+    }
+
+    #[test]
+    fn test_manifest_describes_export() {
+        let entries = vec![
+            entry("2024-02-17T10:31:00.000000+00:00 app[web.1]: second"),
+            entry("2024-02-17T10:30:05.000000+00:00 app[web.1]: first"),
+        ];
+        let contents = raw_lines(&entries);
+        let path = Path::new("/tmp/logs-parser-logs-20240217-103100.log");
+
+        let manifest = Manifest::new(path, b"abc", &entries)
+            .app(Some("my-app".to_string()))
+            .filters(vec!["Level: Error".to_string()]);
+        assert_eq!(manifest.file, "logs-parser-logs-20240217-103100.log");
+        // SHA-256 test vector for "abc"
+        assert_eq!(
+            manifest.sha256,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(manifest.entries, 2);
+        assert_eq!(manifest.first.as_deref(), Some("2024-02-17T10:30:05+00:00"));
+        assert_eq!(manifest.last.as_deref(), Some("2024-02-17T10:31:00+00:00"));
+        assert_eq!(contents.lines().count(), 2);
+        assert_eq!(
+            Manifest::sidecar_path(path),
+            Path::new("/tmp/logs-parser-logs-20240217-103100.log.manifest.json")
+        );
+    }
+}
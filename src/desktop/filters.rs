@@ -1,5 +1,11 @@
+use crate::addons;
 use crate::parser::{LogEntry, LogLevel};
+use crate::requests::extract_request_id;
+use crate::settings::parse_duration;
+use crate::sql;
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
 use regex::Regex;
+use std::time::Duration;
 
 /// Represents different types of filters that can be applied to log entries
 #[derive(Debug, Clone)]
@@ -14,6 +20,74 @@ pub enum Filter {
     Source(String),
     /// Filter by log level
     LogLevel(LogLevel),
+    /// Entries at this level or a more severe one; a list has at most one
+    LevelThreshold(LogLevel),
+    /// Entries reporting a query duration of at least this many milliseconds
+    SlowQuery(f64),
+    /// Router and app lines belonging to one Heroku request id
+    RequestId(String),
+    /// A field of a structured line (JSON, or a router line's logfmt)
+    /// starting with a value
+    Field(String, String),
+    /// Entries from an input whose origin label starts with this
+    /// (e.g. "file", "heroku:my-app")
+    Origin(String),
+    /// Metric reports of Heroku Postgres and Redis (`category:metrics`)
+    Metrics,
+    /// Entries logged from the first time up to the second, either end
+    /// open; a list has at most one
+    TimeRange(Option<Bound>, Option<Bound>),
+    /// Entries the inner filter does not match
+    Not(Box<Filter>),
+}
+
+/// One end of a time range
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bound {
+    /// A fixed time
+    At(DateTime<FixedOffset>),
+    /// An age, counted back from whenever the range is checked, so the
+    /// window keeps moving (`since:10m`)
+    Ago(Duration),
+}
+
+impl Bound {
+    /// The time this bound stands for at `now`
+    pub fn resolve(self, now: DateTime<Utc>) -> DateTime<FixedOffset> {
+        match self {
+            Bound::At(time) => time,
+            Bound::Ago(age) => {
+                let age = chrono::Duration::from_std(age).unwrap_or(chrono::Duration::MAX);
+                now.checked_sub_signed(age)
+                    .unwrap_or(DateTime::<Utc>::MIN_UTC)
+                    .fixed_offset()
+            }
+        }
+    }
+
+    /// Query text for this bound, e.g. `10m` or an RFC 3339 time
+    fn query(self) -> String {
+        match self {
+            Bound::At(time) => time.to_rfc3339(),
+            Bound::Ago(age) => format_age(age),
+        }
+    }
+
+    fn display(self) -> String {
+        match self {
+            Bound::At(time) => display_time(&time),
+            Bound::Ago(age) => format!("{} ago", format_age(age)),
+        }
+    }
+}
+
+/// An age in the largest unit `parse_duration` reads back exactly
+fn format_age(age: Duration) -> String {
+    match age.as_secs() {
+        secs if secs > 0 && secs % 3600 == 0 => format!("{}h", secs / 3600),
+        secs if secs > 0 && secs % 60 == 0 => format!("{}m", secs / 60),
+        secs => format!("{}s", secs),
+    }
 }
 
 // Implement PartialEq manually since Regex doesn't implement PartialEq
@@ -25,6 +99,14 @@ impl PartialEq for Filter {
             (Filter::Dyno(a), Filter::Dyno(b)) => a == b,
             (Filter::Source(a), Filter::Source(b)) => a == b,
             (Filter::LogLevel(a), Filter::LogLevel(b)) => a == b,
+            (Filter::LevelThreshold(a), Filter::LevelThreshold(b)) => a == b,
+            (Filter::SlowQuery(a), Filter::SlowQuery(b)) => a == b,
+            (Filter::RequestId(a), Filter::RequestId(b)) => a == b,
+            (Filter::Field(a, x), Filter::Field(b, y)) => a == b && x == y,
+            (Filter::Origin(a), Filter::Origin(b)) => a == b,
+            (Filter::Metrics, Filter::Metrics) => true,
+            (Filter::TimeRange(a, x), Filter::TimeRange(b, y)) => a == b && x == y,
+            (Filter::Not(a), Filter::Not(b)) => a == b,
             _ => false,
         }
     }
@@ -43,6 +125,82 @@ impl Filter {
             Filter::Dyno(dyno) => entry.dyno.eq_ignore_ascii_case(dyno),
             Filter::Source(source) => entry.source.eq_ignore_ascii_case(source),
             Filter::LogLevel(level) => entry.level == *level,
+            Filter::LevelThreshold(level) => entry.level.rank() <= level.rank(),
+            Filter::SlowQuery(min_ms) => {
+                sql::extract_duration(&entry.message).is_some_and(|ms| ms >= *min_ms)
+            }
+            Filter::RequestId(id) => extract_request_id(&entry.message) == Some(id.as_str()),
+            Filter::Field(key, value) => entry
+                .fields
+                .get(key)
+                .is_some_and(|v| v.to_lowercase().starts_with(&value.to_lowercase())),
+            Filter::Origin(origin) => entry
+                .origin
+                .to_lowercase()
+                .starts_with(&origin.to_lowercase()),
+            Filter::Metrics => addons::is_addon_metrics(entry),
+            Filter::TimeRange(since, until) => {
+                let now = Utc::now();
+                since.is_none_or(|since| entry.timestamp >= since.resolve(now))
+                    && until.is_none_or(|until| entry.timestamp <= until.resolve(now))
+            }
+            Filter::Not(inner) => !inner.matches(entry),
+        }
+    }
+
+    /// Byte ranges of `text` this filter's search term matches, for
+    /// highlighting; empty for filters that don't search the message
+    ///
+    /// Plain text is compared ASCII-case-insensitively here, which keeps
+    /// byte offsets valid in the original text.
+    pub fn match_ranges(&self, text: &str) -> Vec<(usize, usize)> {
+        match self {
+            Filter::TextSearch(term) if !term.is_empty() => {
+                let haystack = text.to_ascii_lowercase();
+                let needle = term.to_ascii_lowercase();
+                haystack
+                    .match_indices(&needle)
+                    .map(|(start, found)| (start, start + found.len()))
+                    .collect()
+            }
+            Filter::Regex(regex) => regex
+                .find_iter(text)
+                .filter(|m| !m.is_empty())
+                .map(|m| (m.start(), m.end()))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Query text that parses back into this filter, for saving it
+    pub fn query(&self) -> String {
+        match self {
+            // Quoted when it would otherwise parse as something else or join
+            // the other text words
+            Filter::TextSearch(text)
+                if text.contains(char::is_whitespace)
+                    || text.starts_with(['"', '!', '-'])
+                    || is_clause(text) =>
+            {
+                format!("\"{}\"", text)
+            }
+            Filter::TextSearch(text) => text.clone(),
+            Filter::Regex(regex) => format!("/{}/", regex.as_str()),
+            Filter::Dyno(dyno) => format!("dyno:{}", dyno),
+            Filter::Source(source) => format!("source:{}", source),
+            Filter::LogLevel(level) => format!("level:{:?}", level).to_lowercase(),
+            Filter::LevelThreshold(level) => format!("level:{:?}+", level).to_lowercase(),
+            Filter::SlowQuery(min_ms) => format!("slow:{}ms", min_ms),
+            Filter::RequestId(id) => format!("request:{}", id),
+            Filter::Field(key, value) => format!("field:{}={}", key, value),
+            Filter::Origin(origin) => format!("origin:{}", origin),
+            Filter::Metrics => "category:metrics".to_string(),
+            Filter::TimeRange(since, until) => {
+                let since = since.map(|bound| format!("since:{}", bound.query()));
+                let until = until.map(|bound| format!("until:{}", bound.query()));
+                since.into_iter().chain(until).collect::<Vec<_>>().join(" ")
+            }
+            Filter::Not(inner) => format!("!{}", inner.query()),
         }
     }
 
@@ -54,8 +212,555 @@ impl Filter {
             Filter::Dyno(dyno) => format!("Dyno: {}", dyno),
             Filter::Source(source) => format!("Source: {}", source),
             Filter::LogLevel(level) => format!("Level: {:?}", level),
+            Filter::LevelThreshold(LogLevel::Error) => "Level: Error".to_string(),
+            Filter::LevelThreshold(level) => format!("Level: {:?} and above", level),
+            Filter::SlowQuery(min_ms) => format!("Slower than: {}ms", min_ms),
+            Filter::RequestId(id) => format!("Request: {}", id),
+            Filter::Field(key, value) => format!("Field: {}={}", key, value),
+            Filter::Origin(origin) => format!("Origin: {}", origin),
+            Filter::Metrics => "Category: metrics".to_string(),
+            Filter::TimeRange(since, until) => match (since, until) {
+                (Some(since), Some(until)) => {
+                    format!("Time: {} – {}", since.display(), until.display())
+                }
+                (Some(since), None) => format!("Since: {}", since.display()),
+                (None, Some(until)) => format!("Until: {}", until.display()),
+                (None, None) => "Any time".to_string(),
+            },
+            Filter::Not(inner) => format!("Not {}", inner.display()),
+        }
+    }
+}
+
+fn display_time(time: &DateTime<FixedOffset>) -> String {
+    time.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// A point in time given in a query: an age such as `10m` (before `now`),
+/// an RFC 3339 timestamp, a local date and time such as `2024-02-17T10:00`,
+/// a date, or a time of day today
+pub fn parse_time(value: &str, now: DateTime<Local>) -> Option<DateTime<FixedOffset>> {
+    if let Some(age) = parse_duration(value) {
+        let age = chrono::Duration::from_std(age).ok()?;
+        return Some((now - age).fixed_offset());
+    }
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Some(time);
+    }
+    let naive = ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%dT%H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+            date.and_hms_opt(0, 0, 0)
+        })
+        .or_else(|| {
+            let time = ["%H:%M:%S", "%H:%M"]
+                .iter()
+                .find_map(|format| NaiveTime::parse_from_str(value, format).ok())?;
+            Some(now.date_naive().and_time(time))
+        })?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|time| time.fixed_offset())
+}
+
+/// One end of a range given in a query: an age stays relative to when it's
+/// checked, anything [`parse_time`] reads is fixed
+pub fn parse_bound(value: &str, now: DateTime<Local>) -> Option<Bound> {
+    match parse_duration(value) {
+        Some(age) => Some(Bound::Ago(age)),
+        None => parse_time(value, now).map(Bound::At),
+    }
+}
+
+/// Parse a filter string into a Filter enum
+/// Formats:
+/// - dyno:web.1 -> Filter by dyno
+/// - source:app -> Filter by source
+/// - level:error -> Filter by level (error, warn, info, debug)
+/// - level:warn+ -> Warn and more severe levels
+/// - slow:100ms -> Entries reporting a query duration of at least 100ms
+/// - request:<id> -> Router and app lines for one request id
+/// - field:status=500 -> Lines whose `status` field starts with 500
+/// - origin:file -> Lines from inputs whose label starts with `file`
+/// - category:metrics -> Heroku Postgres and Redis metric reports
+/// - since:10m, until:2024-02-17T10:30 -> Entries logged in a time range
+/// - status:5, path:/api, method:, host:, code: -> Router field shorthands
+/// - /regex/ -> Regex filter
+/// - !term, -dyno:web.1, not level:debug -> Entries the rest doesn't match
+/// - anything else -> Text search
+pub fn parse_filter(input: &str) -> Option<Filter> {
+    let trimmed = input.trim();
+
+    if trimmed.is_empty() {
+        return None;
+    }
+
+    // Check for negation: `!` before anything, `-` or `not ` before a clause
+    let negated = trimmed
+        .strip_prefix('!')
+        .or_else(|| trimmed.strip_prefix('-').filter(|rest| is_clause(rest)))
+        .or_else(|| {
+            let (word, rest) = trimmed.split_once(char::is_whitespace)?;
+            let rest = rest.trim_start();
+            (word.eq_ignore_ascii_case("not") && is_clause(rest)).then_some(rest)
+        });
+    if let Some(rest) = negated {
+        return parse_filter(rest).map(|filter| Filter::Not(Box::new(filter)));
+    }
+
+    // Check for dyno: prefix
+    if let Some(dyno) = trimmed.strip_prefix("dyno:") {
+        return Some(Filter::Dyno(dyno.to_string()));
+    }
+
+    // Check for source: prefix
+    if let Some(source) = trimmed.strip_prefix("source:") {
+        return Some(Filter::Source(source.to_string()));
+    }
+
+    // Check for level: prefix
+    if let Some(level_str) = trimmed.strip_prefix("level:") {
+        if let Some(level) = level_str.strip_suffix('+').and_then(LogLevel::from_name) {
+            return Some(Filter::LevelThreshold(level));
+        }
+        let level = LogLevel::from_name(level_str).unwrap_or(LogLevel::Unknown);
+        return Some(Filter::LogLevel(level));
+    }
+
+    // Check for field: prefix (key=value)
+    if let Some((key, value)) = trimmed
+        .strip_prefix("field:")
+        .and_then(|f| f.split_once('='))
+    {
+        return Some(Filter::Field(key.to_string(), value.to_string()));
+    }
+
+    // Check for origin: prefix
+    if let Some(origin) = trimmed.strip_prefix("origin:") {
+        return Some(Filter::Origin(origin.to_string()));
+    }
+
+    // Check for category:metrics
+    if trimmed.eq_ignore_ascii_case("category:metrics") {
+        return Some(Filter::Metrics);
+    }
+
+    // Check for since: and until: (an age, a date and time or a time of day)
+    if let Some(since) = trimmed.strip_prefix("since:") {
+        if let Some(since) = parse_bound(since, Local::now()) {
+            return Some(Filter::TimeRange(Some(since), None));
+        }
+    }
+    if let Some(until) = trimmed.strip_prefix("until:") {
+        if let Some(until) = parse_bound(until, Local::now()) {
+            return Some(Filter::TimeRange(None, Some(until)));
+        }
+    }
+
+    // Check for router field shorthands (status:503, path:/api)
+    if let Some((key, value)) = trimmed.split_once(':') {
+        if ROUTER_FIELDS.contains(&key) && !value.is_empty() {
+            return Some(Filter::Field(key.to_string(), value.to_string()));
+        }
+    }
+
+    // Check for request: prefix
+    if let Some(id) = trimmed.strip_prefix("request:") {
+        return Some(Filter::RequestId(id.to_string()));
+    }
+
+    // Check for slow: prefix (milliseconds, "ms" suffix optional)
+    if let Some(ms) = trimmed.strip_prefix("slow:") {
+        if let Ok(ms) = ms.trim_end_matches("ms").parse::<f64>() {
+            return Some(Filter::SlowQuery(ms));
+        }
+    }
+
+    // Check for regex (starts and ends with /)
+    if trimmed.starts_with('/') && trimmed.ends_with('/') && trimmed.len() > 2 {
+        let pattern = &trimmed[1..trimmed.len() - 1];
+        if let Ok(regex) = Regex::new(pattern) {
+            return Some(Filter::Regex(regex));
+        }
+    }
+
+    // Default to text search
+    Some(Filter::TextSearch(trimmed.to_string()))
+}
+
+/// Matches of every filter in `text`, sorted, with overlapping ranges merged
+pub fn match_ranges(filters: &[Filter], text: &str) -> Vec<(usize, usize)> {
+    let mut ranges: Vec<(usize, usize)> =
+        filters.iter().flat_map(|f| f.match_ranges(text)).collect();
+    ranges.sort();
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Prefixes that start a structured clause in a query line
+const CLAUSE_PREFIXES: [&str; 10] = [
+    "dyno:",
+    "source:",
+    "level:",
+    "request:",
+    "slow:",
+    "field:",
+    "origin:",
+    "category:",
+    "since:",
+    "until:",
+];
+
+/// Router line fields that can be filtered as `key:value`
+const ROUTER_FIELDS: [&str; 5] = ["status", "path", "method", "host", "code"];
+
+/// Whether a word starts a structured clause rather than being search text
+fn is_clause(word: &str) -> bool {
+    let router_field = word
+        .split_once(':')
+        .is_some_and(|(key, value)| ROUTER_FIELDS.contains(&key) && !value.is_empty());
+    router_field || word.starts_with('/') || CLAUSE_PREFIXES.iter().any(|p| word.starts_with(p))
+}
+
+/// Parse a query line that may combine several clauses, e.g.
+/// `level:error dyno:web.1 timeout`. Each prefixed clause, `/regex/`,
+/// `"quoted phrase"` and negated word becomes its own filter, except that
+/// `since:` and `until:` make one time range; the remaining words form one
+/// text search.
+pub fn parse_query(input: &str) -> Vec<Filter> {
+    let mut clauses = Vec::new();
+    let mut text = Vec::new();
+    let mut words = input.split_whitespace().peekable();
+
+    while let Some(word) = words.next() {
+        // `!` negates any word, `-` and `not` only a clause, so "could not
+        // connect" and "-1" stay plain text
+        let (negated, word) = if let Some(rest) = word.strip_prefix('!').filter(|r| !r.is_empty()) {
+            (true, rest)
+        } else if let Some(rest) = word.strip_prefix('-').filter(|r| is_clause(r)) {
+            (true, rest)
+        } else if word.eq_ignore_ascii_case("not") {
+            match words.next_if(|next| is_clause(next)) {
+                Some(next) => (true, next),
+                None => (false, word),
+            }
+        } else {
+            (false, word)
+        };
+
+        let mut word = word.to_string();
+        if word.starts_with('"') {
+            // A quoted phrase is searched for as written, even `level:error`
+            while !(word.len() > 1 && word.ends_with('"')) {
+                match words.next() {
+                    Some(next) => {
+                        word.push(' ');
+                        word.push_str(next);
+                    }
+                    None => break,
+                }
+            }
+            let phrase = word.trim_matches('"');
+            if !phrase.is_empty() {
+                let filter = Filter::TextSearch(phrase.to_string());
+                clauses.push(if negated {
+                    Filter::Not(Box::new(filter))
+                } else {
+                    filter
+                });
+            }
+            continue;
+        }
+        if word.starts_with('/') {
+            // A regex may contain spaces: take words up to the closing slash
+            while !(word.len() > 2 && word.ends_with('/')) {
+                match words.next() {
+                    Some(next) => {
+                        word.push(' ');
+                        word.push_str(next);
+                    }
+                    None => break,
+                }
+            }
+        }
+
+        match parse_filter(&word) {
+            Some(Filter::TextSearch(_)) if !negated => text.push(word),
+            Some(Filter::TimeRange(since, until)) if !negated => {
+                let range = clauses.iter_mut().find_map(|f| match f {
+                    Filter::TimeRange(from, to) => Some((from, to)),
+                    _ => None,
+                });
+                match range {
+                    Some((from, to)) => {
+                        *from = since.or(*from);
+                        *to = until.or(*to);
+                    }
+                    None => clauses.push(Filter::TimeRange(since, until)),
+                }
+            }
+            Some(filter) if negated => clauses.push(Filter::Not(Box::new(filter))),
+            Some(filter) => clauses.push(filter),
+            None => {}
+        }
+    }
+
+    if !text.is_empty() {
+        clauses.push(Filter::TextSearch(text.join(" ")));
+    }
+    clauses
+}
+
+/// Holds the committed filters plus the provisional clauses that are
+/// previewed while the user is still typing a query
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterEngine {
+    filters: Vec<Filter>,
+    /// Whether each committed filter applies; a turned-off one stays listed
+    /// so it can be turned back on
+    enabled: Vec<bool>,
+    mode_and: bool,
+    preview: Vec<Filter>,
+}
+
+impl Default for FilterEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FilterEngine {
+    pub fn new() -> Self {
+        Self {
+            filters: Vec::new(),
+            enabled: Vec::new(),
+            mode_and: true,
+            preview: Vec::new(),
+        }
+    }
+
+    /// Committed filters, excluding any preview, whether turned on or off
+    pub fn filters(&self) -> &[Filter] {
+        &self.filters
+    }
+
+    /// Committed filters that are turned on
+    pub fn active_filters(&self) -> Vec<Filter> {
+        self.active().cloned().collect()
+    }
+
+    fn active(&self) -> impl Iterator<Item = &Filter> {
+        self.filters
+            .iter()
+            .zip(&self.enabled)
+            .filter(|(_, enabled)| **enabled)
+            .map(|(filter, _)| filter)
+    }
+
+    pub fn is_enabled(&self, idx: usize) -> bool {
+        self.enabled.get(idx).copied().unwrap_or(false)
+    }
+
+    /// Turn one committed filter off, or back on
+    pub fn toggle_filter(&mut self, idx: usize) {
+        if let Some(enabled) = self.enabled.get_mut(idx) {
+            *enabled = !*enabled;
+        }
+    }
+
+    pub fn remove_filter(&mut self, idx: usize) {
+        if idx < self.filters.len() {
+            self.filters.remove(idx);
+            self.enabled.remove(idx);
+        }
+    }
+
+    pub fn is_and_mode(&self) -> bool {
+        self.mode_and
+    }
+
+    pub fn toggle_mode(&mut self) {
+        self.mode_and = !self.mode_and;
+    }
+
+    /// Commit a filter; any pending preview is discarded since it has been
+    /// superseded by the committed query. A level threshold or time range
+    /// replaces the previous one rather than narrowing it further.
+    pub fn add_filter(&mut self, filter: Filter) {
+        match filter {
+            Filter::LevelThreshold(level) => self.set_level_threshold(Some(level)),
+            Filter::TimeRange(since, until) => self.set_time_range(since, until),
+            filter => {
+                self.filters.push(filter);
+                self.enabled.push(true);
+            }
+        }
+        self.preview.clear();
+    }
+
+    /// Show only entries at `level` or more severe, replacing any threshold
+    /// already set; `None` shows every level again
+    pub fn set_level_threshold(&mut self, level: Option<LogLevel>) {
+        if let Some(idx) = self
+            .filters
+            .iter()
+            .position(|f| matches!(f, Filter::LevelThreshold(_)))
+        {
+            self.remove_filter(idx);
+        }
+        if let Some(level) = level {
+            self.filters.push(Filter::LevelThreshold(level));
+            self.enabled.push(true);
+        }
+    }
+
+    /// Show only entries logged between `since` and `until`, replacing any
+    /// range already set; with neither, every time is shown again
+    pub fn set_time_range(&mut self, since: Option<Bound>, until: Option<Bound>) {
+        if let Some(idx) = self
+            .filters
+            .iter()
+            .position(|f| matches!(f, Filter::TimeRange(..)))
+        {
+            self.remove_filter(idx);
+        }
+        if since.is_some() || until.is_some() {
+            self.filters.push(Filter::TimeRange(since, until));
+            self.enabled.push(true);
+        }
+    }
+
+    /// The committed filters that are on, as one query line, e.g. for a
+    /// preset
+    pub fn query(&self) -> String {
+        self.active()
+            .map(Filter::query)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    pub fn clear_filters(&mut self) {
+        self.filters.clear();
+        self.enabled.clear();
+    }
+
+    /// Replace the provisional clauses (empty clears them)
+    pub fn set_preview(&mut self, filters: Vec<Filter>) {
+        self.preview = filters;
+    }
+
+    /// Drop the provisional clauses, reverting to the committed state
+    pub fn cancel_preview(&mut self) {
+        self.preview.clear();
+    }
+
+    pub fn has_preview(&self) -> bool {
+        !self.preview.is_empty()
+    }
+
+    /// Text and regex filters, committed or previewed, whose matches are
+    /// highlighted in the list (negated ones match nothing to highlight)
+    pub fn search_filters(&self) -> Vec<Filter> {
+        self.active()
+            .chain(self.preview.iter())
+            .filter(|f| matches!(f, Filter::TextSearch(_) | Filter::Regex(_)))
+            .cloned()
+            .collect()
+    }
+
+    /// Check if a log entry passes the committed filters and the preview,
+    /// combined as if the preview had already been committed
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        self.combine(self.active().chain(self.preview.iter()), entry)
+    }
+
+    /// Check if a log entry passes the committed filters alone, for output
+    /// that shouldn't follow a query while it's still being typed
+    pub fn matches_committed(&self, entry: &LogEntry) -> bool {
+        self.combine(self.active(), entry)
+    }
+
+    fn combine<'a>(&self, filters: impl Iterator<Item = &'a Filter>, entry: &LogEntry) -> bool {
+        let mut filters = filters.peekable();
+        if filters.peek().is_none() {
+            return true;
+        }
+        if self.mode_and {
+            filters.all(|f| f.matches(entry))
+        } else {
+            filters.any(|f| f.matches(entry))
         }
     }
+
+    /// Return the entries that pass the current filters
+    pub fn apply(&self, entries: &[LogEntry]) -> Vec<LogEntry> {
+        entries
+            .iter()
+            .filter(|entry| self.matches(entry))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Remembers what the filtered list was last computed from, so entries
+/// appended to the buffer since can be filtered on their own
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IncrementalFilter {
+    engine: Option<FilterEngine>,
+    len: usize,
+    /// First and last entries seen; eviction from the front shifts both
+    ends: Option<(LogEntry, LogEntry)>,
+}
+
+impl IncrementalFilter {
+    /// Index of the first entry the filtered list doesn't reflect yet, or
+    /// `None` when the filters changed or entries were evicted or cleared and
+    /// everything has to be filtered again
+    pub fn resume_from(&self, engine: &FilterEngine, entries: &[LogEntry]) -> Option<usize> {
+        if self.engine.as_ref() != Some(engine) || entries.len() < self.len {
+            return None;
+        }
+        let ends = match (entries.first(), self.len.checked_sub(1)) {
+            (Some(first), Some(last)) => Some((first.clone(), entries[last].clone())),
+            _ => None,
+        };
+        (ends == self.ends).then_some(self.len)
+    }
+
+    /// Record that the filtered list now reflects all of `entries`
+    pub fn update(&mut self, engine: &FilterEngine, entries: &[LogEntry]) {
+        self.engine = Some(engine.clone());
+        self.len = entries.len();
+        self.ends = entries.first().cloned().zip(entries.last().cloned());
+    }
+
+    /// Force the next update to filter everything, e.g. after a change the
+    /// engine doesn't know about
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Count entries matching every filter, ignoring those older than `since`
+pub fn count_matches(
+    entries: &[LogEntry],
+    filters: &[Filter],
+    since: Option<DateTime<FixedOffset>>,
+) -> usize {
+    entries
+        .iter()
+        .filter(|entry| since.is_none_or(|cutoff| entry.timestamp >= cutoff))
+        .filter(|entry| filters.iter().all(|f| f.matches(entry)))
+        .count()
 }
 
 #[cfg(test)]
@@ -142,6 +847,202 @@ mod tests {
         assert!(!filter.matches(&entry2));
     }
 
+    #[test]
+    fn test_engine_toggle_and_remove_filter() {
+        let timeout = create_test_entry("Error: timeout");
+        let mut engine = FilterEngine::new();
+        engine.add_filter(Filter::TextSearch("timeout".to_string()));
+        engine.add_filter(Filter::Dyno("worker.1".to_string()));
+        assert!(!engine.matches(&timeout));
+
+        engine.toggle_filter(1);
+        assert!(!engine.is_enabled(1));
+        assert!(engine.matches(&timeout));
+        assert_eq!(engine.query(), "timeout");
+        assert_eq!(engine.active_filters().len(), 1);
+        assert_eq!(engine.filters().len(), 2);
+
+        engine.remove_filter(0);
+        engine.remove_filter(5);
+        assert_eq!(engine.filters(), &[Filter::Dyno("worker.1".to_string())]);
+        assert!(!engine.is_enabled(0));
+        assert!(engine.matches(&timeout));
+        engine.toggle_filter(0);
+        assert!(!engine.matches(&timeout));
+    }
+
+    #[test]
+    fn test_level_threshold_replaces_previous() {
+        let error = create_test_entry("Error: Connection failed");
+        let warn = create_test_entry("Warning: slow response");
+        let info = create_test_entry("Info: Processing request");
+
+        let mut engine = FilterEngine::new();
+        engine.add_filter(Filter::TextSearch("o".to_string()));
+        engine.set_level_threshold(Some(LogLevel::Error));
+        engine.add_filter(parse_filter("level:warn+").unwrap());
+        assert_eq!(
+            engine.filters(),
+            &[
+                Filter::TextSearch("o".to_string()),
+                Filter::LevelThreshold(LogLevel::Warn)
+            ]
+        );
+        assert!(engine.matches(&error) && engine.matches(&warn));
+        assert!(!engine.matches(&info));
+
+        engine.set_level_threshold(None);
+        assert_eq!(engine.filters(), &[Filter::TextSearch("o".to_string())]);
+        assert!(engine.matches(&info));
+    }
+
+    #[test]
+    fn test_parse_time() {
+        let now = Local.with_ymd_and_hms(2024, 2, 17, 10, 30, 0).unwrap();
+        let local = |h, m| {
+            Local
+                .with_ymd_and_hms(2024, 2, 17, h, m, 0)
+                .unwrap()
+                .fixed_offset()
+        };
+        assert_eq!(parse_time("10m", now), Some(local(10, 20)));
+        assert_eq!(parse_time("2h", now), Some(local(8, 30)));
+        assert_eq!(parse_time("2024-02-17T09:15", now), Some(local(9, 15)));
+        assert_eq!(parse_time("2024-02-17T09:15:00", now), Some(local(9, 15)));
+        assert_eq!(parse_time("2024-02-17", now), Some(local(0, 0)));
+        assert_eq!(parse_time("09:15", now), Some(local(9, 15)));
+        assert_eq!(
+            parse_time("2024-02-17T09:15:00+00:00", now),
+            DateTime::parse_from_rfc3339("2024-02-17T09:15:00+00:00").ok()
+        );
+        assert_eq!(parse_time("soon", now), None);
+        assert_eq!(parse_time("25:00", now), None);
+    }
+
+    #[test]
+    fn test_time_range_filter() {
+        let at = |time: &str| {
+            parse_log_line(&format!("2024-02-17T{}+00:00 app[web.1]: hi", time)).unwrap()
+        };
+        let filters = parse_query("since:2024-02-17T10:00:00+00:00 until:2024-02-17T10:30:00Z hi");
+        let since = DateTime::parse_from_rfc3339("2024-02-17T10:00:00+00:00")
+            .ok()
+            .map(Bound::At);
+        let until = DateTime::parse_from_rfc3339("2024-02-17T10:30:00+00:00")
+            .ok()
+            .map(Bound::At);
+        assert_eq!(
+            filters,
+            [
+                Filter::TimeRange(since, until),
+                Filter::TextSearch("hi".to_string())
+            ]
+        );
+        assert!(!filters[0].matches(&at("09:59:59.000000")));
+        assert!(filters[0].matches(&at("10:00:00.000000")));
+        assert!(filters[0].matches(&at("10:30:00.000000")));
+        assert!(!filters[0].matches(&at("10:30:00.500000")));
+        assert_eq!(parse_query(&filters[0].query()), [filters[0].clone()]);
+        assert_eq!(
+            filters[0].display(),
+            "Time: 2024-02-17 10:00:00 – 2024-02-17 10:30:00"
+        );
+
+        // Not a time: searched for as text
+        assert_eq!(
+            parse_filter("since:soon"),
+            Some(Filter::TextSearch("since:soon".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_relative_time_range_keeps_moving() {
+        let filter = parse_filter("since:10m").unwrap();
+        let ten_minutes = Duration::from_secs(600);
+        assert_eq!(
+            filter,
+            Filter::TimeRange(Some(Bound::Ago(ten_minutes)), None)
+        );
+        assert_eq!(filter.query(), "since:10m");
+        assert_eq!(parse_query(&filter.query()), std::slice::from_ref(&filter));
+        assert_eq!(filter.display(), "Since: 10m ago");
+
+        // A line from five minutes ago is in the window now, and drops out
+        // of it once the window has moved on
+        let logged = Utc::now() - chrono::Duration::minutes(5);
+        let entry = parse_log_line(&format!(
+            "{} app[web.1]: hi",
+            logged.to_rfc3339_opts(chrono::SecondsFormat::Micros, false)
+        ))
+        .unwrap();
+        assert!(filter.matches(&entry));
+        let later = Utc::now() + chrono::Duration::minutes(10);
+        assert!(Bound::Ago(ten_minutes).resolve(later) > entry.timestamp);
+
+        let range = parse_query("since:2h until:90s");
+        assert_eq!(range[0].query(), "since:2h until:90s");
+        assert_eq!(
+            parse_filter("since:2024-02-17T09:15:00+00:00")
+                .unwrap()
+                .query(),
+            "since:2024-02-17T09:15:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_time_range_replaces_previous() {
+        let mut engine = FilterEngine::new();
+        engine.add_filter(parse_filter("since:1h").unwrap());
+        engine.add_filter(Filter::Dyno("web.1".to_string()));
+        engine.add_filter(parse_filter("until:5m").unwrap());
+        assert_eq!(engine.filters().len(), 2);
+        assert!(matches!(
+            engine.filters()[1],
+            Filter::TimeRange(None, Some(_))
+        ));
+
+        engine.set_time_range(None, None);
+        assert_eq!(engine.filters(), &[Filter::Dyno("web.1".to_string())]);
+    }
+
+    #[test]
+    fn test_query_round_trip() {
+        let query = "\"connection refused\" level:warn+ dyno:web.1 -source:heroku slow:250ms field:status=5 /GET .*500/ timeout";
+        let filters = parse_query(query);
+        let mut engine = FilterEngine::new();
+        for filter in filters.clone() {
+            engine.add_filter(filter);
+        }
+        assert_eq!(parse_query(&engine.query()), filters);
+    }
+
+    #[test]
+    fn test_slow_query_filter() {
+        let filter = Filter::SlowQuery(100.0);
+        let slow = create_test_entry("duration: 250.1 ms  statement: SELECT 1 FROM jobs");
+        let fast = create_test_entry("duration: 3.2 ms  statement: SELECT 1 FROM jobs");
+        let untimed = create_test_entry("SELECT 1 FROM jobs");
+
+        assert!(filter.matches(&slow));
+        assert!(!filter.matches(&fast));
+        assert!(!filter.matches(&untimed));
+    }
+
+    #[test]
+    fn test_request_id_filter() {
+        let filter = Filter::RequestId("abc-123".to_string());
+        let router = parse_log_line(
+            "2010-09-16T15:13:46.677020+00:00 heroku[router]: at=info request_id=abc-123 status=200",
+        )
+        .unwrap();
+        let app = create_test_entry("request_id=abc-123 Completed 200 OK");
+        let other = create_test_entry("request_id=abc-1234 Completed 200 OK");
+
+        assert!(filter.matches(&router));
+        assert!(filter.matches(&app));
+        assert!(!filter.matches(&other));
+    }
+
     #[test]
     fn test_filter_display() {
         let filter1 = Filter::TextSearch("error".to_string());
@@ -153,4 +1054,363 @@ mod tests {
         let filter3 = Filter::Source("app".to_string());
         assert_eq!(filter3.display(), "Source: app");
     }
+
+    #[test]
+    fn test_engine_preview_applies_on_top_of_committed() {
+        let mut engine = FilterEngine::new();
+        engine.add_filter(Filter::TextSearch("request".to_string()));
+        engine.set_preview(vec![Filter::TextSearch("failed".to_string())]);
+
+        let failed = create_test_entry("request failed");
+        let ok = create_test_entry("request ok");
+
+        assert!(engine.matches(&failed));
+        assert!(!engine.matches(&ok));
+        assert_eq!(engine.filters().len(), 1);
+    }
+
+    #[test]
+    fn test_engine_matches_committed_ignores_preview() {
+        let mut engine = FilterEngine::new();
+        engine.add_filter(Filter::TextSearch("request".to_string()));
+        engine.set_preview(vec![Filter::TextSearch("fai".to_string())]);
+
+        let ok = create_test_entry("request ok");
+        assert!(!engine.matches(&ok));
+        assert!(engine.matches_committed(&ok));
+        assert!(!engine.matches_committed(&create_test_entry("startup")));
+    }
+
+    #[test]
+    fn test_engine_cancel_preview_reverts() {
+        let mut engine = FilterEngine::new();
+        engine.set_preview(vec![Filter::TextSearch("failed".to_string())]);
+        let ok = create_test_entry("request ok");
+        assert!(!engine.matches(&ok));
+
+        engine.cancel_preview();
+        assert!(!engine.has_preview());
+        assert!(engine.matches(&ok));
+    }
+
+    #[test]
+    fn test_engine_add_filter_clears_preview() {
+        let mut engine = FilterEngine::new();
+        engine.set_preview(vec![Filter::TextSearch("fail".to_string())]);
+        engine.add_filter(Filter::TextSearch("failed".to_string()));
+
+        assert!(!engine.has_preview());
+        assert_eq!(
+            engine.filters(),
+            &[Filter::TextSearch("failed".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_engine_or_mode_with_preview() {
+        let mut engine = FilterEngine::new();
+        engine.toggle_mode();
+        engine.add_filter(Filter::TextSearch("alpha".to_string()));
+        engine.set_preview(vec![Filter::TextSearch("beta".to_string())]);
+
+        assert!(engine.matches(&create_test_entry("alpha")));
+        assert!(engine.matches(&create_test_entry("beta")));
+        assert!(!engine.matches(&create_test_entry("gamma")));
+    }
+
+    #[test]
+    fn test_engine_cancel_restores_previous_state() {
+        let mut engine = FilterEngine::new();
+        engine.add_filter(Filter::Dyno("web.1".to_string()));
+        let before = engine.clone();
+
+        engine.set_preview(vec![Filter::TextSearch("timeout".to_string())]);
+        assert_ne!(engine, before);
+
+        engine.cancel_preview();
+        assert_eq!(engine, before);
+    }
+
+    #[test]
+    fn test_engine_apply_then_cancel_keeps_committed() {
+        let mut engine = FilterEngine::new();
+        engine.set_preview(vec![Filter::TextSearch("timeout".to_string())]);
+        engine.add_filter(Filter::TextSearch("timeout".to_string()));
+
+        // A late cancel (e.g. Esc after Enter) must not undo the committed filter
+        engine.cancel_preview();
+        assert_eq!(
+            engine.filters(),
+            &[Filter::TextSearch("timeout".to_string())]
+        );
+        assert!(!engine.matches(&create_test_entry("request ok")));
+        assert!(engine.matches(&create_test_entry("request timeout")));
+    }
+
+    #[test]
+    fn test_count_matches() {
+        let at = |time: &str, message: &str| {
+            LogEntry::builder()
+                .timestamp(
+                    DateTime::parse_from_rfc3339(&format!("2024-02-17T{}+00:00", time)).unwrap(),
+                )
+                .message(message)
+                .build()
+                .unwrap()
+        };
+        let entries = vec![
+            at("10:00:00", "ERROR early"),
+            at("10:10:00", "ERROR late"),
+            at("10:11:00", "fine"),
+        ];
+        let errors = [Filter::LogLevel(LogLevel::Error)];
+        let cutoff = DateTime::parse_from_rfc3339("2024-02-17T10:05:00+00:00").unwrap();
+
+        assert_eq!(count_matches(&entries, &errors, None), 2);
+        assert_eq!(count_matches(&entries, &errors, Some(cutoff)), 1);
+        assert_eq!(count_matches(&entries, &[], Some(cutoff)), 2);
+    }
+
+    #[test]
+    fn test_incremental_filter_resumes_after_appends() {
+        let engine = FilterEngine::new();
+        let mut entries = vec![create_test_entry("one"), create_test_entry("two")];
+        let mut tracker = IncrementalFilter::default();
+        assert_eq!(tracker.resume_from(&engine, &entries), None);
+
+        tracker.update(&engine, &entries);
+        assert_eq!(tracker.resume_from(&engine, &entries), Some(2));
+        entries.push(create_test_entry("three"));
+        assert_eq!(tracker.resume_from(&engine, &entries), Some(2));
+    }
+
+    #[test]
+    fn test_incremental_filter_recomputes_on_change() {
+        let mut engine = FilterEngine::new();
+        let mut entries = vec![create_test_entry("one"), create_test_entry("two")];
+        let mut tracker = IncrementalFilter::default();
+        tracker.update(&engine, &entries);
+
+        // Eviction of the oldest entry
+        entries.remove(0);
+        entries.push(create_test_entry("three"));
+        assert_eq!(tracker.resume_from(&engine, &entries), None);
+
+        tracker.update(&engine, &entries);
+        engine.add_filter(Filter::TextSearch("two".to_string()));
+        assert_eq!(tracker.resume_from(&engine, &entries), None);
+
+        tracker.update(&engine, &entries);
+        tracker.reset();
+        assert_eq!(tracker.resume_from(&engine, &entries), None);
+        assert_eq!(tracker.resume_from(&engine, &[]), None);
+    }
+
+    #[test]
+    fn test_parse_filter_prefixes() {
+        assert_eq!(
+            parse_filter("dyno:web.1"),
+            Some(Filter::Dyno("web.1".to_string()))
+        );
+        assert_eq!(
+            parse_filter("level:warning"),
+            Some(Filter::LogLevel(LogLevel::Warn))
+        );
+        assert_eq!(parse_filter("slow:100ms"), Some(Filter::SlowQuery(100.0)));
+        assert_eq!(parse_filter("  "), None);
+        assert_eq!(
+            parse_filter("/5\\d\\d/"),
+            Some(Filter::Regex(Regex::new("5\\d\\d").unwrap()))
+        );
+    }
+
+    #[test]
+    fn test_parse_query_combines_clauses() {
+        let filters = parse_query("level:error dyno:web.1 connection refused");
+        assert_eq!(
+            filters,
+            vec![
+                Filter::LogLevel(LogLevel::Error),
+                Filter::Dyno("web.1".to_string()),
+                Filter::TextSearch("connection refused".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_query_quoted_phrase() {
+        assert_eq!(
+            parse_query("\"connection refused\" dyno:web.1"),
+            vec![
+                Filter::TextSearch("connection refused".to_string()),
+                Filter::Dyno("web.1".to_string()),
+            ]
+        );
+        assert_eq!(
+            parse_query("!\"level:error\""),
+            vec![Filter::Not(Box::new(Filter::TextSearch(
+                "level:error".to_string()
+            )))]
+        );
+        // An unclosed quote runs to the end
+        assert_eq!(
+            parse_query("\"timed out"),
+            vec![Filter::TextSearch("timed out".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_parse_query_regex_with_spaces() {
+        let filters = parse_query("source:app /GET .*500/");
+        assert_eq!(
+            filters,
+            vec![
+                Filter::Source("app".to_string()),
+                Filter::Regex(Regex::new("GET .*500").unwrap()),
+            ]
+        );
+        // An unclosed slash is plain text
+        assert_eq!(
+            parse_query("/api/users timeout"),
+            vec![Filter::TextSearch("/api/users timeout".to_string())]
+        );
+        assert!(parse_query("").is_empty());
+    }
+
+    #[test]
+    fn test_field_filter() {
+        let filter = parse_filter("field:status=502").unwrap();
+        assert_eq!(
+            filter,
+            Filter::Field("status".to_string(), "502".to_string())
+        );
+
+        let json = create_test_entry(r#"{"msg":"payment failed","status":502}"#);
+        assert!(filter.matches(&json));
+        assert!(!filter.matches(&create_test_entry("status=502")));
+        // Without a key=value it is plain text
+        assert_eq!(
+            parse_filter("field:status"),
+            Some(Filter::TextSearch("field:status".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_match_ranges() {
+        let mut engine = FilterEngine::new();
+        engine.add_filter(Filter::TextSearch("TIME".to_string()));
+        engine.add_filter(Filter::Not(Box::new(Filter::TextSearch("ok".to_string()))));
+        engine.add_filter(Filter::Dyno("web.1".to_string()));
+        engine.set_preview(vec![Filter::Regex(Regex::new(r"out \d+").unwrap())]);
+
+        let search = engine.search_filters();
+        assert_eq!(search.len(), 2);
+        // "Timeout" and "out 30" overlap and merge; "timed" is separate
+        assert_eq!(
+            match_ranges(&search, "Timeout 30s, timed out 45s é"),
+            vec![(0, 10), (13, 17), (19, 25)]
+        );
+        assert!(match_ranges(&search, "ok").is_empty());
+    }
+
+    #[test]
+    fn test_origin_filter() {
+        let filter = parse_filter("origin:FILE").unwrap();
+        assert_eq!(filter, Filter::Origin("FILE".to_string()));
+
+        let mut entry = create_test_entry("hello");
+        entry.origin = "file:development.log".to_string();
+        assert!(filter.matches(&entry));
+        entry.origin = "heroku:my-app".to_string();
+        assert!(!filter.matches(&entry));
+        assert!(parse_filter("-origin:heroku")
+            .unwrap()
+            .matches(&create_test_entry("hello")));
+    }
+
+    #[test]
+    fn test_metrics_category_filter() {
+        let filter = parse_filter("category:metrics").unwrap();
+        assert_eq!(filter, Filter::Metrics);
+
+        let postgres = parse_log_line(
+            "2024-02-17T10:30:45.123456+00:00 app[heroku-postgres]: source=DATABASE sample#load-avg-1m=0.02",
+        )
+        .unwrap();
+        assert!(filter.matches(&postgres));
+        assert!(!filter.matches(&create_test_entry("sample#load-avg-1m=0.02")));
+        assert!(!parse_filter("-category:metrics")
+            .unwrap()
+            .matches(&postgres));
+    }
+
+    #[test]
+    fn test_router_field_shorthands() {
+        let router = parse_log_line(
+            "2024-02-17T10:30:45.123456+00:00 heroku[router]: at=info method=GET path=\"/api/users\" status=503 service=12ms",
+        )
+        .unwrap();
+
+        assert!(parse_filter("status:503").unwrap().matches(&router));
+        assert!(parse_filter("status:5").unwrap().matches(&router));
+        assert!(parse_filter("path:/api").unwrap().matches(&router));
+        assert!(!parse_filter("path:/admin").unwrap().matches(&router));
+        assert_eq!(
+            parse_query("method:get status:503"),
+            vec![
+                Filter::Field("method".to_string(), "get".to_string()),
+                Filter::Field("status".to_string(), "503".to_string()),
+            ]
+        );
+        // Only known router keys are shorthands
+        assert_eq!(
+            parse_filter("http://x"),
+            Some(Filter::TextSearch("http://x".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_negated_filters() {
+        let health = create_test_entry("GET /health 200");
+        let error = create_test_entry("Error: connection refused");
+
+        let filter = parse_filter("!health").unwrap();
+        assert_eq!(
+            filter,
+            Filter::Not(Box::new(Filter::TextSearch("health".to_string())))
+        );
+        assert!(!filter.matches(&health));
+        assert!(filter.matches(&error));
+
+        assert_eq!(
+            parse_filter("-dyno:web.1"),
+            Some(Filter::Not(Box::new(Filter::Dyno("web.1".to_string()))))
+        );
+        assert_eq!(
+            parse_filter("not level:debug"),
+            Some(Filter::Not(Box::new(Filter::LogLevel(LogLevel::Debug))))
+        );
+        // `-` and `not` only negate clauses
+        assert_eq!(
+            parse_filter("-1"),
+            Some(Filter::TextSearch("-1".to_string()))
+        );
+        assert_eq!(
+            parse_filter("not found"),
+            Some(Filter::TextSearch("not found".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_query_with_negations() {
+        assert_eq!(
+            parse_query("could not connect !health not level:debug -/GET .*200/"),
+            vec![
+                Filter::Not(Box::new(Filter::TextSearch("health".to_string()))),
+                Filter::Not(Box::new(Filter::LogLevel(LogLevel::Debug))),
+                Filter::Not(Box::new(Filter::Regex(Regex::new("GET .*200").unwrap()))),
+                Filter::TextSearch("could not connect".to_string()),
+            ]
+        );
+    }
 }
@@ -0,0 +1,376 @@
+//! Guessing the format of incoming lines, and forcing one
+//!
+//! The first [`SAMPLE_SIZE`] lines of a stream are classified so the UI can
+//! say which format was detected. When the guess is wrong the user can
+//! force a format, with `--format` at startup or later from the UI, after
+//! which lines are parsed only as that format.
+
+use crate::parser::{
+    parse_heroku_line, parse_json_line, parse_log_line, parse_syslog_line, LogEntry, ParsedLine,
+    UnparsedLine,
+};
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Number of lines looked at before reporting a detection
+pub const SAMPLE_SIZE: usize = 20;
+
+/// A log line format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// `timestamp source[dyno]: message`, as printed by `heroku logs`
+    Heroku,
+    /// One JSON object per line
+    Json,
+    /// RFC 5424 syslog; RFC 3164 lines are recognised but shown as plain
+    /// lines
+    Syslog,
+    /// Anything else, shown as plain lines stamped with the time they arrived
+    Plain,
+}
+
+impl LogFormat {
+    /// Formats that can be forced, in the order the override key cycles
+    pub const OVERRIDES: [LogFormat; 4] = [
+        LogFormat::Heroku,
+        LogFormat::Json,
+        LogFormat::Syslog,
+        LogFormat::Plain,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LogFormat::Heroku => "Heroku",
+            LogFormat::Json => "JSON",
+            LogFormat::Syslog => "syslog",
+            LogFormat::Plain => "plain text",
+        }
+    }
+
+    /// Look up a format that can be forced by name, e.g. `json`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "heroku" => Some(LogFormat::Heroku),
+            "json" => Some(LogFormat::Json),
+            "syslog" => Some(LogFormat::Syslog),
+            "plain" | "text" => Some(LogFormat::Plain),
+            _ => None,
+        }
+    }
+
+    /// Guess the format of one line
+    pub fn classify(line: &str) -> Self {
+        if parse_heroku_line(line).is_some() {
+            LogFormat::Heroku
+        } else if parse_json_line(line).is_some() {
+            LogFormat::Json
+        } else if parse_syslog_line(line).is_some() || syslog_regex().is_match(line) {
+            LogFormat::Syslog
+        } else {
+            LogFormat::Plain
+        }
+    }
+
+    /// The override after `current`, wrapping back to automatic detection
+    pub fn next_override(current: Option<Self>) -> Option<Self> {
+        match current {
+            None => Some(Self::OVERRIDES[0]),
+            Some(format) => Self::OVERRIDES
+                .iter()
+                .position(|f| *f == format)
+                .and_then(|idx| Self::OVERRIDES.get(idx + 1))
+                .copied(),
+        }
+    }
+
+    /// Parse a line as this format only
+    pub fn parse(self, line: &str) -> Option<LogEntry> {
+        match self {
+            LogFormat::Heroku => parse_heroku_line(line),
+            LogFormat::Json => parse_json_line(line),
+            LogFormat::Syslog => parse_syslog_line(line),
+            LogFormat::Plain => None,
+        }
+    }
+
+    /// The format forced with `--format <name>`, if any
+    pub fn from_args(args: &[String]) -> Result<Option<Self>> {
+        let Some(at) = args.iter().position(|arg| arg == "--format") else {
+            return Ok(None);
+        };
+        let name = args
+            .get(at + 1)
+            .ok_or_else(|| anyhow!("--format needs a format: heroku, json, syslog or plain"))?;
+        Self::from_name(name).map(Some).ok_or_else(|| {
+            anyhow!(
+                "Unknown format {:?}: use heroku, json, syslog or plain",
+                name
+            )
+        })
+    }
+}
+
+fn syslog_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        // <PRI>VERSION TIMESTAMP ... (RFC 5424) or [<PRI>]Mmm dd hh:mm:ss (RFC 3164)
+        Regex::new(r"^(?:<\d{1,3}>\d \S+ |(?:<\d{1,3}>)?[A-Z][a-z]{2} [ \d]\d \d{2}:\d{2}:\d{2} )")
+            .expect("Failed to compile syslog regex")
+    })
+}
+
+/// Parse an already-parsed line again as a forced format
+pub fn reparse(result: ParsedLine, format: LogFormat) -> ParsedLine {
+    let (line_number, line) = match result {
+        Ok(entry) => (0, entry.raw),
+        Err(unparsed) => (unparsed.line_number, unparsed.line),
+    };
+    format
+        .parse(&line)
+        .ok_or(UnparsedLine { line_number, line })
+}
+
+/// Parse a buffered entry's raw line again, as a forced format or with
+/// detection (`None`)
+///
+/// A line that no longer parses is kept as a plain line with its original
+/// time and dyno, so the buffer stays in order. The origin is kept either way.
+pub fn reparse_entry(entry: &LogEntry, format: Option<LogFormat>) -> LogEntry {
+    let parsed = match format {
+        Some(format) => format.parse(&entry.raw),
+        None => parse_log_line(&entry.raw),
+    };
+    let mut reparsed = parsed.unwrap_or_else(|| {
+        LogEntry::builder()
+            .timestamp(entry.timestamp)
+            .source("local")
+            .dyno(entry.dyno.clone())
+            .message(entry.raw.clone())
+            .raw(entry.raw.clone())
+            .build()
+            .unwrap_or_else(|_| entry.clone())
+    });
+    reparsed.origin = entry.origin.clone();
+    reparsed.session_id = entry.session_id;
+    reparsed
+}
+
+/// Put entries re-parsed from a snapshot of the buffer back into it
+///
+/// While the snapshot was being parsed, the oldest entries may have been
+/// evicted and new ones appended (those were parsed with the current rules
+/// already), so the snapshot is lined up with what is left of it. Returns
+/// the number of entries replaced.
+pub fn apply_reparsed(
+    buffer: &mut [LogEntry],
+    snapshot: &[LogEntry],
+    reparsed: Vec<LogEntry>,
+) -> usize {
+    let Some(first) = buffer.first() else {
+        return 0;
+    };
+    let offset = (0..snapshot.len()).find(|&evicted| {
+        let overlap = (snapshot.len() - evicted).min(buffer.len());
+        snapshot[evicted] == *first && snapshot[evicted + overlap - 1] == buffer[overlap - 1]
+    });
+    let Some(evicted) = offset else {
+        return 0;
+    };
+    let overlap = (snapshot.len() - evicted).min(buffer.len());
+    for (slot, entry) in buffer
+        .iter_mut()
+        .zip(reparsed.into_iter().skip(evicted))
+        .take(overlap)
+    {
+        *slot = entry;
+    }
+    overlap
+}
+
+/// The outcome of sampling a stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Detection {
+    pub format: LogFormat,
+    /// Sampled lines in the detected format
+    pub matching: usize,
+    pub sampled: usize,
+}
+
+impl Detection {
+    /// The most common format among `samples`, preferring parsable ones on
+    /// a tie; `None` without samples
+    pub fn from_samples(samples: &[LogFormat]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let count = |format: LogFormat| samples.iter().filter(|f| **f == format).count();
+        // max_by_key keeps the last maximum, so list the preferred format last
+        let format = [
+            LogFormat::Plain,
+            LogFormat::Syslog,
+            LogFormat::Json,
+            LogFormat::Heroku,
+        ]
+        .into_iter()
+        .max_by_key(|format| count(*format))?;
+        Some(Self {
+            format,
+            matching: count(format),
+            sampled: samples.len(),
+        })
+    }
+}
+
+/// Classifies the first lines of a stream
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Detector {
+    samples: Vec<LogFormat>,
+}
+
+impl Detector {
+    /// Classify a line; returns the detection once the sample is complete,
+    /// and `None` before and after that
+    pub fn observe(&mut self, line: &str) -> Option<Detection> {
+        if self.samples.len() >= SAMPLE_SIZE {
+            return None;
+        }
+        self.samples.push(LogFormat::classify(line));
+        if self.samples.len() == SAMPLE_SIZE {
+            Detection::from_samples(&self.samples)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEROKU: &str = "2024-02-17T10:30:45.123456+00:00 app[web.1]: Started GET \"/\"";
+    const JSON: &str = r#"{"level":30,"time":1708165845123,"msg":"listening"}"#;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(LogFormat::classify(HEROKU), LogFormat::Heroku);
+        assert_eq!(LogFormat::classify(JSON), LogFormat::Json);
+        assert_eq!(
+            LogFormat::classify("<34>1 2024-02-17T10:30:45Z host app - - - started"),
+            LogFormat::Syslog
+        );
+        assert_eq!(
+            LogFormat::classify("Feb 17 10:30:45 host sshd[42]: Accepted key"),
+            LogFormat::Syslog
+        );
+        assert_eq!(LogFormat::classify("plain output"), LogFormat::Plain);
+    }
+
+    #[test]
+    fn test_detector_reports_once_after_sample() {
+        let mut detector = Detector::default();
+        for _ in 0..SAMPLE_SIZE - 2 {
+            assert_eq!(detector.observe(JSON), None);
+        }
+        assert_eq!(detector.observe("not a log line"), None);
+        let detection = detector.observe(JSON).unwrap();
+
+        assert_eq!(detection.format, LogFormat::Json);
+        assert_eq!(detection.matching, SAMPLE_SIZE - 1);
+        assert_eq!(detection.sampled, SAMPLE_SIZE);
+        assert_eq!(detector.observe(JSON), None);
+    }
+
+    #[test]
+    fn test_detection_prefers_parsable_on_tie() {
+        let samples = [LogFormat::Plain, LogFormat::Heroku];
+        assert_eq!(
+            Detection::from_samples(&samples).unwrap().format,
+            LogFormat::Heroku
+        );
+        assert_eq!(Detection::from_samples(&[]), None);
+    }
+
+    #[test]
+    fn test_override_cycle_and_reparse() {
+        let mut current = None;
+        let mut seen = Vec::new();
+        for _ in 0..5 {
+            current = LogFormat::next_override(current);
+            seen.push(current);
+        }
+        assert_eq!(
+            seen,
+            vec![
+                Some(LogFormat::Heroku),
+                Some(LogFormat::Json),
+                Some(LogFormat::Syslog),
+                Some(LogFormat::Plain),
+                None
+            ]
+        );
+
+        let parsed = crate::parser::parse_log_line(JSON).ok_or(UnparsedLine {
+            line_number: 1,
+            line: JSON.to_string(),
+        });
+        assert!(reparse(parsed.clone(), LogFormat::Json).is_ok());
+        let forced = reparse(parsed, LogFormat::Heroku).unwrap_err();
+        assert_eq!(forced.line, JSON);
+    }
+
+    #[test]
+    fn test_format_from_args() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(LogFormat::from_args(&args(&["--stdin"])).unwrap(), None);
+        assert_eq!(
+            LogFormat::from_args(&args(&["--format", "syslog", "--stdin"])).unwrap(),
+            Some(LogFormat::Syslog)
+        );
+        assert!(LogFormat::from_args(&args(&["--format", "xml"])).is_err());
+        assert!(LogFormat::from_args(&args(&["--format"])).is_err());
+    }
+
+    #[test]
+    fn test_reparse_entry() {
+        let entry = parse_log_line(HEROKU).unwrap();
+        assert_eq!(reparse_entry(&entry, None), entry);
+        assert_eq!(reparse_entry(&entry, Some(LogFormat::Heroku)), entry);
+
+        let plain = reparse_entry(&entry, Some(LogFormat::Plain));
+        assert_eq!(plain.message, HEROKU);
+        assert_eq!(plain.source, "local");
+        assert_eq!(plain.dyno, "web.1");
+        assert_eq!(plain.timestamp, entry.timestamp);
+        assert_eq!(LogFormat::from_name("JSON"), Some(LogFormat::Json));
+        assert_eq!(LogFormat::from_name("syslog"), Some(LogFormat::Syslog));
+        assert_eq!(LogFormat::from_name("logfmt"), None);
+    }
+
+    #[test]
+    fn test_apply_reparsed_after_eviction_and_appends() {
+        let line = |n: u32| {
+            format!(
+                "2024-02-17T10:30:{:02}.000000+00:00 app[web.1]: line {}",
+                n, n
+            )
+        };
+        let snapshot: Vec<LogEntry> = (0..5).map(|n| parse_log_line(&line(n)).unwrap()).collect();
+        let reparsed: Vec<LogEntry> = snapshot
+            .iter()
+            .map(|e| reparse_entry(e, Some(LogFormat::Plain)))
+            .collect();
+
+        // Two evicted, one appended while parsing
+        let mut buffer = snapshot[2..].to_vec();
+        buffer.push(parse_log_line(&line(5)).unwrap());
+        assert_eq!(apply_reparsed(&mut buffer, &snapshot, reparsed.clone()), 3);
+        assert_eq!(buffer[..3], reparsed[2..]);
+        assert_eq!(buffer[3].message, "line 5");
+
+        // Buffer cleared and refilled: nothing lines up
+        let mut other = vec![parse_log_line(&line(9)).unwrap()];
+        assert_eq!(apply_reparsed(&mut other, &snapshot, reparsed), 0);
+        assert_eq!(apply_reparsed(&mut [], &snapshot, Vec::new()), 0);
+    }
+}
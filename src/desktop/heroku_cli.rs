@@ -13,7 +13,7 @@ pub struct AppInfo {
 /// Build a PATH string that includes common macOS install locations.
 /// GUI apps launched from the Dock/Finder don't inherit the user's shell PATH,
 /// so Homebrew-installed binaries (/opt/homebrew/bin, /usr/local/bin) are missing.
-fn gui_path() -> String {
+pub fn gui_path() -> String {
     let base = std::env::var("PATH").unwrap_or_default();
     format!("/opt/homebrew/bin:/usr/local/bin:/usr/bin:/bin:{}", base)
 }
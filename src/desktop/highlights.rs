@@ -0,0 +1,203 @@
+//! Highlight patterns, each drawn in its own color
+//!
+//! Unlike filters, highlights hide nothing: they mark occurrences of a term
+//! so e.g. "cache miss" and "cache hit" can be compared at a glance. They
+//! are kept in `~/.config/logs-parser/highlights`, one term or `/regex/` per
+//! line, so they're back after a restart.
+
+use crate::config::config_dir;
+use anyhow::{Context, Result};
+use regex::{Regex, RegexBuilder};
+
+/// Number of distinct highlight colors (`hl-0` to `hl-5` in the stylesheet)
+pub const PALETTE_SIZE: usize = 6;
+
+/// One highlighted term and its palette slot
+#[derive(Debug, Clone)]
+pub struct Highlight {
+    pub label: String,
+    pub color: usize,
+    pattern: Regex,
+}
+
+impl PartialEq for Highlight {
+    fn eq(&self, other: &Self) -> bool {
+        self.label == other.label && self.color == other.color
+    }
+}
+
+/// A piece of a message, with the palette slot of the highlight covering it
+#[derive(Debug, Clone, PartialEq)]
+pub struct HighlightSpan {
+    pub text: String,
+    pub color: Option<usize>,
+}
+
+/// Active highlights, colored in the order they were added
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Highlights {
+    highlights: Vec<Highlight>,
+}
+
+impl Highlights {
+    /// Parse a highlights file, skipping blank lines, comments and invalid
+    /// regexes
+    pub fn parse(contents: &str) -> Self {
+        let mut highlights = Self::default();
+        for line in contents.lines().map(str::trim) {
+            if !line.is_empty() && !line.starts_with('#') {
+                let _ = highlights.add(line);
+            }
+        }
+        highlights
+    }
+
+    /// Load the saved highlights; no file means none
+    pub fn load() -> Self {
+        std::fs::read_to_string(config_dir().join("highlights"))
+            .map(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    /// Write the highlights back to the highlights file
+    pub fn save(&self) -> Result<()> {
+        let dir = config_dir();
+        std::fs::create_dir_all(&dir).context("Failed to create config directory")?;
+        let contents: String = self
+            .highlights
+            .iter()
+            .map(|h| format!("{}\n", h.label))
+            .collect();
+        std::fs::write(dir.join("highlights"), contents).context("Failed to save highlights")
+    }
+
+    /// Add a plain-text (case-insensitive) or `/regex/` highlight
+    pub fn add(&mut self, query: &str) -> Result<(), String> {
+        let pattern = match query.strip_prefix('/').and_then(|q| q.strip_suffix('/')) {
+            Some(regex) if !regex.is_empty() => Regex::new(regex),
+            _ => RegexBuilder::new(&regex::escape(query))
+                .case_insensitive(true)
+                .build(),
+        }
+        .map_err(|e| format!("Invalid highlight: {}", e))?;
+
+        self.highlights.push(Highlight {
+            label: query.to_string(),
+            color: self.highlights.len() % PALETTE_SIZE,
+            pattern,
+        });
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        self.highlights.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.highlights.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Highlight> {
+        self.highlights.iter()
+    }
+
+    /// Split a message into highlighted and plain spans
+    ///
+    /// Where matches overlap, the earliest-starting one wins, and between
+    /// matches starting at the same place the highlight added first wins.
+    pub fn spans(&self, message: &str) -> Vec<HighlightSpan> {
+        let mut matches: Vec<(usize, usize, usize)> = self
+            .highlights
+            .iter()
+            .flat_map(|h| {
+                h.pattern
+                    .find_iter(message)
+                    .filter(|m| !m.is_empty())
+                    .map(|m| (m.start(), m.end(), h.color))
+            })
+            .collect();
+        matches.sort_by_key(|&(start, _, _)| start);
+
+        let mut spans = Vec::new();
+        let mut last = 0;
+        for (start, end, color) in matches {
+            if start < last {
+                continue;
+            }
+            if start > last {
+                spans.push(HighlightSpan {
+                    text: message[last..start].to_string(),
+                    color: None,
+                });
+            }
+            spans.push(HighlightSpan {
+                text: message[start..end].to_string(),
+                color: Some(color),
+            });
+            last = end;
+        }
+        if last < message.len() {
+            spans.push(HighlightSpan {
+                text: message[last..].to_string(),
+                color: None,
+            });
+        }
+        spans
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_colors_cycle_palette() {
+        let mut highlights = Highlights::default();
+        for i in 0..=PALETTE_SIZE {
+            highlights.add(&format!("term{}", i)).unwrap();
+        }
+
+        let colors: Vec<usize> = highlights.iter().map(|h| h.color).collect();
+        assert_eq!(colors[1], 1);
+        assert_eq!(colors[PALETTE_SIZE], 0);
+    }
+
+    #[test]
+    fn test_spans_mark_each_term() {
+        let mut highlights = Highlights::default();
+        highlights.add("cache miss").unwrap();
+        highlights.add("/hit/").unwrap();
+
+        let spans = highlights.spans("Cache miss then hit");
+        let marked: Vec<(&str, Option<usize>)> =
+            spans.iter().map(|s| (s.text.as_str(), s.color)).collect();
+        assert_eq!(
+            marked,
+            vec![("Cache miss", Some(0)), (" then ", None), ("hit", Some(1))]
+        );
+    }
+
+    #[test]
+    fn test_overlapping_matches_keep_first() {
+        let mut highlights = Highlights::default();
+        highlights.add("cache").unwrap();
+        highlights.add("che miss").unwrap();
+
+        let spans = highlights.spans("cache miss");
+        assert_eq!(spans[0].text, "cache");
+        assert_eq!(spans[1].text, " miss");
+        assert_eq!(spans[1].color, None);
+    }
+
+    #[test]
+    fn test_parse_saved_terms() {
+        let highlights = Highlights::parse("# terms\ncache miss\n/(/\n\n/hit|miss/\n");
+        let labels: Vec<&str> = highlights.iter().map(|h| h.label.as_str()).collect();
+        assert_eq!(labels, ["cache miss", "/hit|miss/"]);
+    }
+
+    #[test]
+    fn test_invalid_regex() {
+        assert!(Highlights::default().add("/(/").is_err());
+    }
+}
@@ -0,0 +1,283 @@
+//! Received lines kept on disk so the buffer survives a restart
+//!
+//! With `history = 20MB` in the settings every line is appended to
+//! `~/.config/logs-parser/history.log` as `origin<TAB>raw`, and the byte
+//! offset of each record to `history.idx` (little-endian `u64`s), so the
+//! newest lines can be read back without scanning the whole file. Once the
+//! log outgrows its cap the oldest half is dropped.
+
+use crate::parser::{self, LogEntry};
+use anyhow::{Context, Result};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+const LOG_FILE: &str = "history.log";
+const INDEX_FILE: &str = "history.idx";
+
+/// Append-only log of received lines, capped by size
+#[derive(Debug)]
+pub struct History {
+    dir: PathBuf,
+    data: BufWriter<File>,
+    index: BufWriter<File>,
+    /// Start of every record in the log
+    offsets: Vec<u64>,
+    /// Bytes in the log, including any not yet flushed
+    len: u64,
+    max_bytes: u64,
+}
+
+/// Offsets of the records in `data` from `start` on, which must begin a
+/// record; a partly written last line is left out
+fn scan_records(data: &[u8], start: u64) -> Vec<u64> {
+    let mut offsets = Vec::new();
+    let mut record = start;
+    for (i, byte) in data.iter().enumerate() {
+        if *byte == b'\n' {
+            offsets.push(record);
+            record = start + i as u64 + 1;
+        }
+    }
+    offsets
+}
+
+fn write_index(path: &Path, offsets: &[u64]) -> Result<()> {
+    let bytes: Vec<u8> = offsets.iter().flat_map(|o| o.to_le_bytes()).collect();
+    std::fs::write(path, bytes).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn open_append(path: &Path) -> Result<BufWriter<File>> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    Ok(BufWriter::new(file))
+}
+
+/// Entry for a line read back from the history, tagged with its input
+fn restored_entry(origin: &str, raw: &str) -> LogEntry {
+    let mut entry = parser::parse_log_line(raw).unwrap_or_else(|| LogEntry::local(raw, origin));
+    entry.origin = origin.to_string();
+    entry
+}
+
+impl History {
+    /// Open the history in `dir`, repairing an index left behind by a crash
+    pub fn open(dir: &Path, max_bytes: u64) -> Result<Self> {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        let log_path = dir.join(LOG_FILE);
+        let index_path = dir.join(INDEX_FILE);
+
+        let mut data = Vec::new();
+        if log_path.exists() {
+            File::open(&log_path)
+                .and_then(|mut file| file.read_to_end(&mut data))
+                .with_context(|| format!("Failed to read {}", log_path.display()))?;
+        }
+        let index = std::fs::read(&index_path).unwrap_or_default();
+
+        // Trust indexed offsets that point inside the log, then pick up
+        // records written after the index was last flushed
+        let mut offsets: Vec<u64> = index
+            .chunks_exact(8)
+            .map(|chunk| u64::from_le_bytes(chunk.try_into().expect("8 bytes")))
+            .take_while(|&offset| offset < data.len() as u64)
+            .collect();
+        if offsets.first().is_some_and(|&first| first != 0) {
+            offsets.clear();
+        }
+        let resume = offsets.pop().unwrap_or(0);
+        offsets.extend(scan_records(&data[resume as usize..], resume));
+
+        // Drop a line cut off mid-write
+        let len = data
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |end| end as u64 + 1);
+        if len < data.len() as u64 {
+            OpenOptions::new()
+                .write(true)
+                .open(&log_path)
+                .and_then(|file| file.set_len(len))
+                .with_context(|| format!("Failed to repair {}", log_path.display()))?;
+        }
+        write_index(&index_path, &offsets)?;
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            data: open_append(&log_path)?,
+            index: open_append(&index_path)?,
+            offsets,
+            len,
+            max_bytes,
+        })
+    }
+
+    /// The newest `count` lines, oldest first
+    pub fn recent(&mut self, count: usize) -> Result<Vec<LogEntry>> {
+        let first = self.offsets.len().saturating_sub(count);
+        let Some(&start) = self.offsets.get(first) else {
+            return Ok(Vec::new());
+        };
+        self.flush()?;
+        let path = self.dir.join(LOG_FILE);
+        let mut tail = String::new();
+        File::open(&path)
+            .and_then(|mut file| {
+                file.seek(SeekFrom::Start(start))?;
+                file.read_to_string(&mut tail)
+            })
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Ok(tail
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .map(|(origin, raw)| restored_entry(origin, raw))
+            .collect())
+    }
+
+    /// Add a received line, trimming the log if it outgrew its cap
+    pub fn append(&mut self, origin: &str, raw: &str) -> Result<()> {
+        let record = format!(
+            "{}\t{}\n",
+            origin.replace(['\t', '\n'], " "),
+            raw.replace('\n', " ")
+        );
+        self.data
+            .write_all(record.as_bytes())
+            .and_then(|_| self.index.write_all(&self.len.to_le_bytes()))
+            .context("Failed to write history")?;
+        self.offsets.push(self.len);
+        self.len += record.len() as u64;
+        if self.len > self.max_bytes {
+            self.compact()?;
+        }
+        Ok(())
+    }
+
+    /// Write buffered lines to disk; the log first, so the index never
+    /// points past it
+    pub fn flush(&mut self) -> Result<()> {
+        self.data
+            .flush()
+            .and_then(|_| self.index.flush())
+            .context("Failed to write history")
+    }
+
+    /// Keep only the newest records filling half the cap
+    fn compact(&mut self) -> Result<()> {
+        self.flush()?;
+        let keep_from = self.len.saturating_sub(self.max_bytes / 2);
+        let first = self.offsets.partition_point(|&offset| offset < keep_from);
+        let start = self.offsets.get(first).copied().unwrap_or(self.len);
+
+        let log_path = self.dir.join(LOG_FILE);
+        let mut tail = Vec::new();
+        File::open(&log_path)
+            .and_then(|mut file| {
+                file.seek(SeekFrom::Start(start))?;
+                file.read_to_end(&mut tail)
+            })
+            .with_context(|| format!("Failed to read {}", log_path.display()))?;
+        // Replace the log in one step so a crash leaves either version
+        let temp = self.dir.join(format!("{}.tmp", LOG_FILE));
+        std::fs::write(&temp, &tail)
+            .and_then(|_| std::fs::rename(&temp, &log_path))
+            .with_context(|| format!("Failed to write {}", log_path.display()))?;
+
+        self.offsets = self.offsets[first..].iter().map(|o| o - start).collect();
+        self.len -= start;
+        let index_path = self.dir.join(INDEX_FILE);
+        write_index(&index_path, &self.offsets)?;
+        self.data = open_append(&log_path)?;
+        self.index = open_append(&index_path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "logs-parser-history-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_lines_survive_reopening() {
+        let dir = temp_dir("reopen");
+        let mut history = History::open(&dir, 1 << 20).unwrap();
+        history
+            .append(
+                "heroku:myapp",
+                "2024-02-17T10:30:45.123456+00:00 app[web.1]: Started GET",
+            )
+            .unwrap();
+        history
+            .append("file:development.log", "plain line")
+            .unwrap();
+        history.append("stdin", "last line").unwrap();
+        drop(history);
+
+        let mut history = History::open(&dir, 1 << 20).unwrap();
+        let recent = history.recent(2).unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].message, "plain line");
+        assert_eq!(recent[0].origin, "file:development.log");
+        assert_eq!(recent[1].origin, "stdin");
+
+        let all = history.recent(10).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].dyno, "web.1");
+        assert_eq!(all[0].origin, "heroku:myapp");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_repairs_stale_index_and_torn_line() {
+        let dir = temp_dir("repair");
+        std::fs::create_dir_all(&dir).unwrap();
+        // The index only knows the first record and the last line is torn
+        std::fs::write(dir.join(LOG_FILE), "stdin\tone\nstdin\ttwo\nstdin\tthr").unwrap();
+        std::fs::write(dir.join(INDEX_FILE), 0u64.to_le_bytes()).unwrap();
+
+        let mut history = History::open(&dir, 1 << 20).unwrap();
+        history.append("stdin", "three").unwrap();
+        let messages: Vec<_> = history
+            .recent(10)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.message)
+            .collect();
+        assert_eq!(messages, ["one", "two", "three"]);
+        assert_eq!(std::fs::read(dir.join(INDEX_FILE)).unwrap().len(), 3 * 8);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_oldest_lines_dropped_past_cap() {
+        let dir = temp_dir("cap");
+        let mut history = History::open(&dir, 200).unwrap();
+        for i in 0..50 {
+            history.append("stdin", &format!("line {:02}", i)).unwrap();
+        }
+        history.flush().unwrap();
+        assert!(std::fs::metadata(dir.join(LOG_FILE)).unwrap().len() <= 200);
+
+        let recent = history.recent(1000).unwrap();
+        assert_eq!(recent.last().unwrap().message, "line 49");
+        assert!(recent.len() < 50);
+        drop(history);
+        let mut reopened = History::open(&dir, 200).unwrap();
+        assert_eq!(reopened.recent(1000).unwrap().len(), recent.len());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,662 @@
+//! Translations of user-facing strings
+//!
+//! Components look strings up by key with [`t`], or [`tf`] when the string
+//! has `{name}` placeholders. Keys missing from the active locale fall back
+//! to English. The locale comes from the `language` setting, then from
+//! `LC_ALL` / `LC_MESSAGES` / `LANG`.
+
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+/// A supported UI language
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    En,
+    Es,
+}
+
+impl Locale {
+    /// Parse a language tag such as `es`, `es-MX` or `es_ES.UTF-8`
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let language = tag.split(['_', '-', '.']).next()?.to_lowercase();
+        match language.as_str() {
+            "en" => Some(Locale::En),
+            "es" => Some(Locale::Es),
+            _ => None,
+        }
+    }
+
+    /// The configured locale, else the one from the environment, else English
+    pub fn detect(configured: Option<Locale>) -> Self {
+        configured
+            .or_else(|| {
+                ["LC_ALL", "LC_MESSAGES", "LANG"]
+                    .iter()
+                    .filter_map(|var| std::env::var(var).ok())
+                    .find(|value| !value.is_empty())
+                    .and_then(|value| Self::from_tag(&value))
+            })
+            .unwrap_or_default()
+    }
+
+    fn catalog(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::En => EN,
+            Locale::Es => ES,
+        }
+    }
+}
+
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+/// Choose the UI language; only the first call has an effect
+pub fn set_locale(locale: Locale) {
+    let _ = LOCALE.set(locale);
+}
+
+fn lookup(locale: Locale, key: &'static str) -> &'static str {
+    let find = |catalog: &'static [(&str, &'static str)]| {
+        catalog.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+    };
+    find(locale.catalog()).or_else(|| find(EN)).unwrap_or(key)
+}
+
+/// The string for `key` in the active locale
+pub fn t(key: &'static str) -> &'static str {
+    lookup(LOCALE.get().copied().unwrap_or_default(), key)
+}
+
+/// The string for `key` with each `{name}` replaced by its value
+pub fn tf(key: &'static str, args: &[(&str, &dyn Display)]) -> String {
+    fill(t(key), args)
+}
+
+fn fill(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut text = template.to_string();
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    text
+}
+
+const EN: &[(&str, &str)] = &[
+    ("app.label", "Heroku App"),
+    ("app.placeholder", "Select an app..."),
+    ("banner.crashed", "The app crashed last time. A report was saved to {path}."),
+    ("banner.detected", "Detected {format} format: {matching} of {sampled} sampled lines."),
+    ("banner.download", "Download"),
+    ("banner.force_format", "Press {key} to force a format."),
+    ("banner.muted", "Muted: \"{queries}\" (:unmute <query>)."),
+    ("banner.new_lines", "↓ {count} new"),
+    ("banner.new_lines_title", "Jump to the newest lines"),
+    ("banner.parsing_as", "Parsing as {format}."),
+    ("banner.paused", "Paused — {count} new lines held. Press {key} to resume."),
+    ("banner.plain_text", "Lines are shown as plain text."),
+    ("banner.report", "Report on GitHub"),
+    (
+        "banner.report_title",
+        "Open a GitHub issue filled in with the report, to review before submitting",
+    ),
+    ("banner.snoozed", "Alerts snoozed until {time} (:snooze off to resume)."),
+    ("banner.sorted", "Sorted by {key}, not by time."),
+    ("banner.sorted_desc", "Sorted by {key} (descending), not by time."),
+    ("banner.time_order", "Back to time order"),
+    ("banner.update", "Version {version} is out (this is {current})."),
+    ("common.cancel", "Cancel"),
+    ("common.dismiss", "Dismiss"),
+    ("connect", "Connect"),
+    ("connecting", "Connecting..."),
+    ("detail.close_title", "Close details (Esc)"),
+    ("detail.copy_link", "Copy link"),
+    ("detail.fields", "Fields"),
+    ("detail.level", "Level"),
+    ("detail.message", "Message"),
+    ("detail.open_request", "Open in browser"),
+    ("detail.origin", "Origin"),
+    ("detail.raw", "Raw line"),
+    ("detail.request", "Request"),
+    ("detail.session_line", "Session line"),
+    ("detail.source", "Source"),
+    ("detail.time", "Time"),
+    ("detail.timeline", "Request timeline"),
+    ("detail.title", "Entry details"),
+    ("disconnect", "Disconnect"),
+    ("export.timeline", "Export Timeline"),
+    (
+        "export.timeline_title",
+        "Export per-minute counts and router latency as CSV",
+    ),
+    ("filter.add", "Add Filter"),
+    ("filter.awaiting", "Awaiting: \"{query}\""),
+    ("filter.clear", "Clear"),
+    ("filter.clear_title", "Clear all filters ({key})"),
+    ("filter.confirm_clear", "Confirm Clear"),
+    ("filter.matches", "{count} matches"),
+    (
+        "filter.placeholder",
+        "Enter filter (text, dyno:web.1, source:app, level:error, /regex/) or :await <query>",
+    ),
+    ("filter.remove_title", "Remove filter"),
+    ("filter.toggle_mode", "Toggle {mode}"),
+    (
+        "filter.toggle_title",
+        "Click or press Enter to turn this filter off or on; Delete removes it",
+    ),
+    (
+        "ignored.empty",
+        "Nothing ignored. Use the ⊘ button on a log line to hide lines like it.",
+    ),
+    ("ignored.remove_title", "Stop ignoring"),
+    ("loading.apps", "Fetching apps"),
+    ("loading.auth", "Verifying auth"),
+    ("loading.cli", "Checking CLI"),
+    ("log.empty", "No logs to display. Waiting for log input..."),
+    ("log.expand_title", "Show the full message"),
+    ("log.ignore_title", "Ignore lines like this"),
+    ("log.pin_title", "Pin to the strip above the list"),
+    ("log.open_ref_title", "Open in editor"),
+    ("login.button", "Login to Heroku"),
+    ("login.waiting", "Waiting for browser login..."),
+    ("notice.alert", "Alert"),
+    ("notice.alert_rule_saved", "Alerting on {rule}; saved to the alerts file"),
+    (
+        "notice.alerting_rate",
+        "Alerting when \"{query}\" rises above {factor}× its average over the previous {minutes} minutes",
+    ),
+    ("notice.alerts_on", "Alerts are back on"),
+    ("notice.alerts_removed", "Removed all alerts"),
+    ("notice.already_muted", "\"{query}\" is already muted"),
+    ("notice.already_sharing", "Already sharing at {url}"),
+    ("notice.auto_export_stopped", "Automatic export stopped: {error}"),
+    ("notice.awaited", "Awaited \"{queries}\" appeared"),
+    ("notice.awaiting", "Waiting for \"{query}\""),
+    ("notice.checking_updates", "Checking for updates…"),
+    ("notice.copied", "Copied {text}"),
+    ("notice.copied_json", "Copied line as JSON"),
+    ("notice.copied_line", "Copied line"),
+    ("notice.detecting_format", "Detecting the line format automatically"),
+    ("notice.disconnect_to_open", "Disconnect to open {app}"),
+    ("notice.entries", "entries"),
+    ("notice.export_failed", "Export failed: {error}"),
+    ("notice.history_stopped", "History stopped: {error}"),
+    ("notice.history_unavailable", "History unavailable: {error}"),
+    ("notice.ignoring", "Ignoring lines like: {pattern}"),
+    ("notice.invalid_key", "Invalid key for {name}: {key}"),
+    ("notice.invalid_value", "Invalid value for {name}: {value}"),
+    ("notice.keys_file", "Keys file: {problems}"),
+    ("notice.latest_version", "This is the latest version ({version})"),
+    ("notice.lines_exported", "{count} lines exported to {path}"),
+    ("notice.manifest_failed", "{saved}, but the manifest failed: {error}"),
+    ("notice.muted", "Muted alerts for \"{query}\""),
+    ("notice.no_crashes", "No dyno crashes seen"),
+    ("notice.no_entries_after", "No entries at or after {time}"),
+    ("notice.no_open_command", "Set open_command in settings or $EDITOR to open files"),
+    ("notice.no_preset", "No preset named {name}"),
+    ("notice.no_presets", "No presets saved; :preset save <name> keeps the current filters"),
+    ("notice.not_muted", "\"{query}\" isn't muted"),
+    ("notice.not_sharing", "Not sharing"),
+    ("notice.note_added", "Note added to the session"),
+    ("notice.nothing_to_alert_on", "Nothing to alert on in \"{query}\""),
+    ("notice.nothing_to_await", "Nothing to wait for in \"{query}\""),
+    ("notice.open_failed", "Failed to open {url}: {error}"),
+    ("notice.order_arrival", "Adding new lines in arrival order"),
+    ("notice.order_timestamp", "Keeping lines in timestamp order"),
+    ("notice.otlp_failed", "OTLP export failed: {error}"),
+    ("notice.parsing_as", "Parsing new lines as {format}"),
+    ("notice.passthrough_stopped", "Passthrough stopped: {error}"),
+    ("notice.pause_to_sort", "Pause the list to sort it"),
+    ("notice.paused_on_line", "Paused on line {id} of {path}"),
+    ("notice.pin_limit", "At most {max} entries can be pinned; unpin one first"),
+    ("notice.preset", "Preset {name}: {query}"),
+    ("notice.preset_deleted", "Deleted preset {name}"),
+    ("notice.preset_empty", "Add filters to save them as a preset"),
+    ("notice.preset_saved", "Saved preset {name}: {query}"),
+    ("notice.presets", "Presets: {names}"),
+    ("notice.read_failed", "Failed to read {path}: {error}"),
+    ("notice.record_for_notes", "Start recording to keep notes in the session"),
+    ("notice.recording_stopped", "Recording stopped: {error}"),
+    ("notice.reparsed", "Re-parsed {count} buffered lines"),
+    ("notice.reparsing", "Re-parsing… {done}/{total} lines"),
+    ("notice.replay_finished", "Replay finished"),
+    (
+        "notice.replaying",
+        "Replaying {lines} lines; the {actions} recorded actions are shown as they come up",
+    ),
+    ("notice.restored", "Restored {count} lines from the last run"),
+    ("notice.run_failed", "Failed to run {command}: {error}"),
+    ("notice.saved", "Saved {name}"),
+    ("notice.select_to_copy", "Select or focus a line to copy it"),
+    ("notice.session_empty", "{path} is empty"),
+    ("notice.session_lines", "{path} only has lines 1 to {count}"),
+    ("notice.session_saved", "Session saved to {path}"),
+    ("notice.share_ended", "Stopped sharing"),
+    ("notice.share_failed", "Sharing failed: {error}"),
+    ("notice.share_port_failed", "Sharing failed on port {port}: {error}"),
+    ("notice.share_stopped", "Sharing stopped: {error}"),
+    ("notice.sharing", "Sharing this view read-only at {url}"),
+    ("notice.snooze_over", "Snooze over, alerts are back on"),
+    ("notice.snoozed_until", "Alerts snoozed until {time}"),
+    ("notice.timeline_exported", "Timeline exported to {path}"),
+    ("notice.unmuted", "Unmuted alerts for \"{query}\""),
+    ("notice.uploaded", "Uploaded to {url} (URL copied)"),
+    (
+        "onboarding.cli_missing",
+        "logs-parser reads logs through the Heroku CLI, which isn't installed yet.",
+    ),
+    (
+        "onboarding.connect",
+        "Start streaming the app's logs. New lines appear as they are written.",
+    ),
+    ("onboarding.download", "Or download an installer from "),
+    ("onboarding.installed", "I've installed it — check again"),
+    (
+        "onboarding.login",
+        "Log in so the CLI can read your apps' logs. A browser window will open.",
+    ),
+    ("onboarding.login_button", "Log in to Heroku"),
+    (
+        "onboarding.logging_in",
+        "Finish logging in in the browser window that opened.",
+    ),
+    (
+        "onboarding.pick_app",
+        "Choose the app to watch, here or from the Heroku App menu above.",
+    ),
+    ("onboarding.skip", "Skip the guide"),
+    ("onboarding.step.connect", "Connect"),
+    ("onboarding.step.install", "Install the Heroku CLI"),
+    ("onboarding.step.login", "Log in"),
+    ("onboarding.step.pick_app", "Pick an app"),
+    ("onboarding.via_homebrew", "With Homebrew"),
+    ("onboarding.via_npm", "With npm"),
+    ("onboarding.via_script", "Standalone"),
+    ("onboarding.via_snap", "With snap"),
+    ("passphrase.export", "Export Encrypted"),
+    ("passphrase.title", "Passphrase for the export"),
+    ("pinned.unpin_title", "Unpin"),
+    ("platform.crash", "Crash"),
+    ("platform.empty", "No dyno crashes, restarts or error codes seen yet"),
+    ("platform.latest_crash", "Latest crash"),
+    ("platform.restart", "Restart"),
+    ("platform.select_title", "Show the lines around this event"),
+    ("platform.title", "Platform events"),
+    ("record.start", "● Record"),
+    ("record.stop", "■ Stop Recording"),
+    (
+        "record.title",
+        "Record received lines to a session file for :replay",
+    ),
+    ("replay.close", "Close"),
+    ("replay.close_title", "Stop replaying"),
+    ("replay.pause", "Pause"),
+    ("replay.play", "Play"),
+    ("addons.empty", "No metrics seen yet"),
+    ("requests.connect", "connect avg"),
+    ("requests.empty", "No requests seen yet"),
+    ("requests.latency", "Latency"),
+    (
+        "requests.latency_window",
+        "Last {minutes} minutes: {count} requests, {rate}/s, {kb} KB/s sent",
+    ),
+    ("requests.recent", "Recent"),
+    ("requests.route_errors", "{errors} 5xx"),
+    ("requests.route_service", "avg {avg}ms, max {max}ms"),
+    ("requests.routes", "Routes"),
+    ("requests.select_title", "Show all lines for this request"),
+    ("settings.close", "Close"),
+    ("settings.keys", "Keys"),
+    (
+        "settings.note",
+        "Saved to the settings and keys files in ~/.config/logs-parser. Clear a value to go back to its default; some settings apply on the next start.",
+    ),
+    ("settings.title", "Settings"),
+    ("stats.activity", "Last minute"),
+    ("stats.activity_title", "{rate} lines/s; the tallest bar is {peak} lines in one second"),
+    ("stats.app", "App"),
+    ("stats.errors", "{percent}% errors"),
+    ("stats.filtered", "Filtered"),
+    ("stats.filters", "Filters"),
+    ("stats.last_alert", "Last alert"),
+    ("stats.mode", "Filter Mode"),
+    ("stats.platform", "Platform: "),
+    (
+        "stats.platform_title",
+        "{events} platform events since you last looked, {crashes} of them crashes",
+    ),
+    ("stats.rate", "Rate"),
+    ("stats.time", "Time"),
+    ("stats.title", "Heroku Logs Parser"),
+    ("stats.total", "Total Logs"),
+    ("stats.unparsed", "Unparsed: "),
+    (
+        "stats.unparsed_detail",
+        "{name}: {failed} of {total} lines unparsed",
+    ),
+    ("status.cli_check_failed", "Failed to check CLI: {error}"),
+    ("status.cli_missing", "Heroku CLI not found"),
+    ("status.connecting", "Connecting..."),
+    ("status.connection_failed", "Connection failed: {error}"),
+    ("status.fetch_failed", "Failed to fetch apps: {error}"),
+    ("status.logging_in", "Logging in to Heroku..."),
+    ("status.login_error", "Login error: {error}"),
+    ("status.login_failed", "Failed to start login: {error}"),
+    ("status.no_apps", "No Heroku apps found"),
+    ("status.not_authenticated", "Not logged in to Heroku"),
+    ("status.ready", "Ready to connect"),
+    ("status.reconnect_failed", "Reconnection failed: {error}"),
+    (
+        "status.reconnecting",
+        "Reconnecting (attempt {attempt}/5)...",
+    ),
+    ("status.starting", "Starting..."),
+    ("status.streaming", "Streaming"),
+    ("theme.label", "Theme:"),
+    ("theme.placeholder", "Select theme..."),
+    ("time.apply", "Show Range"),
+    ("time.button", "Time Range"),
+    ("time.clear", "Any Time"),
+    ("time.from", "From"),
+    ("time.last", "Last {age}"),
+    ("time.title", "Show only lines logged in a time range"),
+    ("time.to", "To"),
+];
+
+const ES: &[(&str, &str)] = &[
+    ("app.label", "App de Heroku"),
+    ("app.placeholder", "Elige una app..."),
+    (
+        "banner.crashed",
+        "La app se cerró inesperadamente la última vez. Se guardó un informe en {path}.",
+    ),
+    ("banner.detected", "Formato {format} detectado: {matching} de {sampled} líneas de muestra."),
+    ("banner.download", "Descargar"),
+    ("banner.force_format", "Pulsa {key} para forzar un formato."),
+    ("banner.muted", "Silenciadas: \"{queries}\" (:unmute <consulta>)."),
+    ("banner.new_lines", "↓ {count} nuevas"),
+    ("banner.new_lines_title", "Ir a las líneas más recientes"),
+    ("banner.parsing_as", "Analizando como {format}."),
+    ("banner.paused", "En pausa: {count} líneas nuevas retenidas. Pulsa {key} para reanudar."),
+    ("banner.plain_text", "Las líneas se muestran como texto sin formato."),
+    ("banner.report", "Informar en GitHub"),
+    (
+        "banner.report_title",
+        "Abrir una incidencia en GitHub con el informe, para revisarla antes de enviarla",
+    ),
+    ("banner.snoozed", "Alertas silenciadas hasta las {time} (:snooze off para reanudar)."),
+    ("banner.sorted", "Ordenado por {key}, no por hora."),
+    ("banner.sorted_desc", "Ordenado por {key} (descendente), no por hora."),
+    ("banner.time_order", "Volver al orden por hora"),
+    ("banner.update", "Ya está disponible la versión {version} (esta es la {current})."),
+    ("common.cancel", "Cancelar"),
+    ("common.dismiss", "Descartar"),
+    ("connect", "Conectar"),
+    ("connecting", "Conectando..."),
+    ("detail.close_title", "Cerrar detalles (Esc)"),
+    ("detail.copy_link", "Copiar enlace"),
+    ("detail.fields", "Campos"),
+    ("detail.level", "Nivel"),
+    ("detail.message", "Mensaje"),
+    ("detail.open_request", "Abrir en el navegador"),
+    ("detail.origin", "Origen"),
+    ("detail.raw", "Línea original"),
+    ("detail.request", "Petición"),
+    ("detail.session_line", "Línea de la sesión"),
+    ("detail.source", "Fuente"),
+    ("detail.time", "Hora"),
+    ("detail.timeline", "Cronología de la petición"),
+    ("detail.title", "Detalles de la entrada"),
+    ("disconnect", "Desconectar"),
+    ("export.timeline", "Exportar cronología"),
+    ("export.timeline_title", "Exportar conteos por minuto y latencia del router como CSV"),
+    ("filter.add", "Añadir filtro"),
+    ("filter.awaiting", "Esperando: \"{query}\""),
+    ("filter.clear", "Quitar"),
+    ("filter.clear_title", "Quitar todos los filtros ({key})"),
+    ("filter.confirm_clear", "Confirmar"),
+    ("filter.matches", "{count} coincidencias"),
+    ("filter.placeholder", "Escribe un filtro (texto, dyno:web.1, source:app, level:error, /regex/) o :await <consulta>"),
+    ("filter.remove_title", "Quitar filtro"),
+    ("filter.toggle_mode", "Cambiar {mode}"),
+    ("filter.toggle_title", "Haz clic o pulsa Intro para desactivar o activar este filtro; Supr lo quita"),
+    ("ignored.empty", "No se ignora nada. Usa el botón ⊘ de una línea para ocultar las que se le parezcan."),
+    ("ignored.remove_title", "Dejar de ignorar"),
+    ("loading.apps", "Cargando apps"),
+    ("loading.auth", "Verificando sesión"),
+    ("loading.cli", "Buscando la CLI"),
+    ("log.empty", "No hay logs. Esperando líneas..."),
+    ("log.expand_title", "Mostrar el mensaje completo"),
+    ("log.ignore_title", "Ignorar líneas como esta"),
+    ("log.pin_title", "Fijar en la franja sobre la lista"),
+    ("log.open_ref_title", "Abrir en el editor"),
+    ("login.button", "Iniciar sesión en Heroku"),
+    ("login.waiting", "Esperando el inicio de sesión en el navegador..."),
+    ("notice.alert", "Alerta"),
+    ("notice.alert_rule_saved", "Alertando con {rule}; guardado en el archivo de alertas"),
+    (
+        "notice.alerting_rate",
+        "Alertando cuando \"{query}\" supere {factor}× su media de los {minutes} minutos anteriores",
+    ),
+    ("notice.alerts_on", "Las alertas vuelven a estar activas"),
+    ("notice.alerts_removed", "Se quitaron todas las alertas"),
+    ("notice.already_muted", "\"{query}\" ya está silenciada"),
+    ("notice.already_sharing", "Ya se está compartiendo en {url}"),
+    ("notice.auto_export_stopped", "Exportación automática detenida: {error}"),
+    ("notice.awaited", "Apareció lo esperado: \"{queries}\""),
+    ("notice.awaiting", "Esperando \"{query}\""),
+    ("notice.checking_updates", "Buscando actualizaciones…"),
+    ("notice.copied", "Copiado: {text}"),
+    ("notice.copied_json", "Línea copiada como JSON"),
+    ("notice.copied_line", "Línea copiada"),
+    ("notice.detecting_format", "Detectando el formato de las líneas automáticamente"),
+    ("notice.disconnect_to_open", "Desconéctate para abrir {app}"),
+    ("notice.entries", "entradas"),
+    ("notice.export_failed", "Falló la exportación: {error}"),
+    ("notice.history_stopped", "Historial detenido: {error}"),
+    ("notice.history_unavailable", "Historial no disponible: {error}"),
+    ("notice.ignoring", "Ignorando líneas como: {pattern}"),
+    ("notice.invalid_key", "Tecla no válida para {name}: {key}"),
+    ("notice.invalid_value", "Valor no válido para {name}: {value}"),
+    ("notice.keys_file", "Archivo de teclas: {problems}"),
+    ("notice.latest_version", "Esta es la última versión ({version})"),
+    ("notice.lines_exported", "{count} líneas exportadas a {path}"),
+    ("notice.manifest_failed", "{saved}, pero falló el manifiesto: {error}"),
+    ("notice.muted", "Alertas silenciadas para \"{query}\""),
+    ("notice.no_crashes", "No se ha visto ninguna caída de dynos"),
+    ("notice.no_entries_after", "No hay entradas a partir de {time}"),
+    ("notice.no_open_command", "Define open_command en los ajustes o $EDITOR para abrir archivos"),
+    ("notice.no_preset", "No hay ningún preajuste llamado {name}"),
+    (
+        "notice.no_presets",
+        "No hay preajustes guardados; :preset save <nombre> guarda los filtros actuales",
+    ),
+    ("notice.not_muted", "\"{query}\" no está silenciada"),
+    ("notice.not_sharing", "No se está compartiendo"),
+    ("notice.note_added", "Nota añadida a la sesión"),
+    ("notice.nothing_to_alert_on", "Nada sobre lo que alertar en \"{query}\""),
+    ("notice.nothing_to_await", "Nada que esperar en \"{query}\""),
+    ("notice.open_failed", "No se pudo abrir {url}: {error}"),
+    ("notice.order_arrival", "Añadiendo las líneas nuevas en orden de llegada"),
+    ("notice.order_timestamp", "Manteniendo las líneas en orden de hora"),
+    ("notice.otlp_failed", "Falló la exportación OTLP: {error}"),
+    ("notice.parsing_as", "Analizando las líneas nuevas como {format}"),
+    ("notice.passthrough_stopped", "Reenvío a la salida detenido: {error}"),
+    ("notice.pause_to_sort", "Pausa la lista para ordenarla"),
+    ("notice.paused_on_line", "En pausa en la línea {id} de {path}"),
+    ("notice.pin_limit", "Se pueden fijar como máximo {max} entradas; desfija una primero"),
+    ("notice.preset", "Preajuste {name}: {query}"),
+    ("notice.preset_deleted", "Preajuste {name} eliminado"),
+    ("notice.preset_empty", "Añade filtros para guardarlos como preajuste"),
+    ("notice.preset_saved", "Preajuste {name} guardado: {query}"),
+    ("notice.presets", "Preajustes: {names}"),
+    ("notice.read_failed", "No se pudo leer {path}: {error}"),
+    ("notice.record_for_notes", "Empieza a grabar para guardar notas en la sesión"),
+    ("notice.recording_stopped", "Grabación detenida: {error}"),
+    ("notice.reparsed", "Se volvieron a analizar {count} líneas del búfer"),
+    ("notice.reparsing", "Volviendo a analizar… {done}/{total} líneas"),
+    ("notice.replay_finished", "Reproducción terminada"),
+    (
+        "notice.replaying",
+        "Reproduciendo {lines} líneas; las {actions} acciones grabadas se muestran a medida que aparecen",
+    ),
+    ("notice.restored", "Se recuperaron {count} líneas de la última ejecución"),
+    ("notice.run_failed", "No se pudo ejecutar {command}: {error}"),
+    ("notice.saved", "{name} guardado"),
+    ("notice.select_to_copy", "Selecciona o enfoca una línea para copiarla"),
+    ("notice.session_empty", "{path} está vacío"),
+    ("notice.session_lines", "{path} solo tiene las líneas 1 a {count}"),
+    ("notice.session_saved", "Sesión guardada en {path}"),
+    ("notice.share_ended", "Se dejó de compartir"),
+    ("notice.share_failed", "No se pudo compartir: {error}"),
+    ("notice.share_port_failed", "No se pudo compartir en el puerto {port}: {error}"),
+    ("notice.share_stopped", "Se dejó de compartir: {error}"),
+    ("notice.sharing", "Compartiendo esta vista en solo lectura en {url}"),
+    ("notice.snooze_over", "Fin de la pausa, las alertas vuelven a estar activas"),
+    ("notice.snoozed_until", "Alertas en pausa hasta las {time}"),
+    ("notice.timeline_exported", "Cronología exportada a {path}"),
+    ("notice.unmuted", "Alertas reactivadas para \"{query}\""),
+    ("notice.uploaded", "Subido a {url} (URL copiada)"),
+    ("onboarding.cli_missing", "logs-parser lee los logs con la CLI de Heroku, que aún no está instalada."),
+    ("onboarding.connect", "Empieza a recibir los logs de la app. Las líneas nuevas aparecen al escribirse."),
+    ("onboarding.download", "O descarga un instalador de "),
+    ("onboarding.installed", "Ya la instalé — comprobar de nuevo"),
+    ("onboarding.login", "Inicia sesión para que la CLI pueda leer los logs de tus apps. Se abrirá el navegador."),
+    ("onboarding.login_button", "Iniciar sesión en Heroku"),
+    ("onboarding.logging_in", "Termina de iniciar sesión en la ventana del navegador que se abrió."),
+    ("onboarding.pick_app", "Elige la app que quieres ver, aquí o en el menú App de Heroku de arriba."),
+    ("onboarding.skip", "Saltar la guía"),
+    ("onboarding.step.connect", "Conectar"),
+    ("onboarding.step.install", "Instalar la CLI de Heroku"),
+    ("onboarding.step.login", "Iniciar sesión"),
+    ("onboarding.step.pick_app", "Elegir una app"),
+    ("onboarding.via_homebrew", "Con Homebrew"),
+    ("onboarding.via_npm", "Con npm"),
+    ("onboarding.via_script", "Independiente"),
+    ("onboarding.via_snap", "Con snap"),
+    ("passphrase.export", "Exportar cifrado"),
+    ("passphrase.title", "Frase de contraseña de la exportación"),
+    ("pinned.unpin_title", "Desfijar"),
+    ("platform.crash", "Caída"),
+    ("platform.empty", "Aún no hay caídas, reinicios ni códigos de error de dynos"),
+    ("platform.latest_crash", "Última caída"),
+    ("platform.restart", "Reinicio"),
+    ("platform.select_title", "Mostrar las líneas alrededor de este evento"),
+    ("platform.title", "Eventos de la plataforma"),
+    ("record.start", "● Grabar"),
+    ("record.stop", "■ Detener grabación"),
+    ("record.title", "Grabar las líneas recibidas en un archivo de sesión para :replay"),
+    ("replay.close", "Cerrar"),
+    ("replay.close_title", "Detener la reproducción"),
+    ("replay.pause", "Pausa"),
+    ("replay.play", "Reproducir"),
+    ("addons.empty", "Aún no hay métricas"),
+    ("requests.connect", "conexión media"),
+    ("requests.empty", "Aún no hay peticiones"),
+    ("requests.latency", "Latencia"),
+    (
+        "requests.latency_window",
+        "Últimos {minutes} minutos: {count} peticiones, {rate}/s, {kb} KB/s enviados",
+    ),
+    ("requests.recent", "Recientes"),
+    ("requests.route_errors", "{errors} 5xx"),
+    ("requests.route_service", "media {avg}ms, máx. {max}ms"),
+    ("requests.routes", "Rutas"),
+    ("requests.select_title", "Mostrar todas las líneas de esta petición"),
+    ("settings.close", "Cerrar"),
+    ("settings.keys", "Teclas"),
+    ("settings.note", "Se guarda en los archivos settings y keys de ~/.config/logs-parser. Vacía un valor para volver al predeterminado; algunos ajustes se aplican al siguiente inicio."),
+    ("settings.title", "Ajustes"),
+    ("stats.activity", "Último minuto"),
+    ("stats.activity_title", "{rate} líneas/s; la barra más alta son {peak} líneas en un segundo"),
+    ("stats.app", "App"),
+    ("stats.errors", "{percent}% errores"),
+    ("stats.filtered", "Filtrados"),
+    ("stats.filters", "Filtros"),
+    ("stats.last_alert", "Última alerta"),
+    ("stats.mode", "Modo de filtro"),
+    ("stats.platform", "Plataforma: "),
+    (
+        "stats.platform_title",
+        "{events} eventos de la plataforma desde la última vez que miraste, {crashes} de ellos caídas",
+    ),
+    ("stats.rate", "Ritmo"),
+    ("stats.time", "Hora"),
+    ("stats.title", "Heroku Logs Parser"),
+    ("stats.total", "Total de logs"),
+    ("stats.unparsed", "Sin analizar: "),
+    ("stats.unparsed_detail", "{name}: {failed} de {total} líneas sin analizar"),
+    ("status.cli_check_failed", "No se pudo comprobar la CLI: {error}"),
+    ("status.cli_missing", "No se encontró la CLI de Heroku"),
+    ("status.connecting", "Conectando..."),
+    ("status.connection_failed", "Falló la conexión: {error}"),
+    ("status.fetch_failed", "No se pudieron obtener las apps: {error}"),
+    ("status.logging_in", "Iniciando sesión en Heroku..."),
+    ("status.login_error", "Error al iniciar sesión: {error}"),
+    ("status.login_failed", "No se pudo iniciar sesión: {error}"),
+    ("status.no_apps", "No se encontraron apps de Heroku"),
+    ("status.not_authenticated", "No has iniciado sesión en Heroku"),
+    ("status.ready", "Listo para conectar"),
+    ("status.reconnect_failed", "Falló la reconexión: {error}"),
+    ("status.reconnecting", "Reconectando (intento {attempt}/5)..."),
+    ("status.starting", "Iniciando..."),
+    ("status.streaming", "Recibiendo"),
+    ("theme.label", "Tema:"),
+    ("theme.placeholder", "Elige un tema..."),
+    ("time.apply", "Mostrar intervalo"),
+    ("time.button", "Intervalo"),
+    ("time.clear", "Cualquier hora"),
+    ("time.from", "Desde"),
+    ("time.last", "Últimos {age}"),
+    ("time.title", "Mostrar solo las líneas registradas en un intervalo de tiempo"),
+    ("time.to", "Hasta"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholders(text: &str) -> Vec<&str> {
+        text.split('{')
+            .skip(1)
+            .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+            .collect()
+    }
+
+    #[test]
+    fn test_catalogs_are_complete() {
+        for locale in [Locale::Es] {
+            for (key, english) in EN {
+                let translated = locale.catalog().iter().find(|(k, _)| k == key);
+                let (_, translated) =
+                    translated.unwrap_or_else(|| panic!("{:?} lacks {}", locale, key));
+                assert_eq!(placeholders(translated), placeholders(english), "{}", key);
+            }
+            for (key, _) in locale.catalog() {
+                assert!(EN.iter().any(|(k, _)| k == key), "unknown key {}", key);
+            }
+        }
+    }
+
+    #[test]
+    fn test_lookup_and_fill() {
+        assert_eq!(lookup(Locale::Es, "connect"), "Conectar");
+        assert_eq!(lookup(Locale::En, "connect"), "Connect");
+        assert_eq!(lookup(Locale::Es, "no.such.key"), "no.such.key");
+        assert_eq!(
+            fill(
+                lookup(Locale::En, "status.reconnecting"),
+                &[("attempt", &2)]
+            ),
+            "Reconnecting (attempt 2/5)..."
+        );
+    }
+
+    #[test]
+    fn test_locale_from_tag() {
+        assert_eq!(Locale::from_tag("es_ES.UTF-8"), Some(Locale::Es));
+        assert_eq!(Locale::from_tag("es-MX"), Some(Locale::Es));
+        assert_eq!(Locale::from_tag("EN"), Some(Locale::En));
+        assert_eq!(Locale::from_tag("C"), None);
+        assert_eq!(Locale::detect(Some(Locale::Es)), Locale::Es);
+    }
+}
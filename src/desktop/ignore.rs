@@ -0,0 +1,148 @@
+//! Persistent "never show me this again" list, stored in
+//! `~/.config/logs-parser/ignored`
+//!
+//! Entries are ignored by fingerprint: the source, the dyno type and the
+//! message with ids and numbers masked, so every repeat of a noisy health
+//! check shares one fingerprint:
+//!
+//! ```text
+//! heroku[router]: at=info method=GET path="/healthz" ... service=<n>ms status=<n>
+//! ```
+
+use crate::config::config_dir;
+use crate::parser::LogEntry;
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+fn variable_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(
+            r"(?i)\b(?:[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}|[0-9a-f]*\d[0-9a-f]*)\b|\d+",
+        )
+        .expect("Failed to compile fingerprint regex")
+    })
+}
+
+/// Fingerprint shared by repeats of the same kind of line
+pub fn fingerprint(entry: &LogEntry) -> String {
+    let dyno_type = entry.dyno.split('.').next().unwrap_or(&entry.dyno);
+    format!(
+        "{}[{}]: {}",
+        entry.source,
+        dyno_type,
+        variable_regex().replace_all(&entry.message, "<n>")
+    )
+}
+
+/// Fingerprints of ignored entries, in the order they were added
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IgnoreList {
+    fingerprints: Vec<String>,
+    lookup: HashSet<String>,
+}
+
+impl IgnoreList {
+    /// Parse an ignored file, one fingerprint per line
+    pub fn parse(contents: &str) -> Self {
+        let mut list = Self::default();
+        for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+            list.insert(line.to_string());
+        }
+        list
+    }
+
+    /// Load the user's ignore list; no file means nothing is ignored
+    pub fn load() -> Self {
+        std::fs::read_to_string(config_dir().join("ignored"))
+            .map(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    /// Write the list back to the ignored file
+    pub fn save(&self) -> Result<()> {
+        let dir = config_dir();
+        std::fs::create_dir_all(&dir).context("Failed to create config directory")?;
+        let mut contents = self.fingerprints.join("\n");
+        contents.push('\n');
+        std::fs::write(dir.join("ignored"), contents).context("Failed to save ignore list")
+    }
+
+    fn insert(&mut self, fingerprint: String) {
+        if self.lookup.insert(fingerprint.clone()) {
+            self.fingerprints.push(fingerprint);
+        }
+    }
+
+    /// Ignore every entry sharing this entry's fingerprint
+    pub fn add(&mut self, entry: &LogEntry) {
+        self.insert(fingerprint(entry));
+    }
+
+    pub fn remove(&mut self, idx: usize) {
+        if idx < self.fingerprints.len() {
+            let removed = self.fingerprints.remove(idx);
+            self.lookup.remove(&removed);
+        }
+    }
+
+    pub fn fingerprints(&self) -> &[String] {
+        &self.fingerprints
+    }
+
+    pub fn is_ignored(&self, entry: &LogEntry) -> bool {
+        !self.lookup.is_empty() && self.lookup.contains(&fingerprint(entry))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_log_line;
+
+    fn health_check(dyno: &str, service: u32, request_id: &str) -> LogEntry {
+        parse_log_line(&format!(
+            "2024-02-17T10:30:45.123456+00:00 heroku[router]: at=info method=GET path=\"/healthz\" request_id={} dyno={} service={}ms status=200",
+            request_id, dyno, service
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_fingerprint_masks_ids_and_numbers() {
+        let a = health_check("web.1", 3, "8601b555-6a54-4b6a-9b1a-0d0a7f1e2c3d");
+        let b = health_check("web.2", 12, "3c1f0d2e-1b2a-4c3d-8e9f-a0b1c2d3e4f5");
+
+        assert_eq!(fingerprint(&a), fingerprint(&b));
+        assert!(fingerprint(&a).contains("path=\"/healthz\""));
+    }
+
+    #[test]
+    fn test_ignore_and_remove() {
+        let entry = health_check("web.1", 3, "8601b555-6a54-4b6a-9b1a-0d0a7f1e2c3d");
+        let other =
+            parse_log_line("2024-02-17T10:30:45.123456+00:00 app[web.1]: Order placed").unwrap();
+        let mut list = IgnoreList::default();
+
+        list.add(&entry);
+        list.add(&entry);
+        assert_eq!(list.fingerprints().len(), 1);
+        assert!(list.is_ignored(&entry));
+        assert!(!list.is_ignored(&other));
+
+        list.remove(0);
+        assert!(!list.is_ignored(&entry));
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        let list = IgnoreList::parse("app[web]: ping <n>\n\nheroku[router]: x\n");
+
+        assert_eq!(
+            list.fingerprints(),
+            ["app[web]: ping <n>", "heroku[router]: x"]
+        );
+    }
+}
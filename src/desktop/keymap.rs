@@ -0,0 +1,431 @@
+//! Keyboard shortcuts, with user overrides loaded from `~/.config/logs-parser/keys`
+//!
+//! The keys file holds one `action = key` binding per line, in the same
+//! unquoted `name = value` format as the settings file rather than TOML, e.g.
+//!
+//! ```text
+//! # Use shift+c to clear filters
+//! clear_filters = shift+c
+//! toggle_filter_mode = ctrl+m
+//! ```
+
+use crate::config::{self, config_dir};
+use anyhow::Result;
+use dioxus::prelude::{Key, Modifiers};
+
+/// An app-level action that can be bound to a key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Clear all filters (asks for confirmation first)
+    ClearFilters,
+    /// Toggle between AND and OR filter combination
+    ToggleFilterMode,
+    /// Hide everything but the log list and status line
+    ToggleZenMode,
+    /// Show or hide the elapsed-time column
+    ToggleElapsed,
+    /// Turn SQL keyword highlighting on or off
+    ToggleSqlHighlight,
+    /// Show or hide the recent requests panel
+    ToggleRequests,
+    /// Show or hide the add-on metrics charts
+    ToggleMetrics,
+    /// Color the dyno column per dyno
+    ToggleDynoColors,
+    /// Show or hide the list of ignored lines
+    ToggleIgnored,
+    /// Show or hide the dyno crashes, restarts and error codes seen
+    TogglePlatform,
+    /// Show the lines around the most recent dyno crash
+    JumpToCrash,
+    /// Hold new lines back instead of adding them to the list
+    TogglePause,
+    /// Force the next line format, or go back to detecting it
+    CycleFormat,
+    /// Switch the buffer between arrival and timestamp order
+    ToggleOrder,
+    /// Start an `:export` command in the filter bar to type a destination
+    ExportPrompt,
+    /// Copy the selected or focused line to the clipboard as received
+    CopySelected,
+    /// Copy the selected or focused line to the clipboard as JSON
+    CopySelectedJson,
+    /// Show only errors
+    ShowErrors,
+    /// Show only warnings and errors
+    ShowWarnings,
+    /// Show every level again
+    ShowAllLevels,
+}
+
+/// Every action with its name in the keys file
+const ACTION_NAMES: [(Action, &str); 20] = [
+    (Action::ClearFilters, "clear_filters"),
+    (Action::ToggleFilterMode, "toggle_filter_mode"),
+    (Action::ToggleZenMode, "toggle_zen_mode"),
+    (Action::ToggleElapsed, "toggle_elapsed"),
+    (Action::ToggleSqlHighlight, "toggle_sql_highlight"),
+    (Action::ToggleRequests, "toggle_requests"),
+    (Action::ToggleMetrics, "toggle_metrics"),
+    (Action::ToggleDynoColors, "toggle_dyno_colors"),
+    (Action::ToggleIgnored, "toggle_ignored"),
+    (Action::TogglePlatform, "toggle_platform"),
+    (Action::JumpToCrash, "jump_to_crash"),
+    (Action::TogglePause, "toggle_pause"),
+    (Action::CycleFormat, "cycle_format"),
+    (Action::ToggleOrder, "toggle_order"),
+    (Action::ExportPrompt, "export_prompt"),
+    (Action::CopySelected, "copy_selected"),
+    (Action::CopySelectedJson, "copy_selected_json"),
+    (Action::ShowErrors, "show_errors"),
+    (Action::ShowWarnings, "show_warnings"),
+    (Action::ShowAllLevels, "show_all_levels"),
+];
+
+impl Action {
+    /// Look up an action by its name in the keys file
+    fn from_name(name: &str) -> Option<Self> {
+        ACTION_NAMES
+            .iter()
+            .find(|(_, n)| *n == name)
+            .map(|(action, _)| *action)
+    }
+
+    /// Name of the action in the keys file
+    fn name(self) -> &'static str {
+        ACTION_NAMES
+            .iter()
+            .find(|(action, _)| *action == self)
+            .map(|(_, name)| *name)
+            .expect("every action is named")
+    }
+}
+
+/// Bind an action to a key in the user's keys file, or go back to its
+/// default key (`None`)
+pub fn save_binding(name: &str, key: Option<&str>) -> Result<()> {
+    config::save_value("keys", name, key)
+}
+
+/// A single key, optionally combined with Ctrl/Cmd and/or Shift
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyBinding {
+    key: String,
+    ctrl: bool,
+    shift: bool,
+}
+
+impl KeyBinding {
+    /// Parse a binding such as `x`, `shift+c` or `ctrl+k`
+    ///
+    /// `ctrl` also matches Cmd so bindings behave the same on macOS.
+    pub fn parse(input: &str) -> Option<Self> {
+        let mut ctrl = false;
+        let mut shift = false;
+        let mut key = None;
+
+        for part in input.trim().split('+') {
+            match part.trim().to_lowercase().as_str() {
+                "ctrl" | "cmd" => ctrl = true,
+                "shift" => shift = true,
+                p if p.chars().count() == 1 && key.is_none() => key = Some(p.to_string()),
+                _ => return None,
+            }
+        }
+
+        Some(Self {
+            key: key?,
+            ctrl,
+            shift,
+        })
+    }
+
+    /// Check whether a key event triggers this binding
+    pub fn matches(&self, key: &Key, modifiers: Modifiers) -> bool {
+        let Key::Character(c) = key else {
+            return false;
+        };
+        let ctrl = modifiers.contains(Modifiers::CONTROL) || modifiers.contains(Modifiers::META);
+        let shift = modifiers.contains(Modifiers::SHIFT);
+        c.to_lowercase() == self.key && ctrl == self.ctrl && shift == self.shift
+    }
+
+    /// Human-readable form, e.g. `Shift+C`
+    pub fn display(&self) -> String {
+        let mut out = String::new();
+        if self.ctrl {
+            out.push_str("Ctrl+");
+        }
+        if self.shift {
+            out.push_str("Shift+");
+        }
+        out.push_str(&self.key.to_uppercase());
+        out
+    }
+}
+
+/// Maps key events to actions
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyMap {
+    bindings: Vec<(Action, KeyBinding)>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        let binding = |key: &str| KeyBinding::parse(key).expect("valid default binding");
+        Self {
+            bindings: vec![
+                (Action::ClearFilters, binding("x")),
+                (Action::ToggleFilterMode, binding("m")),
+                (Action::ToggleZenMode, binding("z")),
+                (Action::ToggleElapsed, binding("t")),
+                (Action::ToggleSqlHighlight, binding("s")),
+                (Action::ToggleRequests, binding("r")),
+                (Action::ToggleMetrics, binding("g")),
+                (Action::ToggleDynoColors, binding("d")),
+                (Action::ToggleIgnored, binding("i")),
+                (Action::TogglePlatform, binding("h")),
+                (Action::JumpToCrash, binding("shift+h")),
+                (Action::TogglePause, binding("p")),
+                (Action::CycleFormat, binding("f")),
+                (Action::ToggleOrder, binding("o")),
+                (Action::ExportPrompt, binding("e")),
+                (Action::CopySelected, binding("y")),
+                (Action::CopySelectedJson, binding("shift+y")),
+                (Action::ShowErrors, binding("shift+e")),
+                (Action::ShowWarnings, binding("shift+w")),
+                (Action::ShowAllLevels, binding("shift+a")),
+            ],
+        }
+    }
+}
+
+impl KeyMap {
+    /// Build a keymap from the defaults with overrides from a keys file.
+    /// Unknown actions and unparsable keys are skipped, and described with
+    /// every key left bound to more than one action.
+    pub fn parse(contents: &str) -> (Self, Vec<String>) {
+        let mut keymap = Self::default();
+        let mut problems = Vec::new();
+
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let number = number + 1;
+            let Some((name, key)) = line.split_once('=') else {
+                problems.push(format!("line {}: expected `action = key`", number));
+                continue;
+            };
+            let Some(action) = Action::from_name(name.trim()) else {
+                problems.push(format!(
+                    "line {}: unknown action \"{}\"",
+                    number,
+                    name.trim()
+                ));
+                continue;
+            };
+            let Some(binding) = KeyBinding::parse(key) else {
+                problems.push(format!("line {}: invalid key \"{}\"", number, key.trim()));
+                continue;
+            };
+            keymap.bind(action, binding);
+        }
+
+        for (at, (action, binding)) in keymap.bindings.iter().enumerate() {
+            if let Some((other, _)) = keymap.bindings[at + 1..].iter().find(|(_, b)| b == binding) {
+                problems.push(format!(
+                    "{} is bound to both {} and {}",
+                    binding.display(),
+                    action.name(),
+                    other.name()
+                ));
+            }
+        }
+
+        (keymap, problems)
+    }
+
+    /// Load the keymap from the user's keys file, falling back to defaults,
+    /// with the problems found in the file
+    pub fn load() -> (Self, Vec<String>) {
+        std::fs::read_to_string(config_dir().join("keys"))
+            .map(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    fn bind(&mut self, action: Action, binding: KeyBinding) {
+        self.bindings.retain(|(a, _)| *a != action);
+        self.bindings.push((action, binding));
+    }
+
+    /// Get the binding for an action
+    pub fn binding(&self, action: Action) -> Option<&KeyBinding> {
+        self.bindings
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map(|(_, b)| b)
+    }
+
+    /// Every action's name in the keys file with its binding, e.g.
+    /// `("clear_filters", "X")`
+    pub fn named_bindings(&self) -> Vec<(&'static str, String)> {
+        ACTION_NAMES
+            .iter()
+            .map(|(action, name)| {
+                let key = self.binding(*action).map(KeyBinding::display);
+                (*name, key.unwrap_or_default())
+            })
+            .collect()
+    }
+
+    /// Resolve a key event to an action
+    pub fn action_for(&self, key: &Key, modifiers: Modifiers) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(_, b)| b.matches(key, modifiers))
+            .map(|(a, _)| *a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn char_key(c: &str) -> Key {
+        Key::Character(c.to_string())
+    }
+
+    #[test]
+    fn test_default_clear_is_x_not_c() {
+        let keymap = KeyMap::default();
+
+        assert_eq!(
+            keymap.action_for(&char_key("x"), Modifiers::empty()),
+            Some(Action::ClearFilters)
+        );
+        assert_eq!(keymap.action_for(&char_key("c"), Modifiers::empty()), None);
+        assert_eq!(keymap.action_for(&char_key("c"), Modifiers::CONTROL), None);
+    }
+
+    #[test]
+    fn test_modifiers_must_match() {
+        let keymap = KeyMap::default();
+
+        assert_eq!(keymap.action_for(&char_key("x"), Modifiers::CONTROL), None);
+        assert_eq!(keymap.action_for(&char_key("X"), Modifiers::SHIFT), None);
+    }
+
+    #[test]
+    fn test_shift_picks_json_copy() {
+        let keymap = KeyMap::default();
+
+        assert_eq!(
+            keymap.action_for(&char_key("y"), Modifiers::empty()),
+            Some(Action::CopySelected)
+        );
+        assert_eq!(
+            keymap.action_for(&char_key("Y"), Modifiers::SHIFT),
+            Some(Action::CopySelectedJson)
+        );
+    }
+
+    #[test]
+    fn test_shift_picks_level_threshold() {
+        let keymap = KeyMap::default();
+
+        assert_eq!(
+            keymap.action_for(&char_key("e"), Modifiers::empty()),
+            Some(Action::ExportPrompt)
+        );
+        assert_eq!(
+            keymap.action_for(&char_key("E"), Modifiers::SHIFT),
+            Some(Action::ShowErrors)
+        );
+        assert_eq!(
+            keymap.action_for(&char_key("W"), Modifiers::SHIFT),
+            Some(Action::ShowWarnings)
+        );
+        assert_eq!(
+            keymap.action_for(&char_key("A"), Modifiers::SHIFT),
+            Some(Action::ShowAllLevels)
+        );
+    }
+
+    #[test]
+    fn test_parse_overrides_defaults() {
+        let keymap = KeyMap::parse("# comment\nclear_filters = shift+c\n").0;
+
+        assert_eq!(
+            keymap.action_for(&char_key("C"), Modifiers::SHIFT),
+            Some(Action::ClearFilters)
+        );
+        assert_eq!(keymap.action_for(&char_key("x"), Modifiers::empty()), None);
+        assert_eq!(
+            keymap.action_for(&char_key("m"), Modifiers::empty()),
+            Some(Action::ToggleFilterMode)
+        );
+    }
+
+    #[test]
+    fn test_ctrl_matches_cmd() {
+        let keymap = KeyMap::parse("toggle_filter_mode = ctrl+m").0;
+
+        assert_eq!(
+            keymap.action_for(&char_key("m"), Modifiers::META),
+            Some(Action::ToggleFilterMode)
+        );
+    }
+
+    #[test]
+    fn test_parse_skips_invalid_lines() {
+        let keymap = KeyMap::parse("bogus = y\nclear_filters = ctrl+\nclear_filters\n").0;
+
+        assert_eq!(keymap, KeyMap::default());
+    }
+
+    #[test]
+    fn test_problems_reported() {
+        let (keymap, problems) = KeyMap::parse(
+            "# comment\nbogus = y\nclear_filters = ctrl+\nclear_filters\ntoggle_zen_mode = m\n",
+        );
+
+        assert_eq!(
+            problems,
+            [
+                "line 2: unknown action \"bogus\"",
+                "line 3: invalid key \"ctrl+\"",
+                "line 4: expected `action = key`",
+                "M is bound to both toggle_filter_mode and toggle_zen_mode",
+            ]
+        );
+        assert_eq!(
+            keymap.action_for(&char_key("m"), Modifiers::empty()),
+            Some(Action::ToggleFilterMode)
+        );
+        assert!(KeyMap::parse("toggle_zen_mode = shift+z").1.is_empty());
+    }
+
+    #[test]
+    fn test_binding_display() {
+        let binding = KeyBinding::parse("ctrl+shift+k").unwrap();
+        assert_eq!(binding.display(), "Ctrl+Shift+K");
+    }
+
+    #[test]
+    fn test_named_bindings_parse_back() {
+        let keymap = KeyMap::parse("clear_filters = ctrl+shift+k\n").0;
+        let named = keymap.named_bindings();
+        assert_eq!(named.len(), ACTION_NAMES.len());
+        assert_eq!(named[0], ("clear_filters", "Ctrl+Shift+K".to_string()));
+
+        let contents: String = named
+            .iter()
+            .map(|(name, key)| format!("{} = {}\n", name, key))
+            .collect();
+        let (reparsed, problems) = KeyMap::parse(&contents);
+        assert_eq!(reparsed.named_bindings(), named);
+        assert!(problems.is_empty());
+    }
+}
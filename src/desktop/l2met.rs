@@ -0,0 +1,275 @@
+//! Metrics apps log in the l2met style Heroku popularised
+//!
+//! Tokens such as `count#hits=1`, `measure#db.latency=12ms` and
+//! `sample#queue=3` can appear in any line. Per minute, counts are summed,
+//! measures averaged and samples take their last value; the series feed the
+//! metrics panel and extra columns of the timeline export.
+
+use crate::addons;
+use crate::metrics::{sparkline_of, MetricChart};
+use crate::parser::LogEntry;
+use regex::Regex;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::OnceLock;
+
+/// Minutes kept per metric, one sparkline bar each
+const MAX_MINUTES: usize = 60;
+
+fn token_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(r"\b(count|measure|sample)#([\w.\-]+)=(-?\d+(?:\.\d+)?)([A-Za-z%]*)")
+            .expect("Failed to compile l2met regex")
+    })
+}
+
+/// How values of a metric combine within a minute
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// Summed
+    Count,
+    /// Averaged
+    Measure,
+    /// Last value wins
+    Sample,
+}
+
+impl Kind {
+    fn from_prefix(prefix: &str) -> Option<Self> {
+        match prefix {
+            "count" => Some(Self::Count),
+            "measure" => Some(Self::Measure),
+            "sample" => Some(Self::Sample),
+            _ => None,
+        }
+    }
+
+    fn prefix(self) -> &'static str {
+        match self {
+            Self::Count => "count",
+            Self::Measure => "measure",
+            Self::Sample => "sample",
+        }
+    }
+}
+
+/// One `kind#name=value` token
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    pub kind: Kind,
+    pub name: String,
+    pub value: f64,
+    /// Unit written after the value, such as `ms`; may be empty
+    pub unit: String,
+}
+
+impl Measurement {
+    /// Name including the kind, e.g. `measure#db.latency`
+    pub fn key(&self) -> String {
+        format!("{}#{}", self.kind.prefix(), self.name)
+    }
+}
+
+/// Every metric token in a message
+pub fn measurements(message: &str) -> Vec<Measurement> {
+    token_regex()
+        .captures_iter(message)
+        .filter_map(|captures| {
+            Some(Measurement {
+                kind: Kind::from_prefix(&captures[1])?,
+                name: captures[2].to_string(),
+                value: captures[3].parse().ok()?,
+                unit: captures[4].to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Values of one metric within one minute
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Aggregate {
+    count: usize,
+    sum: f64,
+    last: f64,
+}
+
+impl Aggregate {
+    pub fn add(&mut self, value: f64) {
+        self.count += 1;
+        self.sum += value;
+        self.last = value;
+    }
+
+    /// The minute's value for a metric of `kind`
+    pub fn value(&self, kind: Kind) -> f64 {
+        match kind {
+            Kind::Count => self.sum,
+            Kind::Measure if self.count > 0 => self.sum / self.count as f64,
+            Kind::Measure => 0.0,
+            Kind::Sample => self.last,
+        }
+    }
+}
+
+/// A value without trailing zeros, e.g. `12`, `0.25`, `3.33`
+pub fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{}", value)
+    } else {
+        format!("{:.2}", value)
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Series {
+    kind: Kind,
+    unit: String,
+    /// Minutes since the epoch with their values, oldest first
+    minutes: VecDeque<(i64, Aggregate)>,
+}
+
+/// Per-minute series of the metrics apps log, by source and metric
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AppMetrics {
+    series: BTreeMap<(String, String), Series>,
+}
+
+impl AppMetrics {
+    /// Record the metric tokens of an entry; add-on reports are charted on
+    /// their own and skipped here
+    pub fn ingest(&mut self, entry: &LogEntry) {
+        if addons::is_addon_metrics(entry) {
+            return;
+        }
+        let minute = entry.timestamp.timestamp().div_euclid(60);
+        for measurement in measurements(&entry.message) {
+            let series = self
+                .series
+                .entry((entry.source.clone(), measurement.key()))
+                .or_insert_with(|| Series {
+                    kind: measurement.kind,
+                    unit: measurement.unit.clone(),
+                    minutes: VecDeque::new(),
+                });
+            // Lines arrive roughly in order; a late one joins its minute
+            let at = series.minutes.partition_point(|(m, _)| *m < minute);
+            match series.minutes.get_mut(at) {
+                Some((m, aggregate)) if *m == minute => aggregate.add(measurement.value),
+                _ => {
+                    let mut aggregate = Aggregate::default();
+                    aggregate.add(measurement.value);
+                    series.minutes.insert(at, (minute, aggregate));
+                }
+            }
+            if series.minutes.len() > MAX_MINUTES {
+                series.minutes.pop_front();
+            }
+        }
+    }
+
+    /// A chart per metric, ordered by source and metric name
+    pub fn charts(&self) -> Vec<MetricChart> {
+        self.series
+            .iter()
+            .map(|((source, key), series)| {
+                let values: Vec<f64> = series
+                    .minutes
+                    .iter()
+                    .map(|(_, aggregate)| aggregate.value(series.kind))
+                    .collect();
+                let latest = values
+                    .last()
+                    .map(|&v| format!("{}{}", format_value(v), series.unit))
+                    .unwrap_or_default();
+                MetricChart {
+                    group: source.clone(),
+                    metric: key.clone(),
+                    sparkline: sparkline_of(&values),
+                    latest,
+                }
+            })
+            .collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.series.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_log_line;
+
+    fn line(time: &str, message: &str) -> LogEntry {
+        parse_log_line(&format!(
+            "2024-02-17T{}.000000+00:00 app[web.1]: {}",
+            time, message
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_measurements() {
+        let found =
+            measurements("at=info measure#db.latency=12.5ms count#hits=1 sample#queue=3 foo#bar=1");
+        assert_eq!(found.len(), 3);
+        assert_eq!(found[0].key(), "measure#db.latency");
+        assert_eq!(found[0].value, 12.5);
+        assert_eq!(found[0].unit, "ms");
+        assert_eq!(found[1].kind, Kind::Count);
+        assert_eq!(found[2].key(), "sample#queue");
+        assert!(measurements("user#42 logged in").is_empty());
+    }
+
+    #[test]
+    fn test_minutes_combine_by_kind() {
+        let mut metrics = AppMetrics::default();
+        metrics.ingest(&line(
+            "10:30:01",
+            "count#hits=1 measure#latency=10ms sample#queue=5",
+        ));
+        metrics.ingest(&line(
+            "10:30:40",
+            "count#hits=2 measure#latency=20ms sample#queue=2",
+        ));
+        metrics.ingest(&line(
+            "10:31:05",
+            "count#hits=1 measure#latency=40ms sample#queue=8",
+        ));
+        // Late line for the first minute
+        metrics.ingest(&line("10:30:59", "count#hits=3"));
+
+        let charts = metrics.charts();
+        let latest: Vec<(&str, &str)> = charts
+            .iter()
+            .map(|c| (c.metric.as_str(), c.latest.as_str()))
+            .collect();
+        assert_eq!(
+            latest,
+            [
+                ("count#hits", "1"),
+                ("measure#latency", "40ms"),
+                ("sample#queue", "8"),
+            ]
+        );
+        let hits = &metrics.series[&("app".to_string(), "count#hits".to_string())];
+        let per_minute: Vec<f64> = hits
+            .minutes
+            .iter()
+            .map(|(_, a)| a.value(Kind::Count))
+            .collect();
+        assert_eq!(per_minute, [6.0, 1.0]);
+        assert_eq!(charts[1].sparkline, "▃█");
+    }
+
+    #[test]
+    fn test_format_value() {
+        assert_eq!(format_value(12.0), "12");
+        assert_eq!(format_value(0.25), "0.25");
+        assert_eq!(format_value(10.0 / 3.0), "3.33");
+    }
+}
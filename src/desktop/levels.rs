@@ -0,0 +1,151 @@
+//! Level overrides, loaded from `~/.config/logs-parser/levels`
+//!
+//! Keyword detection gets some Heroku conventions wrong, e.g. a router
+//! `at=error ... sock=client` line is a client hanging up rather than an app
+//! failure. Each line of the levels file holds `/regex/ => level`, matched
+//! against the raw line, where the level is a name (`error`, `warn`, `info`,
+//! `debug`, or a syslog name like `crit` or `notice`) or a syslog severity
+//! number from 0 to 7:
+//!
+//! ```text
+//! /app\[worker\.\d+\]: .*retrying/ => info
+//! /heroku\[router\]: at=error code=H18/ => 3
+//! ```
+
+use crate::config::config_dir;
+use crate::parser::{LogEntry, LogLevel};
+use regex::Regex;
+
+/// Rules applied after the user's, so user rules can override them
+const BUILT_IN_RULES: &str = r"
+/heroku\[router\]: at=error .*\bsock=client\b/ => warn
+";
+
+/// Map a syslog severity (the low three bits of a PRI value) to a level
+pub fn from_syslog_severity(severity: u8) -> LogLevel {
+    match severity {
+        0..=3 => LogLevel::Error,
+        4 => LogLevel::Warn,
+        5 | 6 => LogLevel::Info,
+        _ => LogLevel::Debug,
+    }
+}
+
+fn parse_level(value: &str) -> Option<LogLevel> {
+    if let Ok(severity) = value.parse::<u8>() {
+        return (severity <= 7).then(|| from_syslog_severity(severity));
+    }
+    if value.eq_ignore_ascii_case("unknown") {
+        return Some(LogLevel::Unknown);
+    }
+    LogLevel::from_name(value)
+}
+
+/// A pattern and the level given to lines matching it
+#[derive(Debug, Clone)]
+pub struct LevelRule {
+    pattern: Regex,
+    level: LogLevel,
+}
+
+impl PartialEq for LevelRule {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern.as_str() == other.pattern.as_str() && self.level == other.level
+    }
+}
+
+impl LevelRule {
+    /// Parse a `/regex/ => level` line
+    pub fn parse(line: &str) -> Option<Self> {
+        let (pattern, level) = line.rsplit_once("=>")?;
+        let pattern = pattern.trim().strip_prefix('/')?.strip_suffix('/')?;
+        if pattern.is_empty() {
+            return None;
+        }
+        Some(Self {
+            pattern: Regex::new(pattern).ok()?,
+            level: parse_level(level.trim())?,
+        })
+    }
+}
+
+/// Ordered level rules; the first matching rule decides
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelRules {
+    rules: Vec<LevelRule>,
+}
+
+impl Default for LevelRules {
+    fn default() -> Self {
+        Self::parse("")
+    }
+}
+
+impl LevelRules {
+    /// Parse a levels file, skipping blank lines, comments and invalid rules;
+    /// the built-in rules follow the user's
+    pub fn parse(contents: &str) -> Self {
+        let rules = contents
+            .lines()
+            .chain(BUILT_IN_RULES.lines())
+            .filter(|line| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+            .filter_map(LevelRule::parse)
+            .collect();
+        Self { rules }
+    }
+
+    /// Load rules from the user's levels file, plus the built-in ones
+    pub fn load() -> Self {
+        std::fs::read_to_string(config_dir().join("levels"))
+            .map(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    /// Set the level of an entry matched by a rule
+    pub fn apply(&self, entry: &mut LogEntry) {
+        if let Some(rule) = self.rules.iter().find(|r| r.pattern.is_match(&entry.raw)) {
+            entry.level = rule.level;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_log_line;
+
+    const CLIENT_HANGUP: &str = "2024-02-17T10:30:45.123456+00:00 heroku[router]: at=error code=H27 desc=\"Client Request Interrupted\" method=POST path=\"/upload\" sock=client status=499";
+
+    #[test]
+    fn test_builtin_client_socket_is_warn() {
+        let mut entry = parse_log_line(CLIENT_HANGUP).unwrap();
+        assert_eq!(entry.level, LogLevel::Error);
+
+        LevelRules::default().apply(&mut entry);
+        assert_eq!(entry.level, LogLevel::Warn);
+    }
+
+    #[test]
+    fn test_user_rules_take_precedence() {
+        let mut entry = parse_log_line(CLIENT_HANGUP).unwrap();
+
+        LevelRules::parse(r"/code=H27/ => debug").apply(&mut entry);
+        assert_eq!(entry.level, LogLevel::Debug);
+    }
+
+    #[test]
+    fn test_syslog_levels() {
+        assert_eq!(parse_level("crit"), Some(LogLevel::Error));
+        assert_eq!(parse_level("notice"), Some(LogLevel::Info));
+        assert_eq!(parse_level("4"), Some(LogLevel::Warn));
+        assert_eq!(parse_level("7"), Some(LogLevel::Debug));
+        assert_eq!(parse_level("8"), None);
+    }
+
+    #[test]
+    fn test_invalid_rules_skipped() {
+        let rules = LevelRules::parse("# comment\n/x/ => loud\n/(/ => error\n/ok/ => info");
+
+        assert_eq!(rules.rules.len(), 1 + LevelRules::default().rules.len());
+    }
+}
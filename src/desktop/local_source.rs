@@ -0,0 +1,394 @@
+//! Local log sources other processes can push lines into
+//!
+//! Started with `--unix-socket /tmp/logs.sock` or `--fifo /tmp/logs.fifo`, so
+//! a service's output can be sent to an already-open viewer:
+//!
+//! ```text
+//! ./server 2>&1 | nc -U /tmp/logs.sock
+//! ./server > /tmp/logs.fifo
+//! ```
+//!
+//! `--file log/development.log` follows a file like `tail -f` instead,
+//! starting with its last lines, and `--stdin` (or `-`) reads lines piped
+//! into the viewer itself. `--attach` reads from a running
+//! [daemon](crate::daemon) instead. Any number of these can be given at once, and
+//! each line is tagged with the [`LocalSource::label`] of its input.
+//!
+//! With `--passthrough` the viewer can also sit in the middle of a pipeline:
+//! every received line that passes the filters is echoed to stdout as is.
+//!
+//! ```text
+//! heroku logs --tail | logs-parser-desktop - --passthrough | grep H12
+//! ```
+
+use crate::daemon;
+use crate::parser::{parse_log_line, parse_stream, ParsedLine, UnparsedLine};
+use anyhow::{bail, Context, Result};
+use futures::StreamExt;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::{AsyncRead, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::mpsc;
+
+/// Where local log lines are read from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocalSource {
+    UnixSocket(PathBuf),
+    Fifo(PathBuf),
+    File(PathBuf),
+    Stdin,
+    /// The lines buffered and received by a running daemon
+    Attach,
+}
+
+/// Echo of filtered lines to stdout, requested with `--passthrough`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Passthrough;
+
+impl Passthrough {
+    pub fn from_args(args: &[String]) -> Option<Self> {
+        args.iter()
+            .any(|arg| arg == "--passthrough")
+            .then_some(Self)
+    }
+
+    /// Write a raw line; fails once whatever reads stdout has gone away
+    pub fn write(&self, raw: &str) -> std::io::Result<()> {
+        // Stdout is line buffered, so each line reaches the pipe right away
+        writeln!(std::io::stdout().lock(), "{}", raw)
+    }
+}
+
+/// Lines of a followed file shown when it is opened
+const TAIL_LINES: usize = 1000;
+
+/// How much of the end of a followed file is read to find those lines
+const TAIL_BYTES: u64 = 1024 * 1024;
+
+/// How often a followed file is checked for appended lines
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+impl LocalSource {
+    /// Find every `--unix-socket <path>`, `--fifo <path>`, `--file <path>`
+    /// and `--stdin` in the command-line arguments (without the program name)
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Vec<Self> {
+        let mut sources = Vec::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let source = match arg.as_str() {
+                "--unix-socket" => args.next().map(|p| Self::UnixSocket(p.into())),
+                "--fifo" => args.next().map(|p| Self::Fifo(p.into())),
+                "--file" => args.next().map(|p| Self::File(p.into())),
+                "--stdin" | "-" => Some(Self::Stdin),
+                "--attach" => Some(Self::Attach),
+                _ => None,
+            };
+            if let Some(source) = source.filter(|s| !sources.contains(s)) {
+                sources.push(source);
+            }
+        }
+        sources
+    }
+
+    pub fn path(&self) -> Option<&PathBuf> {
+        match self {
+            Self::UnixSocket(path) | Self::Fifo(path) | Self::File(path) => Some(path),
+            Self::Stdin | Self::Attach => None,
+        }
+    }
+
+    /// Short name for the kind of source, used to label its lines
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::UnixSocket(_) => "socket",
+            Self::Fifo(_) => "fifo",
+            Self::File(_) => "file",
+            Self::Stdin => "stdin",
+            Self::Attach => "daemon",
+        }
+    }
+
+    /// Origin label for lines from this source: the kind and file name,
+    /// e.g. `file:development.log`
+    pub fn label(&self) -> String {
+        match self.path().and_then(|path| path.file_name()) {
+            Some(file) => format!("{}:{}", self.name(), file.to_string_lossy()),
+            None => self.name().to_string(),
+        }
+    }
+
+    /// What is being read, for the notice shown when it starts
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Stdin => "Reading standard input".to_string(),
+            Self::Attach => format!(
+                "Attached to the daemon at {}",
+                daemon::socket_path().display()
+            ),
+            Self::UnixSocket(path) | Self::Fifo(path) | Self::File(path) => {
+                format!("Listening on {}", path.display())
+            }
+        }
+    }
+
+    /// Read and parse lines until the receiving side goes away
+    pub async fn listen(&self, sender: mpsc::UnboundedSender<ParsedLine>) -> Result<()> {
+        match self {
+            Self::UnixSocket(path) => {
+                // A socket file left behind by a previous run blocks bind()
+                let _ = std::fs::remove_file(path);
+                let listener = UnixListener::bind(path)
+                    .with_context(|| format!("Failed to listen on {}", path.display()))?;
+                loop {
+                    let (stream, _) = listener.accept().await?;
+                    tokio::spawn(forward_lines(stream, sender.clone()));
+                }
+            }
+            Self::Fifo(path) => loop {
+                // Opening blocks until a writer appears; reopen after each one closes
+                let fifo = tokio::fs::File::open(path)
+                    .await
+                    .with_context(|| format!("Failed to open {}", path.display()))?;
+                if !forward_lines(fifo, sender.clone()).await {
+                    return Ok(());
+                }
+            },
+            Self::File(path) => {
+                let (mut tail, mut lines) = FileTail::open(path, TAIL_LINES)?;
+                let mut line_number = 0;
+                loop {
+                    for line in lines {
+                        line_number += 1;
+                        let result =
+                            parse_log_line(&line).ok_or(UnparsedLine { line_number, line });
+                        if sender.send(result).is_err() {
+                            return Ok(());
+                        }
+                    }
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                    lines = tail.poll()?;
+                }
+            }
+            Self::Stdin => {
+                forward_lines(tokio::io::stdin(), sender).await;
+                Ok(())
+            }
+            Self::Attach => {
+                let path = daemon::socket_path();
+                let stream = UnixStream::connect(&path).await.with_context(|| {
+                    format!(
+                        "No daemon running at {} (start one with `logs-parser-desktop daemon`)",
+                        path.display()
+                    )
+                })?;
+                if forward_lines(stream, sender).await {
+                    bail!("The daemon stopped");
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Follows appends to a file, by polling its length
+#[derive(Debug)]
+pub struct FileTail {
+    path: PathBuf,
+    /// Bytes read so far
+    offset: u64,
+    /// Start of a line whose newline hasn't been written yet
+    partial: Vec<u8>,
+}
+
+impl FileTail {
+    /// Start following `path`, returning its last `last_lines` complete lines
+    pub fn open(path: &Path, last_lines: usize) -> Result<(Self, Vec<String>)> {
+        let mut file = std::fs::File::open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        let len = file.metadata()?.len();
+        let start = len.saturating_sub(TAIL_BYTES);
+        file.seek(SeekFrom::Start(start))?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+
+        let mut tail = Self {
+            path: path.to_path_buf(),
+            offset: start + bytes.len() as u64,
+            partial: Vec::new(),
+        };
+        let mut lines = tail.split_lines(bytes);
+        // Reading from the middle of the file starts mid-line
+        if start > 0 && !lines.is_empty() {
+            lines.remove(0);
+        }
+        let skip = lines.len().saturating_sub(last_lines);
+        Ok((tail, lines.split_off(skip)))
+    }
+
+    /// Complete lines appended since the last call. A file that shrank was
+    /// truncated or replaced, and is read again from the start.
+    pub fn poll(&mut self) -> Result<Vec<String>> {
+        let mut file = std::fs::File::open(&self.path)
+            .with_context(|| format!("Failed to open {}", self.path.display()))?;
+        let len = file.metadata()?.len();
+        if len < self.offset {
+            self.offset = 0;
+            self.partial.clear();
+        }
+        if len == self.offset {
+            return Ok(Vec::new());
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        self.offset += bytes.len() as u64;
+        Ok(self.split_lines(bytes))
+    }
+
+    /// Split `bytes` after any held partial line into complete lines,
+    /// holding back an unterminated last one
+    fn split_lines(&mut self, bytes: Vec<u8>) -> Vec<String> {
+        self.partial.extend(bytes);
+        let Some(last_newline) = self.partial.iter().rposition(|&b| b == b'\n') else {
+            return Vec::new();
+        };
+        let rest = self.partial.split_off(last_newline + 1);
+        let complete = std::mem::replace(&mut self.partial, rest);
+        String::from_utf8_lossy(&complete)
+            .lines()
+            .map(|line| line.trim_end_matches('\r').to_string())
+            .collect()
+    }
+}
+
+/// Send each parsed line from `reader`; returns false once the receiver has
+/// been dropped
+async fn forward_lines(
+    reader: impl AsyncRead + Unpin,
+    sender: mpsc::UnboundedSender<ParsedLine>,
+) -> bool {
+    let mut entries = std::pin::pin!(parse_stream(BufReader::new(reader)));
+    while let Some(result) = entries.next().await {
+        if sender.send(result).is_err() {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_from_args() {
+        assert_eq!(
+            LocalSource::from_args(args(&["--unix-socket", "/tmp/logs.sock"])),
+            [LocalSource::UnixSocket("/tmp/logs.sock".into())]
+        );
+        assert_eq!(
+            LocalSource::from_args(args(&["-v", "--fifo", "/tmp/logs.fifo"])),
+            [LocalSource::Fifo("/tmp/logs.fifo".into())]
+        );
+        assert!(LocalSource::from_args(args(&["--fifo"])).is_empty());
+        assert!(LocalSource::from_args(args(&[])).is_empty());
+        assert!(LocalSource::from_args(args(&["--passthrough"])).is_empty());
+        assert_eq!(
+            Passthrough::from_args(&args(&["-", "--passthrough"])),
+            Some(Passthrough)
+        );
+        assert_eq!(Passthrough::from_args(&args(&["-"])), None);
+    }
+
+    #[test]
+    fn test_from_args_several_sources() {
+        let sources = LocalSource::from_args(args(&[
+            "--file",
+            "log/development.log",
+            "-",
+            "--file",
+            "log/sidekiq.log",
+            "--stdin",
+            "--attach",
+        ]));
+        assert_eq!(
+            sources,
+            [
+                LocalSource::File("log/development.log".into()),
+                LocalSource::Stdin,
+                LocalSource::File("log/sidekiq.log".into()),
+                LocalSource::Attach,
+            ]
+        );
+        let labels: Vec<_> = sources.iter().map(LocalSource::label).collect();
+        assert_eq!(
+            labels,
+            [
+                "file:development.log",
+                "stdin",
+                "file:sidekiq.log",
+                "daemon"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unix_socket_forwards_lines() {
+        use tokio::io::AsyncWriteExt;
+
+        let path = std::env::temp_dir().join(format!("logs-parser-{}.sock", std::process::id()));
+        let source = LocalSource::UnixSocket(path.clone());
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move { source.listen(tx).await });
+
+        let mut stream = loop {
+            match tokio::net::UnixStream::connect(&path).await {
+                Ok(stream) => break stream,
+                Err(_) => tokio::task::yield_now().await,
+            }
+        };
+        stream
+            .write_all(b"2024-02-17T10:30:45.123456+00:00 app[web.1]: hello\nplain error line\n")
+            .await
+            .unwrap();
+
+        let parsed = rx.recv().await.unwrap().unwrap();
+        assert_eq!(parsed.dyno, "web.1");
+        let plain = rx.recv().await.unwrap().unwrap_err();
+        assert_eq!(plain.line, "plain error line");
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_file_tail_follows_appends() {
+        use std::io::Write;
+
+        let path = std::env::temp_dir().join(format!("logs-parser-{}.log", std::process::id()));
+        std::fs::write(&path, "one\ntwo\nthree\n").unwrap();
+        let (mut tail, lines) = FileTail::open(&path, 2).unwrap();
+        assert_eq!(lines, ["two", "three"]);
+        assert!(tail.poll().unwrap().is_empty());
+
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        file.write_all(b"four\r\nfi").unwrap();
+        assert_eq!(tail.poll().unwrap(), ["four"]);
+        file.write_all(b"ve\n").unwrap();
+        assert_eq!(tail.poll().unwrap(), ["five"]);
+
+        // Truncated, e.g. by log rotation: read again from the start
+        std::fs::write(&path, "fresh\n").unwrap();
+        assert_eq!(tail.poll().unwrap(), ["fresh"]);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
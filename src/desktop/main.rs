@@ -1,24 +1,193 @@
 //! Desktop application entry point
 
 use dioxus::prelude::*;
-use filters::Filter;
-use parser::{LogEntry, LogLevel};
-use regex::Regex;
+use filters::{Bound, Filter, FilterEngine, IncrementalFilter};
+use formats::{Detection, Detector, LogFormat};
+use parser::{LogEntry, LogLevel, ParsedLine};
 use std::sync::Arc;
 use tokio::sync::mpsc;
 
+mod addons;
+mod alert_rules;
+mod auto_export;
+mod backfill;
+mod coalesce;
+mod commands;
 mod components;
+mod config;
+mod control;
+mod crash;
+mod crypt;
+mod daemon;
+mod deep_link;
+mod diff;
+mod dyno_colors;
+mod export;
 mod filters;
+mod formats;
 mod heroku_cli;
+mod highlights;
+mod history;
+mod i18n;
+mod ignore;
+mod keymap;
+mod l2met;
+mod levels;
+mod local_source;
+mod metrics;
+mod minimap;
+mod notifications;
+mod onboarding;
+#[cfg(feature = "otel")]
+mod otel;
+mod parse_stats;
 mod parser;
+mod platform_events;
+mod presets;
+mod rate_alerts;
+mod requests;
+mod rewrite;
+mod router_metrics;
+mod routes;
+mod scroll_anchor;
+mod session;
+mod settings;
+mod share;
+mod snooze;
+mod sorting;
+mod source_refs;
+mod sql;
+mod status_layout;
 mod stream_manager;
+mod thresholds;
+mod tray;
+mod triggers;
+mod truncate;
+mod updates;
+mod upload;
+mod windows;
 
+use addons::AddonMetrics;
+use alert_rules::{AlertRule, AlertRules};
+use auto_export::AutoExport;
+use backfill::HISTORY_LINES;
+use coalesce::Coalescer;
+use commands::{parse_command, Command};
 use components::{
-    ConnectionPanel, ConnectionStatus, FilterBar, LoadingStep, LogView, StatsHeader,
-    StatusIndicator,
+    ConnectionPanel, ConnectionStatus, DetailPanel, FilterBar, IgnoredPanel, LoadingStep, LogView,
+    MetricsPanel, Minimap, OnboardingWizard, PassphrasePrompt, PinnedStrip, PlatformPanel,
+    ReplayBar, RequestsPanel, SettingsPanel, StatsHeader, StatusIndicator,
 };
+use control::{ControlCommand, JumpTarget, SharedStatus};
+use deep_link::DeepLink;
+use export::Manifest;
 use heroku_cli::{spawn_login, AppInfo};
-use stream_manager::StreamManager;
+use highlights::Highlights;
+use history::History;
+use i18n::{t, tf};
+use ignore::IgnoreList;
+use keymap::{Action, KeyBinding, KeyMap};
+use l2met::AppMetrics;
+use levels::LevelRules;
+use local_source::{LocalSource, Passthrough};
+use metrics::RateHistory;
+use onboarding::{Onboarding, OnboardingStep};
+use parse_stats::ParseStats;
+use platform_events::{PlatformEvent, PlatformEvents};
+use presets::Presets;
+use rate_alerts::{RateAlert, RateAlerts};
+use requests::RequestIndex;
+use rewrite::RewriteRules;
+use router_metrics::RouterMetrics;
+use routes::RouteStats;
+use scroll_anchor::ScrollAnchor;
+use session::{Recorder, Replay, Session};
+use settings::Settings;
+use share::SharedView;
+use snooze::Snooze;
+use sorting::{BufferOrder, SortOrder};
+use source_refs::SourceRef;
+use status_layout::StatusLayout;
+use stream_manager::{StreamManager, StreamOptions};
+use thresholds::ThresholdRules;
+use tray::TrayAction;
+use triggers::Triggers;
+use windows::ExtraWindow;
+
+/// How often the view served by `:share` is refreshed
+const SHARE_REFRESH_INTERVAL: tokio::time::Duration = tokio::time::Duration::from_secs(1);
+
+/// How often batched entries are posted to the OTLP collector
+#[cfg(feature = "otel")]
+const OTEL_FLUSH_INTERVAL: tokio::time::Duration = tokio::time::Duration::from_secs(2);
+
+/// Reports the log view's scroll offset and visible height as fractions of
+/// its full height
+const VIEWPORT_JS: &str = r#"
+    const view = document.querySelector('.log-view');
+    if (!view || view.scrollHeight === 0) return [0, 1];
+    return [view.scrollTop / view.scrollHeight, view.clientHeight / view.scrollHeight];
+"#;
+
+/// Reports whether the log view is scrolled to the end, and otherwise the
+/// index of the row at its top and how many pixels of that row are hidden
+const ANCHOR_JS: &str = r#"
+    const view = document.querySelector('.log-view');
+    if (!view || view.scrollTop + view.clientHeight >= view.scrollHeight - 24) return [true, 0, 0];
+    const top = view.getBoundingClientRect().top;
+    const rows = view.querySelectorAll('.log-entry');
+    let low = 0, high = rows.length - 1;
+    while (low < high) {
+        const mid = (low + high) >> 1;
+        if (rows[mid].getBoundingClientRect().bottom > top) high = mid; else low = mid + 1;
+    }
+    if (!rows[low]) return [true, 0, 0];
+    return [false, Number(rows[low].id.slice(4)), top - rows[low].getBoundingClientRect().top];
+"#;
+
+/// Scrolls the log view to its end
+const FOLLOW_JS: &str = r#"
+    const view = document.querySelector('.log-view');
+    if (view) view.scrollTop = view.scrollHeight;
+"#;
+
+/// How long a status-line notice stays visible
+const NOTICE_TIMEOUT: tokio::time::Duration = tokio::time::Duration::from_secs(5);
+
+/// How long the window flashes when an awaited entry appears
+const FLASH_DURATION: tokio::time::Duration = tokio::time::Duration::from_millis(1600);
+
+/// Short beep played through the webview's audio context
+const BEEP_JS: &str = r#"
+const ctx = new (window.AudioContext || window.webkitAudioContext)();
+const osc = ctx.createOscillator();
+osc.frequency.value = 880;
+osc.connect(ctx.destination);
+osc.start();
+osc.stop(ctx.currentTime + 0.2);
+"#;
+
+/// How often `:alert` rates are compared with their baselines
+const ALERT_CHECK_INTERVAL: tokio::time::Duration = tokio::time::Duration::from_secs(5);
+
+/// How often an idle or paused replay checks for something to play
+const REPLAY_IDLE: tokio::time::Duration = tokio::time::Duration::from_millis(100);
+
+/// How long a pending "clear filters" waits for confirmation
+const CLEAR_CONFIRM_TIMEOUT: tokio::time::Duration = tokio::time::Duration::from_secs(3);
+
+/// Most entries the user can pin at once
+const MAX_PINNED: usize = 20;
+
+/// Buffered entries re-parsed between UI updates
+const REPARSE_CHUNK: usize = 2000;
+
+/// Entries kept in memory, and restored from the history on start
+const BUFFER_CAPACITY: usize = 10000;
+
+/// How often lines buffered for the history and automatic exports are
+/// written out
+const FILE_FLUSH_INTERVAL: tokio::time::Duration = tokio::time::Duration::from_secs(1);
 
 async fn init_heroku(
     mut connection_status: Signal<ConnectionStatus>,
@@ -34,18 +203,17 @@ async fn init_heroku(
                     match heroku_cli::fetch_apps().await {
                         Ok(apps) => {
                             if apps.is_empty() {
-                                connection_status.set(ConnectionStatus::Error(
-                                    "No Heroku apps found".to_string(),
-                                ));
+                                connection_status
+                                    .set(ConnectionStatus::Error(t("status.no_apps").to_string()));
                             } else {
                                 available_apps.set(apps);
                                 connection_status.set(ConnectionStatus::Ready);
                             }
                         }
                         Err(e) => {
-                            connection_status.set(ConnectionStatus::Error(format!(
-                                "Failed to fetch apps: {}",
-                                e
+                            connection_status.set(ConnectionStatus::Error(tf(
+                                "status.fetch_failed",
+                                &[("error", &e)],
                             )));
                         }
                     }
@@ -56,92 +224,140 @@ async fn init_heroku(
             }
         }
         Ok(false) => {
-            connection_status.set(ConnectionStatus::Error(
-                "Heroku CLI not found. Install from heroku.com/cli".to_string(),
-            ));
+            connection_status.set(ConnectionStatus::CliMissing);
         }
         Err(e) => {
-            connection_status.set(ConnectionStatus::Error(format!(
-                "Failed to check CLI: {}",
-                e
+            connection_status.set(ConnectionStatus::Error(tf(
+                "status.cli_check_failed",
+                &[("error", &e)],
             )));
         }
     }
 }
 
-fn theme_config_path() -> std::path::PathBuf {
-    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-    std::path::Path::new(&home)
-        .join(".config")
-        .join("logs-parser")
-        .join("theme")
-}
-
-fn read_theme() -> String {
-    std::fs::read_to_string(theme_config_path())
-        .unwrap_or_else(|_| "wmp".to_string())
-        .trim()
-        .to_string()
+/// Logs per second over the minute leading up to the newest entry
+fn recent_rate(logs: &[LogEntry]) -> f64 {
+    let Some(newest) = logs.last() else {
+        return 0.0;
+    };
+    let window_start = newest.timestamp - chrono::Duration::seconds(60);
+    let count = logs
+        .iter()
+        .rev()
+        .take_while(|log| log.timestamp > window_start)
+        .count();
+    count as f64 / 60.0
 }
 
-fn write_theme(theme: &str) {
-    let path = theme_config_path();
-    if let Some(parent) = path.parent() {
-        let _ = std::fs::create_dir_all(parent);
+/// Notice for an action reached during replay, e.g.
+/// `10:31:02 filters: level:error`
+fn action_notice(action: &session::SessionAction) -> String {
+    let time = chrono::DateTime::parse_from_rfc3339(&action.at)
+        .map(|at| at.format("%H:%M:%S").to_string())
+        .unwrap_or_else(|_| action.at.clone());
+    match action.detail.as_str() {
+        "" => format!("{} {}", time, action.kind),
+        detail => format!("{} {}: {}", time, action.kind, detail),
     }
-    let _ = std::fs::write(path, theme);
 }
 
-fn main() {
-    dioxus::launch(App);
+/// Entry for a replayed line with its id in the session; lines recorded
+/// from local sources may not be in Heroku format
+fn replayed_entry(line: &str, id: usize) -> LogEntry {
+    let mut entry = parser::parse_log_line(line).unwrap_or_else(|| LogEntry::local(line, "replay"));
+    entry.origin = "replay".to_string();
+    entry.session_id = Some(id);
+    entry
 }
 
-/// Parse a filter string into a Filter enum
-/// Formats:
-/// - dyno:web.1 -> Filter by dyno
-/// - source:app -> Filter by source
-/// - level:error -> Filter by level (error, warn, info, debug)
-/// - /regex/ -> Regex filter
-/// - anything else -> Text search
-fn parse_filter(input: &str) -> Option<Filter> {
-    let trimmed = input.trim();
-
-    if trimmed.is_empty() {
-        return None;
+/// Add entries to the buffer in its order, dropping the oldest beyond its
+/// capacity
+fn append_logs(
+    logs: &mut Vec<LogEntry>,
+    entries: impl IntoIterator<Item = LogEntry>,
+    order: BufferOrder,
+) {
+    for entry in entries {
+        order.insert(logs, entry);
+        if logs.len() > BUFFER_CAPACITY {
+            logs.remove(0); // Remove oldest entry
+        }
     }
+}
 
-    // Check for dyno: prefix
-    if let Some(dyno) = trimmed.strip_prefix("dyno:") {
-        return Some(Filter::Dyno(dyno.to_string()));
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("ctl") {
+        if let Err(e) = control::run_ctl(&args[1..]) {
+            eprintln!("{:#}", e);
+            std::process::exit(1);
+        }
+        return;
     }
-
-    // Check for source: prefix
-    if let Some(source) = trimmed.strip_prefix("source:") {
-        return Some(Filter::Source(source.to_string()));
+    if args.first().map(String::as_str) == Some("--decrypt") {
+        if let Err(e) = crypt::run_decrypt(&args[1..]) {
+            eprintln!("{:#}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if args.first().map(String::as_str) == Some("daemon") {
+        if let Err(e) = daemon::run_daemon(&args[1..]) {
+            eprintln!("{:#}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if args.first().map(String::as_str) == Some("diff") {
+        if let Err(e) = diff::run_diff(&args[1..]) {
+            eprintln!("{:#}", e);
+            std::process::exit(1);
+        }
+        return;
     }
 
-    // Check for level: prefix
-    if let Some(level_str) = trimmed.strip_prefix("level:") {
-        let level = match level_str.to_lowercase().as_str() {
-            "error" => LogLevel::Error,
-            "warn" | "warning" => LogLevel::Warn,
-            "info" => LogLevel::Info,
-            "debug" => LogLevel::Debug,
-            _ => LogLevel::Unknown,
-        };
-        return Some(Filter::LogLevel(level));
+    match DeepLink::from_args(&args) {
+        Ok(Some(link)) => deep_link::open(link),
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("{:#}", e);
+            std::process::exit(1);
+        }
+    }
+    if let Err(e) = settings::migrate_theme_file() {
+        eprintln!("{:#}", e);
+    }
+    let settings = Settings::load();
+    i18n::set_locale(i18n::Locale::detect(settings.language));
+    if settings.crash_reports {
+        crash::install();
     }
 
-    // Check for regex (starts and ends with /)
-    if trimmed.starts_with('/') && trimmed.ends_with('/') && trimmed.len() > 2 {
-        let pattern = &trimmed[1..trimmed.len() - 1];
-        if let Ok(regex) = Regex::new(pattern) {
-            return Some(Filter::Regex(regex));
+    let mut builder = dioxus::LaunchBuilder::new().with_cfg(windows::config());
+    match LogFormat::from_args(&args) {
+        Ok(Some(format)) => builder = builder.with_context(format),
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("{:#}", e);
+            std::process::exit(1);
         }
     }
+    if let Some(passthrough) = Passthrough::from_args(&args) {
+        builder = builder.with_context(passthrough);
+    }
+    let sources = LocalSource::from_args(args);
+    if !sources.is_empty() {
+        builder = builder.with_context(sources);
+    }
+    builder.launch(App);
+}
 
-    // Default to text search
-    Some(Filter::TextSearch(trimmed.to_string()))
+/// Notice shown when the forced line format changes
+fn format_notice(format: Option<LogFormat>) -> String {
+    match format {
+        Some(format) => tf("notice.parsing_as", &[("format", &format.name())]),
+        None => t("notice.detecting_format").to_string(),
+    }
 }
 
 #[component]
@@ -149,20 +365,123 @@ fn App() -> Element {
     // Connection state
     let mut connection_status = use_signal(|| ConnectionStatus::Loading(LoadingStep::CheckingCli));
     let available_apps = use_signal(Vec::<AppInfo>::new);
-    let mut selected_app = use_signal(|| None::<String>);
+    let mut onboarding = use_signal(Onboarding::load);
+    // Start with the app of the last successful connection selected
+    let mut selected_app = use_signal(move || onboarding.peek().app.clone());
     let mut stream_manager = use_signal(|| None::<Arc<tokio::sync::Mutex<StreamManager>>>);
     let mut should_monitor = use_signal(|| false);
 
     // Log data
     let mut all_logs = use_signal(Vec::<LogEntry>::new);
     let mut filtered_logs = use_signal(Vec::<LogEntry>::new);
+    let mut request_index = use_signal(RequestIndex::default);
+    let mut route_stats = use_signal(RouteStats::load);
+    let mut router_metrics = use_signal(RouterMetrics::default);
+    let mut platform_events = use_signal(PlatformEvents::default);
+    let mut addon_metrics = use_signal(AddonMetrics::default);
+    let mut app_metrics = use_signal(AppMetrics::default);
+    let mut parse_stats = use_signal(ParseStats::default);
 
     // Filter state
-    let mut filters = use_signal(Vec::<Filter>::new);
-    let mut filter_mode_and = use_signal(|| true);
+    let mut filter_engine = use_signal(FilterEngine::new);
+    let mut confirm_clear = use_signal(|| false);
+    let (loaded_keymap, keymap_problems) = use_hook(KeyMap::load);
+    let mut keymap = use_signal(move || loaded_keymap);
+    let status_layout = use_signal(StatusLayout::load);
+    let mut zen_mode = use_signal(|| false);
+    let mut show_elapsed = use_signal(|| false);
+    let rewrites = use_signal(RewriteRules::load);
+    let mut thresholds = use_signal(ThresholdRules::load);
+    let mut settings = use_signal(Settings::load);
+    let mut level_rules = use_signal(LevelRules::load);
+    #[cfg(feature = "otel")]
+    let mut otel_queue = use_signal(otel::OtlpQueue::default);
+    let mut dim_cutoff = use_signal(|| None::<chrono::DateTime<chrono::FixedOffset>>);
+    let mut sql_highlight = use_signal(|| true);
+    let mut show_requests = use_signal(|| false);
+    let mut show_metrics = use_signal(|| false);
+    let mut paused = use_signal(|| false);
+    // Entries received while paused, added when the list resumes
+    let mut held = use_signal(Vec::<LogEntry>::new);
+    let mut unseen_errors = use_signal(|| 0usize);
+    let focused = tray::use_window_focus();
+    let extra_window = try_use_context::<ExtraWindow>().is_some();
+    let mut show_ignored = use_signal(|| false);
+    let mut show_platform = use_signal(|| false);
+    // Values in the settings file while the `:settings` editor is open
+    let mut saved_settings = use_signal(|| None::<Vec<(String, String)>>);
+    // Newer release found on GitHub, until dismissed
+    let mut available_update = use_signal(|| None::<updates::Release>);
+    // Report of a crash in an earlier run, offered once
+    let mut crash_report = use_signal(move || {
+        if extra_window {
+            None
+        } else {
+            crash::unseen_report()
+        }
+    });
+    // Format guessed from the first lines, shown until dismissed
+    let mut detector = use_signal(Detector::default);
+    let mut detection = use_signal(|| None::<Detection>);
+    // Format forced with --format, until changed from the UI
+    let forced_format = try_use_context::<LogFormat>();
+    let mut format_override = use_signal(move || forced_format);
+    // Order of a frozen (paused or finished replay) list; time order if None
+    let mut sort_order = use_signal(|| None::<SortOrder>);
+    // Whether late lines are slotted in by timestamp or added as received
+    let mut buffer_order = use_signal(move || settings.peek().order);
+    let mut ignore_list = use_signal(IgnoreList::load);
+    let mut filter_tracker = use_signal(IncrementalFilter::default);
+    let mut viewport = use_signal(|| (0.0, 1.0));
+    // Row being read while scrolled up from the end; `None` follows the end
+    let mut scroll_anchor = use_signal(|| None::<ScrollAnchor>);
+    let mut recorder = use_signal(|| None::<Recorder>);
+    // Lines kept on disk for the next start, when `history` is set
+    let mut history = use_signal(|| None::<History>);
+    // Filtered lines echoed to stdout, with --passthrough
+    let requested_passthrough = try_use_context::<Passthrough>();
+    let mut passthrough = use_signal(move || requested_passthrough);
+    // Rotating files filtered lines are appended to, when `auto_export` is set
+    let mut auto_export = use_signal(move || {
+        let settings = settings.peek();
+        let rotation = settings.auto_export.filter(|_| !extra_window)?;
+        Some(AutoExport::new(
+            &settings.auto_export_to,
+            rotation,
+            settings.auto_export_keep,
+        ))
+    });
+    let mut replay = use_signal(|| None::<Replay>);
+    let mut dyno_colors = use_signal(|| true);
+    let mut notice = use_signal(|| None::<String>);
+    let mut triggers = use_signal(Triggers::default);
+    let mut rate_alerts = use_signal(RateAlerts::default);
+    // When the most recent alert fired and what it said, for the status header
+    let mut last_alert = use_signal(|| None::<(chrono::DateTime<chrono::Local>, String)>);
+    // Fixed-threshold rules from the alerts file
+    let mut alert_rules = use_signal(AlertRules::load);
+    // Snoozed time and muted alert rules, kept across restarts
+    let mut snooze = use_signal(Snooze::load);
+    let mut coalescer = use_signal(Coalescer::default);
+    let mut highlights = use_signal(Highlights::load);
+    let mut presets = use_signal(Presets::load);
+    // Server task, view JSON and link while `:share` is serving
+    let mut share = use_signal(|| None::<(Task, SharedView, String)>);
+    // Text to start the filter bar's input with, e.g. `:export `
+    let mut filter_prompt = use_signal(|| None::<String>);
+    // Path of an `:export … encrypt` waiting for its passphrase
+    let mut pending_encrypted_export = use_signal(|| None::<Option<String>>);
+    let mut pinned = use_signal(Vec::<LogEntry>::new);
+    // Lines per second by arrival, for the header sparkline
+    let mut activity = use_signal(RateHistory::default);
+    // Entry shown in the detail pane beside the list
+    let mut selected_entry = use_signal(|| None::<LogEntry>);
+    // Row with keyboard focus, copied by `y` when nothing is selected
+    let mut focused_entry = use_signal(|| None::<LogEntry>);
+    let mut flash = use_signal(|| false);
     let mut login_process =
         use_signal(|| None::<std::sync::Arc<tokio::sync::Mutex<tokio::process::Child>>>);
-    let mut theme = use_signal(read_theme);
+    let mut theme = use_signal(move || settings.peek().theme.clone());
 
     // Initialize: Check CLI and fetch apps
     use_effect(move || {
@@ -171,27 +490,651 @@ fn App() -> Element {
         });
     });
 
-    // Apply filters effect
+    // Apply filters effect: lines appended since the last run are filtered
+    // on their own; filter changes, eviction and clears refilter everything
+    use_effect(move || {
+        let all = all_logs.read();
+        let engine = filter_engine.read();
+        let ignored = ignore_list.read();
+        let visible = |entries: &[LogEntry]| {
+            let mut visible = engine.apply(entries);
+            visible.retain(|entry| !ignored.is_ignored(entry));
+            visible
+        };
+        let resume = filter_tracker.peek().resume_from(&engine, &all);
+        match resume {
+            Some(start) if start == all.len() => {}
+            Some(start) => filtered_logs.write().extend(visible(&all[start..])),
+            None => filtered_logs.set(visible(&all)),
+        }
+        filter_tracker.write().update(&engine, &all);
+    });
+
+    // Entries older than `dim_after` are dimmed; move the cutoff with the clock
+    use_future(move || async move {
+        let Some(age) = settings.read().dim_after else {
+            return;
+        };
+        let Ok(age) = chrono::Duration::from_std(age) else {
+            return;
+        };
+        loop {
+            dim_cutoff.set(Some((chrono::Utc::now() - age).fixed_offset()));
+            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+        }
+    });
+
+    // Show a transient message on the status line
+    let mut show_notice = move |text: String| {
+        notice.set(Some(text.clone()));
+        spawn(async move {
+            tokio::time::sleep(NOTICE_TIMEOUT).await;
+            // Only clear if a newer notice hasn't replaced this one
+            if notice().as_deref() == Some(text.as_str()) {
+                notice.set(None);
+            }
+        });
+    };
+
+    // Lines of the keys file that were skipped, reported once on start
     use_effect(move || {
-        let all = all_logs();
-        let active_filters = filters();
-        let mode_and = filter_mode_and();
+        if !keymap_problems.is_empty() {
+            show_notice(tf(
+                "notice.keys_file",
+                &[("problems", &keymap_problems.join("; "))],
+            ));
+        }
+    });
+
+    let mut get_attention = move || {
+        flash.set(true);
+        spawn(async move {
+            tokio::time::sleep(FLASH_DURATION).await;
+            flash.set(false);
+        });
+        let _ = document::eval(BEEP_JS);
+    };
+
+    // An alert fired: flash, beep and show it, and with the window in the
+    // background also send a desktop notification
+    let mut raise_alert = move |message: String| {
+        get_attention();
+        show_notice(message.clone());
+        last_alert.set(Some((chrono::Local::now(), message.clone())));
+        if settings.read().desktop_notifications && !*focused.peek() {
+            spawn(async move {
+                if let Err(e) = notifications::show(t("notice.alert").to_string(), message).await {
+                    show_notice(format!("{:#}", e));
+                }
+            });
+        }
+    };
+
+    // An awaited query matched: pin the entry and get the user's attention,
+    // unless its alert is snoozed or muted
+    let mut fire_triggers = move |entry: &LogEntry, queries: Vec<String>| {
+        pinned.write().push(entry.clone());
+        let now = chrono::Local::now().timestamp();
+        let audible: Vec<String> = queries
+            .into_iter()
+            .filter(|query| !snooze.peek().silences(query, now))
+            .collect();
+        if !audible.is_empty() {
+            let queries = audible.join("\", \"");
+            raise_alert(tf("notice.awaited", &[("queries", &queries)]));
+        }
+    };
+
+    // Level adjustments applied to every parsed entry
+    let classify = move |entry: &mut LogEntry| {
+        if settings.read().router_status_levels {
+            if let Some(level) = requests::router_status_level(entry) {
+                entry.level = level;
+            }
+        }
+        level_rules.read().apply(entry);
+    };
+
+    // Add a received entry to the buffer, from Heroku or a local source
+    let mut ingest = move |mut entry: LogEntry| {
+        let failed = recorder
+            .write()
+            .as_mut()
+            .and_then(|recorder| recorder.record(&entry.raw).err());
+        if let Some(e) = failed {
+            recorder.set(None);
+            show_notice(tf(
+                "notice.recording_stopped",
+                &[("error", &format!("{:#}", e))],
+            ));
+        }
+        // Replayed lines are already on disk in their session file, and
+        // already raised their alerts and went out when they were recorded
+        let replayed = entry.origin == "replay";
+        if !replayed {
+            let failed = history
+                .write()
+                .as_mut()
+                .and_then(|history| history.append(&entry.origin, &entry.raw).err());
+            if let Some(e) = failed {
+                history.set(None);
+                show_notice(tf(
+                    "notice.history_stopped",
+                    &[("error", &format!("{:#}", e))],
+                ));
+            }
+        }
+        classify(&mut entry);
+        // Only committed filters, not a query still being typed in the bar
+        if !replayed
+            && filter_engine.read().matches_committed(&entry)
+            && !ignore_list.read().is_ignored(&entry)
+        {
+            let failed = auto_export
+                .write()
+                .as_mut()
+                .and_then(|export| export.write(&entry.raw, chrono::Local::now()).err());
+            if let Some(e) = failed {
+                auto_export.set(None);
+                show_notice(tf(
+                    "notice.auto_export_stopped",
+                    &[("error", &format!("{:#}", e))],
+                ));
+            }
+            let failed = passthrough().and_then(|out| out.write(&entry.raw).err());
+            if let Some(e) = failed {
+                passthrough.set(None);
+                show_notice(tf("notice.passthrough_stopped", &[("error", &e)]));
+            }
+        }
+        activity
+            .write()
+            .record(chrono::Local::now().timestamp(), entry.level);
+        if !replayed && !rate_alerts.peek().is_empty() {
+            rate_alerts
+                .write()
+                .record(&entry, chrono::Local::now().timestamp());
+        }
+        if !replayed && !alert_rules.peek().is_empty() {
+            let now = chrono::Local::now().timestamp();
+            let fired: Vec<String> = alert_rules
+                .write()
+                .record(&entry, now)
+                .into_iter()
+                .filter(|(query, _)| !snooze.peek().silences(query, now))
+                .map(|(_, message)| message)
+                .collect();
+            if !fired.is_empty() {
+                raise_alert(fired.join("; "));
+            }
+        }
+        request_index.write().ingest(&entry);
+        route_stats.write().ingest(&entry);
+        router_metrics.write().ingest(&entry);
+        {
+            let mut events = platform_events.write();
+            events.ingest(&entry);
+            // Nothing is unseen while the pane is open
+            if *show_platform.peek() {
+                events.mark_seen();
+            }
+        }
+        addon_metrics.write().ingest(&entry);
+        app_metrics.write().ingest(&entry);
+        #[cfg(feature = "otel")]
+        if !replayed
+            && settings.read().otel_endpoint.is_some()
+            && filter_engine.read().matches_committed(&entry)
+            && !ignore_list.read().is_ignored(&entry)
+        {
+            otel_queue.write().push(entry.clone());
+        }
+        if !replayed {
+            let fired = triggers.write().take_matches(&entry);
+            if !fired.is_empty() {
+                fire_triggers(&entry, fired.into_iter().map(|t| t.query).collect());
+            }
+        }
+        if entry.level == LogLevel::Error && !*focused.peek() {
+            unseen_errors += 1;
+        }
+        let kept = if settings.read().coalesce_repeats {
+            coalescer.write().push(entry)
+        } else {
+            vec![entry]
+        };
+        if paused() {
+            held.write().extend(kept);
+        } else {
+            append_logs(&mut all_logs.write(), kept, buffer_order());
+        }
+    };
+
+    // Sample the first lines for format detection, and apply a forced format
+    let mut check_format = move |result: ParsedLine| -> ParsedLine {
+        let line = match &result {
+            Ok(entry) => &entry.raw,
+            Err(unparsed) => &unparsed.line,
+        };
+        if let Some(found) = detector.write().observe(line) {
+            detection.set(Some(found));
+        }
+        match format_override() {
+            Some(format) => formats::reparse(result, format),
+            None => result,
+        }
+    };
+
+    let mut cycle_format = move || {
+        let next = LogFormat::next_override(format_override());
+        format_override.set(next);
+        show_notice(format_notice(next));
+    };
+
+    // Parse the buffered lines again with the current format and rules, a
+    // chunk at a time so the list keeps updating meanwhile
+    let reparse_buffer = move || {
+        spawn(async move {
+            let format = format_override();
+            let snapshot = all_logs.peek().clone();
+            let mut reparsed = Vec::with_capacity(snapshot.len());
+            for chunk in snapshot.chunks(REPARSE_CHUNK) {
+                reparsed.extend(chunk.iter().map(|entry| {
+                    let mut entry = formats::reparse_entry(entry, format);
+                    classify(&mut entry);
+                    entry
+                }));
+                show_notice(tf(
+                    "notice.reparsing",
+                    &[("done", &reparsed.len()), ("total", &snapshot.len())],
+                ));
+                tokio::task::yield_now().await;
+            }
+
+            let replaced = formats::apply_reparsed(&mut all_logs.write(), &snapshot, reparsed);
+            let mut index = request_index.write();
+            let mut routes = route_stats.write();
+            let mut router = router_metrics.write();
+            let mut events = platform_events.write();
+            let mut addons = addon_metrics.write();
+            let mut app = app_metrics.write();
+            let mut stats = parse_stats.write();
+            // Events seen before aren't raised again
+            let seen = events.unseen() == 0 || *show_platform.peek();
+            index.clear();
+            routes.clear();
+            router.clear();
+            events.clear();
+            addons.clear();
+            app.clear();
+            stats.clear();
+            for entry in all_logs.read().iter() {
+                index.ingest(entry);
+                routes.ingest(entry);
+                router.ingest(entry);
+                events.ingest(entry);
+                addons.ingest(entry);
+                app.ingest(entry);
+                // Replayed lines aren't counted as they arrive either
+                let input = entry.origin.split(':').next().unwrap_or_default();
+                if input == "replay" {
+                    continue;
+                }
+                // Only lines no format could parse fall back to `local`
+                if entry.source == "local" {
+                    stats.record_failure(input, &entry.raw);
+                } else {
+                    stats.record_parsed(input);
+                }
+            }
+            if seen {
+                events.mark_seen();
+            }
+            filter_tracker.write().reset();
+            show_notice(tf("notice.reparsed", &[("count", &replaced)]));
+        });
+    };
+
+    let mut toggle_order = move || {
+        let order = buffer_order().toggled();
+        buffer_order.set(order);
+        if order == BufferOrder::Timestamp {
+            all_logs.write().sort_by_key(|entry| entry.timestamp);
+            show_notice(t("notice.order_timestamp").to_string());
+        } else {
+            show_notice(t("notice.order_arrival").to_string());
+        }
+    };
+
+    // Note something the responder did in the session being recorded
+    let mut audit = move |kind: &str, detail: String| {
+        let failed = recorder
+            .write()
+            .as_mut()
+            .and_then(|recorder| recorder.record_action(kind, &detail).err());
+        if let Some(e) = failed {
+            recorder.set(None);
+            show_notice(tf(
+                "notice.recording_stopped",
+                &[("error", &format!("{:#}", e))],
+            ));
+        }
+    };
 
-        if active_filters.is_empty() {
-            filtered_logs.set(all.clone());
+    // The committed filters as one line, for the session record
+    let current_filters = move || {
+        let engine = filter_engine.peek();
+        let joiner = if engine.is_and_mode() {
+            " AND "
+        } else {
+            " OR "
+        };
+        let filters: Vec<String> = engine
+            .active_filters()
+            .iter()
+            .map(Filter::display)
+            .collect();
+        if filters.is_empty() {
+            "none".to_string()
         } else {
-            let filtered: Vec<LogEntry> = all
+            filters.join(joiner)
+        }
+    };
+
+    // Record the filters whenever they change while recording, however
+    // they were changed (filter bar, chips, requests panel, ctl)
+    let mut audited_filters = use_signal(String::new);
+    use_effect(move || {
+        filter_engine.read();
+        let filters = current_filters();
+        if recorder.peek().is_some() && *audited_filters.peek() != filters {
+            audited_filters.set(filters.clone());
+            audit("filters", filters);
+        }
+    });
+
+    let mut toggle_pause = move || {
+        audit(if paused() { "resume" } else { "pause" }, String::new());
+        if paused() {
+            let entries = std::mem::take(&mut *held.write());
+            append_logs(&mut all_logs.write(), entries, buffer_order());
+            sort_order.set(None);
+        }
+        paused.set(!paused());
+    };
+
+    // A list that isn't receiving lines can be shown in another order
+    let frozen =
+        use_memo(move || paused() || replay.read().as_ref().is_some_and(|r| r.is_finished()));
+    let shown_logs = use_memo(move || match sort_order() {
+        Some(order) if frozen() => order.apply(&filtered_logs.read()),
+        _ => filtered_logs(),
+    });
+    // The origin column only helps once lines from several inputs are mixed
+    let show_origin = use_memo(move || {
+        let logs = all_logs.read();
+        logs.first()
+            .is_some_and(|first| logs.iter().any(|log| log.origin != first.origin))
+    });
+
+    // Errors only count as unseen while the window is in the background
+    use_effect(move || {
+        if focused() {
+            unseen_errors.set(0);
+        }
+    });
+
+    // The app and its unseen errors, for the window title and `ctl status`,
+    // so they can be followed from the taskbar or a tmux status bar
+    let shared_status = use_hook(SharedStatus::default);
+    let title_status = shared_status.clone();
+    use_effect(move || {
+        let line = control::status_line(selected_app().as_deref(), unseen_errors(), paused());
+        if settings.read().title_status {
+            dioxus::desktop::window().set_title(&line);
+        }
+        if let Ok(mut status) = title_status.write() {
+            *status = line;
+        }
+    });
+
+    let tray_icon = tray::use_tray(!extra_window, move |action| match action {
+        TrayAction::TogglePause => toggle_pause(),
+        TrayAction::Reconnect => {
+            let Some(manager) = stream_manager() else {
+                return;
+            };
+            spawn(async move {
+                connection_status.set(ConnectionStatus::Connecting);
+                match manager.lock().await.reconnect().await {
+                    Ok(_) => connection_status.set(ConnectionStatus::Streaming),
+                    Err(e) => connection_status.set(ConnectionStatus::Error(tf(
+                        "status.reconnect_failed",
+                        &[("error", &e)],
+                    ))),
+                }
+            });
+        }
+    });
+    use_effect(move || {
+        let Some(icon) = &tray_icon else {
+            return;
+        };
+        let unseen = unseen_errors();
+        let _ = icon.set_tooltip(Some(tray::tooltip(
+            &connection_status().label(),
+            unseen,
+            paused(),
+        )));
+        // Shown beside the icon in the macOS menu bar
+        icon.set_title((unseen > 0).then(|| unseen.to_string()));
+    });
+
+    // Restore the lines kept from the last run, then keep writing new ones
+    use_future(move || async move {
+        let Some(max_bytes) = settings.read().history else {
+            return;
+        };
+        if extra_window {
+            return;
+        }
+        let restored = History::open(&config::config_dir(), max_bytes)
+            .and_then(|mut opened| Ok((opened.recent(BUFFER_CAPACITY)?, opened)));
+        match restored {
+            Ok((mut entries, opened)) => {
+                for entry in &mut entries {
+                    classify(entry);
+                    request_index.write().ingest(entry);
+                    route_stats.write().ingest(entry);
+                    router_metrics.write().ingest(entry);
+                    platform_events.write().ingest(entry);
+                    addon_metrics.write().ingest(entry);
+                    app_metrics.write().ingest(entry);
+                }
+                if !entries.is_empty() {
+                    show_notice(tf("notice.restored", &[("count", &entries.len())]));
+                }
+                append_logs(&mut all_logs.write(), entries, buffer_order());
+                history.set(Some(opened));
+            }
+            Err(e) => show_notice(tf(
+                "notice.history_unavailable",
+                &[("error", &format!("{:#}", e))],
+            )),
+        }
+        loop {
+            tokio::time::sleep(FILE_FLUSH_INTERVAL).await;
+            let failed = history
+                .write()
+                .as_mut()
+                .and_then(|history| history.flush().err());
+            if let Some(e) = failed {
+                history.set(None);
+                show_notice(tf(
+                    "notice.history_stopped",
+                    &[("error", &format!("{:#}", e))],
+                ));
+            }
+        }
+    });
+
+    // Compare `:alert` rates with their baselines
+    use_future(move || async move {
+        loop {
+            tokio::time::sleep(ALERT_CHECK_INTERVAL).await;
+            let now = chrono::Local::now().timestamp();
+            if snooze.peek().expired(now) {
+                snooze.write().wake();
+                if let Err(e) = snooze.peek().save() {
+                    show_notice(format!("{:#}", e));
+                } else {
+                    show_notice(t("notice.snooze_over").to_string());
+                }
+            }
+            if rate_alerts.peek().is_empty() {
+                continue;
+            }
+            // Checked even while silenced, so a rise that started during a
+            // snooze doesn't fire the moment it ends
+            let fired: Vec<String> = rate_alerts
+                .write()
+                .check(now)
+                .into_iter()
+                .filter(|(query, _)| !snooze.peek().silences(query, now))
+                .map(|(_, message)| message)
+                .collect();
+            if !fired.is_empty() {
+                raise_alert(fired.join("; "));
+            }
+        }
+    });
+
+    // Write automatically exported lines out as they accumulate
+    use_future(move || async move {
+        loop {
+            tokio::time::sleep(FILE_FLUSH_INTERVAL).await;
+            let failed = auto_export
+                .write()
+                .as_mut()
+                .and_then(|export| export.flush().err());
+            if let Some(e) = failed {
+                auto_export.set(None);
+                show_notice(tf(
+                    "notice.auto_export_stopped",
+                    &[("error", &format!("{:#}", e))],
+                ));
+            }
+        }
+    });
+
+    // Keep the shared copy of the view current while `:share` is serving
+    use_future(move || async move {
+        loop {
+            tokio::time::sleep(SHARE_REFRESH_INTERVAL).await;
+            let Some(view) = share.peek().as_ref().map(|(_, view, _)| view.clone()) else {
+                continue;
+            };
+            let filters: Vec<String> = filter_engine
+                .peek()
+                .active_filters()
                 .iter()
-                .filter(|log| {
-                    if mode_and {
-                        active_filters.iter().all(|f| f.matches(log))
-                    } else {
-                        active_filters.iter().any(|f| f.matches(log))
-                    }
-                })
-                .cloned()
+                .map(Filter::display)
                 .collect();
-            filtered_logs.set(filtered);
+            let json = share::view_json(&shown_logs.peek(), &filters, &settings.peek().time_format);
+            let mut shared = view
+                .write()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            *shared = json;
+        }
+    });
+
+    // Ship filtered entries to an OpenTelemetry collector in batches
+    #[cfg(feature = "otel")]
+    use_future(move || async move {
+        let Some(endpoint) = settings.read().otel_endpoint.clone() else {
+            return;
+        };
+        let endpoint = match otel::OtlpEndpoint::parse(&endpoint) {
+            Ok(endpoint) => endpoint,
+            Err(e) => {
+                show_notice(format!("{:#}", e));
+                return;
+            }
+        };
+        loop {
+            tokio::time::sleep(OTEL_FLUSH_INTERVAL).await;
+            let batch = otel_queue.write().take();
+            if batch.is_empty() {
+                continue;
+            }
+            let service = selected_app().unwrap_or_else(|| "logs-parser".to_string());
+            if let Err(e) = endpoint.send(&service, &batch).await {
+                show_notice(tf("notice.otlp_failed", &[("error", &format!("{:#}", e))]));
+                // Tried again with the next flush
+                otel_queue.write().requeue(batch);
+            }
+        }
+    });
+
+    // Play back a session loaded with :replay, paced as it was recorded
+    use_future(move || async move {
+        loop {
+            let delay = replay.read().as_ref().and_then(|r| r.next_delay());
+            let Some(delay) = delay else {
+                tokio::time::sleep(REPLAY_IDLE).await;
+                continue;
+            };
+            tokio::time::sleep(delay).await;
+            let line = replay.write().as_mut().and_then(|r| r.advance());
+            if let Some((id, line)) = line {
+                ingest(replayed_entry(&line, id));
+                let due = replay.write().as_mut().map(|r| r.due_actions());
+                for action in due.unwrap_or_default() {
+                    show_notice(action_notice(&action));
+                }
+                if replay.read().as_ref().is_some_and(|r| r.is_finished()) {
+                    show_notice(t("notice.replay_finished").to_string());
+                }
+            }
+        }
+    });
+
+    windows::use_new_window_menu();
+
+    // Lines pushed by other processes via --unix-socket / --fifo, followed
+    // with --file or piped to --stdin; each input runs on its own task
+    let local_sources = try_use_context::<Vec<LocalSource>>();
+    use_future(move || {
+        let local_sources = local_sources.clone().unwrap_or_default();
+        async move {
+            for source in local_sources {
+                let (tx, mut rx) = mpsc::unbounded_channel::<ParsedLine>();
+                let name = source.name();
+                let label = source.label();
+                spawn(async move {
+                    while let Some(result) = rx.recv().await {
+                        let mut entry = match check_format(result) {
+                            Ok(entry) => {
+                                parse_stats.write().record_parsed(name);
+                                entry
+                            }
+                            // Shown anyway, stamped with the time it arrived
+                            Err(unparsed) => {
+                                parse_stats.write().record_failure(name, &unparsed.line);
+                                LogEntry::local(&unparsed.line, name)
+                            }
+                        };
+                        entry.origin = label.clone();
+                        ingest(entry);
+                    }
+                });
+                show_notice(source.describe());
+                spawn(async move {
+                    if let Err(e) = source.listen(tx).await {
+                        show_notice(format!("{:#}", e));
+                    }
+                });
+            }
         }
     });
 
@@ -200,16 +1143,40 @@ fn App() -> Element {
         selected_app.set(Some(app_name));
     };
 
-    let on_connect = move |_| {
+    let mut link_options = use_signal(|| None::<StreamOptions>);
+    let mut on_connect = move |_| {
         if let Some(app_name) = selected_app() {
             connection_status.set(ConnectionStatus::Connecting);
+            if let Some(export) = auto_export.write().as_mut() {
+                export.set_app(&app_name);
+            }
 
             spawn(async move {
                 // Create channel for log entries
-                let (tx, mut rx) = mpsc::unbounded_channel::<LogEntry>();
+                let (tx, mut rx) = mpsc::unbounded_channel::<ParsedLine>();
 
                 // Create stream manager
-                let manager = StreamManager::new(app_name.clone(), tx);
+                // Options from a link apply to the connection it starts
+                let options = link_options
+                    .write()
+                    .take()
+                    .unwrap_or_else(|| StreamOptions {
+                        num: settings.read().stream_history,
+                        ..StreamOptions::default()
+                    });
+                let mut manager = StreamManager::new(app_name.clone(), options, tx);
+                // The history the stream starts with may already be here
+                // from the last run
+                let origin = format!("heroku:{}", app_name);
+                let restored: Vec<String> = all_logs
+                    .read()
+                    .iter()
+                    .rev()
+                    .filter(|entry| entry.origin == origin)
+                    .take(HISTORY_LINES)
+                    .map(|entry| entry.raw.clone())
+                    .collect();
+                manager.skip_lines(restored.iter().rev().map(String::as_str));
                 let manager = Arc::new(tokio::sync::Mutex::new(manager));
 
                 // Try to connect
@@ -226,11 +1193,22 @@ fn App() -> Element {
 
                         // Spawn task to receive logs and update state
                         spawn(async move {
-                            while let Some(entry) = rx.recv().await {
-                                let mut logs = all_logs.write();
-                                logs.push(entry);
-                                if logs.len() > 10000 {
-                                    logs.remove(0); // Remove oldest entry
+                            while let Some(result) = rx.recv().await {
+                                match check_format(result) {
+                                    Ok(mut entry) => {
+                                        parse_stats.write().record_parsed("heroku");
+                                        entry.origin = origin.clone();
+                                        ingest(entry);
+                                    }
+                                    // Shown anyway, stamped with the time it arrived
+                                    Err(unparsed) => {
+                                        parse_stats
+                                            .write()
+                                            .record_failure("heroku", &unparsed.line);
+                                        let mut entry = LogEntry::local(&unparsed.line, "heroku");
+                                        entry.origin = origin.clone();
+                                        ingest(entry);
+                                    }
                                 }
                             }
                         });
@@ -256,9 +1234,10 @@ fn App() -> Element {
                                             connection_status.set(ConnectionStatus::Streaming);
                                         }
                                         Err(e) => {
-                                            connection_status.set(ConnectionStatus::Error(
-                                                format!("Reconnection failed: {}", e),
-                                            ));
+                                            connection_status.set(ConnectionStatus::Error(tf(
+                                                "status.reconnect_failed",
+                                                &[("error", &e)],
+                                            )));
                                             break;
                                         }
                                     }
@@ -267,14 +1246,129 @@ fn App() -> Element {
                         });
                     }
                     Err(e) => {
-                        connection_status
-                            .set(ConnectionStatus::Error(format!("Connection failed: {}", e)));
+                        connection_status.set(ConnectionStatus::Error(tf(
+                            "status.connection_failed",
+                            &[("error", &e)],
+                        )));
                     }
                 }
             });
         }
     };
 
+    // Load a session into an empty buffer and play it; with an id, open it
+    // paused on that line instead
+    let mut start_replay = move |path: &std::path::Path, goto_id: Option<usize>| {
+        let session = match Session::load(path) {
+            Ok(session) if session.is_empty() => {
+                show_notice(tf("notice.session_empty", &[("path", &path.display())]));
+                return;
+            }
+            Ok(session) => session,
+            Err(e) => {
+                show_notice(format!("{:#}", e));
+                return;
+            }
+        };
+        if goto_id.is_some_and(|id| id > session.len()) {
+            show_notice(tf(
+                "notice.session_lines",
+                &[("path", &path.display()), ("count", &session.len())],
+            ));
+            return;
+        }
+        if !session.actions().is_empty() {
+            show_notice(tf(
+                "notice.replaying",
+                &[
+                    ("lines", &session.len()),
+                    ("actions", &session.actions().len()),
+                ],
+            ));
+        }
+        all_logs.write().clear();
+        coalescer.set(Coalescer::default());
+        let mut state = Replay::new(session);
+        let Some(id) = goto_id else {
+            replay.set(Some(state));
+            return;
+        };
+        state.playing = false;
+        let lines = state.seek(id);
+        replay.set(Some(state));
+        for (idx, line) in lines.into_iter().enumerate() {
+            ingest(replayed_entry(&line, idx + 1));
+        }
+        let entry = all_logs
+            .peek()
+            .iter()
+            .rfind(|entry| entry.session_id == Some(id))
+            .cloned();
+        selected_entry.set(entry);
+        show_notice(tf(
+            "notice.paused_on_line",
+            &[("id", &id), ("path", &path.display())],
+        ));
+    };
+
+    // An app and filters from `--app`/`--filter` or a `logsparser://` link,
+    // or a session from `--session`; the app is connected to once the CLI
+    // checks have passed
+    let mut pending_app = use_signal(|| None::<String>);
+    let mut open_link = move |link: DeepLink| {
+        if !link.filters.is_empty() {
+            let mut engine = filter_engine.write();
+            engine.clear_filters();
+            for filter in link
+                .filters
+                .iter()
+                .flat_map(|query| filters::parse_query(query))
+            {
+                engine.add_filter(filter);
+            }
+        }
+        if let Some(path) = &link.session {
+            start_replay(path, link.goto_id);
+            return;
+        }
+        let options = link.stream_options(settings.read().stream_history);
+        let Some(app) = link.app else {
+            return;
+        };
+        let connected = matches!(
+            connection_status(),
+            ConnectionStatus::Connecting
+                | ConnectionStatus::Streaming
+                | ConnectionStatus::Reconnecting(_)
+        );
+        if connected {
+            if selected_app().as_ref() != Some(&app) {
+                show_notice(tf("notice.disconnect_to_open", &[("app", &app)]));
+            }
+            return;
+        }
+        link_options.set(Some(options));
+        selected_app.set(Some(app.clone()));
+        pending_app.set(Some(app));
+    };
+    use_future(move || async move {
+        if extra_window {
+            return;
+        }
+        let Some(mut links) = deep_link::take_links() else {
+            return;
+        };
+        while let Some(link) = links.recv().await {
+            open_link(link);
+        }
+    });
+    use_effect(move || {
+        if connection_status() == ConnectionStatus::Ready && pending_app().is_some() {
+            pending_app.set(None);
+            on_connect(());
+        }
+    });
+
     let on_disconnect = move |_| {
         spawn(async move {
             should_monitor.set(false);
@@ -283,6 +1377,18 @@ fn App() -> Element {
             }
             stream_manager.set(None);
             all_logs.set(Vec::new());
+            held.write().clear();
+            request_index.write().clear();
+            route_stats.write().clear();
+            router_metrics.write().clear();
+            platform_events.write().clear();
+            addon_metrics.write().clear();
+            app_metrics.write().clear();
+            parse_stats.write().clear();
+            detector.set(Detector::default());
+            detection.set(None);
+            triggers.write().clear();
+            rate_alerts.write().clear();
             connection_status.set(ConnectionStatus::Ready);
         });
     };
@@ -321,9 +1427,9 @@ fn App() -> Element {
                             Ok(None) => continue, // still running
                             Err(e) => {
                                 login_process.set(None);
-                                connection_status.set(ConnectionStatus::Error(format!(
-                                    "Login error: {}",
-                                    e
+                                connection_status.set(ConnectionStatus::Error(tf(
+                                    "status.login_error",
+                                    &[("error", &e)],
                                 )));
                                 break;
                             }
@@ -332,51 +1438,960 @@ fn App() -> Element {
                 });
             }
             Err(e) => {
-                connection_status
-                    .set(ConnectionStatus::Error(format!("Failed to start login: {}", e)));
+                connection_status.set(ConnectionStatus::Error(tf(
+                    "status.login_failed",
+                    &[("error", &e)],
+                )));
             }
         }
     };
 
-    let on_cancel_login = move |_| {
-        spawn(async move {
-            if let Some(process) = login_process() {
-                let mut proc = process.lock().await;
-                let _ = proc.kill().await;
-            }
-            login_process.set(None);
-            connection_status.set(ConnectionStatus::NotAuthenticated);
-        });
-    };
+    // The first successful connection completes the first-run guide
+    use_effect(move || {
+        if connection_status() != ConnectionStatus::Streaming {
+            return;
+        }
+        let app = selected_app();
+        if onboarding.peek().completed && onboarding.peek().app == app {
+            return;
+        }
+        onboarding.set(Onboarding {
+            completed: true,
+            app,
+        });
+        if let Err(e) = onboarding.peek().save() {
+            show_notice(format!("{:#}", e));
+        }
+    });
+
+    let on_skip_onboarding = move |_| {
+        onboarding.write().completed = true;
+        if let Err(e) = onboarding.peek().save() {
+            show_notice(format!("{:#}", e));
+        }
+    };
+
+    let on_retry_cli = move |_| {
+        spawn(async move {
+            init_heroku(connection_status, available_apps).await;
+        });
+    };
+
+    let on_cancel_login = move |_| {
+        spawn(async move {
+            if let Some(process) = login_process() {
+                let mut proc = process.lock().await;
+                let _ = proc.kill().await;
+            }
+            login_process.set(None);
+            connection_status.set(ConnectionStatus::NotAuthenticated);
+        });
+    };
+
+    // Sidecar checksum manifest for an export, when enabled in settings;
+    // returns the notice reporting the export, given the one saying where
+    // it was saved
+    let manifest_notice = move |saved: String,
+                                path: &std::path::Path,
+                                contents: &[u8],
+                                entries: &[LogEntry],
+                                filters: Vec<String>| {
+        if !settings.read().export_manifest {
+            return saved;
+        }
+        let manifest = Manifest::new(path, contents, entries)
+            .app(selected_app())
+            .filters(filters);
+        match manifest.write(path) {
+            Ok(sidecar) => format!("{} ({})", saved, sidecar.display()),
+            Err(e) => tf(
+                "notice.manifest_failed",
+                &[("saved", &saved), ("error", &format!("{:#}", e))],
+            ),
+        }
+    };
+
+    // Copy an export, and its manifest if one was written, to the bucket
+    // set in `upload_to`, then put the object's URL on the clipboard
+    let upload_export = move |path: std::path::PathBuf| {
+        let Some(destination) = settings.read().upload_to.clone() else {
+            return;
+        };
+        let manifest = Some(Manifest::sidecar_path(&path))
+            .filter(|sidecar| settings.read().export_manifest && sidecar.exists());
+        spawn(async move {
+            if let Some(manifest) = manifest {
+                if let Err(e) = destination.upload(&manifest).await {
+                    show_notice(format!("{:#}", e));
+                    return;
+                }
+            }
+            match destination.upload(&path).await {
+                Ok(url) => {
+                    let url_js = serde_json::to_string(&url).unwrap_or_default();
+                    let _ = document::eval(&format!("navigator.clipboard.writeText({})", url_js));
+                    show_notice(tf("notice.uploaded", &[("url", &url)]));
+                }
+                Err(e) => show_notice(format!("{:#}", e)),
+            }
+        });
+    };
+
+    let mut export_timeline = move || {
+        let path = export::default_export_path("timeline", "csv");
+        let logs = all_logs.read();
+        let csv = export::timeline_csv(&logs);
+        match std::fs::write(&path, &csv) {
+            Ok(_) => {
+                audit("export", path.display().to_string());
+                show_notice(manifest_notice(
+                    tf("notice.timeline_exported", &[("path", &path.display())]),
+                    &path,
+                    csv.as_bytes(),
+                    &logs,
+                    Vec::new(),
+                ));
+                upload_export(path);
+            }
+            Err(e) => show_notice(tf("notice.export_failed", &[("error", &e)])),
+        }
+    };
+    let on_export_timeline = move |_| export_timeline();
+
+    let mut export_lines = move |path: Option<String>, passphrase: Option<String>| {
+        let logs = shown_logs.read();
+        let lines = export::raw_lines(&logs);
+        let extension = if passphrase.is_some() {
+            "log.enc"
+        } else {
+            "log"
+        };
+        let path = match path {
+            Some(path) => match export::export_destination(&path, "logs", extension) {
+                Ok(path) => path,
+                Err(e) => {
+                    return show_notice(tf(
+                        "notice.export_failed",
+                        &[("error", &format!("{:#}", e))],
+                    ))
+                }
+            },
+            None => export::default_export_path("logs", extension),
+        };
+        let contents = match passphrase {
+            Some(passphrase) => match crypt::encrypt(lines.as_bytes(), &passphrase) {
+                Ok(sealed) => sealed,
+                Err(e) => {
+                    return show_notice(tf(
+                        "notice.export_failed",
+                        &[("error", &format!("{:#}", e))],
+                    ))
+                }
+            },
+            None => lines.into_bytes(),
+        };
+        let filters = filter_engine
+            .read()
+            .active_filters()
+            .iter()
+            .map(Filter::display)
+            .collect();
+        match std::fs::write(&path, &contents) {
+            Ok(_) => {
+                audit("export", path.display().to_string());
+                show_notice(manifest_notice(
+                    tf(
+                        "notice.lines_exported",
+                        &[("count", &logs.len()), ("path", &path.display())],
+                    ),
+                    &path,
+                    &contents,
+                    &logs,
+                    filters,
+                ));
+                upload_export(path);
+            }
+            Err(e) => {
+                let error = format!("{}: {}", path.display(), e);
+                show_notice(tf("notice.export_failed", &[("error", &error)]))
+            }
+        }
+    };
+
+    let mut start_share = move |port: u16| {
+        if let Some((_, _, url)) = share.peek().as_ref() {
+            return show_notice(tf("notice.already_sharing", &[("url", url)]));
+        }
+        let token = match share::new_token() {
+            Ok(token) => token,
+            Err(e) => {
+                return show_notice(tf("notice.share_failed", &[("error", &format!("{:#}", e))]))
+            }
+        };
+        spawn(async move {
+            let listener = match tokio::net::TcpListener::bind(("0.0.0.0", port)).await {
+                Ok(listener) => listener,
+                Err(e) => {
+                    return show_notice(tf(
+                        "notice.share_port_failed",
+                        &[("port", &port), ("error", &e)],
+                    ))
+                }
+            };
+            // Filled in by the refresh loop within a second
+            let view = SharedView::new(std::sync::RwLock::new(share::view_json(
+                &[],
+                &[],
+                &settings.peek().time_format,
+            )));
+            let url = share::share_url(port, &token);
+            let server = spawn({
+                let view = view.clone();
+                async move {
+                    if let Err(e) = share::serve(listener, token, view).await {
+                        share.set(None);
+                        show_notice(tf(
+                            "notice.share_stopped",
+                            &[("error", &format!("{:#}", e))],
+                        ));
+                    }
+                }
+            });
+            audit("share", url.clone());
+            show_notice(tf("notice.sharing", &[("url", &url)]));
+            share.set(Some((server, view, url)));
+        });
+    };
+
+    // The lead-up to a platform event and a little after, with the event's
+    // line open in the detail pane
+    let mut on_select_platform_event = move |event: PlatformEvent| {
+        let at = event.entry.timestamp;
+        filter_engine.write().set_time_range(
+            Some(Bound::At(at - chrono::Duration::minutes(2))),
+            Some(Bound::At(at + chrono::Duration::minutes(1))),
+        );
+        selected_entry.set(Some(event.entry));
+    };
+    let mut jump_to_crash = move || {
+        let crash = platform_events.read().latest_crash().cloned();
+        match crash {
+            Some(crash) => on_select_platform_event(crash),
+            None => show_notice(t("notice.no_crashes").to_string()),
+        }
+    };
+
+    let mut run_command = move |command: Command| match command {
+        Command::Await(query) => {
+            let terms = filters::parse_query(&query);
+            if terms.is_empty() {
+                show_notice(tf("notice.nothing_to_await", &[("query", &query)]));
+                return;
+            }
+            show_notice(tf("notice.awaiting", &[("query", &query)]));
+            triggers.write().add(query, terms);
+        }
+        Command::Alert {
+            query,
+            factor,
+            baseline,
+        } => {
+            let terms = filters::parse_query(&query);
+            if terms.is_empty() {
+                show_notice(tf("notice.nothing_to_alert_on", &[("query", &query)]));
+                return;
+            }
+            show_notice(tf(
+                "notice.alerting_rate",
+                &[
+                    ("query", &query),
+                    ("factor", &factor),
+                    ("minutes", &(baseline.as_secs() / 60)),
+                ],
+            ));
+            rate_alerts.write().add(RateAlert::new(
+                query,
+                terms,
+                factor,
+                baseline,
+                chrono::Local::now().timestamp(),
+            ));
+        }
+        Command::AlertRule {
+            query,
+            threshold,
+            window,
+        } => {
+            let Some(rule) = AlertRule::new(&query, threshold, window) else {
+                show_notice(tf("notice.nothing_to_alert_on", &[("query", &query)]));
+                return;
+            };
+            let line = rule.line();
+            alert_rules.write().add(rule);
+            match alert_rules.peek().save() {
+                Ok(()) => show_notice(tf("notice.alert_rule_saved", &[("rule", &line)])),
+                Err(e) => show_notice(format!("{:#}", e)),
+            }
+        }
+        Command::ClearAlerts => {
+            rate_alerts.write().clear();
+            let had_rules = !alert_rules.peek().is_empty();
+            alert_rules.write().clear();
+            match had_rules.then(|| alert_rules.peek().save()) {
+                Some(Err(e)) => show_notice(format!("{:#}", e)),
+                _ => show_notice(t("notice.alerts_removed").to_string()),
+            }
+        }
+        Command::Snooze(length) => {
+            let message = match length {
+                Some(length) => {
+                    let until = chrono::Local::now().timestamp() + length.as_secs() as i64;
+                    snooze.write().snooze(until);
+                    tf(
+                        "notice.snoozed_until",
+                        &[("time", &snooze::clock_time(until))],
+                    )
+                }
+                None => {
+                    snooze.write().wake();
+                    t("notice.alerts_on").to_string()
+                }
+            };
+            match snooze.peek().save() {
+                Ok(_) => show_notice(message),
+                Err(e) => show_notice(format!("{:#}", e)),
+            }
+        }
+        Command::Mute(query) => {
+            if !snooze.write().mute(&query) {
+                show_notice(tf("notice.already_muted", &[("query", &query)]));
+                return;
+            }
+            match snooze.peek().save() {
+                Ok(_) => show_notice(tf("notice.muted", &[("query", &query)])),
+                Err(e) => show_notice(format!("{:#}", e)),
+            }
+        }
+        Command::Unmute(query) => {
+            if !snooze.write().unmute(&query) {
+                show_notice(tf("notice.not_muted", &[("query", &query)]));
+                return;
+            }
+            match snooze.peek().save() {
+                Ok(_) => show_notice(tf("notice.unmuted", &[("query", &query)])),
+                Err(e) => show_notice(format!("{:#}", e)),
+            }
+        }
+        Command::Highlight(query) => {
+            match query {
+                Some(query) => {
+                    if let Err(message) = highlights.write().add(&query) {
+                        show_notice(message);
+                        return;
+                    }
+                }
+                None => highlights.write().clear(),
+            }
+            if let Err(e) = highlights.peek().save() {
+                show_notice(format!("{:#}", e));
+            }
+        }
+        Command::Replay(path) => start_replay(std::path::Path::new(&path), None),
+        Command::Format { format, reparse } => {
+            format_override.set(format);
+            show_notice(format_notice(format));
+            if reparse {
+                reparse_buffer();
+            }
+        }
+        Command::Sort(order) if order.is_some() && !frozen() => {
+            show_notice(t("notice.pause_to_sort").to_string())
+        }
+        Command::Sort(order) => sort_order.set(order),
+        Command::Export { path, encrypt } => {
+            if encrypt {
+                pending_encrypted_export.set(Some(path));
+            } else {
+                export_lines(path, None);
+            }
+        }
+        Command::Note(_) if recorder.peek().is_none() => {
+            show_notice(t("notice.record_for_notes").to_string())
+        }
+        Command::Note(text) => {
+            audit("note", text);
+            show_notice(t("notice.note_added").to_string());
+        }
+        Command::Share(Some(port)) => start_share(port),
+        Command::Share(None) => match share.take() {
+            Some((server, _, _)) => {
+                server.cancel();
+                audit("share", "stopped".to_string());
+                show_notice(t("notice.share_ended").to_string());
+            }
+            None => show_notice(t("notice.not_sharing").to_string()),
+        },
+        Command::Preset(name) => {
+            let Some(query) = presets.read().get(&name).map(str::to_string) else {
+                show_notice(tf("notice.no_preset", &[("name", &name)]));
+                return;
+            };
+            {
+                let mut engine = filter_engine.write();
+                engine.clear_filters();
+                for filter in filters::parse_query(&query) {
+                    engine.add_filter(filter);
+                }
+            }
+            show_notice(tf("notice.preset", &[("name", &name), ("query", &query)]));
+        }
+        Command::SavePreset(name) => {
+            let query = filter_engine.read().query();
+            if query.is_empty() {
+                show_notice(t("notice.preset_empty").to_string());
+                return;
+            }
+            presets.write().set(&name, &query);
+            match presets.peek().save() {
+                Ok(_) => show_notice(tf(
+                    "notice.preset_saved",
+                    &[("name", &name), ("query", &query)],
+                )),
+                Err(e) => show_notice(format!("{:#}", e)),
+            }
+        }
+        Command::DeletePreset(name) => {
+            if !presets.write().remove(&name) {
+                show_notice(tf("notice.no_preset", &[("name", &name)]));
+                return;
+            }
+            match presets.peek().save() {
+                Ok(_) => show_notice(tf("notice.preset_deleted", &[("name", &name)])),
+                Err(e) => show_notice(format!("{:#}", e)),
+            }
+        }
+        Command::ListPresets => {
+            let names = presets.read().names().join(", ");
+            show_notice(if names.is_empty() {
+                t("notice.no_presets").to_string()
+            } else {
+                tf("notice.presets", &[("names", &names)])
+            });
+        }
+        Command::JumpToCrash => jump_to_crash(),
+        Command::EditSettings => {
+            let open = saved_settings.peek().is_some();
+            saved_settings.set((!open).then(settings::saved_values));
+        }
+        Command::CheckUpdates => {
+            show_notice(t("notice.checking_updates").to_string());
+            spawn(async move {
+                match updates::check().await {
+                    Ok(Some(release)) => available_update.set(Some(release)),
+                    Ok(None) => show_notice(tf(
+                        "notice.latest_version",
+                        &[("version", &updates::CURRENT_VERSION)],
+                    )),
+                    Err(e) => show_notice(format!("{:#}", e)),
+                }
+            });
+        }
+        Command::Reparse => {
+            level_rules.set(LevelRules::load());
+            thresholds.set(ThresholdRules::load());
+            route_stats.set(RouteStats::load());
+            reparse_buffer();
+        }
+        Command::Count { query, since } => {
+            let terms = filters::parse_query(&query);
+            let logs = all_logs.read();
+            let cutoff = since
+                .and_then(|age| chrono::Duration::from_std(age).ok())
+                .map(|age| (chrono::Utc::now() - age).fixed_offset());
+            let count = filters::count_matches(&logs, &terms, cutoff);
+
+            // Rate over the requested window, or over the whole buffer
+            let window_secs = match (since, logs.first(), logs.last()) {
+                (Some(age), _, _) => age.as_secs_f64(),
+                (None, Some(first), Some(last)) => {
+                    (last.timestamp - first.timestamp).num_milliseconds() as f64 / 1000.0
+                }
+                _ => 0.0,
+            };
+            let label = if query.is_empty() {
+                t("notice.entries")
+            } else {
+                query.as_str()
+            };
+            let mut text = format!("{} {}", count, label);
+            if window_secs > 0.0 {
+                text.push_str(&format!(" ({:.1}/min)", count as f64 * 60.0 / window_secs));
+            }
+            show_notice(text);
+        }
+    };
+
+    let mut on_add_filter = move |input: String| {
+        if let Some(command) = parse_command(&input) {
+            filter_engine.write().cancel_preview();
+            match command {
+                Ok(command) => run_command(command),
+                Err(message) => show_notice(message),
+            }
+            return;
+        }
+        let mut engine = filter_engine.write();
+        for filter in filters::parse_query(&input) {
+            engine.add_filter(filter);
+        }
+    };
+
+    let on_select_request = move |id: String| {
+        filter_engine.write().add_filter(Filter::RequestId(id));
+    };
+
+    let on_preview_filter = move |input: String| {
+        // Commands are run on Enter, never previewed as filters
+        let preview = if commands::is_command(&input) {
+            Vec::new()
+        } else {
+            filters::parse_query(&input)
+        };
+        filter_engine.write().set_preview(preview);
+    };
+
+    let on_unpin = move |idx: usize| {
+        let mut entries = pinned.write();
+        if idx < entries.len() {
+            entries.remove(idx);
+        }
+    };
+
+    let on_pin = move |entry: LogEntry| {
+        let mut entries = pinned.write();
+        if entries.iter().any(|pinned| pinned.raw == entry.raw) {
+            return;
+        }
+        if entries.len() >= MAX_PINNED {
+            drop(entries);
+            show_notice(tf("notice.pin_limit", &[("max", &MAX_PINNED)]));
+            return;
+        }
+        entries.push(entry);
+    };
+
+    let on_ignore = move |entry: LogEntry| {
+        filter_tracker.write().reset();
+        ignore_list.write().add(&entry);
+        match ignore_list.read().save() {
+            Ok(_) => show_notice(tf(
+                "notice.ignoring",
+                &[("pattern", &ignore::fingerprint(&entry))],
+            )),
+            Err(e) => show_notice(format!("{:#}", e)),
+        }
+    };
 
-    let on_add_filter = move |input: String| {
-        if let Some(filter) = parse_filter(&input) {
-            let mut current_filters = filters();
-            current_filters.push(filter);
-            filters.set(current_filters);
+    let on_unignore = move |idx: usize| {
+        filter_tracker.write().reset();
+        ignore_list.write().remove(idx);
+        if let Err(e) = ignore_list.read().save() {
+            show_notice(format!("{:#}", e));
         }
     };
 
-    let on_clear_filters = move |_| {
-        filters.set(Vec::new());
+    // Keep the minimap's viewport indicator in line with the log view
+    let measure_viewport = move |_| {
+        spawn(async move {
+            if let Ok(measured) = document::eval(VIEWPORT_JS).join::<(f64, f64)>().await {
+                viewport.set(measured);
+            }
+        });
+    };
+
+    // Remember the row at the top once the user scrolls up from the end
+    let measure_anchor = move || {
+        spawn(async move {
+            let Ok((at_end, index, offset)) =
+                document::eval(ANCHOR_JS).join::<(bool, usize, f64)>().await
+            else {
+                return;
+            };
+            let anchor = if at_end {
+                None
+            } else {
+                let logs = shown_logs.peek();
+                match scroll_anchor.peek().as_ref() {
+                    Some(anchor) => anchor.moved(&logs, index, offset),
+                    None => ScrollAnchor::new(&logs, index, offset),
+                }
+            };
+            scroll_anchor.set(anchor);
+        });
+    };
+    let on_list_scroll = move |_| {
+        measure_viewport(());
+        measure_anchor();
+    };
+
+    // When the list changes, follow its end or put the row being read back
+    // where it was
+    use_effect(move || {
+        let logs = shown_logs.read();
+        let located = scroll_anchor
+            .write()
+            .as_mut()
+            .map(|anchor| (anchor.locate(&logs), anchor.offset));
+        match located {
+            None => {
+                let _ = document::eval(FOLLOW_JS);
+            }
+            Some((Some(index), offset)) => {
+                let _ = document::eval(&format!(
+                    "const view = document.querySelector('.log-view');
+                     const row = document.getElementById('log-{}');
+                     if (view && row) view.scrollTop += row.getBoundingClientRect().top - view.getBoundingClientRect().top + {};",
+                    index, offset
+                ));
+            }
+            // The row is gone, e.g. filtered out; read from wherever the
+            // view is now
+            Some((None, _)) => {
+                scroll_anchor.set(None);
+                measure_anchor();
+            }
+        }
+        measure_viewport(());
+    });
+
+    let jump_to_end = move |_| {
+        scroll_anchor.set(None);
+        let _ = document::eval(FOLLOW_JS);
+    };
+
+    let on_seek = move |idx: usize| {
+        let _ = document::eval(&format!(
+            "document.getElementById('log-{}')?.scrollIntoView({{block: 'start'}})",
+            idx
+        ));
     };
 
+    let on_cancel_preview = move |_| {
+        filter_engine.write().cancel_preview();
+    };
+
+    // Clearing is destructive, so the first request only arms it; a second
+    // request within the timeout actually clears.
+    let mut request_clear = move || {
+        if confirm_clear() {
+            confirm_clear.set(false);
+            filter_engine.write().clear_filters();
+        } else {
+            confirm_clear.set(true);
+            spawn(async move {
+                tokio::time::sleep(CLEAR_CONFIRM_TIMEOUT).await;
+                confirm_clear.set(false);
+            });
+        }
+    };
+
+    let on_clear_filters = move |_| request_clear();
+
     let on_toggle_mode = move |_| {
-        filter_mode_and.set(!filter_mode_and());
+        filter_engine.write().toggle_mode();
+    };
+
+    let on_toggle_recording = move |_| {
+        if let Some(active) = recorder.write().take() {
+            let saved = active.finish().and_then(|path| {
+                let contents = std::fs::read(&path)?;
+                let session = Session::load(&path)?;
+                Ok((path, contents, session))
+            });
+            match saved {
+                Ok((path, contents, session)) => {
+                    let entries: Vec<LogEntry> = session
+                        .lines()
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, line)| replayed_entry(&line.raw, idx + 1))
+                        .collect();
+                    show_notice(manifest_notice(
+                        tf("notice.session_saved", &[("path", &path.display())]),
+                        &path,
+                        &contents,
+                        &entries,
+                        Vec::new(),
+                    ));
+                    upload_export(path);
+                }
+                Err(e) => show_notice(format!("{:#}", e)),
+            }
+            return;
+        }
+        match Recorder::start(export::default_export_path("session", "log")) {
+            Ok(started) => {
+                recorder.set(Some(started));
+                let filters = current_filters();
+                audited_filters.set(filters.clone());
+                audit("filters", filters);
+            }
+            Err(e) => show_notice(format!("{:#}", e)),
+        }
+    };
+
+    let on_replay_seek = move |position: usize| {
+        let Some(lines) = replay.write().as_mut().map(|r| r.seek(position)) else {
+            return;
+        };
+        all_logs.write().clear();
+        coalescer.set(Coalescer::default());
+        for (idx, line) in lines.into_iter().enumerate() {
+            ingest(replayed_entry(&line, idx + 1));
+        }
+    };
+
+    let on_open_ref = move |source_ref: SourceRef| {
+        let template = settings.read().open_command.clone();
+        let Some(argv) =
+            source_refs::open_command(template.as_deref(), &source_ref.file, source_ref.line)
+        else {
+            show_notice(t("notice.no_open_command").to_string());
+            return;
+        };
+        if let Err(e) = std::process::Command::new(&argv[0])
+            .args(&argv[1..])
+            .spawn()
+        {
+            show_notice(tf(
+                "notice.run_failed",
+                &[("command", &argv[0]), ("error", &e)],
+            ));
+        }
+    };
+
+    let mut on_open_url = move |url: String| {
+        let argv = requests::browser_command(&url);
+        if let Err(e) = std::process::Command::new(&argv[0])
+            .args(&argv[1..])
+            .spawn()
+        {
+            show_notice(tf("notice.open_failed", &[("url", &url), ("error", &e)]));
+        }
+    };
+
+    let on_save_setting = move |(name, value): (String, String)| {
+        let value = value.trim();
+        let saved = if value.is_empty() {
+            settings::reset_value(&name)
+        } else if Settings::accepts(&name, value) {
+            settings::save_value(&name, value)
+        } else {
+            show_notice(tf(
+                "notice.invalid_value",
+                &[("name", &name), ("value", &value)],
+            ));
+            return;
+        };
+        if let Err(e) = saved {
+            show_notice(format!("{:#}", e));
+            return;
+        }
+        let loaded = Settings::load();
+        if name == "theme" {
+            theme.set(loaded.theme.clone());
+        }
+        settings.set(loaded);
+        saved_settings.set(Some(settings::saved_values()));
+        show_notice(tf("notice.saved", &[("name", &name)]));
+    };
+
+    let on_save_key = move |(name, key): (String, String)| {
+        let key = key.trim();
+        if !key.is_empty() && KeyBinding::parse(key).is_none() {
+            show_notice(tf("notice.invalid_key", &[("name", &name), ("key", &key)]));
+            return;
+        }
+        if let Err(e) = keymap::save_binding(&name, (!key.is_empty()).then_some(key)) {
+            show_notice(format!("{:#}", e));
+            return;
+        }
+        let (loaded, problems) = KeyMap::load();
+        keymap.set(loaded);
+        show_notice(if problems.is_empty() {
+            tf("notice.saved", &[("name", &name)])
+        } else {
+            tf("notice.keys_file", &[("problems", &problems.join("; "))])
+        });
+    };
+
+    // Look for a newer release once per start; failing quietly, as when
+    // offline
+    use_future(move || async move {
+        if extra_window || !settings.peek().check_updates {
+            return;
+        }
+        if let Ok(Some(release)) = updates::check().await {
+            available_update.set(Some(release));
+        }
+    });
+
+    let mut dismiss_crash_report = move || {
+        if let Some(path) = crash_report() {
+            crash_report.set(None);
+            if let Err(e) = crash::mark_seen(&path) {
+                show_notice(format!("{:#}", e));
+            }
+        }
+    };
+
+    let on_report_crash = move |_| {
+        if let Some(path) = crash_report() {
+            match std::fs::read_to_string(&path) {
+                Ok(report) => on_open_url(crash::issue_url(&report)),
+                Err(e) => show_notice(tf(
+                    "notice.read_failed",
+                    &[("path", &path.display()), ("error", &e)],
+                )),
+            }
+        }
+        dismiss_crash_report();
+    };
+
+    // Commands sent by `logs-parser-desktop ctl ...`, handled by the first
+    // window only
+    use_future(move || {
+        let status = shared_status.clone();
+        async move {
+            if !settings.read().control_socket || extra_window {
+                return;
+            }
+            let (tx, mut rx) = mpsc::unbounded_channel::<ControlCommand>();
+            spawn(async move {
+                while let Some(command) = rx.recv().await {
+                    match command {
+                        ControlCommand::AddFilter(query) => on_add_filter(query),
+                        ControlCommand::ClearFilters => filter_engine.write().clear_filters(),
+                        ControlCommand::Jump(time) => {
+                            let index = JumpTarget::parse(&time)
+                                .and_then(|target| target.find(&shown_logs.read()));
+                            match index {
+                                Some(idx) => {
+                                    let _ = document::eval(&format!(
+                                        "document.getElementById('log-{}')?.scrollIntoView({{block: 'center'}})",
+                                        idx
+                                    ));
+                                }
+                                None => {
+                                    show_notice(tf("notice.no_entries_after", &[("time", &time)]))
+                                }
+                            }
+                        }
+                        ControlCommand::ExportTimeline => export_timeline(),
+                        // Answered by the server from the shared status line
+                        ControlCommand::Status => {}
+                    }
+                }
+            });
+            if let Err(e) = control::serve(&control::socket_path(), tx, status).await {
+                show_notice(format!("{:#}", e));
+            }
+        }
+    });
+
+    // Put the selected line, or else the focused one, on the clipboard
+    let mut copy_entry = move |as_json: bool| {
+        let Some(entry) = selected_entry().or(focused_entry()) else {
+            show_notice(t("notice.select_to_copy").to_string());
+            return;
+        };
+        let text = if as_json {
+            export::entry_json(&entry)
+        } else {
+            entry.raw.clone()
+        };
+        let text_js = serde_json::to_string(&text).unwrap_or_default();
+        let _ = document::eval(&format!("navigator.clipboard.writeText({})", text_js));
+        show_notice(if as_json {
+            t("notice.copied_json").to_string()
+        } else {
+            t("notice.copied_line").to_string()
+        });
+    };
+
+    let on_copy_session_link = move |command: String| {
+        let command_js = serde_json::to_string(&command).unwrap_or_default();
+        let _ = document::eval(&format!("navigator.clipboard.writeText({})", command_js));
+        show_notice(tf("notice.copied", &[("text", &command)]));
     };
 
     let on_theme_change = move |new_theme: String| {
-        write_theme(&new_theme);
+        if let Err(e) = settings::save_value("theme", &new_theme) {
+            show_notice(format!("{:#}", e));
+        }
         theme.set(new_theme);
     };
 
+    let on_key = move |evt: Event<KeyboardData>| {
+        if evt.key() == Key::Escape {
+            confirm_clear.set(false);
+            selected_entry.set(None);
+            return;
+        }
+        match keymap.read().action_for(&evt.key(), evt.modifiers()) {
+            Some(Action::ClearFilters) => request_clear(),
+            Some(Action::ToggleFilterMode) => filter_engine.write().toggle_mode(),
+            Some(Action::ToggleZenMode) => zen_mode.set(!zen_mode()),
+            Some(Action::ToggleElapsed) => show_elapsed.set(!show_elapsed()),
+            Some(Action::ToggleSqlHighlight) => sql_highlight.set(!sql_highlight()),
+            Some(Action::ToggleRequests) => show_requests.set(!show_requests()),
+            Some(Action::ToggleMetrics) => show_metrics.set(!show_metrics()),
+            Some(Action::ToggleDynoColors) => dyno_colors.set(!dyno_colors()),
+            Some(Action::ToggleIgnored) => show_ignored.set(!show_ignored()),
+            Some(Action::TogglePlatform) => {
+                show_platform.set(!show_platform());
+                platform_events.write().mark_seen();
+            }
+            Some(Action::JumpToCrash) => jump_to_crash(),
+            Some(Action::TogglePause) => toggle_pause(),
+            Some(Action::CycleFormat) => cycle_format(),
+            Some(Action::ToggleOrder) => toggle_order(),
+            Some(Action::ExportPrompt) => filter_prompt.set(Some(":export ".to_string())),
+            Some(Action::CopySelected) => copy_entry(false),
+            Some(Action::CopySelectedJson) => copy_entry(true),
+            Some(Action::ShowErrors) => filter_engine
+                .write()
+                .set_level_threshold(Some(LogLevel::Error)),
+            Some(Action::ShowWarnings) => filter_engine
+                .write()
+                .set_level_threshold(Some(LogLevel::Warn)),
+            Some(Action::ShowAllLevels) => filter_engine.write().set_level_threshold(None),
+            None => {}
+        }
+    };
+
     let total_logs = all_logs().len();
     let filtered_count = filtered_logs().len();
+    let engine = filter_engine();
+    let preview_count = engine.has_preview().then_some(filtered_count);
     let is_connected = matches!(
         connection_status(),
         ConnectionStatus::Streaming | ConnectionStatus::Reconnecting(_)
     );
     let is_connecting = matches!(connection_status(), ConnectionStatus::Connecting);
+    let onboarding_step = if onboarding.read().completed {
+        None
+    } else {
+        match connection_status() {
+            ConnectionStatus::CliMissing => Some(OnboardingStep::InstallCli),
+            ConnectionStatus::NotAuthenticated | ConnectionStatus::LoggingIn => {
+                Some(OnboardingStep::Login)
+            }
+            ConnectionStatus::Ready if selected_app().is_none() => Some(OnboardingStep::PickApp),
+            ConnectionStatus::Ready | ConnectionStatus::Connecting => Some(OnboardingStep::Connect),
+            _ => None,
+        }
+    };
     let is_auth_error = matches!(connection_status(), ConnectionStatus::NotAuthenticated);
     let is_logging_in = matches!(connection_status(), ConnectionStatus::LoggingIn);
 
@@ -384,50 +2399,406 @@ fn App() -> Element {
         style { {include_str!("styles.css")} }
 
         div {
-            class: format!("app-container theme-{}", theme()),
+            class: format!(
+                "app-container theme-{}{}",
+                theme(),
+                if flash() { " flash" } else { "" }
+            ),
+            tabindex: "0",
+            onkeydown: on_key,
+            onmounted: move |evt| async move {
+                let _ = evt.set_focus(true).await;
+            },
 
             // Status Indicator
             StatusIndicator {
                 status: connection_status(),
+                notice: notice(),
+            }
+
+            // Zen mode keeps only the status line and the log list
+            if !zen_mode() {
+                // Connection Panel
+                ConnectionPanel {
+                    available_apps: available_apps(),
+                    selected_app: selected_app(),
+                    is_connected: is_connected,
+                    is_connecting: is_connecting,
+                    is_auth_error: is_auth_error,
+                    is_logging_in: is_logging_in,
+                    on_app_select: on_app_select,
+                    on_connect: on_connect,
+                    on_disconnect: on_disconnect,
+                    on_login: on_login,
+                    on_cancel_login: on_cancel_login,
+                    theme: theme(),
+                    on_theme_change: on_theme_change,
+                }
+
+                // Stats Header
+                StatsHeader {
+                    layout: status_layout(),
+                    app_name: selected_app(),
+                    total_logs: total_logs,
+                    filtered_logs: filtered_count,
+                    filter_count: engine.active_filters().len(),
+                    filter_mode_and: engine.is_and_mode(),
+                    rate: recent_rate(&all_logs.read()),
+                    activity: activity(),
+                    parse_stats: parse_stats(),
+                    platform_unseen: platform_events.read().unseen(),
+                    platform_crashes: platform_events.read().unseen_crashes(),
+                    last_alert: last_alert(),
+                }
+
+                // Filter Bar
+                FilterBar {
+                    filters: engine.filters().to_vec(),
+                    enabled: (0..engine.filters().len()).map(|idx| engine.is_enabled(idx)).collect(),
+                    on_toggle_filter: move |idx: usize| filter_engine.write().toggle_filter(idx),
+                    on_remove_filter: move |idx: usize| filter_engine.write().remove_filter(idx),
+                    awaiting: triggers.read().queries(),
+                    highlights: highlights(),
+                    preview_count: preview_count,
+                    on_add_filter: on_add_filter,
+                    on_preview_filter: on_preview_filter,
+                    on_cancel_preview: on_cancel_preview,
+                    on_clear_filters: on_clear_filters,
+                    on_clear_time_range: move |_| filter_engine.write().set_time_range(None, None),
+                    confirm_clear: confirm_clear(),
+                    clear_key: keymap
+                        .read()
+                        .binding(Action::ClearFilters)
+                        .map(|b| b.display())
+                        .unwrap_or_default(),
+                    on_toggle_mode: on_toggle_mode,
+                    on_export_timeline: on_export_timeline,
+                    recording: recorder.read().is_some(),
+                    on_toggle_recording: on_toggle_recording,
+                    filter_mode_and: engine.is_and_mode(),
+                    prompt: filter_prompt,
+                }
+            }
+
+            if pending_encrypted_export().is_some() {
+                PassphrasePrompt {
+                    on_submit: move |passphrase| {
+                        if let Some(path) = pending_encrypted_export.take() {
+                            export_lines(path, Some(passphrase));
+                        }
+                    },
+                    on_cancel: move |_| pending_encrypted_export.set(None),
+                }
+            }
+
+            if let Some(state) = replay() {
+                ReplayBar {
+                    position: state.position(),
+                    len: state.len(),
+                    playing: state.playing,
+                    speed: state.speed,
+                    on_toggle_play: move |_| {
+                        if let Some(r) = replay.write().as_mut() {
+                            r.playing = !r.playing;
+                        }
+                    },
+                    on_seek: on_replay_seek,
+                    on_speed: move |speed| {
+                        if let Some(r) = replay.write().as_mut() {
+                            r.speed = speed;
+                        }
+                    },
+                    on_close: move |_| replay.set(None),
+                }
+            }
+
+            PinnedStrip {
+                entries: pinned(),
+                time_format: settings.read().time_format.clone(),
+                on_unpin: on_unpin,
+            }
+
+            if show_requests() {
+                RequestsPanel {
+                    requests: request_index.read().recent(50),
+                    routes: route_stats.read().busiest(50),
+                    router: router_metrics.read().summary(),
+                    on_select: on_select_request,
+                }
+            }
+
+            if show_metrics() {
+                MetricsPanel {
+                    charts: addon_metrics
+                        .read()
+                        .charts()
+                        .into_iter()
+                        .chain(app_metrics.read().charts())
+                        .collect::<Vec<_>>(),
+                }
+            }
+
+            if let Some(saved) = saved_settings() {
+                SettingsPanel {
+                    settings: Settings::defaults()
+                        .into_iter()
+                        .map(|(name, default)| {
+                            let value = saved
+                                .iter()
+                                .rev()
+                                .find(|(n, _)| n == name)
+                                .map(|(_, v)| v.clone())
+                                .unwrap_or_default();
+                            (name.to_string(), value, default)
+                        })
+                        .collect::<Vec<_>>(),
+                    keys: keymap
+                        .read()
+                        .named_bindings()
+                        .into_iter()
+                        .zip(KeyMap::default().named_bindings())
+                        .map(|((name, key), (_, default))| (name.to_string(), key, default))
+                        .collect::<Vec<_>>(),
+                    on_save_setting: on_save_setting,
+                    on_save_key: on_save_key,
+                    on_close: move |_| saved_settings.set(None),
+                }
+            }
+
+            if show_platform() {
+                PlatformPanel {
+                    events: platform_events.read().recent(),
+                    time_format: settings.read().time_format.clone(),
+                    on_select: on_select_platform_event,
+                    on_jump_crash: move |_| jump_to_crash(),
+                }
+            }
+
+            if show_ignored() {
+                IgnoredPanel {
+                    fingerprints: ignore_list.read().fingerprints().to_vec(),
+                    on_remove: on_unignore,
+                }
+            }
+
+            if paused() {
+                div {
+                    class: "pause-banner",
+                    {tf(
+                        "banner.paused",
+                        &[
+                            ("count", &held.read().len()),
+                            ("key", &keymap.read().binding(Action::TogglePause).map(|b| b.display()).unwrap_or_default()),
+                        ],
+                    )}
+                }
+            }
+
+            {
+                let snoozed_until = snooze.read().until(chrono::Local::now().timestamp());
+                let muted = snooze.read().muted().join("\", \"");
+                rsx! {
+                    if snoozed_until.is_some() || !muted.is_empty() {
+                        div {
+                            class: "pause-banner snooze-banner",
+                            "🔕 "
+                            if let Some(until) = snoozed_until {
+                                {tf("banner.snoozed", &[("time", &snooze::clock_time(until))])}
+                                " "
+                            }
+                            if !muted.is_empty() {
+                                {tf("banner.muted", &[("queries", &muted)])}
+                            }
+                        }
+                    }
+                }
+            }
+
+            if let Some(release) = available_update() {
+                div {
+                    class: "detect-banner update-banner",
+                    span {
+                        {tf(
+                            "banner.update",
+                            &[("version", &release.version), ("current", &updates::CURRENT_VERSION)],
+                        )}
+                    }
+                    button {
+                        class: "btn btn-connect",
+                        onclick: move |_| {
+                            on_open_url(release.url().to_string());
+                            available_update.set(None);
+                        },
+                        {t("banner.download")}
+                    }
+                    button {
+                        class: "detect-dismiss",
+                        title: t("common.dismiss"),
+                        onclick: move |_| available_update.set(None),
+                        "×"
+                    }
+                }
+            }
+
+            if let Some(path) = crash_report() {
+                div {
+                    class: "detect-banner update-banner",
+                    span {
+                        {tf("banner.crashed", &[("path", &path.display())])}
+                    }
+                    button {
+                        class: "btn btn-connect",
+                        title: t("banner.report_title"),
+                        onclick: on_report_crash,
+                        {t("banner.report")}
+                    }
+                    button {
+                        class: "detect-dismiss",
+                        title: t("common.dismiss"),
+                        onclick: move |_| dismiss_crash_report(),
+                        "×"
+                    }
+                }
+            }
+
+            if let (Some(order), true) = (sort_order(), frozen()) {
+                div {
+                    class: "sort-banner",
+                    span {
+                        {tf(
+                            if order.descending { "banner.sorted_desc" } else { "banner.sorted" },
+                            &[("key", &order.key.label())],
+                        )}
+                    }
+                    button {
+                        class: "btn btn-neutral",
+                        onclick: move |_| sort_order.set(None),
+                        {t("banner.time_order")}
+                    }
+                }
+            }
+
+            if let Some(found) = detection() {
+                div {
+                    class: "detect-banner",
+                    span {
+                        {tf(
+                            "banner.detected",
+                            &[
+                                ("format", &found.format.name()),
+                                ("matching", &found.matching),
+                                ("sampled", &found.sampled),
+                            ],
+                        )}
+                        if matches!(found.format, LogFormat::Syslog | LogFormat::Plain) {
+                            " {t(\"banner.plain_text\")}"
+                        }
+                        if let Some(forced) = format_override() {
+                            " {tf(\"banner.parsing_as\", &[(\"format\", &forced.name())])}"
+                        }
+                        " "
+                        {tf(
+                            "banner.force_format",
+                            &[("key", &keymap.read().binding(Action::CycleFormat).map(|b| b.display()).unwrap_or_default())],
+                        )}
+                    }
+                    button {
+                        class: "detect-dismiss",
+                        title: t("common.dismiss"),
+                        onclick: move |_| detection.set(None),
+                        "×"
+                    }
+                }
             }
 
-            // Connection Panel
-            ConnectionPanel {
-                available_apps: available_apps(),
-                selected_app: selected_app(),
-                is_connected: is_connected,
-                is_connecting: is_connecting,
-                is_auth_error: is_auth_error,
-                is_logging_in: is_logging_in,
-                on_app_select: on_app_select,
-                on_connect: on_connect,
-                on_disconnect: on_disconnect,
-                on_login: on_login,
-                on_cancel_login: on_cancel_login,
-                theme: theme(),
-                on_theme_change: on_theme_change,
-            }
-
-            // Stats Header
-            StatsHeader {
-                total_logs: total_logs,
-                filtered_logs: filtered_count,
-                filter_mode_and: filter_mode_and(),
-            }
-
-            // Filter Bar
-            FilterBar {
-                filters: filters(),
-                on_add_filter: on_add_filter,
-                on_clear_filters: on_clear_filters,
-                on_toggle_mode: on_toggle_mode,
-                filter_mode_and: filter_mode_and(),
-            }
-
-            // Log View
-            LogView {
-                logs: filtered_logs(),
-                scroll_position: 0.0,
+            if let Some(step) = onboarding_step {
+                OnboardingWizard {
+                    step: step,
+                    is_logging_in: is_logging_in,
+                    available_apps: available_apps(),
+                    on_retry: on_retry_cli,
+                    on_login: on_login,
+                    on_app_select: on_app_select,
+                    on_connect: on_connect,
+                    on_skip: on_skip_onboarding,
+                }
+            }
+
+            div {
+                class: "log-area",
+
+                // Log View
+                LogView {
+                    logs: shown_logs(),
+                    scroll_position: 0.0,
+                    time_format: settings.read().time_format.clone(),
+                    dim_before: dim_cutoff(),
+                    // Elapsed time is measured from the oldest buffered entry
+                    elapsed_since: if show_elapsed() {
+                        all_logs.read().first().map(|log| log.timestamp)
+                    } else {
+                        None
+                    },
+                    rewrites: rewrites(),
+                    thresholds: thresholds(),
+                    sql_highlight: sql_highlight(),
+                    highlights: highlights(),
+                    search: filter_engine.read().search_filters(),
+                    show_origin: show_origin(),
+                    dyno_palette: dyno_colors().then(|| settings.read().dyno_palette),
+                    truncate_at: settings.read().truncate_at,
+                    on_open_ref: on_open_ref,
+                    on_ignore: on_ignore,
+                    on_pin: on_pin,
+                    selected: selected_entry.read().as_ref().map(|entry| entry.raw.clone()),
+                    on_select: move |entry| selected_entry.set(Some(entry)),
+                    // While the detail pane is open it follows the focused row
+                    on_focus: move |entry: LogEntry| {
+                        if selected_entry.peek().is_some() {
+                            selected_entry.set(Some(entry.clone()));
+                        }
+                        focused_entry.set(Some(entry));
+                    },
+                    on_scroll: on_list_scroll,
+                }
+
+                if let Some(new_lines) = scroll_anchor
+                    .read()
+                    .as_ref()
+                    .map(|anchor| anchor.new_lines)
+                    .filter(|&new_lines| new_lines > 0)
+                {
+                    button {
+                        class: "new-lines-jump",
+                        title: t("banner.new_lines_title"),
+                        onclick: jump_to_end,
+                        {tf("banner.new_lines", &[("count", &new_lines)])}
+                    }
+                }
+
+                Minimap {
+                    buckets: minimap::buckets(&shown_logs.read(), minimap::BUCKETS),
+                    viewport: viewport(),
+                    on_seek: on_seek,
+                }
+
+                if let Some(entry) = selected_entry() {
+                    DetailPanel {
+                        waterfall: requests::extract_request_id(&entry.message)
+                            .and_then(|id| requests::waterfall(&all_logs.read(), id)),
+                        request_url: requests::request_url(
+                            &entry,
+                            settings.read().request_base_url.as_deref(),
+                        ),
+                        session_link: replay.read().as_ref().and_then(|r| {
+                            Some(session::goto_command(r.session_path()?, entry.session_id?))
+                        }),
+                        entry: entry,
+                        on_open_url: on_open_url,
+                        on_copy_link: on_copy_session_link,
+                        on_close: move |_| selected_entry.set(None),
+                    }
+                }
             }
         }
     }
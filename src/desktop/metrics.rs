@@ -0,0 +1,164 @@
+//! Per-second line counts for the activity sparkline in the status header
+//!
+//! Lines are counted by when they arrive, so the sparkline shows what the
+//! stream is doing right now even when log timestamps lag or a replay is
+//! running.
+
+use crate::parser::LogLevel;
+use std::collections::VecDeque;
+
+/// Seconds of history kept, one sparkline bar each
+pub const WINDOW_SECS: usize = 60;
+
+/// Bar glyphs from lowest to highest
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Lines that arrived during one second
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SecondCounts {
+    pub total: usize,
+    pub error: usize,
+}
+
+/// Counts for the last [`WINDOW_SECS`] seconds, keyed by Unix time
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RateHistory {
+    seconds: VecDeque<(i64, SecondCounts)>,
+}
+
+impl RateHistory {
+    /// Count a line of `level` that arrived during second `now`
+    pub fn record(&mut self, now: i64, level: LogLevel) {
+        match self.seconds.back_mut() {
+            Some((second, _)) if *second == now => {}
+            _ => self.seconds.push_back((now, SecondCounts::default())),
+        }
+        let (_, counts) = self.seconds.back_mut().expect("bucket was just ensured");
+        counts.total += 1;
+        if level == LogLevel::Error {
+            counts.error += 1;
+        }
+        self.prune(now);
+    }
+
+    fn prune(&mut self, now: i64) {
+        let oldest = now - WINDOW_SECS as i64 + 1;
+        while self
+            .seconds
+            .front()
+            .is_some_and(|(second, _)| *second < oldest)
+        {
+            self.seconds.pop_front();
+        }
+    }
+
+    /// Counts for each of the last [`WINDOW_SECS`] seconds up to `now`,
+    /// oldest first, with quiet seconds as zeros
+    pub fn window(&self, now: i64) -> Vec<SecondCounts> {
+        let oldest = now - WINDOW_SECS as i64 + 1;
+        let mut window = vec![SecondCounts::default(); WINDOW_SECS];
+        for (second, counts) in &self.seconds {
+            if (oldest..=now).contains(second) {
+                window[(second - oldest) as usize] = *counts;
+            }
+        }
+        window
+    }
+
+    /// Share of lines in the window that were errors, from 0 to 1
+    pub fn error_rate(&self, now: i64) -> f64 {
+        let window = self.window(now);
+        let total: usize = window.iter().map(|c| c.total).sum();
+        let errors: usize = window.iter().map(|c| c.error).sum();
+        if total == 0 {
+            0.0
+        } else {
+            errors as f64 / total as f64
+        }
+    }
+}
+
+/// One row of the metrics panel: recent values of one metric
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricChart {
+    /// What reported it: an add-on such as `postgresql-curly-12345`, or the
+    /// log source of an application metric
+    pub group: String,
+    /// Metric name, e.g. `load-avg-1m` or `measure#db.latency`
+    pub metric: String,
+    pub sparkline: String,
+    /// Latest value as logged
+    pub latest: String,
+}
+
+/// Sparkline of fractional values, scaled so the largest is a full block;
+/// negative values count as zero
+pub fn sparkline_of(values: &[f64]) -> String {
+    let peak = values.iter().copied().fold(0.0, f64::max);
+    let scaled: Vec<usize> = values
+        .iter()
+        .map(|&v| {
+            if peak > 0.0 {
+                (v.max(0.0) / peak * 1000.0).round() as usize
+            } else {
+                0
+            }
+        })
+        .collect();
+    sparkline(&scaled)
+}
+
+/// One bar per value, scaled so the largest is a full block; any nonzero
+/// value gets at least the lowest bar and zero is a space
+pub fn sparkline(values: &[usize]) -> String {
+    let max = values.iter().copied().max().unwrap_or(0);
+    values
+        .iter()
+        .map(|&value| {
+            if value == 0 {
+                ' '
+            } else {
+                BARS[value * (BARS.len() - 1) / max]
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_fills_quiet_seconds() {
+        let mut history = RateHistory::default();
+        history.record(100, LogLevel::Info);
+        history.record(100, LogLevel::Error);
+        history.record(102, LogLevel::Warn);
+
+        let window = history.window(102);
+        assert_eq!(window.len(), WINDOW_SECS);
+        assert_eq!(window[WINDOW_SECS - 3], SecondCounts { total: 2, error: 1 });
+        assert_eq!(window[WINDOW_SECS - 2], SecondCounts::default());
+        assert_eq!(window[WINDOW_SECS - 1].total, 1);
+        assert!((history.error_rate(102) - 1.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_old_seconds_drop_out() {
+        let mut history = RateHistory::default();
+        history.record(100, LogLevel::Error);
+        history.record(100 + WINDOW_SECS as i64, LogLevel::Info);
+
+        assert_eq!(history.seconds.len(), 1);
+        assert_eq!(history.error_rate(100 + WINDOW_SECS as i64), 0.0);
+        assert_eq!(history.error_rate(1000), 0.0);
+    }
+
+    #[test]
+    fn test_sparkline_scales_to_peak() {
+        assert_eq!(sparkline(&[0, 1, 4, 8]), " ▁▄█");
+        assert_eq!(sparkline(&[3, 3]), "██");
+        assert_eq!(sparkline(&[]), "");
+        assert_eq!(sparkline_of(&[0.02, 0.08, -1.0]), "▂█ ");
+    }
+}
@@ -0,0 +1,95 @@
+//! Whole-buffer overview shown beside the log view
+//!
+//! The buffer is split into a fixed number of buckets, each marked with the
+//! most severe level among its entries, so errors stand out as ticks even in
+//! a long session.
+
+use crate::parser::{LogEntry, LogLevel};
+
+/// Buckets drawn in the minimap, regardless of buffer size
+pub const BUCKETS: usize = 200;
+
+/// One slice of the buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bucket {
+    /// Index of the first entry in the slice
+    pub start: usize,
+    /// `Error` or `Warn` when the slice contains one
+    pub marker: Option<LogLevel>,
+}
+
+/// Split `logs` into at most `count` equal slices
+pub fn buckets(logs: &[LogEntry], count: usize) -> Vec<Bucket> {
+    if logs.is_empty() || count == 0 {
+        return Vec::new();
+    }
+    let count = count.min(logs.len());
+
+    (0..count)
+        .map(|bucket| {
+            let start = bucket * logs.len() / count;
+            let end = (bucket + 1) * logs.len() / count;
+            let levels = logs[start..end].iter().map(|log| log.level);
+            let marker = levels.fold(None, |marker, level| match (marker, level) {
+                (_, LogLevel::Error) => Some(LogLevel::Error),
+                (None, LogLevel::Warn) => Some(LogLevel::Warn),
+                (marker, _) => marker,
+            });
+            Bucket { start, marker }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_log_line;
+
+    fn entry(message: &str) -> LogEntry {
+        parse_log_line(&format!(
+            "2024-02-17T10:30:45.123456+00:00 app[web.1]: {}",
+            message
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_buckets_mark_most_severe_level() {
+        let logs = vec![
+            entry("started"),
+            entry("WARN slow query"),
+            entry("ERROR boom"),
+            entry("WARN retrying"),
+            entry("ok"),
+            entry("ok"),
+        ];
+
+        assert_eq!(
+            buckets(&logs, 3),
+            vec![
+                Bucket {
+                    start: 0,
+                    marker: Some(LogLevel::Warn)
+                },
+                Bucket {
+                    start: 2,
+                    marker: Some(LogLevel::Error)
+                },
+                Bucket {
+                    start: 4,
+                    marker: None
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_small_buffer_gets_one_bucket_per_entry() {
+        let logs = vec![entry("one"), entry("two")];
+
+        let result = buckets(&logs, BUCKETS);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[1].start, 1);
+        assert!(buckets(&[], BUCKETS).is_empty());
+    }
+}
@@ -0,0 +1,25 @@
+//! Native desktop notifications for alerts
+//!
+//! Shown through the OS notification center (Notification Center on macOS,
+//! the freedesktop notification service on Linux, toasts on Windows), so an
+//! alert is seen while the window is in the background.
+
+use anyhow::{Context, Result};
+
+/// Name notifications are shown under
+const APP_NAME: &str = "Logs Parser";
+
+/// Show a notification; sending it can block, so it runs off the UI thread
+pub async fn show(summary: String, body: String) -> Result<()> {
+    tokio::task::spawn_blocking(move || {
+        notify_rust::Notification::new()
+            .appname(APP_NAME)
+            .summary(&summary)
+            .body(&body)
+            .show()
+            .map(|_| ())
+            .context("Failed to show a desktop notification")
+    })
+    .await
+    .context("Desktop notification task failed")?
+}
@@ -0,0 +1,140 @@
+//! First-run guide: install the Heroku CLI, log in, pick an app, connect
+//!
+//! Progress is kept in `~/.config/logs-parser/onboarding`, so the guide
+//! stops appearing after the first successful connection and the app picked
+//! then is selected on later launches:
+//!
+//! ```text
+//! completed = true
+//! app = my-app-production
+//! ```
+
+use crate::config::config_dir;
+use crate::i18n::t;
+use anyhow::{Context, Result};
+
+/// Steps of the first-run guide, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnboardingStep {
+    InstallCli,
+    Login,
+    PickApp,
+    Connect,
+}
+
+impl OnboardingStep {
+    pub const ALL: [OnboardingStep; 4] = [
+        OnboardingStep::InstallCli,
+        OnboardingStep::Login,
+        OnboardingStep::PickApp,
+        OnboardingStep::Connect,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            OnboardingStep::InstallCli => t("onboarding.step.install"),
+            OnboardingStep::Login => t("onboarding.step.login"),
+            OnboardingStep::PickApp => t("onboarding.step.pick_app"),
+            OnboardingStep::Connect => t("onboarding.step.connect"),
+        }
+    }
+}
+
+/// Commands that install the Heroku CLI on `os` (as in
+/// [`std::env::consts::OS`]), with a note on what each does
+pub fn install_instructions(os: &str) -> Vec<(&'static str, &'static str)> {
+    match os {
+        "macos" => vec![(
+            t("onboarding.via_homebrew"),
+            "brew tap heroku/brew && brew install heroku",
+        )],
+        "linux" => vec![
+            (
+                t("onboarding.via_script"),
+                "curl https://cli-assets.heroku.com/install.sh | sh",
+            ),
+            (
+                t("onboarding.via_snap"),
+                "sudo snap install --classic heroku",
+            ),
+        ],
+        "windows" => vec![(t("onboarding.via_npm"), "npm install -g heroku")],
+        _ => Vec::new(),
+    }
+}
+
+/// Saved progress through the guide
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Onboarding {
+    /// Set once a connection has succeeded
+    pub completed: bool,
+    /// App of the last successful connection
+    pub app: Option<String>,
+}
+
+impl Onboarding {
+    pub fn parse(contents: &str) -> Self {
+        let mut onboarding = Self::default();
+        for line in contents.lines() {
+            let Some((name, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+            match name.trim() {
+                "completed" => onboarding.completed = value == "true",
+                "app" if !value.is_empty() => onboarding.app = Some(value.to_string()),
+                _ => {}
+            }
+        }
+        onboarding
+    }
+
+    /// Load saved progress; none means this is the first run
+    pub fn load() -> Self {
+        std::fs::read_to_string(config_dir().join("onboarding"))
+            .map(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    fn render(&self) -> String {
+        let mut contents = format!("completed = {}\n", self.completed);
+        if let Some(app) = &self.app {
+            contents.push_str(&format!("app = {}\n", app));
+        }
+        contents
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let dir = config_dir();
+        std::fs::create_dir_all(&dir).context("Failed to create config directory")?;
+        std::fs::write(dir.join("onboarding"), self.render())
+            .context("Failed to save onboarding progress")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_round_trip() {
+        let onboarding = Onboarding {
+            completed: true,
+            app: Some("my-app-production".to_string()),
+        };
+
+        assert_eq!(Onboarding::parse(&onboarding.render()), onboarding);
+        assert_eq!(Onboarding::parse(""), Onboarding::default());
+        assert_eq!(
+            Onboarding::parse("completed = maybe\napp =\n"),
+            Onboarding::default()
+        );
+    }
+
+    #[test]
+    fn test_install_instructions_per_os() {
+        assert!(install_instructions("macos")[0].1.contains("brew"));
+        assert_eq!(install_instructions("linux").len(), 2);
+        assert!(install_instructions("haiku").is_empty());
+    }
+}
@@ -0,0 +1,256 @@
+//! OpenTelemetry log export (OTLP over HTTP with JSON encoding)
+//!
+//! Built with `--features otel`. When `otel_endpoint` is set, entries passing
+//! the current filters are batched and posted to `<endpoint>/v1/logs`, so a
+//! local collector can ship them on. Only plain `http://` endpoints are
+//! supported, which is what a collector sidecar normally listens on.
+//! A batch the collector doesn't take is kept for the next flush, up to
+//! [`MAX_QUEUED`] entries.
+
+use crate::parser::{LogEntry, LogLevel};
+use crate::requests::extract_request_id;
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Entries kept waiting for the collector; the oldest are dropped beyond this
+pub const MAX_QUEUED: usize = 10_000;
+
+/// How long connecting to, or hearing back from, the collector may take
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// OTel severity number and text for a level
+fn severity(level: LogLevel) -> (u8, &'static str) {
+    match level {
+        LogLevel::Error => (17, "ERROR"),
+        LogLevel::Warn => (13, "WARN"),
+        LogLevel::Info => (9, "INFO"),
+        LogLevel::Debug => (5, "DEBUG"),
+        LogLevel::Unknown => (0, "UNSPECIFIED"),
+    }
+}
+
+fn string_attribute(key: &str, value: &str) -> Value {
+    json!({ "key": key, "value": { "stringValue": value } })
+}
+
+/// Map one entry to an OTLP `LogRecord`
+pub fn log_record(entry: &LogEntry) -> Value {
+    let (severity_number, severity_text) = severity(entry.level);
+    let mut attributes = vec![
+        string_attribute("heroku.source", &entry.source),
+        string_attribute("heroku.dyno", &entry.dyno),
+    ];
+    if let Some(request_id) = extract_request_id(&entry.message) {
+        attributes.push(string_attribute("heroku.request_id", request_id));
+    }
+
+    json!({
+        // Nanoseconds don't fit a JSON number exactly, so OTLP uses a string
+        "timeUnixNano": entry
+            .timestamp
+            .timestamp_nanos_opt()
+            .unwrap_or_default()
+            .to_string(),
+        "severityNumber": severity_number,
+        "severityText": severity_text,
+        "body": { "stringValue": entry.message },
+        "attributes": attributes,
+    })
+}
+
+/// Build an `ExportLogsServiceRequest` for a batch of entries
+pub fn export_request(service_name: &str, entries: &[LogEntry]) -> Value {
+    json!({
+        "resourceLogs": [{
+            "resource": {
+                "attributes": [string_attribute("service.name", service_name)],
+            },
+            "scopeLogs": [{
+                "scope": { "name": "logs-parser" },
+                "logRecords": entries.iter().map(log_record).collect::<Vec<_>>(),
+            }],
+        }],
+    })
+}
+
+/// Entries waiting to be sent, oldest first
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OtlpQueue {
+    entries: VecDeque<LogEntry>,
+}
+
+impl OtlpQueue {
+    /// Queue an entry; replayed lines already went out when they were
+    /// recorded, so they're skipped
+    pub fn push(&mut self, entry: LogEntry) {
+        if entry.origin == "replay" {
+            return;
+        }
+        self.entries.push_back(entry);
+        self.trim();
+    }
+
+    /// Take everything queued as the next batch
+    pub fn take(&mut self) -> Vec<LogEntry> {
+        self.entries.drain(..).collect()
+    }
+
+    /// Put a batch that failed to send back ahead of what arrived since
+    pub fn requeue(&mut self, batch: Vec<LogEntry>) {
+        for entry in batch.into_iter().rev() {
+            self.entries.push_front(entry);
+        }
+        self.trim();
+    }
+
+    fn trim(&mut self) {
+        let excess = self.entries.len().saturating_sub(MAX_QUEUED);
+        self.entries.drain(..excess);
+    }
+}
+
+/// Where to send logs, parsed from an `http://host:port[/prefix]` endpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OtlpEndpoint {
+    host: String,
+    port: u16,
+    path: String,
+}
+
+impl OtlpEndpoint {
+    pub fn parse(endpoint: &str) -> Result<Self> {
+        let Some(rest) = endpoint.strip_prefix("http://") else {
+            bail!("OTLP endpoint must start with http://: {}", endpoint);
+        };
+        let (authority, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().context("Invalid OTLP port")?),
+            None => (authority, 4318),
+        };
+        let prefix = prefix.trim_end_matches('/');
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            path: if prefix.is_empty() {
+                "/v1/logs".to_string()
+            } else {
+                format!("/{}/v1/logs", prefix)
+            },
+        })
+    }
+
+    /// Post a batch and fail unless the collector answers 2xx
+    pub async fn send(&self, service_name: &str, entries: &[LogEntry]) -> Result<()> {
+        let body = export_request(service_name, entries).to_string();
+        let connect = TcpStream::connect((self.host.as_str(), self.port));
+        let mut stream = tokio::time::timeout(TIMEOUT, connect)
+            .await
+            .context("Timed out")
+            .and_then(|connected| connected.context("Connection failed"))
+            .with_context(|| {
+                format!(
+                    "Failed to connect to OTLP collector at {}:{}",
+                    self.host, self.port
+                )
+            })?;
+        let request = format!(
+            "POST {} HTTP/1.1\r\nHost: {}:{}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.path,
+            self.host,
+            self.port,
+            body.len(),
+            body
+        );
+        stream.write_all(request.as_bytes()).await?;
+
+        let mut response = String::new();
+        tokio::time::timeout(TIMEOUT, stream.read_to_string(&mut response))
+            .await
+            .context("OTLP collector didn't respond in time")??;
+        let status = response.split_whitespace().nth(1).unwrap_or_default();
+        if !status.starts_with('2') {
+            bail!("OTLP collector responded with {}", status);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_log_line;
+
+    #[test]
+    fn test_log_record_maps_fields() {
+        let entry = parse_log_line(
+            "2024-02-17T10:30:45.123456+00:00 app[web.1]: ERROR request_id=abc-123 boom",
+        )
+        .unwrap();
+        let record = log_record(&entry);
+
+        assert_eq!(record["severityNumber"], 17);
+        assert_eq!(record["severityText"], "ERROR");
+        assert_eq!(record["timeUnixNano"], "1708165845123456000");
+        assert_eq!(record["body"]["stringValue"], entry.message);
+        let attributes = record["attributes"].as_array().unwrap();
+        assert!(attributes.contains(&string_attribute("heroku.dyno", "web.1")));
+        assert!(attributes.contains(&string_attribute("heroku.request_id", "abc-123")));
+    }
+
+    #[test]
+    fn test_queue_keeps_failed_batch_and_caps() {
+        let entry = |message: &str| {
+            parse_log_line(&format!(
+                "2024-02-17T10:30:45.123456+00:00 app[web.1]: {}",
+                message
+            ))
+            .unwrap()
+        };
+        let mut queue = OtlpQueue::default();
+        queue.push(entry("first"));
+        let batch = queue.take();
+        queue.push(entry("second"));
+        queue.requeue(batch);
+        let messages: Vec<String> = queue.take().into_iter().map(|e| e.message).collect();
+        assert_eq!(messages, ["first", "second"]);
+
+        for _ in 0..MAX_QUEUED {
+            queue.push(entry("old"));
+        }
+        queue.push(entry("new"));
+        let batch = queue.take();
+        assert_eq!(batch.len(), MAX_QUEUED);
+        assert_eq!(batch.last().unwrap().message, "new");
+    }
+
+    #[test]
+    fn test_queue_skips_replayed_lines() {
+        let mut entry =
+            parse_log_line("2024-02-17T10:30:45.123456+00:00 app[web.1]: replayed").unwrap();
+        entry.origin = "replay".to_string();
+        let mut queue = OtlpQueue::default();
+        queue.push(entry);
+        assert!(queue.take().is_empty());
+    }
+
+    #[test]
+    fn test_parse_endpoint() {
+        assert_eq!(
+            OtlpEndpoint::parse("http://localhost:4318").unwrap(),
+            OtlpEndpoint {
+                host: "localhost".to_string(),
+                port: 4318,
+                path: "/v1/logs".to_string(),
+            }
+        );
+        assert_eq!(
+            OtlpEndpoint::parse("http://collector/otlp/").unwrap().path,
+            "/otlp/v1/logs"
+        );
+        assert!(OtlpEndpoint::parse("https://collector").is_err());
+    }
+}
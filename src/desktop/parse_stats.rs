@@ -0,0 +1,110 @@
+//! Counts of lines each input failed to parse
+//!
+//! Unparsed Heroku lines are dropped, so without these counts a stream whose
+//! format isn't recognized just looks quieter than it is.
+
+use std::collections::{BTreeMap, VecDeque};
+
+/// Number of recent failed lines kept per input
+const MAX_SAMPLES: usize = 5;
+
+/// Parse outcomes for one input (`heroku`, `socket`, `fifo`)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceStats {
+    pub parsed: usize,
+    pub failed: usize,
+    /// Most recent failed lines, oldest first
+    pub samples: VecDeque<String>,
+}
+
+impl SourceStats {
+    /// Fraction of lines that failed to parse
+    pub fn failure_ratio(&self) -> f64 {
+        let total = self.parsed + self.failed;
+        if total == 0 {
+            0.0
+        } else {
+            self.failed as f64 / total as f64
+        }
+    }
+}
+
+/// Parse outcomes keyed by input name
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParseStats {
+    sources: BTreeMap<String, SourceStats>,
+}
+
+impl ParseStats {
+    pub fn record_parsed(&mut self, source: &str) {
+        self.sources.entry(source.to_string()).or_default().parsed += 1;
+    }
+
+    pub fn record_failure(&mut self, source: &str, line: &str) {
+        let stats = self.sources.entry(source.to_string()).or_default();
+        stats.failed += 1;
+        if stats.samples.len() == MAX_SAMPLES {
+            stats.samples.pop_front();
+        }
+        stats.samples.push_back(line.to_string());
+    }
+
+    /// Inputs with at least one failed line, worst ratio first
+    pub fn failing(&self) -> Vec<(&str, &SourceStats)> {
+        let mut failing: Vec<(&str, &SourceStats)> = self
+            .sources
+            .iter()
+            .filter(|(_, stats)| stats.failed > 0)
+            .map(|(name, stats)| (name.as_str(), stats))
+            .collect();
+        failing.sort_by(|a, b| b.1.failure_ratio().total_cmp(&a.1.failure_ratio()));
+        failing
+    }
+
+    pub fn clear(&mut self) {
+        self.sources.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failure_ratio() {
+        let mut stats = ParseStats::default();
+        stats.record_parsed("heroku");
+        stats.record_parsed("heroku");
+        stats.record_parsed("heroku");
+        stats.record_failure("heroku", "{\"level\":\"info\"}");
+        stats.record_parsed("socket");
+
+        let failing = stats.failing();
+        assert_eq!(failing.len(), 1);
+        assert_eq!(failing[0].0, "heroku");
+        assert_eq!(failing[0].1.failure_ratio(), 0.25);
+    }
+
+    #[test]
+    fn test_failing_orders_worst_first() {
+        let mut stats = ParseStats::default();
+        stats.record_parsed("heroku");
+        stats.record_failure("heroku", "a");
+        stats.record_failure("fifo", "b");
+
+        let names: Vec<&str> = stats.failing().iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, vec!["fifo", "heroku"]);
+    }
+
+    #[test]
+    fn test_samples_keep_most_recent() {
+        let mut stats = ParseStats::default();
+        for i in 0..(MAX_SAMPLES + 2) {
+            stats.record_failure("heroku", &format!("line {}", i));
+        }
+
+        let samples = &stats.failing()[0].1.samples;
+        assert_eq!(samples.len(), MAX_SAMPLES);
+        assert_eq!(samples.front().map(String::as_str), Some("line 2"));
+    }
+}
@@ -1,6 +1,12 @@
-use chrono::{DateTime, FixedOffset};
+use crate::addons;
+use anyhow::{bail, Result};
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use futures::Stream;
 use regex::Regex;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
 use std::sync::OnceLock;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
 
 /// Represents a log level extracted from message content
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -28,8 +34,45 @@ impl LogLevel {
             LogLevel::Unknown
         }
     }
+
+    /// Map a level name such as `error`, `warning` or syslog's `crit`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "emerg" | "alert" | "crit" | "critical" | "err" | "error" | "fatal" | "panic" => {
+                Some(LogLevel::Error)
+            }
+            "warn" | "warning" => Some(LogLevel::Warn),
+            "notice" | "info" => Some(LogLevel::Info),
+            "debug" | "trace" => Some(LogLevel::Debug),
+            _ => None,
+        }
+    }
+
+    /// Severity rank, 0 for the most severe
+    pub fn rank(self) -> u8 {
+        match self {
+            LogLevel::Error => 0,
+            LogLevel::Warn => 1,
+            LogLevel::Info => 2,
+            LogLevel::Debug => 3,
+            LogLevel::Unknown => 4,
+        }
+    }
+
+    /// Map a pino/bunyan numeric level (10 trace up to 60 fatal)
+    fn from_number(level: f64) -> Self {
+        match level {
+            l if l >= 50.0 => LogLevel::Error,
+            l if l >= 40.0 => LogLevel::Warn,
+            l if l >= 30.0 => LogLevel::Info,
+            _ => LogLevel::Debug,
+        }
+    }
 }
 
+/// Time of day with milliseconds, used unless `time_format` is set
+pub const DEFAULT_TIME_FORMAT: &str = "%H:%M:%S%.3f";
+
 /// Represents a parsed Heroku log entry
 #[derive(Debug, Clone, PartialEq)]
 pub struct LogEntry {
@@ -39,15 +82,190 @@ pub struct LogEntry {
     pub message: String,
     pub level: LogLevel,
     pub raw: String,
+    /// Extra fields of a structured (JSON) line, values rendered as text
+    pub fields: HashMap<String, String>,
+    /// Label of the input the line came from, such as `heroku:my-app` or
+    /// `file:development.log`; empty until the input tags it
+    pub origin: String,
+    /// Id of a replayed line in its session file, for `--goto-id`
+    pub session_id: Option<usize>,
 }
 
 impl LogEntry {
-    /// Get just the time portion formatted
-    pub fn format_time(&self) -> String {
-        self.timestamp.format("%H:%M:%S%.3f").to_string()
+    /// Format the timestamp with a strftime-style format such as
+    /// [`DEFAULT_TIME_FORMAT`]
+    pub fn format_time(&self, format: &str) -> String {
+        self.timestamp.format(format).to_string()
+    }
+
+    /// Start building an entry directly, without formatting and parsing a line
+    pub fn builder() -> LogEntryBuilder {
+        LogEntryBuilder::default()
+    }
+
+    /// Wrap a line that isn't in Heroku format, stamped with the current time
+    pub fn local(line: &str, dyno: &str) -> Self {
+        Self::builder()
+            .source("local")
+            .dyno(dyno)
+            .message(line)
+            .raw(line)
+            .build()
+            .expect("local source and dyno names are valid")
+    }
+
+    /// Format the time elapsed since `start` as `T+HH:MM:SS.mmm`
+    ///
+    /// Entries that arrived out of order and predate `start` get a `T-` prefix.
+    pub fn format_elapsed(&self, start: DateTime<FixedOffset>) -> String {
+        let elapsed = self.timestamp - start;
+        let sign = if elapsed < chrono::Duration::zero() {
+            '-'
+        } else {
+            '+'
+        };
+        let millis = elapsed.num_milliseconds().unsigned_abs();
+        format!(
+            "T{}{:02}:{:02}:{:02}.{:03}",
+            sign,
+            millis / 3_600_000,
+            millis / 60_000 % 60,
+            millis / 1000 % 60,
+            millis % 1000
+        )
+    }
+}
+
+/// Builder for a [`LogEntry`]
+///
+/// Defaults to `app[web.1]` stamped with the current time. The level is
+/// detected from the message, `heroku[router]` lines get their logfmt pairs
+/// as fields, and unless set `raw` is rendered in Heroku format so it parses
+/// back to the same entry.
+#[derive(Debug, Clone, Default)]
+pub struct LogEntryBuilder {
+    timestamp: Option<DateTime<FixedOffset>>,
+    source: Option<String>,
+    dyno: Option<String>,
+    message: String,
+    level: Option<LogLevel>,
+    raw: Option<String>,
+    fields: HashMap<String, String>,
+    origin: String,
+}
+
+impl LogEntryBuilder {
+    pub fn timestamp(mut self, timestamp: DateTime<FixedOffset>) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    pub fn source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    pub fn dyno(mut self, dyno: impl Into<String>) -> Self {
+        self.dyno = Some(dyno.into());
+        self
+    }
+
+    pub fn message(mut self, message: impl Into<String>) -> Self {
+        self.message = message.into();
+        self
+    }
+
+    /// Use this level instead of detecting one from the message
+    pub fn level(mut self, level: LogLevel) -> Self {
+        self.level = Some(level);
+        self
+    }
+
+    pub fn fields(mut self, fields: HashMap<String, String>) -> Self {
+        self.fields = fields;
+        self
+    }
+
+    pub fn origin(mut self, origin: impl Into<String>) -> Self {
+        self.origin = origin.into();
+        self
+    }
+
+    /// Keep the original line instead of rendering one
+    pub fn raw(mut self, raw: impl Into<String>) -> Self {
+        self.raw = Some(raw.into());
+        self
+    }
+
+    /// Build the entry, rejecting a source or dyno the parser couldn't produce
+    pub fn build(self) -> Result<LogEntry> {
+        let source = self.source.unwrap_or_else(|| "app".to_string());
+        let dyno = self.dyno.unwrap_or_else(|| "web.1".to_string());
+        if source.is_empty() || !source.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            bail!("Invalid log source: {:?}", source);
+        }
+        if dyno.is_empty() || dyno.contains(']') {
+            bail!("Invalid dyno name: {:?}", dyno);
+        }
+
+        let timestamp = self
+            .timestamp
+            .unwrap_or_else(|| chrono::Local::now().fixed_offset());
+        let raw = self.raw.unwrap_or_else(|| {
+            format!(
+                "{} {}[{}]: {}",
+                timestamp.format("%Y-%m-%dT%H:%M:%S%.6f%:z"),
+                source,
+                dyno,
+                self.message
+            )
+        });
+
+        // Router and add-on metric lines are logfmt; unpack them unless
+        // fields were given
+        let logfmt = (source == "heroku" && dyno == "router") || addons::is_addon_dyno(&dyno);
+        let fields = if self.fields.is_empty() && logfmt {
+            parse_logfmt(&self.message)
+        } else {
+            self.fields
+        };
+
+        Ok(LogEntry {
+            timestamp,
+            level: self
+                .level
+                .unwrap_or_else(|| LogLevel::from_message(&self.message)),
+            source,
+            dyno,
+            message: self.message,
+            raw,
+            fields,
+            origin: self.origin,
+            session_id: None,
+        })
     }
 }
 
+/// Positions where a new calendar day starts, for separator rows
+///
+/// Empty when every entry falls on the same day. Otherwise the first entry is
+/// included too, so each day in the list is labelled.
+pub fn day_boundaries(logs: &[LogEntry]) -> Vec<(usize, NaiveDate)> {
+    let mut boundaries = Vec::new();
+    let mut current = None;
+    for (idx, log) in logs.iter().enumerate() {
+        let day = log.timestamp.date_naive();
+        if current != Some(day) {
+            boundaries.push((idx, day));
+            current = Some(day);
+        }
+    }
+    if boundaries.len() == 1 {
+        boundaries.clear();
+    }
+    boundaries
+}
+
 /// Get the regex pattern for parsing Heroku logs
 fn log_regex() -> &'static Regex {
     static REGEX: OnceLock<Regex> = OnceLock::new();
@@ -61,33 +279,270 @@ fn log_regex() -> &'static Regex {
     })
 }
 
-/// Parse a single Heroku log line
+/// Keys holding the message, level and time of a JSON line, in the
+/// spellings used by pino, bunyan, logrus, tracing and friends
+const JSON_MESSAGE_KEYS: [&str; 2] = ["msg", "message"];
+const JSON_LEVEL_KEYS: [&str; 3] = ["level", "severity", "lvl"];
+const JSON_TIME_KEYS: [&str; 4] = ["time", "timestamp", "ts", "@timestamp"];
+
+/// Parse a single log line
 ///
-/// Returns `Some(LogEntry)` if the line matches the expected format,
-/// or `None` if the line cannot be parsed.
+/// Heroku lines (`timestamp source[dyno]: message`) are tried first; a
+/// message that is a JSON object is unpacked into level, message and
+/// fields. A bare JSON object line is parsed on its own, as `app[json]`
+/// unless it carries `source`/`dyno` keys, and an RFC 5424 syslog line as
+/// `syslog[app-name]`. Returns `None` otherwise.
 pub fn parse_log_line(line: &str) -> Option<LogEntry> {
-    let regex = log_regex();
-    let captures = regex.captures(line)?;
+    parse_heroku_line(line)
+        .or_else(|| parse_json_line(line))
+        .or_else(|| parse_syslog_line(line))
+}
+
+/// Parse a `timestamp source[dyno]: message` line only
+pub fn parse_heroku_line(line: &str) -> Option<LogEntry> {
+    let captures = log_regex().captures(line)?;
 
     // Extract timestamp
     let timestamp_str = captures.get(1)?.as_str();
     let timestamp = DateTime::parse_from_rfc3339(timestamp_str).ok()?;
 
-    // Extract other fields
-    let source = captures.get(2)?.as_str().to_string();
-    let dyno = captures.get(3)?.as_str().to_string();
-    let message = captures.get(4)?.as_str().to_string();
-
-    // Detect log level from message content
-    let level = LogLevel::from_message(&message);
-
-    Some(LogEntry {
-        timestamp,
-        source,
-        dyno,
-        message,
-        level,
-        raw: line.to_string(),
+    // Extract other fields; the level is detected from message content
+    let message = captures.get(4)?.as_str();
+    let builder = LogEntry::builder()
+        .timestamp(timestamp)
+        .source(captures.get(2)?.as_str())
+        .dyno(captures.get(3)?.as_str())
+        .raw(line);
+    match json_object(message) {
+        Some(object) => apply_json(builder, object, message),
+        None => builder.message(message),
+    }
+    .build()
+    .ok()
+}
+
+/// Split a logfmt message (`at=info path="/a b" status=200`) into fields
+///
+/// Quotes around a value are dropped; a key without `=` gets an empty value.
+pub fn parse_logfmt(message: &str) -> HashMap<String, String> {
+    let mut fields = HashMap::new();
+    let mut rest = message.trim_start();
+    while !rest.is_empty() {
+        let key_end = rest
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let key = &rest[..key_end];
+        rest = &rest[key_end..];
+        let value = match rest.strip_prefix('=') {
+            Some(after) => match after.strip_prefix('"') {
+                Some(quoted) => {
+                    let end = quoted.find('"').unwrap_or(quoted.len());
+                    rest = quoted.get(end + 1..).unwrap_or("");
+                    &quoted[..end]
+                }
+                None => {
+                    let end = after.find(char::is_whitespace).unwrap_or(after.len());
+                    rest = &after[end..];
+                    &after[..end]
+                }
+            },
+            None => "",
+        };
+        if !key.is_empty() {
+            fields.insert(key.to_string(), value.to_string());
+        }
+        rest = rest.trim_start();
+    }
+    fields
+}
+
+/// Parse a line that is a JSON object on its own
+pub fn parse_json_line(line: &str) -> Option<LogEntry> {
+    let mut object = json_object(line)?;
+    let mut builder = LogEntry::builder().source("app").dyno("json").raw(line);
+    if let Some(time) = take_first(&mut object, &JSON_TIME_KEYS).and_then(|v| json_time(&v)) {
+        builder = builder.timestamp(time);
+    }
+    if let Some(Value::String(source)) = object.remove("source") {
+        builder = builder.source(source);
+    }
+    if let Some(Value::String(dyno)) = object.remove("dyno") {
+        builder = builder.dyno(dyno);
+    }
+    apply_json(builder, object, line).build().ok()
+}
+
+/// Level of a syslog severity (the PRI value modulo 8)
+fn syslog_level(severity: u8) -> LogLevel {
+    match severity {
+        0..=3 => LogLevel::Error,
+        4 => LogLevel::Warn,
+        5 | 6 => LogLevel::Info,
+        _ => LogLevel::Debug,
+    }
+}
+
+/// Split RFC 5424 structured data (`[id key="value" ...][...]` or `-`) off
+/// the front of `text`, returning its parameters and the rest
+fn split_structured_data(text: &str) -> Option<(HashMap<String, String>, &str)> {
+    let mut params = HashMap::new();
+    if let Some(rest) = text.strip_prefix('-') {
+        return Some((params, rest));
+    }
+    let mut rest = text;
+    while let Some(element) = rest.strip_prefix('[') {
+        // Find the closing bracket outside quoted values
+        let mut quoted = false;
+        let mut escaped = false;
+        let end = element.char_indices().find_map(|(i, c)| {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => quoted = !quoted,
+                ']' if !quoted => return Some(i),
+                _ => {}
+            }
+            None
+        })?;
+        let (_, pairs) = element[..end]
+            .split_once(' ')
+            .unwrap_or((&element[..end], ""));
+        params.extend(parse_logfmt(pairs));
+        rest = &element[end + 1..];
+    }
+    (rest.len() < text.len()).then_some((params, rest))
+}
+
+/// Parse an RFC 5424 syslog line:
+/// `<PRI>1 TIMESTAMP HOSTNAME APP-NAME PROCID MSGID [SD] MSG`
+///
+/// Lines are shown as `syslog[app-name]`, levelled by severity, with the
+/// host, process id, message id and structured data as fields. A `-`
+/// timestamp is replaced by the time the line arrived.
+pub fn parse_syslog_line(line: &str) -> Option<LogEntry> {
+    let (pri, rest) = line.strip_prefix('<')?.split_once('>')?;
+    let pri: u16 = pri.parse().ok().filter(|p| *p <= 191)?;
+    let rest = rest.strip_prefix("1 ")?;
+    let mut parts = rest.splitn(6, ' ');
+    let (time, host, app, procid, msgid, rest) = (
+        parts.next()?,
+        parts.next()?,
+        parts.next()?,
+        parts.next()?,
+        parts.next()?,
+        parts.next().unwrap_or("-"),
+    );
+    let (mut fields, message) = split_structured_data(rest)?;
+    let message = message.strip_prefix(' ').unwrap_or(message);
+    // Messages may start with a UTF-8 byte order mark
+    let message = message.strip_prefix('\u{feff}').unwrap_or(message);
+
+    for (key, value) in [("host", host), ("procid", procid), ("msgid", msgid)] {
+        if value != "-" {
+            fields.insert(key.to_string(), value.to_string());
+        }
+    }
+    let mut builder = LogEntry::builder()
+        .source("syslog")
+        .dyno(if app == "-" { host } else { app })
+        .message(message)
+        .level(syslog_level((pri % 8) as u8))
+        .fields(fields)
+        .raw(line);
+    if time != "-" {
+        builder = builder.timestamp(DateTime::parse_from_rfc3339(time).ok()?);
+    }
+    builder.build().ok()
+}
+
+fn json_object(text: &str) -> Option<Map<String, Value>> {
+    if !text.trim_start().starts_with('{') {
+        return None;
+    }
+    match serde_json::from_str(text) {
+        Ok(Value::Object(object)) => Some(object),
+        _ => None,
+    }
+}
+
+/// Set level, message and fields from a JSON object; a level the object
+/// doesn't carry is detected from the message as usual, and without a
+/// message key the whole object text is the message
+fn apply_json(
+    mut builder: LogEntryBuilder,
+    mut object: Map<String, Value>,
+    text: &str,
+) -> LogEntryBuilder {
+    let level = take_first(&mut object, &JSON_LEVEL_KEYS).and_then(|value| match value {
+        Value::Number(n) => n.as_f64().map(LogLevel::from_number),
+        Value::String(name) => LogLevel::from_name(&name),
+        _ => None,
+    });
+    if let Some(level) = level {
+        builder = builder.level(level);
+    }
+    // Drop the timestamp so it doesn't show up as a field
+    take_first(&mut object, &JSON_TIME_KEYS);
+    let message = take_first(&mut object, &JSON_MESSAGE_KEYS)
+        .map(|value| json_text(&value))
+        .unwrap_or_else(|| text.to_string());
+    let fields = object
+        .into_iter()
+        .map(|(key, value)| (key, json_text(&value)))
+        .collect();
+    builder.message(message).fields(fields)
+}
+
+fn take_first(object: &mut Map<String, Value>, keys: &[&str]) -> Option<Value> {
+    keys.iter().find_map(|key| object.remove(*key))
+}
+
+/// Strings as-is, everything else as compact JSON
+fn json_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// An RFC 3339 string, or epoch milliseconds (pino) or seconds
+fn json_time(value: &Value) -> Option<DateTime<FixedOffset>> {
+    let utc = match value {
+        Value::String(s) => return DateTime::parse_from_rfc3339(s).ok(),
+        Value::Number(n) => {
+            let n = n.as_f64()?;
+            if n >= 1e11 {
+                DateTime::from_timestamp_millis(n as i64)?
+            } else {
+                DateTime::from_timestamp_millis((n * 1000.0) as i64)?
+            }
+        }
+        _ => return None,
+    };
+    Some(utc.fixed_offset())
+}
+
+/// A line from a stream that isn't in Heroku format
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnparsedLine {
+    /// 1-based position in the stream, or 0 when not known
+    pub line_number: usize,
+    pub line: String,
+}
+
+/// Outcome of parsing one line from a stream
+pub type ParsedLine = Result<LogEntry, UnparsedLine>;
+
+/// Parse every line read from `reader`
+///
+/// Lines that don't parse are yielded as errors so callers can wrap, count
+/// or drop them. The stream ends at EOF or on the first read error.
+pub fn parse_stream(reader: impl AsyncBufRead + Unpin) -> impl Stream<Item = ParsedLine> {
+    futures::stream::unfold((reader.lines(), 0), |(mut lines, line_number)| async move {
+        let line = lines.next_line().await.ok()??;
+        let line_number = line_number + 1;
+        let result = parse_log_line(&line).ok_or(UnparsedLine { line_number, line });
+        Some((result, (lines, line_number)))
     })
 }
 
@@ -96,6 +551,16 @@ mod tests {
     use super::*;
     use chrono::Timelike;
 
+    macro_rules! fixture {
+        ($name:literal) => {
+            (
+                $name,
+                include_str!(concat!("../../tests/fixtures/", $name, ".log")),
+                include_str!(concat!("../../tests/fixtures/", $name, ".golden")),
+            )
+        };
+    }
+
     #[test]
     fn test_parse_basic_log() {
         let line = "2010-09-16T15:13:46.677020+00:00 app[web.1]: Starting process";
@@ -169,4 +634,222 @@ mod tests {
         assert_eq!(entry.timestamp.minute(), 30);
         assert_eq!(entry.timestamp.second(), 45);
     }
+
+    #[test]
+    fn test_builder_round_trips_through_parser() {
+        let timestamp = DateTime::parse_from_rfc3339("2024-02-17T10:30:45.123456+00:00").unwrap();
+        let entry = LogEntry::builder()
+            .timestamp(timestamp)
+            .source("heroku")
+            .dyno("router")
+            .message("at=info status=200")
+            .build()
+            .unwrap();
+
+        assert_eq!(entry.level, LogLevel::Info);
+        assert_eq!(parse_log_line(&entry.raw), Some(entry));
+    }
+
+    #[test]
+    fn test_builder_defaults_and_overrides() {
+        let entry = LogEntry::builder()
+            .message("WARN cache miss")
+            .raw("cache miss")
+            .build()
+            .unwrap();
+
+        assert_eq!(entry.source, "app");
+        assert_eq!(entry.dyno, "web.1");
+        assert_eq!(entry.level, LogLevel::Warn);
+        assert_eq!(entry.raw, "cache miss");
+    }
+
+    #[test]
+    fn test_builder_rejects_invalid_names() {
+        assert!(LogEntry::builder().source("my app").build().is_err());
+        assert!(LogEntry::builder().dyno("").build().is_err());
+        assert!(LogEntry::builder().dyno("web]1").build().is_err());
+    }
+
+    #[test]
+    fn test_local_entry() {
+        let entry = LogEntry::local("WARN disk almost full", "fifo");
+
+        assert_eq!(entry.source, "local");
+        assert_eq!(entry.dyno, "fifo");
+        assert_eq!(entry.level, LogLevel::Warn);
+        assert_eq!(entry.raw, "WARN disk almost full");
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        let start = parse_log_line("2024-02-17T10:30:00.000000+00:00 app[web.1]: Deploy")
+            .unwrap()
+            .timestamp;
+        let entry = parse_log_line("2024-02-17T10:33:12.450000+00:00 app[web.1]: Test").unwrap();
+
+        assert_eq!(entry.format_elapsed(start), "T+00:03:12.450");
+        assert_eq!(entry.format_elapsed(entry.timestamp), "T+00:00:00.000");
+    }
+
+    #[test]
+    fn test_format_elapsed_before_start() {
+        let start = parse_log_line("2024-02-17T10:30:01.500000+00:00 app[web.1]: Deploy")
+            .unwrap()
+            .timestamp;
+        let entry = parse_log_line("2024-02-17T10:30:00.000000+00:00 app[web.2]: Late").unwrap();
+
+        assert_eq!(entry.format_elapsed(start), "T-00:00:01.500");
+    }
+
+    /// Captures in `tests/fixtures`, each with a `.golden` file holding the
+    /// expected parse of every line
+    const FIXTURES: &[(&str, &str, &str)] = &[
+        fixture!("router"),
+        fixture!("rails"),
+        fixture!("node"),
+        fixture!("python"),
+        fixture!("platform"),
+        fixture!("build"),
+        fixture!("json"),
+        fixture!("syslog"),
+    ];
+
+    /// One golden line: `time source[dyno] LEVEL message`, or `unparsed`
+    fn golden_line(line: &str) -> String {
+        match parse_log_line(line) {
+            Some(entry) => format!(
+                "{} {}[{}] {:?} {}",
+                entry.timestamp.format("%H:%M:%S%.6f%:z"),
+                entry.source,
+                entry.dyno,
+                entry.level,
+                entry.message
+            ),
+            None => "unparsed".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_golden_fixtures() {
+        for (name, log, golden) in FIXTURES {
+            let actual: Vec<String> = log.lines().map(golden_line).collect();
+            let expected: Vec<&str> = golden.lines().collect();
+            assert_eq!(
+                actual, expected,
+                "fixture {} no longer parses as recorded",
+                name
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parse_stream_reports_unparsed_lines() {
+        use futures::StreamExt;
+
+        let input: &[u8] =
+            b"2024-02-17T10:30:45.123456+00:00 app[web.1]: one\n-----> Building\n2024-02-17T10:30:46.000000+00:00 app[web.1]: two\n";
+        let results: Vec<_> = parse_stream(input).collect().await;
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap().message, "one");
+        assert_eq!(
+            results[1],
+            Err(UnparsedLine {
+                line_number: 2,
+                line: "-----> Building".to_string(),
+            })
+        );
+        assert_eq!(results[2].as_ref().unwrap().message, "two");
+    }
+
+    #[test]
+    fn test_day_boundaries() {
+        let logs: Vec<LogEntry> = [
+            "2024-02-17T23:59:58.000000+00:00",
+            "2024-02-17T23:59:59.000000+00:00",
+            "2024-02-18T00:00:01.000000+00:00",
+        ]
+        .iter()
+        .map(|ts| parse_log_line(&format!("{} app[web.1]: tick", ts)).unwrap())
+        .collect();
+
+        let day = |d: u32| NaiveDate::from_ymd_opt(2024, 2, d).unwrap();
+        assert_eq!(day_boundaries(&logs), vec![(0, day(17)), (2, day(18))]);
+        assert!(day_boundaries(&logs[..2]).is_empty());
+    }
+
+    #[test]
+    fn test_parse_json_fields() {
+        let line = r#"2024-02-17T10:30:45.123456+00:00 app[web.1]: {"level":"error","msg":"payment failed","status":502,"retry":true}"#;
+        let entry = parse_log_line(line).expect("Failed to parse");
+
+        assert_eq!(entry.message, "payment failed");
+        assert_eq!(entry.level, LogLevel::Error);
+        assert_eq!(entry.raw, line);
+        assert_eq!(entry.fields.get("status").map(String::as_str), Some("502"));
+        assert_eq!(entry.fields.get("retry").map(String::as_str), Some("true"));
+        assert!(!entry.fields.contains_key("level"));
+        assert!(!entry.fields.contains_key("msg"));
+    }
+
+    #[test]
+    fn test_parse_plain_heroku_line_has_no_fields() {
+        let line = "2010-09-16T15:13:46.677020+00:00 app[web.1]: {not json";
+        let entry = parse_log_line(line).expect("Failed to parse");
+
+        assert_eq!(entry.message, "{not json");
+        assert!(entry.fields.is_empty());
+    }
+
+    #[test]
+    fn test_parse_syslog_fields() {
+        let entry = parse_log_line(
+            r#"<165>1 2024-02-17T10:30:45.123456+00:00 web-01 nginx 2231 ACCESS [origin ip="10.0.0.7" port="443"][meta seq="1"] GET /"#,
+        )
+        .unwrap();
+        assert_eq!(entry.fields["host"], "web-01");
+        assert_eq!(entry.fields["procid"], "2231");
+        assert_eq!(entry.fields["msgid"], "ACCESS");
+        assert_eq!(entry.fields["ip"], "10.0.0.7");
+        assert_eq!(entry.fields["seq"], "1");
+        assert_eq!(entry.message, "GET /");
+
+        // No timestamp: stamped on arrival
+        let undated = parse_syslog_line("<14>1 - host app - - - hello").unwrap();
+        assert_eq!(undated.message, "hello");
+        assert!(parse_syslog_line("<14>2 - host app - - - hello").is_none());
+    }
+
+    #[test]
+    fn test_parse_logfmt() {
+        let fields = parse_logfmt(
+            r#"at=error code=H12 desc="Request timeout" method=GET path="/" sock=client"#,
+        );
+
+        assert_eq!(fields["at"], "error");
+        assert_eq!(fields["desc"], "Request timeout");
+        assert_eq!(fields["path"], "/");
+        assert_eq!(fields.len(), 6);
+        assert_eq!(parse_logfmt("flag key=")["flag"], "");
+        assert!(parse_logfmt("   ").is_empty());
+    }
+
+    #[test]
+    fn test_router_lines_get_logfmt_fields() {
+        let router = parse_log_line(
+            "2010-09-16T15:13:46.677020+00:00 heroku[router]: at=info method=GET path=/ status=200 service=12ms",
+        )
+        .unwrap();
+        assert_eq!(router.fields["status"], "200");
+        assert_eq!(router.fields["service"], "12ms");
+        assert_eq!(
+            router.message,
+            "at=info method=GET path=/ status=200 service=12ms"
+        );
+
+        let app =
+            parse_log_line("2010-09-16T15:13:46.677020+00:00 app[web.1]: status=200").unwrap();
+        assert!(app.fields.is_empty());
+    }
 }
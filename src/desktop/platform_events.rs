@@ -0,0 +1,222 @@
+//! Dyno crashes, restarts and Heroku error codes picked out of the stream
+//!
+//! Heroku reports trouble with a dyno on `heroku[<dyno>]` lines:
+//!
+//! ```text
+//! heroku[web.1]: State changed from up to crashed
+//! heroku[web.1]: Restarting
+//! heroku[worker.1]: Error R14 (Memory quota exceeded)
+//! heroku[router]: at=error code=H12 desc="Request timeout" ... dyno=web.2 ...
+//! ```
+//!
+//! Each becomes an event in the platform events pane, and ones not yet
+//! looked at are counted in the status header.
+
+use crate::parser::LogEntry;
+use regex::Regex;
+use std::collections::VecDeque;
+use std::sync::OnceLock;
+
+/// Events kept for the pane
+const MAX_EVENTS: usize = 200;
+
+fn error_code_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(r"^Error ([A-Z]\d{2}) \((.+)\)").expect("Failed to compile error code regex")
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlatformEventKind {
+    /// The dyno's state changed to `crashed`
+    Crash,
+    /// The dyno is being restarted, by Heroku or after a crash
+    Restart,
+    /// A Heroku error code such as `R14` or `H12`
+    Error(String),
+}
+
+/// Something Heroku reported about a dyno
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlatformEvent {
+    pub kind: PlatformEventKind,
+    /// The dyno affected; for router errors, the dyno that served the
+    /// request, when there was one
+    pub dyno: String,
+    pub description: String,
+    pub entry: LogEntry,
+}
+
+impl PlatformEvent {
+    /// The event a line reports, if any
+    pub fn from_entry(entry: &LogEntry) -> Option<Self> {
+        if entry.source != "heroku" {
+            return None;
+        }
+        let event = |kind, dyno: &str, description: &str| {
+            Some(Self {
+                kind,
+                dyno: dyno.to_string(),
+                description: description.to_string(),
+                entry: entry.clone(),
+            })
+        };
+        let message = entry.message.trim();
+        if entry.dyno == "router" {
+            if entry.fields.get("at").map(String::as_str) != Some("error") {
+                return None;
+            }
+            let code = entry.fields.get("code")?;
+            let dyno = entry
+                .fields
+                .get("dyno")
+                .filter(|dyno| !dyno.is_empty() && dyno.as_str() != "none")
+                .unwrap_or(&entry.dyno);
+            let description = entry.fields.get("desc").map_or("", String::as_str);
+            return event(PlatformEventKind::Error(code.clone()), dyno, description);
+        }
+        if let Some(captures) = error_code_regex().captures(message) {
+            return event(
+                PlatformEventKind::Error(captures[1].to_string()),
+                &entry.dyno,
+                &captures[2],
+            );
+        }
+        if message.starts_with("State changed from") && message.ends_with("to crashed") {
+            return event(PlatformEventKind::Crash, &entry.dyno, message);
+        }
+        if message == "Restarting"
+            || message == "Cycling"
+            || message == "State changed from crashed to starting"
+        {
+            return event(PlatformEventKind::Restart, &entry.dyno, message);
+        }
+        None
+    }
+
+    pub fn is_crash(&self) -> bool {
+        self.kind == PlatformEventKind::Crash
+    }
+}
+
+/// Recent platform events, with a count of those not yet seen
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlatformEvents {
+    events: VecDeque<PlatformEvent>,
+    unseen: usize,
+    unseen_crashes: usize,
+}
+
+impl PlatformEvents {
+    /// Record the event a line reports, if any
+    pub fn ingest(&mut self, entry: &LogEntry) {
+        let Some(event) = PlatformEvent::from_entry(entry) else {
+            return;
+        };
+        self.unseen += 1;
+        if event.is_crash() {
+            self.unseen_crashes += 1;
+        }
+        if self.events.len() == MAX_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+
+    /// Newest first
+    pub fn recent(&self) -> Vec<PlatformEvent> {
+        self.events.iter().rev().cloned().collect()
+    }
+
+    pub fn latest_crash(&self) -> Option<&PlatformEvent> {
+        self.events.iter().rev().find(|event| event.is_crash())
+    }
+
+    /// Events since the pane was last looked at
+    pub fn unseen(&self) -> usize {
+        self.unseen
+    }
+
+    /// Crashes among the unseen events
+    pub fn unseen_crashes(&self) -> usize {
+        self.unseen_crashes
+    }
+
+    pub fn mark_seen(&mut self) {
+        self.unseen = 0;
+        self.unseen_crashes = 0;
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_log_line;
+
+    fn line(text: &str) -> LogEntry {
+        parse_log_line(&format!("2024-02-17T10:30:45.123456+00:00 {}", text)).unwrap()
+    }
+
+    #[test]
+    fn test_from_entry() {
+        let crash =
+            PlatformEvent::from_entry(&line("heroku[web.1]: State changed from up to crashed"))
+                .unwrap();
+        assert_eq!(
+            (crash.kind, crash.dyno.as_str()),
+            (PlatformEventKind::Crash, "web.1")
+        );
+
+        let memory =
+            PlatformEvent::from_entry(&line("heroku[worker.1]: Error R14 (Memory quota exceeded)"))
+                .unwrap();
+        assert_eq!(memory.kind, PlatformEventKind::Error("R14".to_string()));
+        assert_eq!(memory.description, "Memory quota exceeded");
+
+        let timeout = PlatformEvent::from_entry(&line(
+            "heroku[router]: at=error code=H12 desc=\"Request timeout\" method=GET path=\"/\" host=app.herokuapp.com dyno=web.2 connect=1ms service=30000ms status=503 bytes=0",
+        ))
+        .unwrap();
+        assert_eq!(timeout.kind, PlatformEventKind::Error("H12".to_string()));
+        assert_eq!(
+            (timeout.dyno.as_str(), timeout.description.as_str()),
+            ("web.2", "Request timeout")
+        );
+
+        let restart = PlatformEvent::from_entry(&line("heroku[web.1]: Restarting")).unwrap();
+        assert_eq!(restart.kind, PlatformEventKind::Restart);
+    }
+
+    #[test]
+    fn test_ordinary_lines_are_not_events() {
+        for text in [
+            "heroku[web.1]: State changed from starting to up",
+            "heroku[router]: at=info method=GET path=\"/\" status=200",
+            "app[web.1]: Error R14 (Memory quota exceeded)",
+        ] {
+            assert_eq!(PlatformEvent::from_entry(&line(text)), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_unseen_counts() {
+        let mut events = PlatformEvents::default();
+        events.ingest(&line("heroku[web.1]: State changed from up to crashed"));
+        events.ingest(&line(
+            "heroku[web.1]: State changed from crashed to starting",
+        ));
+        events.ingest(&line("app[web.1]: hello"));
+        assert_eq!((events.unseen(), events.unseen_crashes()), (2, 1));
+        assert_eq!(events.recent()[0].kind, PlatformEventKind::Restart);
+        assert_eq!(events.latest_crash().unwrap().dyno, "web.1");
+
+        events.mark_seen();
+        assert_eq!((events.unseen(), events.unseen_crashes()), (0, 0));
+        assert_eq!(events.recent().len(), 2);
+    }
+}
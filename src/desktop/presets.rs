@@ -0,0 +1,118 @@
+//! Named filter queries, saved in `~/.config/logs-parser/presets`
+//!
+//! `:preset save errors` keeps the current filters under a name and
+//! `:preset errors` puts them back. The file holds one `name = query` per
+//! line, in the filter bar's own syntax, so it can be written by hand too:
+//!
+//! ```text
+//! errors = level:error -dyno:router
+//! slow-web = dyno:web slow:500ms
+//! ```
+
+use crate::config::config_dir;
+use anyhow::{Context, Result};
+
+/// Saved filter queries by name, in the order they were added
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Presets {
+    presets: Vec<(String, String)>,
+}
+
+/// Whether a name can be saved and read back from the presets file
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(char::is_whitespace) && !name.contains('=')
+}
+
+impl Presets {
+    /// Parse a presets file, skipping blank lines, comments and lines
+    /// without a name and query
+    pub fn parse(contents: &str) -> Self {
+        let mut presets = Self::default();
+        for line in contents.lines().map(str::trim) {
+            if line.starts_with('#') {
+                continue;
+            }
+            if let Some((name, query)) = line.split_once('=') {
+                let (name, query) = (name.trim(), query.trim());
+                if is_valid_name(name) && !query.is_empty() {
+                    presets.set(name, query);
+                }
+            }
+        }
+        presets
+    }
+
+    /// Load the user's presets; no file means none
+    pub fn load() -> Self {
+        std::fs::read_to_string(config_dir().join("presets"))
+            .map(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    /// Write the presets back to the presets file
+    pub fn save(&self) -> Result<()> {
+        let dir = config_dir();
+        std::fs::create_dir_all(&dir).context("Failed to create config directory")?;
+        let contents: String = self
+            .presets
+            .iter()
+            .map(|(name, query)| format!("{} = {}\n", name, query))
+            .collect();
+        std::fs::write(dir.join("presets"), contents).context("Failed to save presets")
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.presets
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, query)| query.as_str())
+    }
+
+    /// Save a query under a name, replacing any preset already called that
+    pub fn set(&mut self, name: &str, query: &str) {
+        match self.presets.iter_mut().find(|(n, _)| n == name) {
+            Some((_, existing)) => *existing = query.to_string(),
+            None => self.presets.push((name.to_string(), query.to_string())),
+        }
+    }
+
+    /// Forget a preset; false if there was none by that name
+    pub fn remove(&mut self, name: &str) -> bool {
+        let before = self.presets.len();
+        self.presets.retain(|(n, _)| n != name);
+        self.presets.len() != before
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.presets.iter().map(|(name, _)| name.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_presets_file() {
+        let presets = Presets::parse(
+            "# mine\nerrors = level:error -dyno:router\nbad name = x\nempty =\nslow = slow:500ms\n",
+        );
+        assert_eq!(presets.names(), ["errors", "slow"]);
+        assert_eq!(presets.get("errors"), Some("level:error -dyno:router"));
+        assert_eq!(presets.get("bad"), None);
+    }
+
+    #[test]
+    fn test_set_replaces_and_remove() {
+        let mut presets = Presets::default();
+        presets.set("errors", "level:error");
+        presets.set("web", "dyno:web");
+        presets.set("errors", "level:warn+");
+        assert_eq!(presets.names(), ["errors", "web"]);
+        assert_eq!(presets.get("errors"), Some("level:warn+"));
+
+        assert!(presets.remove("errors"));
+        assert!(!presets.remove("errors"));
+        assert_eq!(presets.names(), ["web"]);
+    }
+}
@@ -0,0 +1,221 @@
+//! Alerts on a sudden rise in matching lines, relative to a baseline
+//!
+//! `:alert level:error > 3x 10m` fires when the last minute holds more than
+//! three times as many matching lines as an average minute of the ten before
+//! it, catching a regression in a service that always logs some errors.
+//! Lines are counted per second as they arrive, like the activity
+//! sparkline, and the counts are checked on a timer. An alert fires once,
+//! then again only after the rate has fallen back.
+
+use crate::filters::Filter;
+use crate::parser::LogEntry;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Baseline used when `:alert` isn't given one
+pub const DEFAULT_BASELINE: Duration = Duration::from_secs(600);
+
+/// Seconds of the recent window compared against the baseline
+const RECENT_SECS: i64 = 60;
+
+/// Fewest recent matches worth alerting on, so a quiet query going from no
+/// lines to one doesn't fire
+const MIN_MATCHES: usize = 5;
+
+/// One relative alert and the per-second counts of its matches
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateAlert {
+    pub query: String,
+    filters: Vec<Filter>,
+    factor: f64,
+    baseline_secs: i64,
+    /// When the alert was added; it can't fire before a full baseline
+    started: i64,
+    counts: VecDeque<(i64, usize)>,
+    firing: bool,
+}
+
+impl RateAlert {
+    pub fn new(
+        query: String,
+        filters: Vec<Filter>,
+        factor: f64,
+        baseline: Duration,
+        now: i64,
+    ) -> Self {
+        Self {
+            query,
+            filters,
+            factor,
+            baseline_secs: baseline.as_secs().max(1) as i64,
+            started: now,
+            counts: VecDeque::new(),
+            firing: false,
+        }
+    }
+
+    /// Count an entry that arrived during second `now`, if it matches
+    pub fn record(&mut self, entry: &LogEntry, now: i64) {
+        if !self.filters.iter().all(|f| f.matches(entry)) {
+            return;
+        }
+        match self.counts.back_mut() {
+            Some((second, count)) if *second == now => *count += 1,
+            _ => self.counts.push_back((now, 1)),
+        }
+    }
+
+    /// Compare the last minute with the baseline before it; returns a
+    /// message when the alert fires
+    pub fn check(&mut self, now: i64) -> Option<String> {
+        let recent_from = now - RECENT_SECS;
+        let baseline_from = recent_from - self.baseline_secs;
+        while self
+            .counts
+            .front()
+            .is_some_and(|(second, _)| *second <= baseline_from)
+        {
+            self.counts.pop_front();
+        }
+        if now - self.started < RECENT_SECS + self.baseline_secs {
+            return None;
+        }
+
+        let (recent, earlier): (Vec<_>, Vec<_>) = self
+            .counts
+            .iter()
+            .partition(|(second, _)| *second > recent_from);
+        let recent: usize = recent.iter().map(|(_, count)| count).sum();
+        let earlier: usize = earlier.iter().map(|(_, count)| count).sum();
+        let baseline_minutes = self.baseline_secs as f64 / RECENT_SECS as f64;
+        let average = earlier as f64 / baseline_minutes;
+
+        let rising = recent >= MIN_MATCHES && recent as f64 > self.factor * average;
+        let fires = rising && !self.firing;
+        self.firing = rising;
+        fires.then(|| {
+            format!(
+                "\"{}\" is up: {} lines in the last minute against {:.1} a minute over the previous {}",
+                self.query,
+                recent,
+                average,
+                format_baseline(self.baseline_secs)
+            )
+        })
+    }
+}
+
+/// A baseline length such as `10 minutes` or `90s`
+fn format_baseline(secs: i64) -> String {
+    match secs {
+        60 => "minute".to_string(),
+        s if s % 3600 == 0 => format!("{} hours", s / 3600),
+        s if s % 60 == 0 => format!("{} minutes", s / 60),
+        s => format!("{}s", s),
+    }
+}
+
+/// Every relative alert set with `:alert`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RateAlerts {
+    alerts: Vec<RateAlert>,
+}
+
+impl RateAlerts {
+    pub fn add(&mut self, alert: RateAlert) {
+        self.alerts.push(alert);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.alerts.is_empty()
+    }
+
+    pub fn record(&mut self, entry: &LogEntry, now: i64) {
+        for alert in &mut self.alerts {
+            alert.record(entry, now);
+        }
+    }
+
+    /// Queries and messages of the alerts firing at `now`
+    pub fn check(&mut self, now: i64) -> Vec<(String, String)> {
+        self.alerts
+            .iter_mut()
+            .filter_map(|alert| Some((alert.query.clone(), alert.check(now)?)))
+            .collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.alerts.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filters::parse_query;
+    use crate::parser::parse_log_line;
+
+    fn line(message: &str) -> LogEntry {
+        parse_log_line(&format!(
+            "2024-02-17T10:30:45.000000+00:00 app[web.1]: {}",
+            message
+        ))
+        .unwrap()
+    }
+
+    fn errors_alert(now: i64) -> RateAlert {
+        RateAlert::new(
+            "level:error".to_string(),
+            parse_query("level:error"),
+            3.0,
+            Duration::from_secs(600),
+            now,
+        )
+    }
+
+    #[test]
+    fn test_fires_on_rise_over_baseline() {
+        let mut alert = errors_alert(0);
+        // Two errors a minute for ten minutes, and info lines that don't count
+        for minute in 0..10 {
+            alert.record(&line("Error: timeout"), minute * 60 + 10);
+            alert.record(&line("Error: timeout"), minute * 60 + 30);
+            alert.record(&line("ok"), minute * 60 + 31);
+        }
+        assert_eq!(alert.check(659), None);
+
+        for second in 601..608 {
+            alert.record(&line("Error: timeout"), second);
+        }
+        let message = alert.check(660).unwrap();
+        assert!(message.contains("7 lines in the last minute against 2.0 a minute"));
+        assert!(message.contains("previous 10 minutes"));
+
+        // Still high: not fired again until it has fallen back
+        alert.record(&line("Error: timeout"), 661);
+        assert_eq!(alert.check(662), None);
+        assert_eq!(alert.check(800), None);
+        for second in 801..811 {
+            alert.record(&line("Error: timeout"), second);
+        }
+        assert!(alert.check(811).is_some());
+    }
+
+    #[test]
+    fn test_waits_for_full_baseline() {
+        let mut alert = errors_alert(100);
+        for second in 100..120 {
+            alert.record(&line("Error: timeout"), second);
+        }
+        assert_eq!(alert.check(120), None);
+    }
+
+    #[test]
+    fn test_few_matches_never_fire() {
+        let mut alert = errors_alert(0);
+        for second in 700..704 {
+            alert.record(&line("Error: timeout"), second);
+        }
+        assert_eq!(alert.check(705), None);
+    }
+}
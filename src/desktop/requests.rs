@@ -0,0 +1,405 @@
+//! Correlation of log entries by Heroku request id
+//!
+//! The router logs `request_id=<uuid>` for every request, and apps commonly
+//! echo the same id (from the `X-Request-Id` header) in their own lines,
+//! either as `request_id=<uuid>` or as a Rails-style `[<uuid>]` tag.
+
+use crate::parser::{LogEntry, LogLevel};
+use chrono::{DateTime, FixedOffset};
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Maximum number of request ids tracked before the oldest are dropped
+const MAX_TRACKED_REQUESTS: usize = 1000;
+
+fn request_id_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(
+            r"(?:request_id=([\w-]+)|\[([0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12})\])",
+        )
+        .expect("Failed to compile request id regex")
+    })
+}
+
+/// Extract the request id a log message refers to, if any
+pub fn extract_request_id(message: &str) -> Option<&str> {
+    let captures = request_id_regex().captures(message)?;
+    captures
+        .get(1)
+        .or_else(|| captures.get(2))
+        .map(|m| m.as_str())
+}
+
+/// The `service=` latency in milliseconds of a router line
+pub fn router_service_ms(entry: &LogEntry) -> Option<u64> {
+    entry
+        .fields
+        .get("service")?
+        .trim_end_matches("ms")
+        .parse()
+        .ok()
+}
+
+/// Level implied by the `status=` of a Heroku router line
+///
+/// The router writes `at=info` even for failed requests, so keyword detection
+/// files a 503 as Info. Returns `None` for lines that aren't router lines.
+pub fn router_status_level(entry: &LogEntry) -> Option<LogLevel> {
+    if entry.source != "heroku" || entry.dyno != "router" {
+        return None;
+    }
+    let status: u16 = entry.fields.get("status")?.parse().ok()?;
+    Some(match status {
+        500.. => LogLevel::Error,
+        400..=499 => LogLevel::Warn,
+        _ => LogLevel::Info,
+    })
+}
+
+/// Address to repeat a router line's request at: its `path` on `base`, or
+/// else on the line's `host`, or on `<app>.herokuapp.com` for lines
+/// streamed from an app
+pub fn request_url(entry: &LogEntry, base: Option<&str>) -> Option<String> {
+    if entry.source != "heroku" || entry.dyno != "router" {
+        return None;
+    }
+    let path = entry.fields.get("path")?;
+    let base = match (base, entry.fields.get("host")) {
+        (Some(base), _) => base.trim_end_matches('/').to_string(),
+        (None, Some(host)) => format!("https://{}", host),
+        (None, None) => format!(
+            "https://{}.herokuapp.com",
+            entry.origin.strip_prefix("heroku:")?
+        ),
+    };
+    Some(format!("{}{}", base, path))
+}
+
+/// Command that opens a URL in the default browser
+pub fn browser_command(url: &str) -> Vec<String> {
+    let opener = if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(windows) {
+        "explorer"
+    } else {
+        "xdg-open"
+    };
+    vec![opener.to_string(), url.to_string()]
+}
+
+/// What a step of a request timeline stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepKind {
+    /// The router taking the request, worked out from its `connect` and
+    /// `service` times since it only logs the response
+    Received,
+    /// A line an app logged with the request id
+    App,
+    /// The router's line for the response
+    Responded,
+}
+
+/// One line of a request timeline
+#[derive(Debug, Clone, PartialEq)]
+pub struct WaterfallStep {
+    pub kind: StepKind,
+    /// Milliseconds from the start of the request to the previous step and
+    /// to this one
+    pub from_ms: i64,
+    pub at_ms: i64,
+    /// `source[dyno]`
+    pub label: String,
+    pub message: String,
+}
+
+/// The lines of one request laid out in time, approximating a trace
+#[derive(Debug, Clone, PartialEq)]
+pub struct Waterfall {
+    pub steps: Vec<WaterfallStep>,
+    /// Milliseconds from the first step to the last
+    pub total_ms: i64,
+}
+
+/// Timeline of the buffered lines carrying `request_id`, oldest first;
+/// `None` unless there are at least two steps to lay out
+pub fn waterfall(entries: &[LogEntry], request_id: &str) -> Option<Waterfall> {
+    let mut lines: Vec<&LogEntry> = entries
+        .iter()
+        .filter(|entry| extract_request_id(&entry.message) == Some(request_id))
+        .collect();
+    lines.sort_by_key(|entry| entry.timestamp);
+
+    let mut timed: Vec<(DateTime<FixedOffset>, StepKind, String, String)> = Vec::new();
+    for entry in lines {
+        let label = format!("{}[{}]", entry.source, entry.dyno);
+        if entry.dyno != "router" {
+            timed.push((entry.timestamp, StepKind::App, label, entry.message.clone()));
+            continue;
+        }
+        let field = |name: &str| entry.fields.get(name).cloned().unwrap_or_default();
+        let connect_ms: i64 = field("connect").trim_end_matches("ms").parse().unwrap_or(0);
+        let service_ms = router_service_ms(entry).unwrap_or(0) as i64;
+        let received = entry.timestamp - chrono::Duration::milliseconds(connect_ms + service_ms);
+        timed.push((
+            received,
+            StepKind::Received,
+            label.clone(),
+            format!("{} {}", field("method"), field("path")),
+        ));
+        timed.push((
+            entry.timestamp,
+            StepKind::Responded,
+            label,
+            format!("{} in {}ms", field("status"), service_ms),
+        ));
+    }
+    if timed.len() < 2 {
+        return None;
+    }
+    // App clocks can run slightly ahead of the router's
+    timed.sort_by_key(|(at, kind, _, _)| (*at, *kind != StepKind::Received));
+
+    let start = timed[0].0;
+    let mut previous = 0;
+    let steps: Vec<WaterfallStep> = timed
+        .into_iter()
+        .map(|(at, kind, label, message)| {
+            let at_ms = (at - start).num_milliseconds();
+            let step = WaterfallStep {
+                kind,
+                from_ms: previous,
+                at_ms,
+                label,
+                message,
+            };
+            previous = at_ms;
+            step
+        })
+        .collect();
+    let total_ms = steps.last().map_or(0, |step| step.at_ms);
+    Some(Waterfall { steps, total_ms })
+}
+
+/// What is known about one request from its router line and app lines
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestSummary {
+    pub request_id: String,
+    pub method: Option<String>,
+    pub path: Option<String>,
+    pub status: Option<u16>,
+    pub service_ms: Option<u64>,
+    /// Number of non-router lines carrying this request id
+    pub app_lines: usize,
+    pub last_seen: DateTime<FixedOffset>,
+}
+
+impl RequestSummary {
+    fn new(request_id: &str, timestamp: DateTime<FixedOffset>) -> Self {
+        Self {
+            request_id: request_id.to_string(),
+            method: None,
+            path: None,
+            status: None,
+            service_ms: None,
+            app_lines: 0,
+            last_seen: timestamp,
+        }
+    }
+
+    fn apply_router_line(&mut self, entry: &LogEntry) {
+        self.method = entry.fields.get("method").cloned();
+        self.path = entry.fields.get("path").cloned();
+        self.status = entry.fields.get("status").and_then(|s| s.parse().ok());
+        self.service_ms = router_service_ms(entry);
+    }
+}
+
+/// Index of recently seen requests, keyed by request id
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RequestIndex {
+    requests: HashMap<String, RequestSummary>,
+}
+
+impl RequestIndex {
+    /// Record an entry if it carries a request id
+    pub fn ingest(&mut self, entry: &LogEntry) {
+        let Some(request_id) = extract_request_id(&entry.message) else {
+            return;
+        };
+
+        let summary = self
+            .requests
+            .entry(request_id.to_string())
+            .or_insert_with(|| RequestSummary::new(request_id, entry.timestamp));
+        summary.last_seen = summary.last_seen.max(entry.timestamp);
+
+        if entry.dyno == "router" {
+            summary.apply_router_line(entry);
+        } else {
+            summary.app_lines += 1;
+        }
+
+        if self.requests.len() > MAX_TRACKED_REQUESTS {
+            self.evict_oldest();
+        }
+    }
+
+    fn evict_oldest(&mut self) {
+        let oldest = self
+            .requests
+            .values()
+            .min_by_key(|r| r.last_seen)
+            .map(|r| r.request_id.clone());
+        if let Some(id) = oldest {
+            self.requests.remove(&id);
+        }
+    }
+
+    /// Most recently seen requests first
+    pub fn recent(&self, limit: usize) -> Vec<RequestSummary> {
+        let mut requests: Vec<RequestSummary> = self.requests.values().cloned().collect();
+        requests.sort_by_key(|r| std::cmp::Reverse(r.last_seen));
+        requests.truncate(limit);
+        requests
+    }
+
+    pub fn clear(&mut self) {
+        self.requests.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_log_line;
+
+    const ID: &str = "8601b555-6a54-4b1b-9b4f-5b4a3d1a1c4f";
+
+    fn router_line() -> LogEntry {
+        parse_log_line(&format!(
+            "2024-02-17T10:30:45.123456+00:00 heroku[router]: at=info method=GET path=\"/users/42\" host=app.herokuapp.com request_id={} fwd=\"1.2.3.4\" dyno=web.1 connect=0ms service=182ms status=503 bytes=13 protocol=https",
+            ID
+        ))
+        .unwrap()
+    }
+
+    fn app_line() -> LogEntry {
+        parse_log_line(&format!(
+            "2024-02-17T10:30:45.100000+00:00 app[web.1]: [{}] Completed 503 Service Unavailable",
+            ID
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_extract_request_id() {
+        assert_eq!(extract_request_id(&router_line().message), Some(ID));
+        assert_eq!(extract_request_id(&app_line().message), Some(ID));
+        assert_eq!(extract_request_id("no id here"), None);
+    }
+
+    #[test]
+    fn test_router_status_level() {
+        assert_eq!(router_status_level(&router_line()), Some(LogLevel::Error));
+        assert_eq!(router_status_level(&app_line()), None);
+
+        let not_found = parse_log_line(
+            "2024-02-17T10:30:45.123456+00:00 heroku[router]: at=info method=GET path=\"/errors\" status=404 bytes=0",
+        )
+        .unwrap();
+        assert_eq!(router_status_level(&not_found), Some(LogLevel::Warn));
+    }
+
+    #[test]
+    fn test_request_url() {
+        assert_eq!(
+            request_url(&router_line(), None).as_deref(),
+            Some("https://app.herokuapp.com/users/42")
+        );
+        assert_eq!(
+            request_url(&router_line(), Some("http://localhost:3000/")).as_deref(),
+            Some("http://localhost:3000/users/42")
+        );
+        assert_eq!(request_url(&app_line(), None), None);
+
+        let mut no_host = parse_log_line(
+            "2024-02-17T10:30:45.123456+00:00 heroku[router]: at=info method=GET path=\"/errors\" status=404 bytes=0",
+        )
+        .unwrap();
+        assert_eq!(request_url(&no_host, None), None);
+        no_host.origin = "heroku:my-app".to_string();
+        assert_eq!(
+            request_url(&no_host, None).as_deref(),
+            Some("https://my-app.herokuapp.com/errors")
+        );
+    }
+
+    #[test]
+    fn test_waterfall() {
+        let started = parse_log_line(&format!(
+            "2024-02-17T10:30:45.000000+00:00 app[web.1]: [{}] Started GET \"/users/42\"",
+            ID
+        ))
+        .unwrap();
+        let lines = vec![router_line(), app_line(), started, app_line()];
+
+        let waterfall = waterfall(&lines, ID).unwrap();
+        let steps: Vec<(StepKind, i64, i64)> = waterfall
+            .steps
+            .iter()
+            .map(|step| (step.kind, step.from_ms, step.at_ms))
+            .collect();
+        // Received at 10:30:44.941, 182ms of service before 10:30:45.123
+        assert_eq!(
+            steps,
+            [
+                (StepKind::Received, 0, 0),
+                (StepKind::App, 0, 58),
+                (StepKind::App, 58, 158),
+                (StepKind::App, 158, 158),
+                (StepKind::Responded, 158, 182),
+            ]
+        );
+        assert_eq!(waterfall.total_ms, 182);
+        assert_eq!(waterfall.steps[0].message, "GET /users/42");
+        assert_eq!(waterfall.steps[4].message, "503 in 182ms");
+        assert_eq!(waterfall.steps[4].label, "heroku[router]");
+
+        assert_eq!(super::waterfall(&[app_line()], ID), None);
+    }
+
+    #[test]
+    fn test_index_combines_router_and_app_lines() {
+        let mut index = RequestIndex::default();
+        index.ingest(&app_line());
+        index.ingest(&app_line());
+        index.ingest(&router_line());
+
+        let recent = index.recent(10);
+        assert_eq!(recent.len(), 1);
+        let summary = &recent[0];
+        assert_eq!(summary.request_id, ID);
+        assert_eq!(summary.method.as_deref(), Some("GET"));
+        assert_eq!(summary.path.as_deref(), Some("/users/42"));
+        assert_eq!(summary.status, Some(503));
+        assert_eq!(summary.service_ms, Some(182));
+        assert_eq!(summary.app_lines, 2);
+    }
+
+    #[test]
+    fn test_recent_orders_newest_first() {
+        let mut index = RequestIndex::default();
+        for (i, second) in ["10", "12", "11"].iter().enumerate() {
+            let line = format!(
+                "2024-02-17T10:30:{}.000000+00:00 app[web.1]: request_id=req-{} done",
+                second, i
+            );
+            index.ingest(&parse_log_line(&line).unwrap());
+        }
+
+        let ids: Vec<String> = index.recent(2).into_iter().map(|r| r.request_id).collect();
+        assert_eq!(ids, vec!["req-1", "req-2"]);
+    }
+}
@@ -0,0 +1,133 @@
+//! Display rewrite rules, loaded from `~/.config/logs-parser/rewrites`
+//!
+//! Rules make messages easier to read, e.g. by collapsing UUIDs. They only
+//! change what is displayed; `LogEntry::raw` keeps the original line. Each
+//! line of the rewrites file holds `/regex/ => replacement`, where the
+//! replacement may use `$1`-style capture references:
+//!
+//! ```text
+//! /[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}/ => <uuid>
+//! /^method=(\w+) path=(\S+)/ => $1 $2
+//! ```
+
+use crate::config::config_dir;
+use regex::Regex;
+use std::borrow::Cow;
+
+/// A single regex replacement
+#[derive(Debug, Clone)]
+pub struct RewriteRule {
+    pattern: Regex,
+    replacement: String,
+}
+
+impl PartialEq for RewriteRule {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern.as_str() == other.pattern.as_str() && self.replacement == other.replacement
+    }
+}
+
+impl RewriteRule {
+    /// Parse a `/regex/ => replacement` line
+    pub fn parse(line: &str) -> Option<Self> {
+        let (pattern, replacement) = line.split_once("=>")?;
+        let pattern = pattern.trim().strip_prefix('/')?.strip_suffix('/')?;
+        if pattern.is_empty() {
+            return None;
+        }
+        Some(Self {
+            pattern: Regex::new(pattern).ok()?,
+            // Only the separator's padding is trimmed so replacements can
+            // keep meaningful trailing spaces
+            replacement: replacement
+                .strip_prefix(' ')
+                .unwrap_or(replacement)
+                .to_string(),
+        })
+    }
+}
+
+/// An ordered list of rewrite rules, applied one after another
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RewriteRules {
+    rules: Vec<RewriteRule>,
+}
+
+impl RewriteRules {
+    /// Parse a rewrites file, skipping blank lines, comments and invalid rules
+    pub fn parse(contents: &str) -> Self {
+        let rules = contents
+            .lines()
+            .filter(|line| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+            .filter_map(RewriteRule::parse)
+            .collect();
+        Self { rules }
+    }
+
+    /// Load rules from the user's rewrites file; no file means no rules
+    pub fn load() -> Self {
+        std::fs::read_to_string(config_dir().join("rewrites"))
+            .map(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    /// Apply every rule to a message, borrowing it when nothing changes
+    pub fn apply<'a>(&self, message: &'a str) -> Cow<'a, str> {
+        let mut result = Cow::Borrowed(message);
+        for rule in &self.rules {
+            if let Cow::Owned(replaced) =
+                rule.pattern.replace_all(&result, rule.replacement.as_str())
+            {
+                result = Cow::Owned(replaced);
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_uuid() {
+        let rules = RewriteRules::parse(
+            "/[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}/ => <uuid>",
+        );
+
+        assert_eq!(
+            rules.apply("job 0b6f9c6e-5d1a-4d4e-9f43-3c1e0c2b7a11 done"),
+            "job <uuid> done"
+        );
+    }
+
+    #[test]
+    fn test_rules_apply_in_order() {
+        let rules = RewriteRules::parse("/foo/ => bar\n/bar/ => baz");
+
+        assert_eq!(rules.apply("foo"), "baz");
+    }
+
+    #[test]
+    fn test_capture_references() {
+        let rules = RewriteRules::parse(r"/^method=(\w+) path=(\S+)/ => $1 $2");
+
+        assert_eq!(rules.apply("method=GET path=/users"), "GET /users");
+    }
+
+    #[test]
+    fn test_unchanged_message_is_borrowed() {
+        let rules = RewriteRules::parse("/nomatch/ => x");
+
+        assert!(matches!(rules.apply("hello"), Cow::Borrowed("hello")));
+    }
+
+    #[test]
+    fn test_invalid_rules_skipped() {
+        let rules =
+            RewriteRules::parse("# comment\nno slashes => x\n/(/ => x\n// => x\n/ok/ => fine");
+
+        assert_eq!(rules.rules.len(), 1);
+        assert_eq!(rules.apply("ok"), "fine");
+    }
+}
@@ -0,0 +1,202 @@
+//! Latency, throughput and status codes of router lines over a sliding window
+//!
+//! Each Heroku router line's `service=`, `connect=`, `status=` and `bytes=`
+//! are read as numbers. Requests are kept for the last five minutes of log
+//! time, counted from the newest router line, so replays and restored
+//! history are summarised the same way as a live stream.
+
+use crate::parser::LogEntry;
+use crate::requests::router_service_ms;
+use chrono::{DateTime, Duration, FixedOffset};
+use std::collections::{HashMap, VecDeque};
+
+/// How much log time the summary covers
+pub const WINDOW_SECS: i64 = 300;
+
+/// Numeric fields of one router line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RouterRequest {
+    pub at: DateTime<FixedOffset>,
+    pub service_ms: u64,
+    pub connect_ms: u64,
+    pub status: u16,
+    pub bytes: u64,
+}
+
+impl RouterRequest {
+    /// Read a router line; `None` for other lines and for router lines
+    /// without a status or service time
+    pub fn from_entry(entry: &LogEntry) -> Option<Self> {
+        if entry.source != "heroku" || entry.dyno != "router" {
+            return None;
+        }
+        let number = |name: &str| -> Option<u64> {
+            entry.fields.get(name)?.trim_end_matches("ms").parse().ok()
+        };
+        Some(Self {
+            at: entry.timestamp,
+            service_ms: router_service_ms(entry)?,
+            connect_ms: number("connect").unwrap_or(0),
+            status: entry.fields.get("status")?.parse().ok()?,
+            bytes: number("bytes").unwrap_or(0),
+        })
+    }
+}
+
+/// What the router did within the window
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RouterSummary {
+    pub requests: usize,
+    pub per_second: f64,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    pub p99_ms: u64,
+    pub avg_connect_ms: u64,
+    pub bytes_per_second: u64,
+    /// Requests per status class, as its first code (`200` for 2xx), in
+    /// class order
+    pub classes: Vec<(u16, usize)>,
+    /// Requests per status code, most frequent first
+    pub codes: Vec<(u16, usize)>,
+}
+
+/// Router requests within the window
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RouterMetrics {
+    requests: VecDeque<RouterRequest>,
+    newest: Option<DateTime<FixedOffset>>,
+}
+
+impl RouterMetrics {
+    /// Count a router line, dropping requests that fell out of the window;
+    /// other lines are ignored
+    pub fn ingest(&mut self, entry: &LogEntry) {
+        let Some(request) = RouterRequest::from_entry(entry) else {
+            return;
+        };
+        let newest = self
+            .newest
+            .map_or(request.at, |newest| newest.max(request.at));
+        self.newest = Some(newest);
+        let cutoff = newest - Duration::seconds(WINDOW_SECS);
+        if request.at < cutoff {
+            return;
+        }
+        self.requests.push_back(request);
+        while self.requests.front().is_some_and(|r| r.at < cutoff) {
+            self.requests.pop_front();
+        }
+    }
+
+    pub fn summary(&self) -> RouterSummary {
+        let (Some(oldest), Some(newest)) = (self.requests.iter().map(|r| r.at).min(), self.newest)
+        else {
+            return RouterSummary::default();
+        };
+        let count = self.requests.len();
+        let seconds = ((newest - oldest).num_seconds() + 1).clamp(1, WINDOW_SECS) as f64;
+
+        let mut service: Vec<u64> = self.requests.iter().map(|r| r.service_ms).collect();
+        service.sort_unstable();
+        let percentile = |p: usize| service[(p * count).div_ceil(100).max(1) - 1];
+
+        let mut classes: HashMap<u16, usize> = HashMap::new();
+        let mut codes: HashMap<u16, usize> = HashMap::new();
+        for request in &self.requests {
+            *classes.entry(request.status / 100 * 100).or_default() += 1;
+            *codes.entry(request.status).or_default() += 1;
+        }
+        let mut classes: Vec<(u16, usize)> = classes.into_iter().collect();
+        classes.sort();
+        let mut codes: Vec<(u16, usize)> = codes.into_iter().collect();
+        codes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        let connect: u64 = self.requests.iter().map(|r| r.connect_ms).sum();
+        let bytes: u64 = self.requests.iter().map(|r| r.bytes).sum();
+        RouterSummary {
+            requests: count,
+            per_second: count as f64 / seconds,
+            p50_ms: percentile(50),
+            p95_ms: percentile(95),
+            p99_ms: percentile(99),
+            avg_connect_ms: connect / count as u64,
+            bytes_per_second: (bytes as f64 / seconds) as u64,
+            classes,
+            codes,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.requests.clear();
+        self.newest = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_log_line;
+
+    fn router(second: u32, status: u16, service: u64) -> LogEntry {
+        parse_log_line(&format!(
+            "2024-02-17T10:{:02}:{:02}.000000+00:00 heroku[router]: at=info method=GET path=\"/\" host=app.herokuapp.com connect=2ms service={}ms status={} bytes=1000",
+            second / 60,
+            second % 60,
+            service,
+            status
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_from_entry() {
+        let request = RouterRequest::from_entry(&router(0, 503, 30000)).unwrap();
+        assert_eq!(
+            (
+                request.service_ms,
+                request.connect_ms,
+                request.status,
+                request.bytes
+            ),
+            (30000, 2, 503, 1000)
+        );
+        let app =
+            parse_log_line("2024-02-17T10:30:45.123456+00:00 app[web.1]: status=200").unwrap();
+        assert_eq!(RouterRequest::from_entry(&app), None);
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut metrics = RouterMetrics::default();
+        for i in 1..=100 {
+            let status = if i % 10 == 0 { 500 } else { 200 };
+            metrics.ingest(&router(i as u32 % 10, status, i));
+        }
+        let summary = metrics.summary();
+        assert_eq!(summary.requests, 100);
+        assert_eq!(
+            (summary.p50_ms, summary.p95_ms, summary.p99_ms),
+            (50, 95, 99)
+        );
+        assert_eq!(summary.per_second, 10.0);
+        assert_eq!(summary.avg_connect_ms, 2);
+        assert_eq!(summary.bytes_per_second, 10_000);
+        assert_eq!(summary.classes, [(200, 90), (500, 10)]);
+        assert_eq!(summary.codes, [(200, 90), (500, 10)]);
+    }
+
+    #[test]
+    fn test_window_slides() {
+        let mut metrics = RouterMetrics::default();
+        metrics.ingest(&router(0, 200, 10));
+        metrics.ingest(&router(200, 200, 20));
+        metrics.ingest(&router(400, 200, 30));
+        // Too old for the window by the time it arrives
+        metrics.ingest(&router(50, 200, 40));
+
+        let summary = metrics.summary();
+        assert_eq!(summary.requests, 2);
+        assert_eq!(summary.p99_ms, 30);
+        assert_eq!(RouterMetrics::default().summary(), RouterSummary::default());
+    }
+}
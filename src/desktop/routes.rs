@@ -0,0 +1,244 @@
+//! Router request stats per route, with ids in paths folded into templates
+//!
+//! `/users/123` and `/users/456` are both counted as `/users/:id`: numeric
+//! segments become `:id`, UUIDs `:uuid` and long hex digests `:hash`, and
+//! the query string is dropped. Routes the heuristics can't see, such as
+//! slugs, can be listed in `~/.config/logs-parser/routes`, one template per
+//! line with `:name` for a segment that varies:
+//!
+//! ```text
+//! /posts/:slug
+//! /orgs/:org/members/:member
+//! ```
+
+use crate::config::config_dir;
+use crate::parser::LogEntry;
+use crate::requests::router_service_ms;
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+fn uuid_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(r"(?i)^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$")
+            .expect("Failed to compile uuid regex")
+    })
+}
+
+/// Placeholder for a path segment that looks like an id
+fn placeholder(segment: &str) -> Option<&'static str> {
+    if !segment.is_empty() && segment.chars().all(|c| c.is_ascii_digit()) {
+        Some(":id")
+    } else if uuid_regex().is_match(segment) {
+        Some(":uuid")
+    } else if segment.len() >= 12
+        && segment.chars().all(|c| c.is_ascii_hexdigit())
+        && segment.chars().any(|c| c.is_ascii_digit())
+    {
+        Some(":hash")
+    } else {
+        None
+    }
+}
+
+/// Templates paths are matched against before the heuristics apply
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RoutePatterns {
+    templates: Vec<Vec<String>>,
+}
+
+impl RoutePatterns {
+    /// Parse a routes file, skipping blank lines, comments and lines that
+    /// aren't paths
+    pub fn parse(contents: &str) -> Self {
+        let templates = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| line.starts_with('/'))
+            .map(|line| line.split('/').map(str::to_string).collect())
+            .collect();
+        Self { templates }
+    }
+
+    /// Load templates from the user's routes file; no file means none
+    pub fn load() -> Self {
+        std::fs::read_to_string(config_dir().join("routes"))
+            .map(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    /// The route a request path belongs to
+    pub fn template(&self, path: &str) -> String {
+        let path = path.split(['?', '#']).next().unwrap_or(path);
+        let segments: Vec<&str> = path.split('/').collect();
+        let configured = self.templates.iter().find(|template| {
+            template.len() == segments.len()
+                && template
+                    .iter()
+                    .zip(&segments)
+                    .all(|(t, s)| t.starts_with(':') || t == s)
+        });
+        if let Some(template) = configured {
+            return template.join("/");
+        }
+        segments
+            .iter()
+            .map(|segment| placeholder(segment).unwrap_or(segment))
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+/// Requests seen for one method and route
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteSummary {
+    pub method: String,
+    pub template: String,
+    pub count: usize,
+    /// Responses with a 5xx status
+    pub errors: usize,
+    total_service_ms: u64,
+    pub max_service_ms: u64,
+}
+
+impl RouteSummary {
+    /// Mean `service=` time of the route's requests
+    pub fn avg_service_ms(&self) -> u64 {
+        self.total_service_ms / self.count.max(1) as u64
+    }
+}
+
+/// Router request stats by method and route
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RouteStats {
+    patterns: RoutePatterns,
+    routes: HashMap<(String, String), RouteSummary>,
+}
+
+impl RouteStats {
+    /// Empty stats using the user's routes file
+    pub fn load() -> Self {
+        Self {
+            patterns: RoutePatterns::load(),
+            routes: HashMap::new(),
+        }
+    }
+
+    /// Count a router line; other lines are ignored
+    pub fn ingest(&mut self, entry: &LogEntry) {
+        if entry.source != "heroku" || entry.dyno != "router" {
+            return;
+        }
+        let Some(path) = entry.fields.get("path") else {
+            return;
+        };
+        let method = entry.fields.get("method").cloned().unwrap_or_default();
+        let template = self.patterns.template(path);
+        let summary = self
+            .routes
+            .entry((method.clone(), template.clone()))
+            .or_insert_with(|| RouteSummary {
+                method,
+                template,
+                count: 0,
+                errors: 0,
+                total_service_ms: 0,
+                max_service_ms: 0,
+            });
+        summary.count += 1;
+        if entry
+            .fields
+            .get("status")
+            .is_some_and(|s| s.starts_with('5'))
+        {
+            summary.errors += 1;
+        }
+        let service_ms = router_service_ms(entry).unwrap_or(0);
+        summary.total_service_ms += service_ms;
+        summary.max_service_ms = summary.max_service_ms.max(service_ms);
+    }
+
+    /// Busiest routes first
+    pub fn busiest(&self, limit: usize) -> Vec<RouteSummary> {
+        let mut routes: Vec<RouteSummary> = self.routes.values().cloned().collect();
+        routes.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| a.template.cmp(&b.template))
+                .then_with(|| a.method.cmp(&b.method))
+        });
+        routes.truncate(limit);
+        routes
+    }
+
+    pub fn clear(&mut self) {
+        self.routes.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_log_line;
+
+    fn router(method: &str, path: &str, status: u16, service: u64) -> LogEntry {
+        parse_log_line(&format!(
+            "2024-02-17T10:30:45.123456+00:00 heroku[router]: at=info method={} path=\"{}\" host=app.herokuapp.com service={}ms status={} bytes=13",
+            method, path, service, status
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_heuristic_templates() {
+        let patterns = RoutePatterns::default();
+        assert_eq!(patterns.template("/users/123"), "/users/:id");
+        assert_eq!(
+            patterns.template("/orders/8601b555-6a54-4b1b-9b4f-5b4a3d1a1c4f/items?page=2"),
+            "/orders/:uuid/items"
+        );
+        assert_eq!(
+            patterns.template("/assets/app-3f9a1c2b7d4e.css"),
+            "/assets/app-3f9a1c2b7d4e.css"
+        );
+        assert_eq!(patterns.template("/blobs/3f9a1c2b7d4e"), "/blobs/:hash");
+        assert_eq!(patterns.template("/"), "/");
+        assert_eq!(patterns.template("/v2/status"), "/v2/status");
+    }
+
+    #[test]
+    fn test_configured_templates_first() {
+        let patterns = RoutePatterns::parse("# slugs\n/posts/:slug\n/orgs/:org/members/:member\n");
+        assert_eq!(patterns.template("/posts/hello-world"), "/posts/:slug");
+        assert_eq!(
+            patterns.template("/orgs/acme/members/42"),
+            "/orgs/:org/members/:member"
+        );
+        assert_eq!(
+            patterns.template("/posts/hello/comments"),
+            "/posts/hello/comments"
+        );
+    }
+
+    #[test]
+    fn test_stats_per_route() {
+        let mut stats = RouteStats::default();
+        stats.ingest(&router("GET", "/users/1", 200, 10));
+        stats.ingest(&router("GET", "/users/2", 503, 30));
+        stats.ingest(&router("POST", "/users/2", 201, 50));
+        stats.ingest(&parse_log_line("2024-02-17T10:30:45.123456+00:00 app[web.1]: hi").unwrap());
+
+        let routes = stats.busiest(10);
+        assert_eq!(routes.len(), 2);
+        assert_eq!(
+            (routes[0].method.as_str(), routes[0].template.as_str()),
+            ("GET", "/users/:id")
+        );
+        assert_eq!(routes[0].count, 2);
+        assert_eq!(routes[0].errors, 1);
+        assert_eq!(routes[0].avg_service_ms(), 20);
+        assert_eq!(routes[0].max_service_ms, 30);
+        assert_eq!(routes[1].method, "POST");
+    }
+}
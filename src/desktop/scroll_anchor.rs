@@ -0,0 +1,107 @@
+//! Keeping the log view on the lines being read while new ones arrive
+//!
+//! Rows are keyed by position, so when the full buffer drops its oldest
+//! line, or timestamp order slots a late one in above, every row below moves
+//! and the scroll offset alone no longer shows the same lines. Once the user
+//! scrolls up from the end, the row at the top of the view is remembered and
+//! found again after each update so the view can be put back on it.
+
+use crate::parser::LogEntry;
+
+/// The row at the top of the log view while the user reads history
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScrollAnchor {
+    entry: LogEntry,
+    index: usize,
+    /// Rows from the anchored one to the end of the list
+    below: usize,
+    /// Pixels of the anchored row scrolled out of view above
+    pub offset: f64,
+    /// Rows added below since the user scrolled away from the end
+    pub new_lines: usize,
+}
+
+impl ScrollAnchor {
+    /// Anchor on the row at `index`, `offset` pixels of which are hidden
+    pub fn new(logs: &[LogEntry], index: usize, offset: f64) -> Option<Self> {
+        Some(Self {
+            entry: logs.get(index)?.clone(),
+            index,
+            below: logs.len() - index,
+            offset,
+            new_lines: 0,
+        })
+    }
+
+    /// Anchor on another row after the user scrolled, keeping the count of
+    /// new lines
+    pub fn moved(&self, logs: &[LogEntry], index: usize, offset: f64) -> Option<Self> {
+        Self::new(logs, index, offset).map(|anchor| Self {
+            new_lines: self.new_lines,
+            ..anchor
+        })
+    }
+
+    /// Find the anchored row in an updated list, counting the rows added
+    /// below it; `None` once it's gone, e.g. after the filters changed
+    pub fn locate(&mut self, logs: &[LogEntry]) -> Option<usize> {
+        // Rows mostly leave from the front, so look back from where it was
+        let start = self.index.min(logs.len().checked_sub(1)?);
+        let index = (0..=start)
+            .rev()
+            .chain(start + 1..logs.len())
+            .find(|&i| logs[i] == self.entry)?;
+        let below = logs.len() - index;
+        self.new_lines += below.saturating_sub(self.below);
+        self.index = index;
+        self.below = below;
+        Some(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_log_line;
+
+    fn lines(range: std::ops::Range<usize>) -> Vec<LogEntry> {
+        range
+            .map(|i| {
+                parse_log_line(&format!(
+                    "2024-02-17T10:30:45.000000+00:00 app[web.1]: line {}",
+                    i
+                ))
+                .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_locate_after_oldest_dropped() {
+        let mut anchor = ScrollAnchor::new(&lines(0..10), 4, 3.0).unwrap();
+
+        // Two new lines pushed the two oldest out of the buffer
+        assert_eq!(anchor.locate(&lines(2..12)), Some(2));
+        assert_eq!(anchor.new_lines, 2);
+        assert_eq!(anchor.locate(&lines(4..14)), Some(0));
+        assert_eq!(anchor.new_lines, 4);
+    }
+
+    #[test]
+    fn test_moving_keeps_new_lines() {
+        let mut anchor = ScrollAnchor::new(&lines(0..10), 4, 0.0).unwrap();
+        anchor.locate(&lines(0..13));
+
+        let moved = anchor.moved(&lines(0..13), 8, 1.5).unwrap();
+        assert_eq!(moved.new_lines, 3);
+        assert_eq!(moved.offset, 1.5);
+    }
+
+    #[test]
+    fn test_anchor_lost() {
+        let mut anchor = ScrollAnchor::new(&lines(0..10), 4, 0.0).unwrap();
+        assert_eq!(anchor.locate(&lines(20..30)), None);
+        assert_eq!(anchor.locate(&[]), None);
+        assert!(ScrollAnchor::new(&lines(0..3), 3, 0.0).is_none());
+    }
+}
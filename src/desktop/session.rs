@@ -0,0 +1,430 @@
+//! Recording the live stream to a session file and replaying it
+//!
+//! A session file holds one received line per row, prefixed with the
+//! milliseconds since recording started so replay keeps the original pacing:
+//!
+//! ```text
+//! 0<TAB>2024-02-17T10:30:45.123456+00:00 app[web.1]: Starting process
+//! 1520<TAB>2024-02-17T10:30:46.643000+00:00 heroku[router]: at=info ...
+//! ```
+//!
+//! What the responder did while recording is kept too, so a postmortem can
+//! tell what they were looking at and when. Action rows start with `#` and
+//! add the wall-clock time, the kind of action and its details:
+//!
+//! ```text
+//! #2100<TAB>2024-02-17T10:31:02+00:00<TAB>filters<TAB>level:error AND dyno:web
+//! #9800<TAB>2024-02-17T10:31:10+00:00<TAB>note<TAB>rollback started
+//! ```
+//!
+//! A line's id is its position among the recorded lines, counting from 1.
+//! Rows are only ever appended, so the id stays the same wherever the file
+//! is copied, and `--session <file> --goto-id <id>` opens the replay on it.
+
+use crate::crypt;
+use anyhow::{bail, Context, Result};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Playback speeds offered by the replay controls
+pub const SPEEDS: [f64; 5] = [0.5, 1.0, 2.0, 5.0, 10.0];
+
+/// Longest pause replay will sit through between two lines
+const MAX_GAP: Duration = Duration::from_secs(5);
+
+/// One recorded line and when it arrived
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionLine {
+    pub offset: Duration,
+    pub raw: String,
+}
+
+fn format_line(offset: Duration, raw: &str) -> String {
+    format!("{}\t{}\n", offset.as_millis(), raw)
+}
+
+/// Something the responder did while recording
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionAction {
+    pub offset: Duration,
+    /// Wall-clock time, RFC 3339
+    pub at: String,
+    /// `filters`, `pause`, `resume`, `export`, `share` or `note`
+    pub kind: String,
+    pub detail: String,
+}
+
+fn format_action(offset: Duration, at: &str, kind: &str, detail: &str) -> String {
+    format!(
+        "#{}\t{}\t{}\t{}\n",
+        offset.as_millis(),
+        at,
+        kind,
+        detail.replace(['\t', '\n'], " ")
+    )
+}
+
+/// A recorded session
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Session {
+    lines: Vec<SessionLine>,
+    actions: Vec<SessionAction>,
+    /// File the session was loaded from
+    path: Option<PathBuf>,
+}
+
+impl Session {
+    pub fn parse(contents: &str) -> Result<Self> {
+        let mut lines = Vec::new();
+        let mut actions = Vec::new();
+        for (idx, row) in contents.lines().enumerate() {
+            if row.is_empty() {
+                continue;
+            }
+            if let Some(action) = row.strip_prefix('#') {
+                let mut parts = action.splitn(4, '\t');
+                let (Some(millis), Some(at), Some(kind), Some(detail)) =
+                    (parts.next(), parts.next(), parts.next(), parts.next())
+                else {
+                    bail!(
+                        "Line {}: expected #<millis><TAB><time><TAB><action><TAB><details>",
+                        idx + 1
+                    );
+                };
+                actions.push(SessionAction {
+                    offset: parse_offset(millis, idx)?,
+                    at: at.to_string(),
+                    kind: kind.to_string(),
+                    detail: detail.to_string(),
+                });
+                continue;
+            }
+            let Some((millis, raw)) = row.split_once('\t') else {
+                bail!("Line {}: expected <millis><TAB><line>", idx + 1);
+            };
+            lines.push(SessionLine {
+                offset: parse_offset(millis, idx)?,
+                raw: raw.to_string(),
+            });
+        }
+        Ok(Self {
+            lines,
+            actions,
+            path: None,
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents =
+            std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        if crypt::is_encrypted(&contents) {
+            bail!(
+                "{} is encrypted; decrypt it with --decrypt first",
+                path.display()
+            );
+        }
+        let mut session = Self::parse(&String::from_utf8_lossy(&contents))?;
+        session.path = Some(std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()));
+        Ok(session)
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    pub fn lines(&self) -> &[SessionLine] {
+        &self.lines
+    }
+
+    pub fn actions(&self) -> &[SessionAction] {
+        &self.actions
+    }
+}
+
+/// Command line that opens a session on the line with this id
+pub fn goto_command(path: &Path, id: usize) -> String {
+    let path = path.display().to_string();
+    let plain = path
+        .chars()
+        .all(|c| c.is_alphanumeric() || "/._-+:@,".contains(c));
+    let path = if plain {
+        path
+    } else {
+        format!("'{}'", path.replace('\'', "'\\''"))
+    };
+    format!("logs-parser-desktop --session {} --goto-id {}", path, id)
+}
+
+fn parse_offset(millis: &str, idx: usize) -> Result<Duration> {
+    let millis: u64 = millis
+        .parse()
+        .with_context(|| format!("Line {}: invalid offset {:?}", idx + 1, millis))?;
+    Ok(Duration::from_millis(millis))
+}
+
+/// Appends received lines to a session file
+#[derive(Debug)]
+pub struct Recorder {
+    writer: BufWriter<File>,
+    path: PathBuf,
+    started: Instant,
+}
+
+impl Recorder {
+    pub fn start(path: PathBuf) -> Result<Self> {
+        let file =
+            File::create(&path).with_context(|| format!("Failed to create {}", path.display()))?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+            path,
+            started: Instant::now(),
+        })
+    }
+
+    pub fn record(&mut self, raw: &str) -> Result<()> {
+        self.writer
+            .write_all(format_line(self.started.elapsed(), raw).as_bytes())
+            .context("Failed to write session file")
+    }
+
+    /// Note something the responder did, e.g. `("export", path)`
+    pub fn record_action(&mut self, kind: &str, detail: &str) -> Result<()> {
+        let at = chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, false);
+        self.writer
+            .write_all(format_action(self.started.elapsed(), &at, kind, detail).as_bytes())
+            .context("Failed to write session file")
+    }
+
+    /// Flush the file and return where it was written
+    pub fn finish(mut self) -> Result<PathBuf> {
+        self.writer
+            .flush()
+            .context("Failed to write session file")?;
+        Ok(self.path)
+    }
+}
+
+/// Playback state of a loaded session
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    session: Session,
+    /// Index of the next line to play
+    position: usize,
+    /// Index of the next action to report
+    action_position: usize,
+    pub speed: f64,
+    pub playing: bool,
+}
+
+impl Replay {
+    pub fn new(session: Session) -> Self {
+        Self {
+            session,
+            position: 0,
+            action_position: 0,
+            speed: 1.0,
+            playing: true,
+        }
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn len(&self) -> usize {
+        self.session.len()
+    }
+
+    pub fn session_path(&self) -> Option<&Path> {
+        self.session.path()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.session.len()
+    }
+
+    /// How long to wait before playing the next line, or `None` when paused
+    /// or finished
+    pub fn next_delay(&self) -> Option<Duration> {
+        if !self.playing {
+            return None;
+        }
+        let next = self.session.lines.get(self.position)?;
+        let gap = match self.position.checked_sub(1) {
+            Some(prev) => next.offset.saturating_sub(self.session.lines[prev].offset),
+            None => Duration::ZERO,
+        };
+        Some(gap.min(MAX_GAP).div_f64(self.speed))
+    }
+
+    /// Take the next line to play, with its id
+    pub fn advance(&mut self) -> Option<(usize, String)> {
+        let line = self.session.lines.get(self.position)?;
+        self.position += 1;
+        Some((self.position, line.raw.clone()))
+    }
+
+    /// Actions recorded up to the last line played, not yet reported; once
+    /// finished, the rest
+    pub fn due_actions(&mut self) -> Vec<SessionAction> {
+        let played = match self.position.checked_sub(1) {
+            _ if self.is_finished() => Duration::MAX,
+            Some(last) => self.session.lines[last].offset,
+            None => return Vec::new(),
+        };
+        let due = self.session.actions[self.action_position..]
+            .iter()
+            .take_while(|action| action.offset <= played)
+            .cloned()
+            .collect::<Vec<_>>();
+        self.action_position += due.len();
+        due
+    }
+
+    /// Move to `position`, returning every line before it so the buffer can
+    /// be rebuilt as it was at that point
+    pub fn seek(&mut self, position: usize) -> Vec<String> {
+        self.position = position.min(self.session.len());
+        // Actions before the new position count as already seen
+        let played = self
+            .position
+            .checked_sub(1)
+            .map(|last| self.session.lines[last].offset);
+        self.action_position = self
+            .session
+            .actions
+            .partition_point(|action| played.is_some_and(|played| action.offset <= played));
+        self.session.lines[..self.position]
+            .iter()
+            .map(|line| line.raw.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSION: &str = "0\tfirst\n1500\tsecond\n1500\tthird\n9000\tfourth\n";
+
+    #[test]
+    fn test_parse_session() {
+        let session = Session::parse(SESSION).unwrap();
+
+        assert_eq!(session.len(), 4);
+        assert_eq!(
+            session.lines[1],
+            SessionLine {
+                offset: Duration::from_millis(1500),
+                raw: "second".to_string()
+            }
+        );
+        assert_eq!(
+            format_line(Duration::from_millis(1500), "second"),
+            "1500\tsecond\n"
+        );
+    }
+
+    #[test]
+    fn test_actions_recorded_and_replayed() {
+        let contents = format!(
+            "{}{}{}{}",
+            format_line(Duration::ZERO, "first"),
+            format_action(
+                Duration::from_millis(700),
+                "2024-02-17T10:31:02+00:00",
+                "filters",
+                "level:error"
+            ),
+            format_line(Duration::from_millis(1500), "second"),
+            format_action(
+                Duration::from_millis(2000),
+                "2024-02-17T10:31:04+00:00",
+                "note",
+                "rollback\tstarted"
+            ),
+        );
+        let session = Session::parse(&contents).unwrap();
+        assert_eq!(session.len(), 2);
+        assert_eq!(session.actions().len(), 2);
+        assert_eq!(session.actions()[1].detail, "rollback started");
+
+        let mut replay = Replay::new(session.clone());
+        replay.advance();
+        assert!(replay.due_actions().is_empty());
+        replay.advance();
+        // Finished, so the note recorded after the last line is due too
+        let kinds: Vec<String> = replay.due_actions().into_iter().map(|a| a.kind).collect();
+        assert_eq!(kinds, ["filters", "note"]);
+        assert!(replay.due_actions().is_empty());
+
+        let mut replay = Replay::new(session);
+        replay.seek(1);
+        assert!(replay.due_actions().is_empty());
+        replay.seek(0);
+        replay.advance();
+        replay.advance();
+        assert_eq!(replay.due_actions().len(), 2);
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_rows() {
+        let err = Session::parse("0\tok\nno offset here\n").unwrap_err();
+        assert!(err.to_string().contains("Line 2"));
+        assert!(Session::parse("soon\tline\n").is_err());
+        assert!(Session::parse("#100\tnote only\n").is_err());
+    }
+
+    #[test]
+    fn test_goto_command() {
+        assert_eq!(
+            goto_command(Path::new("/tmp/incident.log"), 12),
+            "logs-parser-desktop --session /tmp/incident.log --goto-id 12"
+        );
+        assert_eq!(
+            goto_command(Path::new("/tmp/bob's session.log"), 3),
+            "logs-parser-desktop --session '/tmp/bob'\\''s session.log' --goto-id 3"
+        );
+    }
+
+    #[test]
+    fn test_replay_paces_lines_by_speed() {
+        let mut replay = Replay::new(Session::parse(SESSION).unwrap());
+
+        assert_eq!(replay.next_delay(), Some(Duration::ZERO));
+        assert_eq!(replay.advance(), Some((1, "first".to_string())));
+        replay.speed = 2.0;
+        assert_eq!(replay.next_delay(), Some(Duration::from_millis(750)));
+        replay.advance();
+        assert_eq!(replay.next_delay(), Some(Duration::ZERO));
+        replay.advance();
+        // Long gaps are capped
+        replay.speed = 1.0;
+        assert_eq!(replay.next_delay(), Some(MAX_GAP));
+        replay.advance();
+        assert!(replay.is_finished());
+        assert_eq!(replay.next_delay(), None);
+    }
+
+    #[test]
+    fn test_replay_pause_and_seek() {
+        let mut replay = Replay::new(Session::parse(SESSION).unwrap());
+        replay.playing = false;
+        assert_eq!(replay.next_delay(), None);
+
+        assert_eq!(replay.seek(2), vec!["first", "second"]);
+        assert_eq!(replay.position(), 2);
+        assert_eq!(replay.seek(99).len(), 4);
+        assert!(replay.is_finished());
+    }
+}
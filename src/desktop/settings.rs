@@ -0,0 +1,556 @@
+//! General preferences, loaded from `~/.config/logs-parser/settings`
+//!
+//! One `name = value` per line:
+//!
+//! ```text
+//! router_status_levels = off
+//! dim_after = 5m
+//! control_socket = on
+//! title_status = off
+//! check_updates = off
+//! crash_reports = on
+//! desktop_notifications = off
+//! open_command = code --goto {file}:{line}
+//! request_base_url = http://localhost:3000
+//! stream_history = 200
+//! time_format = iso
+//! dyno_palette = colorblind
+//! truncate_at = 500
+//! language = es
+//! theme = win7
+//! coalesce_repeats = off
+//! export_manifest = on
+//! upload_to = s3://incident-evidence/logs/
+//! history = 20MB
+//! order = timestamp
+//! auto_export = hourly
+//! auto_export_to = ~/logs/{app}-{date}-{hour}.log
+//! auto_export_keep = 48
+//! ```
+//!
+//! The theme picked in the connection panel is saved here too, replacing
+//! the separate `theme` file earlier versions wrote.
+
+use crate::auto_export::{Rotation, DEFAULT_TEMPLATE};
+use crate::backfill::{parse_history_lines, HISTORY_LINES};
+use crate::config::{self, config_dir};
+use crate::dyno_colors::DynoPalette;
+use crate::i18n::Locale;
+use crate::parser::DEFAULT_TIME_FORMAT;
+use crate::sorting::BufferOrder;
+use crate::upload::Destination;
+use anyhow::{Context, Result};
+use chrono::format::StrftimeItems;
+use std::time::Duration;
+
+/// Theme used until one is picked
+pub const DEFAULT_THEME: &str = "wmp";
+
+/// Message length, in characters, beyond which the list truncates
+const DEFAULT_TRUNCATE_AT: usize = 2000;
+
+/// User preferences that don't warrant a file of their own
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settings {
+    /// Derive the level of router lines from their HTTP status
+    pub router_status_levels: bool,
+    /// Dim entries older than this, so fresh lines stand out while tailing
+    pub dim_after: Option<Duration>,
+    /// Listen for `ctl` commands from scripts
+    pub control_socket: bool,
+    /// Show the app and its unseen errors in the window title
+    pub title_status: bool,
+    /// Look for a newer release on GitHub at startup
+    pub check_updates: bool,
+    /// Write a report file when the app crashes; off unless turned on
+    pub crash_reports: bool,
+    /// Show alerts as OS notifications while the window is in the background
+    pub desktop_notifications: bool,
+    /// Command run when a `file:line` reference is clicked
+    pub open_command: Option<String>,
+    /// Where router requests are opened, instead of the app's own host
+    pub request_base_url: Option<String>,
+    /// Lines of history a Heroku stream starts with
+    pub stream_history: usize,
+    /// strftime-style format for entry timestamps
+    pub time_format: String,
+    /// Colors used when dyno coloring is on
+    pub dyno_palette: DynoPalette,
+    /// Shorten messages longer than this many characters until expanded
+    pub truncate_at: Option<usize>,
+    /// Collapse high-frequency repeats like health checks into summaries
+    pub coalesce_repeats: bool,
+    /// Write a checksum manifest beside each export and recorded session
+    pub export_manifest: bool,
+    /// Bucket every export is uploaded to
+    pub upload_to: Option<Destination>,
+    /// Whether late lines are added as received or slotted in by timestamp
+    pub order: BufferOrder,
+    /// Keep received lines on disk, up to this many bytes, and restore them
+    /// on the next start
+    pub history: Option<u64>,
+    /// Append filtered lines to files started on this schedule
+    pub auto_export: Option<Rotation>,
+    /// File name template for automatic exports
+    pub auto_export_to: String,
+    /// Automatic export files kept; `None` keeps them all
+    pub auto_export_keep: Option<usize>,
+    /// UI language; `None` follows the system locale
+    pub language: Option<Locale>,
+    /// Color theme name
+    pub theme: String,
+    /// OTLP/HTTP collector receiving filtered entries
+    #[cfg(feature = "otel")]
+    pub otel_endpoint: Option<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            router_status_levels: true,
+            dim_after: None,
+            control_socket: false,
+            title_status: true,
+            check_updates: true,
+            crash_reports: false,
+            desktop_notifications: true,
+            open_command: None,
+            request_base_url: None,
+            stream_history: HISTORY_LINES,
+            time_format: DEFAULT_TIME_FORMAT.to_string(),
+            dyno_palette: DynoPalette::default(),
+            truncate_at: Some(DEFAULT_TRUNCATE_AT),
+            coalesce_repeats: true,
+            export_manifest: false,
+            upload_to: None,
+            order: BufferOrder::default(),
+            history: None,
+            auto_export: None,
+            auto_export_to: DEFAULT_TEMPLATE.to_string(),
+            auto_export_keep: None,
+            language: None,
+            theme: DEFAULT_THEME.to_string(),
+            #[cfg(feature = "otel")]
+            otel_endpoint: None,
+        }
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "on" | "yes" => Some(true),
+        "false" | "off" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parse a size like `500KB`, `20MB` or `1GB`
+pub fn parse_size(value: &str) -> Option<u64> {
+    let split = value.find(|c: char| !c.is_ascii_digit())?;
+    let amount: u64 = value[..split].parse().ok()?;
+    let unit = match value[split..].trim().to_uppercase().as_str() {
+        "KB" => 1 << 10,
+        "MB" => 1 << 20,
+        "GB" => 1 << 30,
+        _ => return None,
+    };
+    amount.checked_mul(unit)
+}
+
+/// Resolve a `time_format` value: a preset name or a strftime format
+fn parse_time_format(value: &str) -> Option<String> {
+    let format = match value {
+        "iso" => "%Y-%m-%dT%H:%M:%S%.3f%:z",
+        "date" => "%Y-%m-%d %H:%M:%S%.3f",
+        "seconds" => "%H:%M:%S",
+        "default" => DEFAULT_TIME_FORMAT,
+        custom => custom,
+    };
+    // Reject formats chrono can't render rather than panicking in the view
+    StrftimeItems::new(format).parse().ok()?;
+    Some(format.to_string())
+}
+
+/// Parse an age like `90s`, `5m` or `1h`
+pub fn parse_duration(value: &str) -> Option<Duration> {
+    let split = value.find(|c: char| !c.is_ascii_digit())?;
+    let amount: u64 = value[..split].parse().ok()?;
+    let unit = match value[split..].trim() {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        _ => return None,
+    };
+    amount.checked_mul(unit).map(Duration::from_secs)
+}
+
+impl Settings {
+    /// Build settings from a settings file; unknown names and invalid values
+    /// are skipped, leaving the default in place
+    pub fn parse(contents: &str) -> Self {
+        let mut settings = Self::default();
+
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((name, value)) = line.split_once('=') else {
+                continue;
+            };
+            settings.apply(name.trim(), value.trim());
+        }
+
+        settings
+    }
+
+    /// Set one setting from its value in the file; false if the name is
+    /// unknown or the value invalid, which leaves the setting as it was
+    fn apply(&mut self, name: &str, value: &str) -> bool {
+        let flag = match name {
+            "router_status_levels" => &mut self.router_status_levels,
+            "control_socket" => &mut self.control_socket,
+            "title_status" => &mut self.title_status,
+            "check_updates" => &mut self.check_updates,
+            "crash_reports" => &mut self.crash_reports,
+            "desktop_notifications" => &mut self.desktop_notifications,
+            "coalesce_repeats" => &mut self.coalesce_repeats,
+            "export_manifest" => &mut self.export_manifest,
+            _ => return self.apply_value(name, value),
+        };
+        parse_bool(value).map(|enabled| *flag = enabled).is_some()
+    }
+
+    /// `apply` for settings that aren't on/off switches
+    fn apply_value(&mut self, name: &str, value: &str) -> bool {
+        let off = parse_bool(value) == Some(false);
+        match name {
+            "upload_to" => Destination::parse(value)
+                .map(|destination| self.upload_to = Some(destination))
+                .is_some(),
+            "order" => BufferOrder::parse(value)
+                .map(|order| self.order = order)
+                .is_some(),
+            "history" if off => {
+                self.history = None;
+                true
+            }
+            "history" => parse_size(value)
+                .map(|size| self.history = (size > 0).then_some(size))
+                .is_some(),
+            "auto_export" if off => {
+                self.auto_export = None;
+                true
+            }
+            "auto_export" => Rotation::parse(value)
+                .map(|rotation| self.auto_export = Some(rotation))
+                .is_some(),
+            "auto_export_to" if !value.is_empty() => {
+                self.auto_export_to = value.to_string();
+                true
+            }
+            "auto_export_keep" if off => {
+                self.auto_export_keep = None;
+                true
+            }
+            "auto_export_keep" => value
+                .parse::<usize>()
+                .map(|keep| self.auto_export_keep = (keep > 0).then_some(keep))
+                .is_ok(),
+            "theme" if !value.is_empty() => {
+                self.theme = value.to_string();
+                true
+            }
+            "language" => Locale::from_tag(value)
+                .map(|locale| self.language = Some(locale))
+                .is_some(),
+            "open_command" if !value.is_empty() => {
+                self.open_command = Some(value.to_string());
+                true
+            }
+            "request_base_url" if !value.is_empty() => {
+                self.request_base_url = Some(value.to_string());
+                true
+            }
+            #[cfg(feature = "otel")]
+            "otel_endpoint" if !value.is_empty() => {
+                self.otel_endpoint = Some(value.to_string());
+                true
+            }
+            "stream_history" => parse_history_lines(value)
+                .map(|lines| self.stream_history = lines)
+                .is_some(),
+            "time_format" => parse_time_format(value)
+                .map(|format| self.time_format = format)
+                .is_some(),
+            "dyno_palette" => DynoPalette::from_name(value)
+                .map(|palette| self.dyno_palette = palette)
+                .is_some(),
+            "truncate_at" if off => {
+                self.truncate_at = None;
+                true
+            }
+            "truncate_at" => value
+                .parse::<usize>()
+                .map(|limit| self.truncate_at = (limit > 0).then_some(limit))
+                .is_ok(),
+            "dim_after" if off => {
+                self.dim_after = None;
+                true
+            }
+            "dim_after" => parse_duration(value)
+                .map(|age| self.dim_after = Some(age))
+                .is_some(),
+            _ => false,
+        }
+    }
+
+    /// Whether `name = value` would be taken from the settings file
+    pub fn accepts(name: &str, value: &str) -> bool {
+        Self::default().apply(name, value)
+    }
+
+    /// Every setting's name and default, as written in the settings file
+    /// (empty for settings that are unset by default)
+    pub fn defaults() -> Vec<(&'static str, String)> {
+        let on_off = |enabled: bool| if enabled { "on" } else { "off" }.to_string();
+        let settings = Self::default();
+        let defaults = vec![
+            (
+                "router_status_levels",
+                on_off(settings.router_status_levels),
+            ),
+            ("dim_after", "off".to_string()),
+            ("control_socket", on_off(settings.control_socket)),
+            ("title_status", on_off(settings.title_status)),
+            ("check_updates", on_off(settings.check_updates)),
+            ("crash_reports", on_off(settings.crash_reports)),
+            (
+                "desktop_notifications",
+                on_off(settings.desktop_notifications),
+            ),
+            ("open_command", String::new()),
+            ("request_base_url", String::new()),
+            ("stream_history", settings.stream_history.to_string()),
+            ("time_format", settings.time_format),
+            ("dyno_palette", "vivid".to_string()),
+            ("truncate_at", DEFAULT_TRUNCATE_AT.to_string()),
+            ("coalesce_repeats", on_off(settings.coalesce_repeats)),
+            ("export_manifest", on_off(settings.export_manifest)),
+            ("upload_to", String::new()),
+            ("order", "arrival".to_string()),
+            ("history", "off".to_string()),
+            ("auto_export", "off".to_string()),
+            ("auto_export_to", settings.auto_export_to),
+            ("auto_export_keep", "off".to_string()),
+            ("language", String::new()),
+            ("theme", settings.theme),
+        ];
+        #[cfg(feature = "otel")]
+        let defaults = [defaults, vec![("otel_endpoint", String::new())]].concat();
+        defaults
+    }
+
+    /// Load the user's settings file, falling back to defaults
+    pub fn load() -> Self {
+        std::fs::read_to_string(config_dir().join("settings"))
+            .map(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+}
+
+/// Set one value in the user's settings file, keeping everything else
+pub fn save_value(name: &str, value: &str) -> Result<()> {
+    config::save_value("settings", name, Some(value))
+}
+
+/// Remove a setting from the user's settings file, so its default applies
+pub fn reset_value(name: &str) -> Result<()> {
+    config::save_value("settings", name, None)
+}
+
+/// Values set in the user's settings file, as written
+pub fn saved_values() -> Vec<(String, String)> {
+    std::fs::read_to_string(config_dir().join("settings"))
+        .map(|contents| config::values(&contents))
+        .unwrap_or_default()
+}
+
+/// Move the theme from the `theme` file earlier versions wrote into the
+/// settings file, unless the settings already name one
+pub fn migrate_theme_file() -> Result<()> {
+    let legacy = config_dir().join("theme");
+    let Ok(theme) = std::fs::read_to_string(&legacy) else {
+        return Ok(());
+    };
+    let contents = std::fs::read_to_string(config_dir().join("settings")).unwrap_or_default();
+    let named = contents.lines().any(|line| {
+        line.split_once('=')
+            .is_some_and(|(n, _)| n.trim() == "theme")
+    });
+    let theme = theme.trim();
+    if !theme.is_empty() && !named {
+        save_value("theme", theme)?;
+    }
+    std::fs::remove_file(&legacy).context("Failed to remove the old theme file")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_are_accepted() {
+        let mut contents = String::new();
+        for (name, default) in Settings::defaults() {
+            if !default.is_empty() {
+                assert!(Settings::accepts(name, &default), "{} = {}", name, default);
+                contents.push_str(&format!("{} = {}\n", name, default));
+            }
+        }
+        assert_eq!(Settings::parse(&contents), Settings::default());
+    }
+
+    #[test]
+    fn test_accepts() {
+        assert!(Settings::accepts("dim_after", "5m"));
+        assert!(Settings::accepts("history", "off"));
+        assert!(Settings::accepts("crash_reports", "yes"));
+        assert!(Settings::accepts("stream_history", "200"));
+        assert!(!Settings::accepts("stream_history", "5000"));
+        assert!(!Settings::accepts("dim_after", "soon"));
+        assert!(!Settings::accepts("crash_reports", "maybe"));
+        assert!(!Settings::accepts("upload_to", "ftp://x"));
+        assert!(!Settings::accepts("colour", "red"));
+    }
+
+    #[test]
+    fn test_parse_router_status_levels() {
+        assert!(!Settings::parse("router_status_levels = off").router_status_levels);
+        assert!(Settings::parse("router_status_levels = yes").router_status_levels);
+        assert!(Settings::parse("control_socket = on").control_socket);
+        assert!(Settings::default().title_status);
+        assert!(!Settings::parse("title_status = off").title_status);
+        assert!(!Settings::parse("check_updates = off").check_updates);
+        assert!(!Settings::default().crash_reports);
+        assert!(Settings::parse("crash_reports = on").crash_reports);
+        assert!(Settings::default().desktop_notifications);
+        assert!(!Settings::parse("desktop_notifications = off").desktop_notifications);
+        assert!(!Settings::parse("coalesce_repeats = off").coalesce_repeats);
+        assert!(Settings::parse("export_manifest = on").export_manifest);
+        assert_eq!(
+            Settings::parse("order = timestamp").order,
+            BufferOrder::Timestamp
+        );
+        assert_eq!(
+            Settings::parse("upload_to = gs://evidence").upload_to,
+            Destination::parse("gs://evidence")
+        );
+        assert_eq!(Settings::parse("language = es").language, Some(Locale::Es));
+        assert_eq!(
+            Settings::parse("dyno_palette = colorblind").dyno_palette,
+            DynoPalette::ColorBlind
+        );
+    }
+
+    #[test]
+    fn test_invalid_value_keeps_previous() {
+        let settings = Settings::parse(
+            "upload_to = gs://evidence\nupload_to = ftp://x\nlanguage = es\nlanguage = klingon",
+        );
+        assert_eq!(settings.upload_to, Destination::parse("gs://evidence"));
+        assert_eq!(settings.language, Some(Locale::Es));
+    }
+
+    #[test]
+    fn test_parse_history() {
+        assert_eq!(Settings::parse("history = 20MB").history, Some(20 << 20));
+        assert_eq!(Settings::parse("history = 512kb").history, Some(512 << 10));
+        assert_eq!(Settings::parse("history = off").history, None);
+        assert_eq!(Settings::parse("history = 20").history, None);
+        // Too large to count in bytes: rejected rather than overflowing
+        assert_eq!(parse_size("99999999999GB"), None);
+        assert_eq!(
+            Settings::parse("history = 99999999999GB").history,
+            Settings::default().history
+        );
+    }
+
+    #[test]
+    fn test_parse_auto_export() {
+        let settings = Settings::parse(
+            "auto_export = daily\nauto_export_to = ~/logs/{app}-{date}.log\nauto_export_keep = 7",
+        );
+        assert_eq!(settings.auto_export, Some(Rotation::Daily));
+        assert_eq!(settings.auto_export_to, "~/logs/{app}-{date}.log");
+        assert_eq!(settings.auto_export_keep, Some(7));
+        assert_eq!(Settings::default().auto_export_to, DEFAULT_TEMPLATE);
+        assert_eq!(
+            Settings::parse("auto_export = 50MB").auto_export,
+            Some(Rotation::Size(50 << 20))
+        );
+    }
+
+    #[test]
+    fn test_parse_dim_after() {
+        assert_eq!(
+            Settings::parse("dim_after = 5m").dim_after,
+            Some(Duration::from_secs(300))
+        );
+        assert_eq!(
+            Settings::parse("dim_after = 90s").dim_after,
+            Some(Duration::from_secs(90))
+        );
+        assert_eq!(Settings::parse("dim_after = off").dim_after, None);
+        // Too many seconds to count: rejected rather than overflowing
+        assert_eq!(parse_duration("9999999999999999h"), None);
+        assert_eq!(
+            Settings::parse("dim_after = 9999999999999999h").dim_after,
+            Settings::default().dim_after
+        );
+    }
+
+    #[test]
+    fn test_parse_open_command() {
+        assert_eq!(
+            Settings::parse("open_command = code --goto {file}:{line}").open_command,
+            Some("code --goto {file}:{line}".to_string())
+        );
+        assert_eq!(
+            Settings::parse("request_base_url = http://localhost:3000").request_base_url,
+            Some("http://localhost:3000".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_time_format() {
+        assert_eq!(
+            Settings::parse("time_format = iso").time_format,
+            "%Y-%m-%dT%H:%M:%S%.3f%:z"
+        );
+        assert_eq!(
+            Settings::parse("time_format = %d/%m %H:%M").time_format,
+            "%d/%m %H:%M"
+        );
+        assert_eq!(
+            Settings::parse("time_format = %Q").time_format,
+            DEFAULT_TIME_FORMAT
+        );
+    }
+
+    #[test]
+    fn test_parse_truncate_at() {
+        assert_eq!(Settings::parse("truncate_at = 500").truncate_at, Some(500));
+        assert_eq!(Settings::parse("truncate_at = off").truncate_at, None);
+        assert_eq!(Settings::parse("truncate_at = 0").truncate_at, None);
+    }
+
+    #[test]
+    fn test_parse_skips_invalid_lines() {
+        let settings = Settings::parse(
+            "# comment\nunknown = 1\nrouter_status_levels = maybe\ndim_after = 5 parsecs",
+        );
+
+        assert_eq!(settings, Settings::default());
+    }
+}
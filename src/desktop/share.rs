@@ -0,0 +1,275 @@
+//! Read-only sharing of the current view over the local network
+//!
+//! `:share` serves the lines currently shown, after filters, sorting and
+//! pausing, at `http://<this machine>:8642/?token=<token>`. A teammate opening
+//! the link sees a live copy that refreshes every two seconds, and can't
+//! change anything. `:share off` stops serving. The token is random for each
+//! share, so only people given the link can read the lines.
+
+use crate::parser::LogEntry;
+use anyhow::{anyhow, Result};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::Serialize;
+use std::net::{IpAddr, UdpSocket};
+use std::sync::{Arc, RwLock};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Port served on when `:share` isn't given one
+pub const DEFAULT_PORT: u16 = 8642;
+
+/// Newest lines of the view included in each refresh
+const MAX_LINES: usize = 2000;
+
+/// Largest request head read before giving up on a client
+const MAX_REQUEST: usize = 8192;
+
+/// The page a teammate opens; it polls `lines.json` with the same token
+const PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>logs-parser</title>
+<style>
+body { margin: 0; background: #1e1e2e; color: #cdd6f4; font: 12px/1.5 ui-monospace, Menlo, monospace; }
+header { position: sticky; top: 0; padding: 6px 10px; background: #313244; color: #a6adc8; }
+div.line { padding: 0 10px; white-space: pre-wrap; word-break: break-all; }
+.Error { color: #f38ba8; } .Warn { color: #f9e2af; } .Debug { color: #7f849c; }
+</style>
+</head>
+<body>
+<header id="status">Connecting…</header>
+<main id="lines"></main>
+<script>
+const status = document.getElementById("status");
+const list = document.getElementById("lines");
+async function refresh() {
+  try {
+    const response = await fetch("lines.json" + location.search, { cache: "no-store" });
+    if (!response.ok) throw new Error(response.status + " " + response.statusText);
+    const view = await response.json();
+    const follow = innerHeight + scrollY >= document.body.scrollHeight - 20;
+    list.replaceChildren(...view.lines.map(line => {
+      const row = document.createElement("div");
+      row.className = "line " + line.level;
+      row.textContent = line.time + " " + line.source + "[" + line.dyno + "] " + line.level + ": " + line.message;
+      return row;
+    }));
+    const filters = view.filters.length ? view.filters.join(", ") : "none";
+    status.textContent = view.lines.length + " of " + view.total + " lines shown · filters: " + filters;
+    if (follow) scrollTo(0, document.body.scrollHeight);
+  } catch (e) {
+    status.textContent = "Sharing stopped or unreachable (" + e.message + ")";
+  }
+}
+refresh();
+setInterval(refresh, 2000);
+</script>
+</body>
+</html>
+"#;
+
+/// Latest view as JSON, updated by the app and read by the server
+pub type SharedView = Arc<RwLock<String>>;
+
+#[derive(Debug, Serialize)]
+struct SharedLine<'a> {
+    time: String,
+    level: String,
+    source: &'a str,
+    dyno: &'a str,
+    message: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct View<'a> {
+    filters: &'a [String],
+    total: usize,
+    lines: Vec<SharedLine<'a>>,
+}
+
+/// JSON for the newest lines of a view and the filters that produced it
+pub fn view_json(logs: &[LogEntry], filters: &[String], time_format: &str) -> String {
+    let newest = &logs[logs.len().saturating_sub(MAX_LINES)..];
+    let view = View {
+        filters,
+        total: logs.len(),
+        lines: newest
+            .iter()
+            .map(|entry| SharedLine {
+                time: entry.format_time(time_format),
+                level: format!("{:?}", entry.level),
+                source: &entry.source,
+                dyno: &entry.dyno,
+                message: &entry.message,
+            })
+            .collect(),
+    };
+    serde_json::to_string(&view).expect("view serializes")
+}
+
+/// A random token for a share link
+pub fn new_token() -> Result<String> {
+    let mut bytes = [0u8; 16];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| anyhow!("No secure random numbers available"))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// This machine's address on the local network, found by asking which
+/// interface would route outwards (nothing is sent)
+pub fn lan_address() -> Option<IpAddr> {
+    let socket = UdpSocket::bind(("0.0.0.0", 0)).ok()?;
+    socket.connect(("192.0.2.1", 80)).ok()?;
+    socket.local_addr().ok().map(|addr| addr.ip())
+}
+
+/// Link to give teammates
+pub fn share_url(port: u16, token: &str) -> String {
+    let host = lan_address()
+        .map(|ip| ip.to_string())
+        .unwrap_or_else(|| "localhost".to_string());
+    format!("http://{}:{}/?token={}", host, port, token)
+}
+
+/// Status, content type and body answering a request for `target`
+fn respond(target: &str, token: &str, view: &str) -> (&'static str, &'static str, String) {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let given = query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("token="));
+    // Compared in full so the time taken doesn't reveal a matching prefix
+    let authorized = given.is_some_and(|given| {
+        given.len() == token.len()
+            && given
+                .bytes()
+                .zip(token.bytes())
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    });
+    if !authorized {
+        return (
+            "403 Forbidden",
+            "text/plain; charset=utf-8",
+            "This link is missing its token or has expired\n".to_string(),
+        );
+    }
+    match path {
+        "/" => ("200 OK", "text/html; charset=utf-8", PAGE.to_string()),
+        "/lines.json" => ("200 OK", "application/json", view.to_string()),
+        _ => (
+            "404 Not Found",
+            "text/plain; charset=utf-8",
+            "Not found\n".to_string(),
+        ),
+    }
+}
+
+/// Answer one connection: read the request head and reply to a GET
+async fn handle(mut stream: TcpStream, token: &str, view: &SharedView) -> Result<()> {
+    let mut head = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") && head.len() < MAX_REQUEST {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        head.extend_from_slice(&chunk[..read]);
+    }
+    let head = String::from_utf8_lossy(&head);
+    let mut request = head.split_whitespace();
+    let (status, content_type, body) = match (request.next(), request.next()) {
+        (Some("GET"), Some(target)) => {
+            let view = view.read().map(|v| v.clone()).unwrap_or_default();
+            respond(target, token, &view)
+        }
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain; charset=utf-8",
+            "Read only\n".to_string(),
+        ),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    Ok(())
+}
+
+/// Serve the page and the view until the task is cancelled
+pub async fn serve(listener: TcpListener, token: String, view: SharedView) -> Result<()> {
+    let token = Arc::new(token);
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let token = token.clone();
+        let view = view.clone();
+        tokio::spawn(async move {
+            let _ = handle(stream, &token, &view).await;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_log_line;
+
+    #[test]
+    fn test_view_json() {
+        let logs = vec![
+            parse_log_line("2024-02-17T10:30:45.123456+00:00 app[web.1]: Error: boom").unwrap(),
+        ];
+        let json: serde_json::Value =
+            serde_json::from_str(&view_json(&logs, &["level:error".to_string()], "%H:%M:%S"))
+                .unwrap();
+        assert_eq!(json["total"], 1);
+        assert_eq!(json["filters"][0], "level:error");
+        assert_eq!(json["lines"][0]["time"], "10:30:45");
+        assert_eq!(json["lines"][0]["level"], "Error");
+        assert_eq!(json["lines"][0]["dyno"], "web.1");
+    }
+
+    #[test]
+    fn test_requests_need_the_token() {
+        let token = new_token().unwrap();
+        assert_eq!(token.len(), 32);
+
+        let (status, _, body) = respond(&format!("/lines.json?token={}", token), &token, "{}");
+        assert_eq!((status, body.as_str()), ("200 OK", "{}"));
+        let (status, content_type, _) = respond(&format!("/?token={}", token), &token, "{}");
+        assert_eq!(
+            (status, content_type),
+            ("200 OK", "text/html; charset=utf-8")
+        );
+
+        assert_eq!(respond("/lines.json", &token, "{}").0, "403 Forbidden");
+        assert_eq!(respond("/?token=guess", &token, "{}").0, "403 Forbidden");
+        assert_eq!(
+            respond(&format!("/admin?token={}", token), &token, "{}").0,
+            "404 Not Found"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_serves_over_http() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let view: SharedView = Arc::new(RwLock::new(r#"{"lines":[]}"#.to_string()));
+        tokio::spawn(serve(listener, "secret".to_string(), view));
+
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        stream
+            .write_all(b"GET /lines.json?token=secret HTTP/1.1\r\nHost: x\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with(r#"{"lines":[]}"#));
+    }
+}
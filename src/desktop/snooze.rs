@@ -0,0 +1,161 @@
+//! Silencing alerts during known-noisy periods
+//!
+//! `:snooze 30m` keeps every `:alert` and `:await` from beeping, flashing or
+//! posting a notice until the time is up, and `:mute <query>` does the same
+//! for one rule until `:unmute <query>`. Awaited entries are still pinned.
+//! Both are kept in `~/.config/logs-parser/snooze` so a restart during a
+//! maintenance window stays quiet:
+//!
+//! ```text
+//! until 2024-02-17T11:00:00+00:00
+//! mute level:error
+//! ```
+
+use crate::config::config_dir;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, TimeZone};
+
+/// Snoozed time and muted rules
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Snooze {
+    /// Unix time alerts are silenced until
+    until: Option<i64>,
+    muted: Vec<String>,
+}
+
+impl Snooze {
+    /// Parse a snooze file, skipping lines it doesn't recognise
+    pub fn parse(contents: &str) -> Self {
+        let mut snooze = Self::default();
+        for line in contents.lines().map(str::trim) {
+            if let Some(until) = line.strip_prefix("until ") {
+                if let Ok(until) = DateTime::parse_from_rfc3339(until.trim()) {
+                    snooze.until = Some(until.timestamp());
+                }
+            } else if let Some(query) = line.strip_prefix("mute ") {
+                snooze.mute(query.trim());
+            }
+        }
+        snooze
+    }
+
+    /// Load the saved state; no file means nothing is silenced
+    pub fn load() -> Self {
+        std::fs::read_to_string(config_dir().join("snooze"))
+            .map(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    /// Write the state back to the snooze file
+    pub fn save(&self) -> Result<()> {
+        let dir = config_dir();
+        std::fs::create_dir_all(&dir).context("Failed to create config directory")?;
+        let mut contents = String::new();
+        if let Some(until) = self.until.and_then(|t| Local.timestamp_opt(t, 0).single()) {
+            contents.push_str(&format!("until {}\n", until.to_rfc3339()));
+        }
+        for query in &self.muted {
+            contents.push_str(&format!("mute {}\n", query));
+        }
+        std::fs::write(dir.join("snooze"), contents).context("Failed to save snooze state")
+    }
+
+    /// Silence every alert until `until`
+    pub fn snooze(&mut self, until: i64) {
+        self.until = Some(until);
+    }
+
+    /// End a snooze early
+    pub fn wake(&mut self) {
+        self.until = None;
+    }
+
+    /// When the snooze ends, while one is running at `now`
+    pub fn until(&self, now: i64) -> Option<i64> {
+        self.until.filter(|&until| until > now)
+    }
+
+    /// Whether a snooze has run out and is still to be forgotten
+    pub fn expired(&self, now: i64) -> bool {
+        self.until.is_some_and(|until| until <= now)
+    }
+
+    /// Mute one rule by its query; false if it already was
+    pub fn mute(&mut self, query: &str) -> bool {
+        if query.is_empty() || self.is_muted(query) {
+            return false;
+        }
+        self.muted.push(query.to_string());
+        true
+    }
+
+    /// Unmute a rule; false if it wasn't muted
+    pub fn unmute(&mut self, query: &str) -> bool {
+        let before = self.muted.len();
+        self.muted.retain(|muted| muted != query);
+        self.muted.len() != before
+    }
+
+    pub fn muted(&self) -> &[String] {
+        &self.muted
+    }
+
+    pub fn is_muted(&self, query: &str) -> bool {
+        self.muted.iter().any(|muted| muted == query)
+    }
+
+    /// Whether an alert for `query` should stay quiet at `now`
+    pub fn silences(&self, query: &str, now: i64) -> bool {
+        self.until(now).is_some() || self.is_muted(query)
+    }
+}
+
+/// Local clock time of a unix time, for saying when a snooze ends
+pub fn clock_time(time: i64) -> String {
+    Local
+        .timestamp_opt(time, 0)
+        .single()
+        .map(|time| time.format("%H:%M").to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snooze_runs_out() {
+        let mut snooze = Snooze::default();
+        snooze.snooze(1_000);
+        assert!(snooze.silences("level:error", 999));
+        assert_eq!(snooze.until(999), Some(1_000));
+        assert!(!snooze.silences("level:error", 1_000));
+
+        assert!(!snooze.expired(999));
+        assert!(snooze.expired(1_000));
+        snooze.wake();
+        assert!(!snooze.expired(1_000));
+    }
+
+    #[test]
+    fn test_mute_one_rule() {
+        let mut snooze = Snooze::default();
+        assert!(snooze.mute("level:error"));
+        assert!(!snooze.mute("level:error"));
+        assert!(snooze.silences("level:error", 0));
+        assert!(!snooze.silences("status:5", 0));
+
+        assert!(snooze.unmute("level:error"));
+        assert!(!snooze.unmute("level:error"));
+        assert!(!snooze.silences("level:error", 0));
+    }
+
+    #[test]
+    fn test_parse_saved_state() {
+        let snooze = Snooze::parse(
+            "until 2024-02-17T11:00:00+00:00\nmute level:error\nmute cache miss\nnonsense\nuntil soon\n",
+        );
+        assert_eq!(snooze.until(0), Some(1_708_167_600));
+        assert_eq!(snooze.muted(), ["level:error", "cache miss"]);
+    }
+}
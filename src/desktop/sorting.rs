@@ -0,0 +1,232 @@
+//! Ordering a frozen view by something other than time
+//!
+//! While the list is paused or a replay has finished, `:sort <key>` shows
+//! the entries ordered by level, dyno, source or a structured field such
+//! as a router line's `service` time.
+//!
+//! The live buffer itself is in arrival order, or with `order = timestamp`
+//! kept sorted by timestamp as lines come in, for dynos whose lines are
+//! flushed late.
+
+use crate::parser::LogEntry;
+use std::cmp::Ordering;
+
+/// What to order entries by
+#[derive(Debug, Clone, PartialEq)]
+pub enum SortKey {
+    /// Most severe first
+    Level,
+    Dyno,
+    Source,
+    /// A structured field, numerically when both values start with a number
+    Field(String),
+}
+
+impl SortKey {
+    /// `level`, `dyno` and `source` are built in; any other name is a field
+    pub fn parse(name: &str) -> Self {
+        match name.to_lowercase().as_str() {
+            "level" => SortKey::Level,
+            "dyno" => SortKey::Dyno,
+            "source" => SortKey::Source,
+            _ => SortKey::Field(name.to_string()),
+        }
+    }
+
+    pub fn label(&self) -> &str {
+        match self {
+            SortKey::Level => "level",
+            SortKey::Dyno => "dyno",
+            SortKey::Source => "source",
+            SortKey::Field(name) => name,
+        }
+    }
+}
+
+/// The number a value starts with, e.g. 182 for `182ms`
+fn leading_number(value: &str) -> Option<f64> {
+    let end = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))
+        .unwrap_or(value.len());
+    value[..end].parse().ok()
+}
+
+fn compare_values(a: &str, b: &str) -> Ordering {
+    match (leading_number(a), leading_number(b)) {
+        (Some(x), Some(y)) => x.total_cmp(&y),
+        _ => a.cmp(b),
+    }
+}
+
+/// Order the live buffer keeps entries in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BufferOrder {
+    /// As received
+    #[default]
+    Arrival,
+    /// By timestamp; a late line is inserted where it belongs
+    Timestamp,
+}
+
+impl BufferOrder {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "arrival" => Some(Self::Arrival),
+            "timestamp" | "time" => Some(Self::Timestamp),
+            _ => None,
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Arrival => Self::Timestamp,
+            Self::Timestamp => Self::Arrival,
+        }
+    }
+
+    /// Add an entry to a buffer kept in this order; in timestamp order it
+    /// goes after any entries with the same time
+    pub fn insert(self, entries: &mut Vec<LogEntry>, entry: LogEntry) {
+        let in_order = entries
+            .last()
+            .is_none_or(|last| last.timestamp <= entry.timestamp);
+        if self == Self::Arrival || in_order {
+            entries.push(entry);
+        } else {
+            let at = entries.partition_point(|e| e.timestamp <= entry.timestamp);
+            entries.insert(at, entry);
+        }
+    }
+}
+
+/// A sort key and direction
+#[derive(Debug, Clone, PartialEq)]
+pub struct SortOrder {
+    pub key: SortKey,
+    pub descending: bool,
+}
+
+impl SortOrder {
+    /// The entries in this order; ties, and entries without the field
+    /// (placed last either way), keep their time order
+    pub fn apply(&self, entries: &[LogEntry]) -> Vec<LogEntry> {
+        let mut sorted = entries.to_vec();
+        sorted.sort_by(|a, b| {
+            let ordering = match &self.key {
+                SortKey::Level => a.level.rank().cmp(&b.level.rank()),
+                SortKey::Dyno => a.dyno.cmp(&b.dyno),
+                SortKey::Source => a.source.cmp(&b.source),
+                SortKey::Field(name) => match (a.fields.get(name), b.fields.get(name)) {
+                    (Some(x), Some(y)) => compare_values(x, y),
+                    (Some(_), None) => return Ordering::Less,
+                    (None, Some(_)) => return Ordering::Greater,
+                    (None, None) => Ordering::Equal,
+                },
+            };
+            if self.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+        sorted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_log_line;
+
+    fn router(second: u32, service: &str) -> LogEntry {
+        parse_log_line(&format!(
+            "2024-02-17T10:30:{:02}.000000+00:00 heroku[router]: at=info method=GET path=/ service={} status=200",
+            second, service
+        ))
+        .unwrap()
+    }
+
+    fn service_times(entries: &[LogEntry]) -> Vec<String> {
+        entries
+            .iter()
+            .map(|e| e.fields["service"].clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_sort_by_numeric_field() {
+        let entries = vec![router(1, "90ms"), router(2, "1200ms"), router(3, "15ms")];
+        let order = SortOrder {
+            key: SortKey::parse("service"),
+            descending: true,
+        };
+        assert_eq!(
+            service_times(&order.apply(&entries)),
+            ["1200ms", "90ms", "15ms"]
+        );
+
+        let ascending = SortOrder {
+            descending: false,
+            ..order
+        };
+        assert_eq!(
+            service_times(&ascending.apply(&entries)),
+            ["15ms", "90ms", "1200ms"]
+        );
+    }
+
+    #[test]
+    fn test_sort_by_level_keeps_time_order_for_ties() {
+        let line = |second: u32, message: &str| {
+            parse_log_line(&format!(
+                "2024-02-17T10:30:{:02}.000000+00:00 app[web.1]: {}",
+                second, message
+            ))
+            .unwrap()
+        };
+        let entries = vec![line(1, "info: a"), line(2, "error: b"), line(3, "info: c")];
+        let order = SortOrder {
+            key: SortKey::Level,
+            descending: false,
+        };
+        let sorted: Vec<String> = order
+            .apply(&entries)
+            .into_iter()
+            .map(|e| e.message)
+            .collect();
+        assert_eq!(sorted, ["error: b", "info: a", "info: c"]);
+    }
+
+    #[test]
+    fn test_timestamp_order_places_late_lines() {
+        let mut entries = Vec::new();
+        for (second, service) in [(1, "a"), (3, "c"), (2, "b"), (3, "d"), (0, "z")] {
+            BufferOrder::Timestamp.insert(&mut entries, router(second, service));
+        }
+        assert_eq!(service_times(&entries), ["z", "a", "b", "c", "d"]);
+
+        let mut arrival = Vec::new();
+        BufferOrder::Arrival.insert(&mut arrival, router(3, "c"));
+        BufferOrder::Arrival.insert(&mut arrival, router(2, "b"));
+        assert_eq!(service_times(&arrival), ["c", "b"]);
+        assert_eq!(
+            BufferOrder::parse("timestamp"),
+            Some(BufferOrder::Timestamp)
+        );
+        assert_eq!(BufferOrder::Timestamp.toggled(), BufferOrder::Arrival);
+    }
+
+    #[test]
+    fn test_missing_fields_go_last() {
+        let plain =
+            parse_log_line("2024-02-17T10:30:00.000000+00:00 app[web.1]: no fields").unwrap();
+        let entries = vec![plain.clone(), router(1, "5ms")];
+        for descending in [false, true] {
+            let order = SortOrder {
+                key: SortKey::parse("service"),
+                descending,
+            };
+            assert_eq!(order.apply(&entries).last(), Some(&plain));
+        }
+    }
+}
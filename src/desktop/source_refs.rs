@@ -0,0 +1,100 @@
+//! `file:line` references in log messages, e.g. `app/models/user.rb:42` in a
+//! Ruby backtrace or `src/main.rs:10:5` in a Rust panic
+
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Command used when `open_command` isn't set and `$EDITOR` is
+const EDITOR_TEMPLATE: &str = "+{line} {file}";
+
+/// A source location found in a message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceRef {
+    /// Byte range of the reference within the message
+    pub start: usize,
+    pub end: usize,
+    pub file: String,
+    pub line: u32,
+}
+
+fn ref_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(r"([\w./-]+\.(?:rb|rs|py|js|jsx|ts|tsx|go|ex|exs|java|kt|php|erb|cr)):(\d+)")
+            .expect("Failed to compile source reference regex")
+    })
+}
+
+/// Find all `file:line` references in a message
+pub fn find_refs(message: &str) -> Vec<SourceRef> {
+    ref_regex()
+        .captures_iter(message)
+        .filter_map(|captures| {
+            let whole = captures.get(0)?;
+            Some(SourceRef {
+                start: whole.start(),
+                end: whole.end(),
+                file: captures[1].to_string(),
+                line: captures[2].parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Build the argv for opening a reference
+///
+/// `template` uses `{file}` and `{line}` placeholders, e.g.
+/// `code --goto {file}:{line}`. Without one, `$EDITOR +{line} {file}` is used.
+pub fn open_command(template: Option<&str>, file: &str, line: u32) -> Option<Vec<String>> {
+    let template = match template {
+        Some(template) => template.to_string(),
+        None => format!("{} {}", std::env::var("EDITOR").ok()?, EDITOR_TEMPLATE),
+    };
+    let argv: Vec<String> = template
+        .split_whitespace()
+        .map(|part| {
+            part.replace("{file}", file)
+                .replace("{line}", &line.to_string())
+        })
+        .collect();
+    (!argv.is_empty()).then_some(argv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_refs() {
+        let message = "from app/models/user.rb:42:in `save' and src/main.rs:10:5";
+        let refs = find_refs(message);
+
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs[0].file, "app/models/user.rb");
+        assert_eq!(refs[0].line, 42);
+        assert_eq!(
+            &message[refs[0].start..refs[0].end],
+            "app/models/user.rb:42"
+        );
+        assert_eq!(refs[1].file, "src/main.rs");
+        assert_eq!(refs[1].line, 10);
+    }
+
+    #[test]
+    fn test_find_refs_ignores_other_colons() {
+        assert!(find_refs("connected to db.example.com:5432 at 10:30").is_empty());
+    }
+
+    #[test]
+    fn test_open_command_template() {
+        assert_eq!(
+            open_command(Some("code --goto {file}:{line}"), "src/main.rs", 10),
+            Some(vec![
+                "code".to_string(),
+                "--goto".to_string(),
+                "src/main.rs:10".to_string()
+            ])
+        );
+        assert_eq!(open_command(Some("  "), "src/main.rs", 10), None);
+    }
+}
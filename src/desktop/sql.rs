@@ -0,0 +1,178 @@
+//! Detection and pretty-rendering of SQL statements in log messages
+//!
+//! ORM debug output and Postgres logs often embed a full statement in the
+//! message, e.g. `User Load (0.5ms)  SELECT "users".* FROM "users" WHERE ...`.
+
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// A piece of a message, flagged if it is an SQL keyword
+#[derive(Debug, Clone, PartialEq)]
+pub struct SqlToken {
+    pub text: String,
+    pub keyword: bool,
+}
+
+fn statement_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(
+            // SELECT requires a column list directly before FROM so prose like
+            // "select an option from the menu" isn't mistaken for a query
+            r#"(?i)\b(SELECT\s+(?:DISTINCT\s+)?\S+(?:\s*,\s*\S+)*\s+FROM|INSERT\s+INTO|UPDATE\s+[\w."]+\s+SET|DELETE\s+FROM)\b"#,
+        )
+        .expect("Failed to compile SQL statement regex")
+    })
+}
+
+fn transaction_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        // Only a whole statement, alone or after a `statement:` or Rails
+        // `TRANSACTION (0.2ms)` prefix, so "begin processing" or "commit
+        // abc123" isn't mistaken for one
+        Regex::new(r"(?:^|:|\))\s*(?:BEGIN(?:\s+TRANSACTION)?|COMMIT|ROLLBACK);?\s*$")
+            .expect("Failed to compile SQL transaction regex")
+    })
+}
+
+fn keyword_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(
+            r"(?i)\b(SELECT|DISTINCT|FROM|WHERE|AND|OR|NOT|IN|IS|NULL|LIKE|ILIKE|INSERT|INTO|VALUES|UPDATE|SET|DELETE|LEFT|RIGHT|INNER|OUTER|JOIN|ON|GROUP|ORDER|BY|HAVING|LIMIT|OFFSET|AS|RETURNING|UNION|BEGIN|COMMIT|ROLLBACK|ASC|DESC)\b",
+        )
+        .expect("Failed to compile SQL keyword regex")
+    })
+}
+
+fn clause_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(
+            r"(?i)\s+((?:LEFT\s+|RIGHT\s+|INNER\s+|OUTER\s+)*JOIN|FROM|WHERE|GROUP\s+BY|ORDER\s+BY|HAVING|LIMIT|OFFSET|VALUES|SET|RETURNING)\b",
+        )
+        .expect("Failed to compile SQL clause regex")
+    })
+}
+
+fn duration_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        // `duration: 123ms`, `duration=1.5 ms` (Postgres) or `(0.5ms)` (Rails)
+        Regex::new(r"(?i)(?:duration[:=]\s*(\d+(?:\.\d+)?)\s*ms|\((\d+(?:\.\d+)?)ms\))")
+            .expect("Failed to compile duration regex")
+    })
+}
+
+/// Check whether a message contains an SQL statement
+pub fn is_sql(message: &str) -> bool {
+    statement_regex().is_match(message) || transaction_regex().is_match(message)
+}
+
+/// Split a message into plain text and SQL keyword tokens
+pub fn highlight(message: &str) -> Vec<SqlToken> {
+    let mut tokens = Vec::new();
+    let mut last = 0;
+
+    for m in keyword_regex().find_iter(message) {
+        if m.start() > last {
+            tokens.push(SqlToken {
+                text: message[last..m.start()].to_string(),
+                keyword: false,
+            });
+        }
+        tokens.push(SqlToken {
+            text: m.as_str().to_string(),
+            keyword: true,
+        });
+        last = m.end();
+    }
+
+    if last < message.len() {
+        tokens.push(SqlToken {
+            text: message[last..].to_string(),
+            keyword: false,
+        });
+    }
+
+    tokens
+}
+
+/// Reformat a statement with each major clause on its own line
+pub fn format_sql(message: &str) -> String {
+    clause_regex().replace_all(message, "\n$1").into_owned()
+}
+
+/// Extract a query duration in milliseconds from a message
+pub fn extract_duration(message: &str) -> Option<f64> {
+    let captures = duration_regex().captures(message)?;
+    captures
+        .get(1)
+        .or_else(|| captures.get(2))?
+        .as_str()
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RAILS: &str =
+        r#"User Load (0.5ms)  SELECT "users".* FROM "users" WHERE "users"."id" = $1 LIMIT $2"#;
+    const POSTGRES: &str =
+        "duration: 123.456 ms  statement: UPDATE jobs SET state = 'done' WHERE id = 4";
+
+    #[test]
+    fn test_detects_statements() {
+        assert!(is_sql(RAILS));
+        assert!(is_sql(POSTGRES));
+        assert!(is_sql("INSERT INTO events (name) VALUES ('signup')"));
+        assert!(!is_sql("Please select an option from the menu"));
+        assert!(is_sql("SELECT id, name FROM users"));
+        assert!(!is_sql("Update available, set a reminder"));
+    }
+
+    #[test]
+    fn test_detects_transaction_statements() {
+        assert!(is_sql("BEGIN"));
+        assert!(is_sql("TRANSACTION (0.2ms)  COMMIT"));
+        assert!(is_sql("LOG:  statement: ROLLBACK;"));
+        assert!(is_sql("statement: BEGIN TRANSACTION"));
+        assert!(!is_sql("begin processing batch 42"));
+        assert!(!is_sql("Deploying commit abc123 to production"));
+        assert!(!is_sql("Deploy: rollback"));
+        assert!(!is_sql("Rollback of v42 is in progress and on track"));
+    }
+
+    #[test]
+    fn test_highlight_marks_keywords() {
+        let tokens = highlight("select id from users");
+        let keywords: Vec<&str> = tokens
+            .iter()
+            .filter(|t| t.keyword)
+            .map(|t| t.text.as_str())
+            .collect();
+
+        assert_eq!(keywords, vec!["select", "from"]);
+        let rebuilt: String = tokens.iter().map(|t| t.text.as_str()).collect();
+        assert_eq!(rebuilt, "select id from users");
+    }
+
+    #[test]
+    fn test_format_breaks_clauses() {
+        assert_eq!(
+            format_sql("SELECT * FROM users LEFT JOIN teams ON true WHERE id = 1 ORDER BY id"),
+            "SELECT *\nFROM users\nLEFT JOIN teams ON true\nWHERE id = 1\nORDER BY id"
+        );
+    }
+
+    #[test]
+    fn test_extract_duration() {
+        assert_eq!(extract_duration(RAILS), Some(0.5));
+        assert_eq!(extract_duration(POSTGRES), Some(123.456));
+        assert_eq!(extract_duration("query took duration=42ms"), Some(42.0));
+        assert_eq!(extract_duration("no timing here"), None);
+    }
+}
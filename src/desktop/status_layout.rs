@@ -0,0 +1,169 @@
+//! Configurable status header layout, loaded from `~/.config/logs-parser/status`
+//!
+//! Like tmux's status-left/status-right, the file lists which segments appear
+//! on each side of the header:
+//!
+//! ```text
+//! left = app, total, filtered, activity
+//! right = clock, title
+//! ```
+
+use crate::config::config_dir;
+
+/// A single piece of information shown in the status header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusSegment {
+    /// Name of the connected Heroku app
+    App,
+    /// Number of logs in the buffer
+    Total,
+    /// Number of logs passing the filters
+    Filtered,
+    /// AND/OR filter mode
+    Mode,
+    /// Number of active filters
+    Filters,
+    /// Logs per second over the last minute
+    Rate,
+    /// Sparkline of lines per second over the last minute, with the share
+    /// of errors
+    Activity,
+    /// Share of lines that failed to parse, shown only when some did
+    Unparsed,
+    /// Dyno crashes, restarts and error codes not yet looked at, shown
+    /// only when there are some
+    Platform,
+    /// Time of the most recent alert, with its message on hover
+    LastAlert,
+    /// Current local time
+    Clock,
+    /// App title
+    Title,
+}
+
+impl StatusSegment {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "app" => Some(StatusSegment::App),
+            "total" => Some(StatusSegment::Total),
+            "filtered" => Some(StatusSegment::Filtered),
+            "mode" => Some(StatusSegment::Mode),
+            "filters" => Some(StatusSegment::Filters),
+            "rate" => Some(StatusSegment::Rate),
+            "activity" => Some(StatusSegment::Activity),
+            "unparsed" => Some(StatusSegment::Unparsed),
+            "platform" => Some(StatusSegment::Platform),
+            "last_alert" => Some(StatusSegment::LastAlert),
+            "clock" => Some(StatusSegment::Clock),
+            "title" => Some(StatusSegment::Title),
+            _ => None,
+        }
+    }
+}
+
+/// Segments shown on the left and right of the status header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusLayout {
+    pub left: Vec<StatusSegment>,
+    pub right: Vec<StatusSegment>,
+}
+
+impl Default for StatusLayout {
+    fn default() -> Self {
+        Self {
+            left: vec![
+                StatusSegment::Total,
+                StatusSegment::Filtered,
+                StatusSegment::Mode,
+                StatusSegment::Activity,
+                StatusSegment::Unparsed,
+                StatusSegment::Platform,
+            ],
+            right: vec![StatusSegment::Title],
+        }
+    }
+}
+
+impl StatusLayout {
+    /// Build a layout from a status file; sides not mentioned keep their
+    /// defaults and unknown segment names are skipped
+    pub fn parse(contents: &str) -> Self {
+        let mut layout = Self::default();
+
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((side, segments)) = line.split_once('=') else {
+                continue;
+            };
+            let segments: Vec<StatusSegment> = segments
+                .split(',')
+                .filter_map(|name| StatusSegment::from_name(name.trim()))
+                .collect();
+            match side.trim() {
+                "left" => layout.left = segments,
+                "right" => layout.right = segments,
+                _ => {}
+            }
+        }
+
+        layout
+    }
+
+    /// Load the layout from the user's status file, falling back to defaults
+    pub fn load() -> Self {
+        std::fs::read_to_string(config_dir().join("status"))
+            .map(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_both_sides() {
+        let layout = StatusLayout::parse("left = app, rate\nright = clock");
+
+        assert_eq!(layout.left, vec![StatusSegment::App, StatusSegment::Rate]);
+        assert_eq!(layout.right, vec![StatusSegment::Clock]);
+    }
+
+    #[test]
+    fn test_parse_keeps_unmentioned_side() {
+        let layout = StatusLayout::parse("# only the right\nright = filters, title");
+
+        assert_eq!(layout.left, StatusLayout::default().left);
+        assert_eq!(
+            layout.right,
+            vec![StatusSegment::Filters, StatusSegment::Title]
+        );
+    }
+
+    #[test]
+    fn test_parse_last_alert() {
+        let layout = StatusLayout::parse("right = last_alert, clock");
+
+        assert_eq!(
+            layout.right,
+            vec![StatusSegment::LastAlert, StatusSegment::Clock]
+        );
+    }
+
+    #[test]
+    fn test_parse_skips_unknown_segments() {
+        let layout = StatusLayout::parse("left = total, bogus, mode");
+
+        assert_eq!(layout.left, vec![StatusSegment::Total, StatusSegment::Mode]);
+    }
+
+    #[test]
+    fn test_empty_side_hides_segments() {
+        let layout = StatusLayout::parse("right =");
+
+        assert!(layout.right.is_empty());
+    }
+}
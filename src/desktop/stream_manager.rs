@@ -1,33 +1,91 @@
 //! Stream manager for handling Heroku log streaming process lifecycle
 
 use anyhow::{Context, Result};
-use crate::parser::parse_log_line;
-use crate::parser::LogEntry;
-use tokio::io::{AsyncBufReadExt, BufReader};
+use crate::backfill::{SeenLines, HISTORY_LINES};
+use crate::parser::{parse_stream, ParsedLine};
+use futures::StreamExt;
+use std::sync::{Arc, Mutex};
+use tokio::io::BufReader;
 use tokio::process::{Child, Command};
 use tokio::sync::mpsc;
 use tokio::time::{sleep, Duration};
 
 use super::heroku_cli::find_heroku_binary;
 
+/// What `heroku logs` is asked for besides the app
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamOptions {
+    /// Lines of history before tailing, as in `--num`
+    pub num: usize,
+    /// Only lines from this dyno or process type, as in `--dyno`
+    pub dyno: Option<String>,
+    /// Only lines from this source, such as `app` or `heroku`, as in
+    /// `--source`
+    pub source: Option<String>,
+}
+
+impl Default for StreamOptions {
+    fn default() -> Self {
+        Self {
+            num: HISTORY_LINES,
+            dyno: None,
+            source: None,
+        }
+    }
+}
+
+impl StreamOptions {
+    /// Arguments passed on to `heroku logs`
+    pub fn args(&self) -> Vec<String> {
+        let mut args = vec!["--num".to_string(), self.num.to_string()];
+        if let Some(dyno) = &self.dyno {
+            args.extend(["--dyno".to_string(), dyno.clone()]);
+        }
+        if let Some(source) = &self.source {
+            args.extend(["--source".to_string(), source.clone()]);
+        }
+        args
+    }
+}
+
 pub struct StreamManager {
     app_name: String,
+    options: StreamOptions,
     process: Option<Child>,
-    log_sender: mpsc::UnboundedSender<LogEntry>,
+    log_sender: mpsc::UnboundedSender<ParsedLine>,
     reconnect_attempts: u32,
+    /// Shared with every connection's reader, so the history each one
+    /// starts with skips lines already sent
+    seen: Arc<Mutex<SeenLines>>,
 }
 
 impl StreamManager {
-    pub fn new(app_name: String, log_sender: mpsc::UnboundedSender<LogEntry>) -> Self {
+    pub fn new(
+        app_name: String,
+        options: StreamOptions,
+        log_sender: mpsc::UnboundedSender<ParsedLine>,
+    ) -> Self {
         Self {
             app_name,
+            options,
             process: None,
             log_sender,
             reconnect_attempts: 0,
+            seen: Arc::new(Mutex::new(SeenLines::default())),
+        }
+    }
+
+    /// Treat these raw lines, such as ones restored from the last run, as
+    /// already sent
+    pub fn skip_lines<'a>(&mut self, lines: impl IntoIterator<Item = &'a str>) {
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        for line in lines {
+            seen.first_sight(line);
         }
     }
 
-    /// Connect to Heroku app and start streaming logs
+    /// Connect to Heroku app and start streaming logs, starting with recent
+    /// history
     pub async fn connect(&mut self) -> Result<()> {
         // Kill existing process if any
         self.disconnect().await;
@@ -40,6 +98,7 @@ impl StreamManager {
             .env("PATH", gui_path)
             .arg("logs")
             .arg("--tail")
+            .args(self.options.args())
             .arg("--app")
             .arg(&self.app_name)
             .stdout(std::process::Stdio::piped())
@@ -50,17 +109,26 @@ impl StreamManager {
         let stdout = child.stdout.take().context("Failed to capture stdout")?;
 
         let sender = self.log_sender.clone();
+        let seen = self.seen.clone();
 
         // Spawn task to read stdout line by line
         tokio::spawn(async move {
-            let reader = BufReader::new(stdout);
-            let mut lines = reader.lines();
-
-            while let Ok(Some(line)) = lines.next_line().await {
-                if let Some(entry) = parse_log_line(&line) {
-                    if sender.send(entry).is_err() {
-                        break;
-                    }
+            let mut entries = std::pin::pin!(parse_stream(BufReader::new(stdout)));
+
+            while let Some(result) = entries.next().await {
+                let raw = match &result {
+                    Ok(entry) => &entry.raw,
+                    Err(unparsed) => &unparsed.line,
+                };
+                if !seen
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .first_sight(raw)
+                {
+                    continue;
+                }
+                if sender.send(result).is_err() {
+                    break;
                 }
             }
         });
@@ -121,3 +189,22 @@ impl Drop for StreamManager {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_options_args() {
+        assert_eq!(StreamOptions::default().args(), ["--num", "1500"]);
+        let options = StreamOptions {
+            num: 200,
+            dyno: Some("web.1".to_string()),
+            source: Some("app".to_string()),
+        };
+        assert_eq!(
+            options.args(),
+            ["--num", "200", "--dyno", "web.1", "--source", "app"]
+        );
+    }
+}
@@ -0,0 +1,211 @@
+//! Row styles for outlying field values, loaded from
+//! `~/.config/logs-parser/thresholds`
+//!
+//! Each line holds `field op value => style...`, where `op` is one of `>`,
+//! `>=`, `<` or `<=` and the styles are background colors (`red`, `orange`,
+//! `yellow`, `green`, `blue`, `purple`) or `bold`. Values may carry a unit:
+//! durations compare in milliseconds (`250ms`, `1.5s`) and sizes in bytes
+//! (`512kB`, `1MB`, `24866320bytes`), so a rule's value and a field's value
+//! can be written differently:
+//!
+//! ```text
+//! service > 1000ms => red
+//! bytes >= 1MB => bold
+//! ```
+//!
+//! Every matching rule adds its styles; rows are only decorated, never
+//! hidden.
+
+use crate::config::config_dir;
+use crate::parser::LogEntry;
+
+/// Styles a rule can give a row, each a `threshold-<name>` class
+const STYLES: [&str; 7] = ["red", "orange", "yellow", "green", "blue", "purple", "bold"];
+
+/// Numeric value of a field such as `12`, `1.5s`, `250ms` or `4020564kB`,
+/// with durations in milliseconds and sizes in bytes
+pub fn numeric_value(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))
+        .unwrap_or(value.len());
+    let number: f64 = value[..split].parse().ok()?;
+    let scale = match value[split..].trim().to_lowercase().as_str() {
+        "" | "ms" | "b" | "bytes" => 1.0,
+        "us" | "µs" => 0.001,
+        "s" => 1000.0,
+        "kb" => 1024.0,
+        "mb" => 1024.0 * 1024.0,
+        "gb" => 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some(number * scale)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
+impl Comparison {
+    fn holds(self, value: f64, limit: f64) -> bool {
+        match self {
+            Self::Greater => value > limit,
+            Self::GreaterOrEqual => value >= limit,
+            Self::Less => value < limit,
+            Self::LessOrEqual => value <= limit,
+        }
+    }
+}
+
+/// A field comparison and the styles of rows that satisfy it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThresholdRule {
+    field: String,
+    comparison: Comparison,
+    limit: f64,
+    styles: Vec<&'static str>,
+}
+
+impl ThresholdRule {
+    /// Parse a `field op value => style...` line
+    pub fn parse(line: &str) -> Option<Self> {
+        let (condition, styles) = line.rsplit_once("=>")?;
+        let mut words = condition.split_whitespace();
+        let field = words.next()?.to_string();
+        let comparison = match words.next()? {
+            ">" => Comparison::Greater,
+            ">=" => Comparison::GreaterOrEqual,
+            "<" => Comparison::Less,
+            "<=" => Comparison::LessOrEqual,
+            _ => return None,
+        };
+        let limit = numeric_value(words.next()?)?;
+        if words.next().is_some() {
+            return None;
+        }
+        let styles = styles
+            .split_whitespace()
+            .map(|name| {
+                STYLES
+                    .iter()
+                    .find(|s| s.eq_ignore_ascii_case(name))
+                    .copied()
+            })
+            .collect::<Option<Vec<_>>>()?;
+        if styles.is_empty() {
+            return None;
+        }
+        Some(Self {
+            field,
+            comparison,
+            limit,
+            styles,
+        })
+    }
+
+    fn matches(&self, entry: &LogEntry) -> bool {
+        entry
+            .fields
+            .get(&self.field)
+            .and_then(|value| numeric_value(value))
+            .is_some_and(|value| self.comparison.holds(value, self.limit))
+    }
+}
+
+/// Threshold rules; all matching rules apply
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ThresholdRules {
+    rules: Vec<ThresholdRule>,
+}
+
+impl ThresholdRules {
+    /// Parse a thresholds file, skipping blank lines, comments and invalid
+    /// rules
+    pub fn parse(contents: &str) -> Self {
+        let rules = contents
+            .lines()
+            .filter(|line| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+            .filter_map(ThresholdRule::parse)
+            .collect();
+        Self { rules }
+    }
+
+    /// Load rules from the user's thresholds file; no file means no rules
+    pub fn load() -> Self {
+        std::fs::read_to_string(config_dir().join("thresholds"))
+            .map(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    /// Row classes for an entry, e.g. `threshold-red threshold-bold`
+    pub fn classes(&self, entry: &LogEntry) -> String {
+        let mut styles: Vec<&str> = Vec::new();
+        for rule in self.rules.iter().filter(|rule| rule.matches(entry)) {
+            for style in &rule.styles {
+                if !styles.contains(style) {
+                    styles.push(style);
+                }
+            }
+        }
+        styles
+            .iter()
+            .map(|style| format!("threshold-{}", style))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_log_line;
+
+    fn router(service: &str, bytes: &str) -> LogEntry {
+        parse_log_line(&format!(
+            "2024-02-17T10:30:45.123456+00:00 heroku[router]: at=info method=GET path=\"/\" status=200 service={} bytes={}",
+            service, bytes
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_numeric_values() {
+        assert_eq!(numeric_value("12"), Some(12.0));
+        assert_eq!(numeric_value("250ms"), Some(250.0));
+        assert_eq!(numeric_value("1.5s"), Some(1500.0));
+        assert_eq!(numeric_value("2kB"), Some(2048.0));
+        assert_eq!(numeric_value("1MB"), Some(1048576.0));
+        assert_eq!(numeric_value("24866320bytes"), Some(24866320.0));
+        assert_eq!(numeric_value("GET"), None);
+        assert_eq!(numeric_value("12 apples"), None);
+    }
+
+    #[test]
+    fn test_matching_rules_combine() {
+        let rules = ThresholdRules::parse(
+            "# slow or large responses\nservice > 1s => red\nbytes >= 1MB => bold red\nservice < 5 => green",
+        );
+
+        assert_eq!(rules.classes(&router("12ms", "512")), "");
+        assert_eq!(rules.classes(&router("1500ms", "512")), "threshold-red");
+        assert_eq!(
+            rules.classes(&router("1500ms", "2097152")),
+            "threshold-red threshold-bold"
+        );
+        assert_eq!(rules.classes(&router("2ms", "512")), "threshold-green");
+    }
+
+    #[test]
+    fn test_invalid_rules_skipped() {
+        let rules = ThresholdRules::parse(
+            "service > 1000 => loud\nservice = 10 => red\nservice > fast => red\nservice > 10 =>\nservice > 10 => Yellow",
+        );
+
+        assert_eq!(rules.rules.len(), 1);
+        assert_eq!(rules.rules[0].styles, ["yellow"]);
+    }
+}
@@ -0,0 +1,89 @@
+//! System tray / menu-bar icon, so the app can monitor while minimized
+//!
+//! The tooltip shows the connection state and how many errors arrived while
+//! the window was in the background; the icon's menu can pause the list,
+//! restart the stream or bring the window back.
+
+use dioxus::desktop::tao::event::{Event, WindowEvent};
+use dioxus::desktop::trayicon::menu::{Menu, MenuItem, PredefinedMenuItem};
+use dioxus::desktop::trayicon::{init_tray_icon, TrayIcon};
+use dioxus::desktop::{use_tray_menu_event_handler, use_window, use_wry_event_handler, window};
+use dioxus::prelude::*;
+
+const OPEN: &str = "tray-open";
+const PAUSE: &str = "tray-pause";
+const RECONNECT: &str = "tray-reconnect";
+
+/// Tray menu entries handled by the app
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayAction {
+    TogglePause,
+    Reconnect,
+}
+
+fn tray_menu() -> Menu {
+    let menu = Menu::new();
+    let _ = menu.append_items(&[
+        &MenuItem::with_id(OPEN, "Open Window", true, None),
+        &MenuItem::with_id(PAUSE, "Pause / Resume", true, None),
+        &MenuItem::with_id(RECONNECT, "Reconnect", true, None),
+        &PredefinedMenuItem::separator(),
+        &PredefinedMenuItem::quit(None),
+    ]);
+    menu
+}
+
+/// Tooltip text for the tray icon
+pub fn tooltip(status: &str, unseen_errors: usize, paused: bool) -> String {
+    let mut text = format!("logs-parser: {}", status);
+    if paused {
+        text.push_str(" (paused)");
+    }
+    if unseen_errors > 0 {
+        text.push_str(&format!(" — {} new errors", unseen_errors));
+    }
+    text
+}
+
+/// Show the tray icon when `enabled`, calling `on_action` for its menu
+pub fn use_tray(
+    enabled: bool,
+    mut on_action: impl FnMut(TrayAction) + 'static,
+) -> Option<TrayIcon> {
+    let icon = use_hook(|| enabled.then(|| init_tray_icon(tray_menu(), None)));
+    use_tray_menu_event_handler(move |event| {
+        if !enabled {
+            return;
+        }
+        if event.id == OPEN {
+            let desktop = window();
+            desktop.set_visible(true);
+            desktop.set_minimized(false);
+            desktop.set_focus();
+        } else if event.id == PAUSE {
+            on_action(TrayAction::TogglePause);
+        } else if event.id == RECONNECT {
+            on_action(TrayAction::Reconnect);
+        }
+    });
+    icon
+}
+
+/// Whether this window currently has focus
+pub fn use_window_focus() -> Signal<bool> {
+    let mut focused = use_signal(|| true);
+    let id = use_window().id();
+    use_wry_event_handler(move |event, _| {
+        if let Event::WindowEvent {
+            window_id,
+            event: WindowEvent::Focused(now_focused),
+            ..
+        } = event
+        {
+            if *window_id == id {
+                focused.set(*now_focused);
+            }
+        }
+    });
+    focused
+}
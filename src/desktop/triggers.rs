@@ -0,0 +1,110 @@
+//! One-shot triggers that fire when a matching entry first appears
+
+use crate::filters::Filter;
+use crate::parser::LogEntry;
+
+/// A query being waited on, as typed by the user
+#[derive(Debug, Clone, PartialEq)]
+pub struct AwaitTrigger {
+    pub query: String,
+    /// Clauses of the query, all of which have to match
+    filters: Vec<Filter>,
+}
+
+/// Pending await triggers; each fires at most once
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Triggers {
+    pending: Vec<AwaitTrigger>,
+}
+
+impl Triggers {
+    pub fn add(&mut self, query: String, filters: Vec<Filter>) {
+        self.pending.push(AwaitTrigger { query, filters });
+    }
+
+    /// Queries still being waited on
+    pub fn queries(&self) -> Vec<String> {
+        self.pending.iter().map(|t| t.query.clone()).collect()
+    }
+
+    /// Remove and return the triggers matched by an entry
+    pub fn take_matches(&mut self, entry: &LogEntry) -> Vec<AwaitTrigger> {
+        if self.pending.is_empty() {
+            return Vec::new();
+        }
+        let (fired, pending) = self
+            .pending
+            .drain(..)
+            .partition(|t| t.filters.iter().all(|f| f.matches(entry)));
+        self.pending = pending;
+        fired
+    }
+
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_log_line;
+
+    fn entry(message: &str) -> LogEntry {
+        parse_log_line(&format!(
+            "2024-02-17T10:30:45.123456+00:00 app[release.1]: {}",
+            message
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_trigger_fires_once() {
+        let mut triggers = Triggers::default();
+        triggers.add(
+            "migration complete".to_string(),
+            vec![Filter::TextSearch("migration complete".to_string())],
+        );
+
+        assert!(triggers
+            .take_matches(&entry("running migrations"))
+            .is_empty());
+
+        let fired = triggers.take_matches(&entry("Migration complete in 3s"));
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].query, "migration complete");
+
+        assert!(triggers
+            .take_matches(&entry("Migration complete in 3s"))
+            .is_empty());
+        assert!(triggers.queries().is_empty());
+    }
+
+    #[test]
+    fn test_only_matching_triggers_fire() {
+        let mut triggers = Triggers::default();
+        triggers.add(
+            "a".to_string(),
+            vec![Filter::TextSearch("alpha".to_string())],
+        );
+        triggers.add(
+            "b".to_string(),
+            vec![Filter::TextSearch("beta".to_string())],
+        );
+
+        let fired = triggers.take_matches(&entry("beta ready"));
+        assert_eq!(fired.len(), 1);
+        assert_eq!(triggers.queries(), vec!["a".to_string()]);
+    }
+
+    #[test]
+    fn test_every_clause_must_match() {
+        let mut triggers = Triggers::default();
+        let query = "level:error dyno:release.1";
+        triggers.add(query.to_string(), crate::filters::parse_query(query));
+
+        assert!(triggers.take_matches(&entry("INFO ready")).is_empty());
+        let fired = triggers.take_matches(&entry("ERROR migration failed"));
+        assert_eq!(fired.len(), 1);
+    }
+}
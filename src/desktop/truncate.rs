@@ -0,0 +1,56 @@
+//! Shortening of very long messages, e.g. single-line JSON blobs
+
+/// Split a message at `limit` characters
+///
+/// Returns the part to show and the number of hidden bytes, or `None` when
+/// the message fits. The cut never splits a UTF-8 character.
+pub fn truncate(message: &str, limit: usize) -> Option<(&str, usize)> {
+    let (cut, _) = message.char_indices().nth(limit)?;
+    Some((&message[..cut], message.len() - cut))
+}
+
+/// Human-readable size of hidden text, e.g. `812B` or `3.2KB`
+pub fn format_size(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{}B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1}KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1}MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_message_untouched() {
+        assert_eq!(truncate("short", 10), None);
+        assert_eq!(truncate("exactly10!", 10), None);
+    }
+
+    #[test]
+    fn test_truncate_counts_hidden_bytes() {
+        let message = format!("{{\"data\":\"{}\"}}", "x".repeat(4000));
+        let (shown, hidden) = truncate(&message, 100).unwrap();
+
+        assert_eq!(shown.len(), 100);
+        assert_eq!(hidden, message.len() - 100);
+    }
+
+    #[test]
+    fn test_truncate_respects_char_boundaries() {
+        let (shown, hidden) = truncate("héllo wörld", 2).unwrap();
+
+        assert_eq!(shown, "hé");
+        assert_eq!(hidden, "llo wörld".len());
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(812), "812B");
+        assert_eq!(format_size(3277), "3.2KB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0MB");
+    }
+}
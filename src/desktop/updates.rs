@@ -0,0 +1,165 @@
+//! Checking GitHub for a newer release
+//!
+//! At startup (unless `check_updates = off`) and on `:update`, the latest
+//! release is fetched from the GitHub API with `curl`, which every desktop
+//! OS ships. When its version is newer than this build, a banner offers the
+//! download for this OS, or the release page when there is none.
+
+use crate::heroku_cli::gui_path;
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use tokio::process::Command;
+
+/// Version of this build
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// GitHub repository releases are published in
+pub const REPOSITORY: &str = "harveyalex/logs-parser";
+
+/// A published release
+#[derive(Debug, Clone, PartialEq)]
+pub struct Release {
+    /// Version without the tag's `v`, e.g. `0.3.0`
+    pub version: String,
+    /// Release page on GitHub
+    pub page_url: String,
+    /// Installer for this OS, when the release has one
+    pub download_url: Option<String>,
+}
+
+impl Release {
+    /// Where to send the user to get it
+    pub fn url(&self) -> &str {
+        self.download_url.as_deref().unwrap_or(&self.page_url)
+    }
+}
+
+#[derive(Deserialize)]
+struct ApiRelease {
+    tag_name: String,
+    html_url: String,
+    #[serde(default)]
+    assets: Vec<ApiAsset>,
+}
+
+#[derive(Deserialize)]
+struct ApiAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// Parse the API's answer for a release, picking the asset for `os` (as in
+/// `std::env::consts::OS`)
+pub fn parse_release(json: &str, os: &str) -> Result<Release> {
+    let release: ApiRelease = serde_json::from_str(json).context("Unexpected release data")?;
+    let download_url = release
+        .assets
+        .into_iter()
+        .find(|asset| is_installer_for(&asset.name, os))
+        .map(|asset| asset.browser_download_url);
+    Ok(Release {
+        version: release.tag_name.trim_start_matches('v').to_string(),
+        page_url: release.html_url,
+        download_url,
+    })
+}
+
+/// Whether a release asset installs the app on `os`
+fn is_installer_for(name: &str, os: &str) -> bool {
+    let name = name.to_lowercase();
+    let (extensions, words): (&[&str], &[&str]) = match os {
+        "macos" => (&[".dmg"], &["macos", "darwin"]),
+        "windows" => (&[".msi", ".exe"], &["windows"]),
+        "linux" => (&[".appimage", ".deb"], &["linux"]),
+        _ => return false,
+    };
+    extensions.iter().any(|ext| name.ends_with(ext))
+        || (words.iter().any(|word| name.contains(word)) && !name.ends_with(".sha256"))
+}
+
+/// Whether version `a` comes after `b`, comparing dotted numbers; a part
+/// that isn't a number, such as `1-beta`, counts by its leading digits
+pub fn is_newer(a: &str, b: &str) -> bool {
+    fn parts(version: &str) -> Vec<u64> {
+        version
+            .split('.')
+            .map(|part| {
+                let digits: String = part.chars().take_while(char::is_ascii_digit).collect();
+                digits.parse().unwrap_or(0)
+            })
+            .collect()
+    }
+    let (mut a, mut b) = (parts(a), parts(b));
+    let len = a.len().max(b.len());
+    a.resize(len, 0);
+    b.resize(len, 0);
+    a > b
+}
+
+/// The latest release, when it is newer than this build
+pub async fn check() -> Result<Option<Release>> {
+    let url = format!(
+        "https://api.github.com/repos/{}/releases/latest",
+        REPOSITORY
+    );
+    let output = Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--location"])
+        .args(["--max-time", "15"])
+        .args(["--header", "Accept: application/vnd.github+json"])
+        .arg(&url)
+        .env("PATH", gui_path())
+        .output()
+        .await
+        .context("Failed to run curl (is it installed?)")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("Update check failed: {}", stderr.trim());
+    }
+    let release = parse_release(
+        &String::from_utf8_lossy(&output.stdout),
+        std::env::consts::OS,
+    )?;
+    Ok(is_newer(&release.version, CURRENT_VERSION).then_some(release))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RELEASE: &str = r#"{
+        "tag_name": "v0.3.0",
+        "html_url": "https://github.com/harveyalex/logs-parser/releases/tag/v0.3.0",
+        "assets": [
+            {"name": "logs-parser.dmg", "browser_download_url": "https://example.com/logs-parser.dmg"},
+            {"name": "logs-parser-linux-x86_64.AppImage", "browser_download_url": "https://example.com/linux"}
+        ]
+    }"#;
+
+    #[test]
+    fn test_parse_release() {
+        let release = parse_release(RELEASE, "macos").unwrap();
+        assert_eq!(release.version, "0.3.0");
+        assert_eq!(release.url(), "https://example.com/logs-parser.dmg");
+
+        assert_eq!(
+            parse_release(RELEASE, "linux").unwrap().url(),
+            "https://example.com/linux"
+        );
+        let windows = parse_release(RELEASE, "windows").unwrap();
+        assert_eq!(windows.download_url, None);
+        assert_eq!(windows.url(), windows.page_url);
+
+        assert!(parse_release("{\"message\": \"Not Found\"}", "macos").is_err());
+    }
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("0.3.0", "0.2.0"));
+        assert!(is_newer("0.10.0", "0.9.1"));
+        assert!(is_newer("1.0", "0.9.9"));
+        assert!(is_newer("0.2.1", "0.2"));
+        assert!(!is_newer("0.2.0", "0.2.0"));
+        assert!(!is_newer("0.2", "0.2.0"));
+        assert!(!is_newer("0.1.9", "0.2.0"));
+    }
+}
@@ -0,0 +1,168 @@
+//! Uploading exports to an S3 or GCS bucket
+//!
+//! Set `upload_to = s3://bucket/prefix/` (or `gs://...`) in the settings and
+//! every export is copied there with the `aws` or `gcloud` CLI, which take
+//! their credentials from the environment as usual (`AWS_ACCESS_KEY_ID`,
+//! `AWS_PROFILE`, `CLOUDSDK_AUTH_CREDENTIAL_FILE_OVERRIDE`, ...).
+
+use crate::heroku_cli::gui_path;
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+use tokio::process::Command;
+
+/// Bucket and key prefix exports are uploaded under
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Destination {
+    S3 { bucket: String, prefix: String },
+    Gcs { bucket: String, prefix: String },
+}
+
+impl Destination {
+    /// Parse `s3://bucket[/prefix]` or `gs://bucket[/prefix]`
+    pub fn parse(url: &str) -> Option<Self> {
+        let (scheme, rest) = url.trim().split_once("://")?;
+        let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+        if bucket.is_empty() {
+            return None;
+        }
+        let bucket = bucket.to_string();
+        // A prefix is a "directory": keys go below it
+        let prefix = match prefix.trim_matches('/') {
+            "" => String::new(),
+            prefix => format!("{}/", prefix),
+        };
+        match scheme {
+            "s3" => Some(Self::S3 { bucket, prefix }),
+            "gs" => Some(Self::Gcs { bucket, prefix }),
+            _ => None,
+        }
+    }
+
+    fn key(&self, file_name: &str) -> String {
+        match self {
+            Self::S3 { prefix, .. } | Self::Gcs { prefix, .. } => {
+                format!("{}{}", prefix, file_name)
+            }
+        }
+    }
+
+    /// Bucket URL of the object a file is uploaded to, as given to the CLI
+    fn object_uri(&self, file_name: &str) -> String {
+        match self {
+            Self::S3 { bucket, .. } => format!("s3://{}/{}", bucket, self.key(file_name)),
+            Self::Gcs { bucket, .. } => format!("gs://{}/{}", bucket, self.key(file_name)),
+        }
+    }
+
+    /// HTTPS URL of the uploaded object, for sharing; S3 URLs include the
+    /// region when `AWS_REGION` is set
+    pub fn object_url(&self, file_name: &str, region: Option<&str>) -> String {
+        match self {
+            Self::S3 { bucket, .. } => match region {
+                Some(region) => format!(
+                    "https://{}.s3.{}.amazonaws.com/{}",
+                    bucket,
+                    region,
+                    self.key(file_name)
+                ),
+                None => format!(
+                    "https://{}.s3.amazonaws.com/{}",
+                    bucket,
+                    self.key(file_name)
+                ),
+            },
+            Self::Gcs { bucket, .. } => format!(
+                "https://storage.googleapis.com/{}/{}",
+                bucket,
+                self.key(file_name)
+            ),
+        }
+    }
+
+    /// Program and arguments that copy `path` to the bucket
+    fn command(&self, path: &Path, file_name: &str) -> (&'static str, Vec<String>) {
+        let source = path.display().to_string();
+        let target = self.object_uri(file_name);
+        match self {
+            Self::S3 { .. } => ("aws", vec!["s3".into(), "cp".into(), source, target]),
+            Self::Gcs { .. } => (
+                "gcloud",
+                vec!["storage".into(), "cp".into(), source, target],
+            ),
+        }
+    }
+
+    /// Copy the file at `path` to the bucket, returning its HTTPS URL
+    pub async fn upload(&self, path: &Path) -> Result<String> {
+        let file_name = path
+            .file_name()
+            .context("Export path has no file name")?
+            .to_string_lossy()
+            .into_owned();
+        let (program, args) = self.command(path, &file_name);
+        let output = Command::new(program)
+            .args(&args)
+            .env("PATH", gui_path())
+            .output()
+            .await
+            .with_context(|| format!("Failed to run {} (is it installed?)", program))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!("Upload failed: {}", stderr.trim());
+        }
+        let region = std::env::var("AWS_REGION").ok();
+        Ok(self.object_url(&file_name, region.as_deref()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_destination() {
+        assert_eq!(
+            Destination::parse("s3://evidence/incidents/"),
+            Some(Destination::S3 {
+                bucket: "evidence".to_string(),
+                prefix: "incidents/".to_string(),
+            })
+        );
+        assert_eq!(
+            Destination::parse("gs://evidence"),
+            Some(Destination::Gcs {
+                bucket: "evidence".to_string(),
+                prefix: String::new(),
+            })
+        );
+        assert_eq!(Destination::parse("s3://"), None);
+        assert_eq!(Destination::parse("ftp://evidence/x"), None);
+        assert_eq!(Destination::parse("evidence"), None);
+    }
+
+    #[test]
+    fn test_object_urls() {
+        let s3 = Destination::parse("s3://evidence/incidents").unwrap();
+        assert_eq!(
+            s3.object_url("capture.log", Some("eu-west-1")),
+            "https://evidence.s3.eu-west-1.amazonaws.com/incidents/capture.log"
+        );
+        let (program, args) = s3.command(Path::new("/tmp/capture.log"), "capture.log");
+        assert_eq!(program, "aws");
+        assert_eq!(
+            args,
+            [
+                "s3",
+                "cp",
+                "/tmp/capture.log",
+                "s3://evidence/incidents/capture.log"
+            ]
+        );
+
+        let gcs = Destination::parse("gs://evidence/").unwrap();
+        assert_eq!(
+            gcs.object_url("capture.log", None),
+            "https://storage.googleapis.com/evidence/capture.log"
+        );
+    }
+}
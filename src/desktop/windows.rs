@@ -0,0 +1,74 @@
+//! Application windows and their menu bar
+//!
+//! Every window runs its own [`App`](crate::App), so each has an independent
+//! connection, buffer and filters; File > New Window opens another, e.g. to
+//! watch production and staging side by side.
+
+use crate::deep_link::{self, DeepLink};
+use dioxus::desktop::muda::{Menu, MenuItem, PredefinedMenuItem, Submenu};
+use dioxus::desktop::tao::event::Event;
+use dioxus::desktop::{use_muda_event_handler, window, Config};
+use dioxus::prelude::*;
+
+/// Menu id of File > New Window
+const NEW_WINDOW: &str = "new-window";
+
+/// Context marking windows opened after the first; process-wide services
+/// such as the control socket only run in the first window
+#[derive(Debug, Clone, Copy)]
+pub struct ExtraWindow;
+
+fn menu_bar() -> Menu {
+    let file_menu = Submenu::new("File", true);
+    let _ = file_menu.append_items(&[
+        &MenuItem::with_id(NEW_WINDOW, "New Window", true, "CmdOrCtrl+N".parse().ok()),
+        &PredefinedMenuItem::separator(),
+        &PredefinedMenuItem::close_window(None),
+        &PredefinedMenuItem::quit(None),
+    ]);
+
+    // Without an Edit menu macOS has no copy and paste shortcuts
+    let edit_menu = Submenu::new("Edit", true);
+    let _ = edit_menu.append_items(&[
+        &PredefinedMenuItem::undo(None),
+        &PredefinedMenuItem::redo(None),
+        &PredefinedMenuItem::separator(),
+        &PredefinedMenuItem::cut(None),
+        &PredefinedMenuItem::copy(None),
+        &PredefinedMenuItem::paste(None),
+        &PredefinedMenuItem::select_all(None),
+    ]);
+
+    let menu = Menu::new();
+    let _ = menu.append_items(&[&file_menu, &edit_menu]);
+    menu
+}
+
+/// Desktop configuration shared by every window
+pub fn config() -> Config {
+    Config::new()
+        .with_menu(menu_bar())
+        .with_custom_event_handler(|event, _| {
+            // macOS hands over `logsparser://` links as an event, both the
+            // one that launched the app and any opened while it runs
+            if let Event::Opened { urls } = event {
+                for url in urls {
+                    match DeepLink::parse_url(url.as_str()) {
+                        Ok(link) => deep_link::open(link),
+                        Err(e) => eprintln!("{:#}", e),
+                    }
+                }
+            }
+        })
+}
+
+/// Open File > New Window in a new window
+pub fn use_new_window_menu() {
+    use_muda_event_handler(move |event| {
+        // Menu events reach every window; only the focused one acts
+        if event.id == NEW_WINDOW && window().is_focused() {
+            let dom = VirtualDom::new(crate::App).with_root_context(ExtraWindow);
+            window().new_window(dom, config());
+        }
+    });
+}